@@ -0,0 +1,279 @@
+//! 命令行参数：
+//! - `flick [文件...] [--server <名称>] [--now]`：快速上传，文件支持通配符 (带 `--now` 时不显示界面)
+//! - `flick upload|download|list ...`：无界面执行，结果以 JSON 输出到标准输出
+//! - `flick upload --dest <远程文件>`：从标准输入读取并流式上传，用于管道
+
+use std::cell::Cell;
+use std::io::Write;
+use std::path::PathBuf;
+
+use anyhow::{anyhow, Result};
+use clap::{Parser, Subcommand};
+use serde_json::{json, Value};
+
+use crate::app::services::headless_service::{self, TransferOutcome};
+use crate::app::services::quick_upload_service;
+use crate::domain::config::{AppConfig, ServerConfig};
+use crate::shared::path_utils;
+use crate::shared::target_template::TemplateContext;
+
+/// 命令行参数
+#[derive(Debug, Parser)]
+#[command(name = "flick", version, about = "轻量 SFTP/SCP 文件传输工具", args_conflicts_with_subcommands = true)]
+pub struct Args {
+    #[command(subcommand)]
+    pub command: Option<Command>,
+    /// 要上传的文件，支持 `*.log` 等通配符，传入时进入快速上传模式
+    pub files: Vec<String>,
+    /// 目标服务器名称，未指定时使用默认服务器
+    #[arg(long)]
+    pub server: Option<String>,
+    /// 不显示界面，直接上传到服务器的默认目录后退出
+    #[arg(long, requires = "files")]
+    pub now: bool,
+}
+
+/// 无界面子命令；全部成功时退出码为 0，任一失败为 1，参数错误为 2
+#[derive(Debug, Subcommand, PartialEq)]
+pub enum Command {
+    /// 上传本地文件或目录
+    Upload {
+        /// 目标服务器名称，未指定时使用默认服务器
+        #[arg(long)]
+        server: Option<String>,
+        /// 远程目录，未指定时使用服务器的默认目录
+        #[arg(long)]
+        dir: Option<String>,
+        /// 远程文件路径，指定时从标准输入读取内容上传
+        #[arg(long, conflicts_with_all = ["dir", "files"])]
+        dest: Option<String>,
+        /// 本地文件或目录
+        #[arg(required_unless_present = "dest")]
+        files: Vec<String>,
+    },
+    /// 下载远程文件或目录
+    Download {
+        /// 目标服务器名称，未指定时使用默认服务器
+        #[arg(long)]
+        server: Option<String>,
+        /// 本地目录
+        #[arg(long, default_value = ".")]
+        dir: String,
+        /// 远程文件或目录
+        #[arg(required = true)]
+        paths: Vec<String>,
+    },
+    /// 列出远程目录
+    List {
+        /// 目标服务器名称，未指定时使用默认服务器
+        #[arg(long)]
+        server: Option<String>,
+        /// 远程目录
+        #[arg(long, default_value = "~")]
+        path: String,
+    },
+}
+
+/// 执行子命令并把 JSON 结果输出到标准输出，返回进程退出码
+pub fn run_headless(config: &AppConfig, command: &Command) -> i32 {
+    let (ok, output) = match execute(config, command) {
+        Ok(result) => result,
+        Err(e) => (false, json!({ "ok": false, "error": e.to_string() })),
+    };
+    println!("{}", output);
+    if ok {
+        0
+    } else {
+        1
+    }
+}
+
+fn execute(config: &AppConfig, command: &Command) -> Result<(bool, Value)> {
+    match command {
+        Command::List { server, path } => {
+            let server = quick_upload_service::select_server(&config.servers, server.as_deref())?;
+            let entries = headless_service::list(&server, path)?;
+            let output = json!({ "ok": true, "server": server.name, "path": path, "entries": entries });
+            Ok((true, output))
+        }
+        Command::Upload { server, dest: Some(dest), .. } => {
+            let server = quick_upload_service::select_server(&config.servers, server.as_deref())?;
+            // 标准输出留给 JSON 结果，字节数写到标准错误
+            let outcome = headless_service::upload_stream(&server, &mut std::io::stdin().lock(), dest, |bytes| {
+                eprint!("\r已上传 {} 字节", bytes);
+            })?;
+            eprintln!();
+            Ok(transfer_report(&server, &[outcome]))
+        }
+        Command::Upload { server, dir, files, .. } => {
+            let server = quick_upload_service::select_server(&config.servers, server.as_deref())?;
+            let dir = dir.clone().unwrap_or_else(|| server.default_target_dir.clone());
+            let files = files
+                .iter()
+                .map(|f| path_utils::normalize_path(f))
+                .collect::<Result<Vec<PathBuf>>>()?;
+            let outcomes = headless_service::upload(&server, &files, &dir)?;
+            Ok(transfer_report(&server, &outcomes))
+        }
+        Command::Download { server, dir, paths } => {
+            let server = quick_upload_service::select_server(&config.servers, server.as_deref())?;
+            let dir = path_utils::normalize_path(dir)?;
+            let outcomes = headless_service::download(&server, paths, &dir)?;
+            Ok(transfer_report(&server, &outcomes))
+        }
+    }
+}
+
+fn transfer_report(server: &ServerConfig, outcomes: &[TransferOutcome]) -> (bool, Value) {
+    let ok = headless_service::all_ok(outcomes);
+    (ok, json!({ "ok": ok, "server": server.name, "results": outcomes }))
+}
+
+/// 无界面上传：连接服务器并依次上传到其默认目录，进度输出到标准输出
+pub fn upload_now(config: &AppConfig, args: &Args) -> Result<()> {
+    if args.files.is_empty() {
+        return Err(anyhow!("未指定要上传的文件"));
+    }
+    let files = path_utils::expand_file_args(&args.files)?;
+    let server = quick_upload_service::select_server(&config.servers, args.server.as_deref())?;
+
+    let total = files.len();
+    // 整批文件按同一时间展开目录模板，共用一个连接
+    let ctx = TemplateContext::now(&server.name);
+    let (mut uploader, target_dir) = quick_upload_service::prepare_upload(&server, &ctx)?;
+    for (i, local_path) in files.into_iter().enumerate() {
+        println!(
+            "[{}/{}] 上传 {} 到 {} ({}:{}) {}",
+            i + 1,
+            total,
+            local_path.display(),
+            server.name,
+            server.host,
+            server.port,
+            target_dir
+        );
+        // 上次输出的百分比，-2 表示已提示过无法获取进度
+        let last = Cell::new(-1i32);
+        quick_upload_service::upload_to_dir(&mut uploader, &local_path, &target_dir, |progress| {
+            if progress < 0.0 {
+                if last.replace(-2) != -2 {
+                    println!("正在上传 (scp 不报告进度)...");
+                }
+                return;
+            }
+            let percent = (progress * 100.0) as i32;
+            if last.replace(percent) != percent {
+                print!("\r上传中 {:>3}%", percent);
+                let _ = std::io::stdout().flush();
+            }
+        })?;
+        println!("\n上传完成");
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(list: &[&str]) -> Result<Args, clap::Error> {
+        Args::try_parse_from(std::iter::once("flick").chain(list.iter().copied()))
+    }
+
+    #[test]
+    fn test_parse_file_only() {
+        let parsed = args(&["a.zip"]).unwrap();
+        assert_eq!(parsed.files, vec!["a.zip"]);
+        assert_eq!(parsed.server, None);
+        assert!(!parsed.now);
+        assert!(parsed.command.is_none());
+
+        let parsed = args(&[]).unwrap();
+        assert!(parsed.files.is_empty() && parsed.command.is_none());
+    }
+
+    #[test]
+    fn test_parse_server_and_now() {
+        let parsed = args(&["a.zip", "--server", "prod", "--now"]).unwrap();
+        assert_eq!(parsed.files, vec!["a.zip"]);
+        assert_eq!(parsed.server.as_deref(), Some("prod"));
+        assert!(parsed.now);
+
+        let parsed = args(&["--now", "--server=my server", "a.zip"]).unwrap();
+        assert_eq!(parsed.server.as_deref(), Some("my server"));
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert!(args(&["--now"]).is_err());
+        assert!(args(&["a.zip", "--server"]).is_err());
+        assert!(args(&["a.zip", "--verbose"]).is_err());
+        assert!(args(&["upload"]).is_err());
+    }
+
+    #[test]
+    fn test_parse_multiple_files() {
+        let parsed = args(&["a.zip", "*.log", "--now"]).unwrap();
+        assert_eq!(parsed.files, vec!["a.zip", "*.log"]);
+        assert!(parsed.now);
+    }
+
+    #[test]
+    fn test_parse_subcommands() {
+        let parsed = args(&["list", "--server", "prod", "--path", "/var/log"]).unwrap();
+        assert_eq!(
+            parsed.command,
+            Some(Command::List { server: Some("prod".into()), path: "/var/log".into() })
+        );
+
+        let parsed = args(&["list"]).unwrap();
+        assert_eq!(parsed.command, Some(Command::List { server: None, path: "~".into() }));
+
+        let parsed = args(&["upload", "a.zip", "b.zip", "--dir", "/srv"]).unwrap();
+        assert_eq!(
+            parsed.command,
+            Some(Command::Upload {
+                server: None,
+                dir: Some("/srv".into()),
+                dest: None,
+                files: vec!["a.zip".into(), "b.zip".into()],
+            })
+        );
+
+        let parsed = args(&["upload", "--server", "prod", "--dest", "/tmp/build.tar"]).unwrap();
+        assert_eq!(
+            parsed.command,
+            Some(Command::Upload {
+                server: Some("prod".into()),
+                dir: None,
+                dest: Some("/tmp/build.tar".into()),
+                files: Vec::new(),
+            })
+        );
+        assert!(args(&["upload", "--dest", "/tmp/x", "a.zip"]).is_err());
+        assert!(args(&["upload", "--dest", "/tmp/x", "--dir", "/srv"]).is_err());
+
+        let parsed = args(&["download", "/var/log/syslog"]).unwrap();
+        assert_eq!(
+            parsed.command,
+            Some(Command::Download { server: None, dir: ".".into(), paths: vec!["/var/log/syslog".into()] })
+        );
+    }
+
+    #[test]
+    fn test_transfer_report() {
+        let server = ServerConfig { name: "prod".into(), ..Default::default() };
+        let outcome = TransferOutcome {
+            source: "a".into(),
+            target: "/srv/a".into(),
+            is_dir: false,
+            ok: false,
+            error: Some("权限不足".into()),
+        };
+        let (ok, value) = transfer_report(&server, &[outcome]);
+        assert!(!ok);
+        assert_eq!(value["ok"], json!(false));
+        assert_eq!(value["server"], json!("prod"));
+        assert_eq!(value["results"][0]["error"], json!("权限不足"));
+    }
+}
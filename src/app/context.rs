@@ -3,21 +3,44 @@ use std::sync::{Arc, Mutex};
 use anyhow::Result;
 
 use crate::domain::config::AppConfig;
-use crate::domain::ports::ConfigRepository;
+use crate::domain::ports::{ConfigRepository, HistoryRepository, QueueRepository};
+use crate::domain::transfer::TransferQueue;
 use crate::infra::config_store::TomlConfigStore;
+use crate::infra::history_store::TomlHistoryStore;
+use crate::infra::queue_store::JsonQueueStore;
 
 pub struct AppContext {
     pub config: Arc<Mutex<AppConfig>>,
     pub config_repo: Arc<dyn ConfigRepository + Send + Sync>,
+    pub history_repo: Arc<dyn HistoryRepository + Send + Sync>,
+    /// 资源管理器与快速上传共用的传输队列
+    pub transfer_queue: Arc<Mutex<TransferQueue>>,
+    /// 启动时需要提示的问题 (如配置文件损坏后已恢复为默认配置)
+    pub startup_warning: Option<String>,
 }
 
 impl AppContext {
     pub fn bootstrap() -> Result<Self> {
         let repo = Arc::new(TomlConfigStore::new());
-        let config = repo.load()?;
+        let (config, startup_warning) = repo.load_or_recover()?;
+        let mut queue = TransferQueue::new();
+        queue.set_max_retries(config.max_retries);
+        queue.set_batch_policy(config.batch_policy());
+
+        // 恢复上次异常退出时未完成的任务，读取失败不影响启动
+        let queue_repo = Arc::new(JsonQueueStore::new());
+        let saved = queue_repo.load().unwrap_or_else(|e| {
+            eprintln!("读取未完成的传输失败: {}", e);
+            Vec::new()
+        });
+        queue.set_store(queue_repo);
+        queue.restore(saved);
         Ok(Self {
             config: Arc::new(Mutex::new(config)),
             config_repo: repo,
+            history_repo: Arc::new(TomlHistoryStore::new()),
+            transfer_queue: Arc::new(Mutex::new(queue)),
+            startup_warning,
         })
     }
 }
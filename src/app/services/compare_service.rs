@@ -0,0 +1,243 @@
+//! 比较本地与远程当前目录：只在一侧存在的条目，以及两侧都有但大小或修改时间不同的文件
+
+use std::collections::BTreeMap;
+
+use crate::domain::transfer::MTIME_TOLERANCE_SECS;
+use crate::infra::local_fs::LocalEntry;
+use crate::infra::remote_fs::RemoteEntry;
+
+/// 单个条目的比较结果
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DiffStatus {
+    LocalOnly,
+    RemoteOnly,
+    Differs,
+    Same,
+}
+
+impl DiffStatus {
+    /// 传给界面的状态名
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DiffStatus::LocalOnly => "local-only",
+            DiffStatus::RemoteOnly => "remote-only",
+            DiffStatus::Differs => "differs",
+            DiffStatus::Same => "same",
+        }
+    }
+}
+
+/// 条目在一侧的信息
+#[derive(Debug, Clone, PartialEq)]
+pub struct DiffSide {
+    pub is_dir: bool,
+    pub size: u64,
+    /// 修改时间 (Unix 秒)，未知时为 0
+    pub mtime: u64,
+    pub modified: String,
+}
+
+/// 按名称对齐的一行比较结果
+#[derive(Debug, Clone, PartialEq)]
+pub struct DiffItem {
+    pub name: String,
+    pub status: DiffStatus,
+    pub local: Option<DiffSide>,
+    pub remote: Option<DiffSide>,
+}
+
+impl DiffItem {
+    /// 任一侧为目录即按目录排序和显示
+    pub fn is_dir(&self) -> bool {
+        self.local.iter().chain(self.remote.iter()).any(|s| s.is_dir)
+    }
+}
+
+/// 两个目录的比较结果，目录在前、按名称排序
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DirDiff {
+    pub items: Vec<DiffItem>,
+}
+
+impl DirDiff {
+    pub fn count(&self, status: DiffStatus) -> usize {
+        self.items.iter().filter(|i| i.status == status).count()
+    }
+
+    /// 除相同以外的条目
+    pub fn differences(&self) -> impl Iterator<Item = &DiffItem> {
+        self.items.iter().filter(|i| i.status != DiffStatus::Same)
+    }
+
+    pub fn summary(&self) -> String {
+        format!(
+            "仅本地 {}，仅远程 {}，不同 {}，相同 {}",
+            self.count(DiffStatus::LocalOnly),
+            self.count(DiffStatus::RemoteOnly),
+            self.count(DiffStatus::Differs),
+            self.count(DiffStatus::Same)
+        )
+    }
+}
+
+/// 两侧都存在的条目是否不同：类型不同，或文件的大小不同、修改时间相差超过容差。
+/// 目录只比较是否存在，任一侧修改时间未知时只比较大小
+fn differs(local: &DiffSide, remote: &DiffSide) -> bool {
+    if local.is_dir != remote.is_dir {
+        return true;
+    }
+    if local.is_dir {
+        return false;
+    }
+    let mtime_known = local.mtime > 0 && remote.mtime > 0;
+    local.size != remote.size || (mtime_known && local.mtime.abs_diff(remote.mtime) > MTIME_TOLERANCE_SECS)
+}
+
+/// 按名称 (区分大小写) 对齐本地与远程条目并逐项比较
+pub fn diff_dirs(local: &[LocalEntry], remote: &[RemoteEntry]) -> DirDiff {
+    let mut pairs: BTreeMap<&str, (Option<DiffSide>, Option<DiffSide>)> = BTreeMap::new();
+    for e in local {
+        pairs.entry(&e.name).or_default().0 = Some(DiffSide {
+            is_dir: e.is_dir,
+            size: e.size,
+            mtime: e.mtime,
+            modified: e.modified.clone(),
+        });
+    }
+    for e in remote {
+        pairs.entry(&e.name).or_default().1 = Some(DiffSide {
+            is_dir: e.is_dir,
+            size: e.size,
+            mtime: e.mtime,
+            modified: e.modified.clone(),
+        });
+    }
+
+    let mut items: Vec<DiffItem> = pairs
+        .into_iter()
+        .map(|(name, (local, remote))| {
+            let status = match (&local, &remote) {
+                (Some(l), Some(r)) if differs(l, r) => DiffStatus::Differs,
+                (Some(_), Some(_)) => DiffStatus::Same,
+                (Some(_), None) => DiffStatus::LocalOnly,
+                _ => DiffStatus::RemoteOnly,
+            };
+            DiffItem { name: name.to_string(), status, local, remote }
+        })
+        .collect();
+    items.sort_by(|a, b| {
+        b.is_dir()
+            .cmp(&a.is_dir())
+            .then_with(|| a.name.to_lowercase().cmp(&b.name.to_lowercase()))
+            .then_with(|| a.name.cmp(&b.name))
+    });
+    DirDiff { items }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn local(name: &str, is_dir: bool, size: u64, mtime: u64) -> LocalEntry {
+        LocalEntry {
+            name: name.to_string(),
+            is_dir,
+            size,
+            modified: String::new(),
+            mtime,
+            path: PathBuf::from(name),
+            dir_size: None,
+        }
+    }
+
+    fn remote(name: &str, is_dir: bool, size: u64, mtime: u64) -> RemoteEntry {
+        RemoteEntry {
+            name: name.to_string(),
+            is_dir,
+            size,
+            modified: String::new(),
+            mtime,
+            dir_size: None,
+            is_symlink: false,
+            link_target: String::new(),
+        }
+    }
+
+    fn status_of(diff: &DirDiff, name: &str) -> DiffStatus {
+        diff.items.iter().find(|i| i.name == name).unwrap().status
+    }
+
+    #[test]
+    fn test_only_one_side() {
+        let diff = diff_dirs(&[local("a.txt", false, 1, 100)], &[remote("b.txt", false, 1, 100)]);
+        assert_eq!(status_of(&diff, "a.txt"), DiffStatus::LocalOnly);
+        assert_eq!(status_of(&diff, "b.txt"), DiffStatus::RemoteOnly);
+        assert!(diff.items[0].remote.is_none());
+        assert!(diff.items[1].local.is_none());
+    }
+
+    #[test]
+    fn test_same_and_differing_files() {
+        let diff = diff_dirs(
+            &[
+                local("same", false, 10, 1000),
+                local("size", false, 10, 1000),
+                local("newer", false, 10, 1000),
+                local("skew", false, 10, 1000),
+            ],
+            &[
+                remote("same", false, 10, 1000),
+                remote("size", false, 11, 1000),
+                remote("newer", false, 10, 900),
+                remote("skew", false, 10, 1000 + MTIME_TOLERANCE_SECS),
+            ],
+        );
+        assert_eq!(status_of(&diff, "same"), DiffStatus::Same);
+        assert_eq!(status_of(&diff, "size"), DiffStatus::Differs);
+        assert_eq!(status_of(&diff, "newer"), DiffStatus::Differs);
+        assert_eq!(status_of(&diff, "skew"), DiffStatus::Same);
+    }
+
+    #[test]
+    fn test_unknown_mtime_compares_size_only() {
+        let diff = diff_dirs(&[local("f", false, 5, 0)], &[remote("f", false, 5, 1234)]);
+        assert_eq!(status_of(&diff, "f"), DiffStatus::Same);
+    }
+
+    #[test]
+    fn test_directories() {
+        let diff = diff_dirs(
+            &[local("both", true, 0, 1), local("kind", true, 0, 1)],
+            &[remote("both", true, 4096, 999), remote("kind", false, 0, 1)],
+        );
+        assert_eq!(status_of(&diff, "both"), DiffStatus::Same);
+        assert_eq!(status_of(&diff, "kind"), DiffStatus::Differs);
+    }
+
+    #[test]
+    fn test_names_are_case_sensitive() {
+        let diff = diff_dirs(&[local("Readme", false, 1, 1)], &[remote("readme", false, 1, 1)]);
+        assert_eq!(diff.count(DiffStatus::LocalOnly), 1);
+        assert_eq!(diff.count(DiffStatus::RemoteOnly), 1);
+    }
+
+    #[test]
+    fn test_order_and_summary() {
+        let diff = diff_dirs(
+            &[local("b.txt", false, 1, 1), local("Zdir", true, 0, 0), local("same", false, 1, 1)],
+            &[remote("A.txt", false, 1, 1), remote("adir", true, 0, 0), remote("same", false, 1, 1)],
+        );
+        let names: Vec<&str> = diff.items.iter().map(|i| i.name.as_str()).collect();
+        assert_eq!(names, vec!["adir", "Zdir", "A.txt", "b.txt", "same"]);
+        assert_eq!(diff.summary(), "仅本地 2，仅远程 2，不同 0，相同 1");
+        assert_eq!(diff.differences().count(), 4);
+    }
+
+    #[test]
+    fn test_empty() {
+        let diff = diff_dirs(&[], &[]);
+        assert!(diff.items.is_empty());
+        assert_eq!(diff.summary(), "仅本地 0，仅远程 0，不同 0，相同 0");
+    }
+}
@@ -1,7 +1,877 @@
-use crate::domain::config::Bookmark;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use anyhow::{anyhow, Result};
+
+use crate::domain::config::{AppConfig, Bookmark, ServerConfig};
+use crate::domain::ports::ConfigRepository;
+use crate::domain::transfer::ConflictPolicy;
+use crate::infra::remote_fs::{escape_shell_arg, RemoteStat};
+use crate::infra::ssh::host;
+use crate::shared::{path_utils, remote_path};
 
 pub fn dedup_bookmark(bookmarks: &[Bookmark], path: &str, side: &str) -> bool {
     bookmarks
         .iter()
         .any(|b| b.path == path && b.side == side)
 }
+
+/// 一侧面板的浏览位置：当前目录和最近访问的目录
+#[derive(Debug, Clone)]
+pub struct BrowsePosition {
+    pub path: String,
+    pub recent: Vec<String>,
+}
+
+/// 记住上次浏览的本地目录和 (可选的) 某服务器远程目录并写回配置
+/// 本地当前目录为空 (我的电脑) 时只更新最近访问列表
+pub fn remember_paths(
+    config_state: &Arc<Mutex<AppConfig>>,
+    repo: &Arc<dyn ConfigRepository + Send + Sync>,
+    local: Option<BrowsePosition>,
+    remote: Option<(&ServerConfig, BrowsePosition)>,
+) -> Result<()> {
+    let mut guard = config_state
+        .lock()
+        .map_err(|_| anyhow!("配置锁定失败"))?;
+
+    if let Some(local) = local {
+        if !local.path.is_empty() {
+            guard.last_local_path = Some(local.path);
+        }
+        guard.recent_local_dirs = local.recent;
+    }
+    if let Some((server, position)) = remote {
+        if let Some(s) = guard.servers.iter_mut().find(|s| {
+            s.name == server.name && s.host == server.host && s.user == server.user
+        }) {
+            s.last_remote_path = Some(position.path);
+            s.recent_dirs = position.recent;
+        }
+    }
+
+    repo.save(&guard)
+}
+
+/// 标记为默认的服务器索引 (按配置顺序)
+pub fn default_server_indices(servers: &[ServerConfig]) -> Vec<usize> {
+    servers
+        .iter()
+        .enumerate()
+        .filter(|(_, s)| s.is_default)
+        .map(|(i, _)| i)
+        .collect()
+}
+
+/// 所有服务器用到的标签，按首次出现的顺序去重
+pub fn server_tags(servers: &[ServerConfig]) -> Vec<String> {
+    let mut tags: Vec<String> = Vec::new();
+    for tag in servers.iter().filter_map(|s| s.tag.as_deref()) {
+        if !tags.iter().any(|t| t == tag) {
+            tags.push(tag.to_string());
+        }
+    }
+    tags
+}
+
+/// 带指定标签的服务器索引，标签为空时返回全部
+pub fn servers_with_tag(servers: &[ServerConfig], tag: &str) -> Vec<usize> {
+    servers
+        .iter()
+        .enumerate()
+        .filter(|(_, s)| tag.is_empty() || s.tag.as_deref() == Some(tag))
+        .map(|(i, _)| i)
+        .collect()
+}
+
+/// 下拉框中显示的服务器名称，有标签时加上 `[标签]` 前缀
+pub fn server_label(server: &ServerConfig) -> String {
+    match &server.tag {
+        Some(tag) => format!("[{}] {}", tag, server.name),
+        None => server.name.clone(),
+    }
+}
+
+/// 按模板为选中条目生成新名称：`{n}` 为从 1 开始的序号，`{name}` 为原名去掉扩展名。
+/// 新名称为空、含 `/`、彼此重复或与目录中其他条目同名时返回错误；名称不变的条目不出现在结果中。
+/// 只用 `{name}` 的模板 (如统一改扩展名) 只要结果互不重复即可
+pub fn plan_bulk_rename(
+    selected: &[String],
+    existing: &[String],
+    template: &str,
+) -> Result<Vec<(String, String)>> {
+    let mut seen = std::collections::HashSet::new();
+    let mut plan = Vec::new();
+    for (i, old) in selected.iter().enumerate() {
+        let stem = match old.rfind('.') {
+            Some(dot) if dot > 0 => &old[..dot],
+            _ => old.as_str(),
+        };
+        let new = template
+            .replace("{n}", &(i + 1).to_string())
+            .replace("{name}", stem);
+        if new.is_empty() || new.contains('/') || new.contains('\\') {
+            return Err(anyhow!("无效的文件名: {:?}", new));
+        }
+        if !seen.insert(new.clone()) {
+            return Err(anyhow!("模板生成了重复的文件名: {}", new));
+        }
+        if new != *old && existing.contains(&new) {
+            return Err(anyhow!("与现有条目重名: {}", new));
+        }
+        if new != *old {
+            plan.push((old.clone(), new));
+        }
+    }
+    Ok(plan)
+}
+
+/// 远程粘贴的一步：目标已存在且选择覆盖时 `replace` 为真，需先删除目标
+#[derive(Debug, Clone, PartialEq)]
+pub struct PasteStep {
+    pub src: String,
+    pub dst: String,
+    pub is_dir: bool,
+    pub replace: bool,
+}
+
+/// 粘贴到 `dir` 时与现有条目同名的名称；粘贴回原目录的项不算
+pub fn paste_conflicts(items: &[(String, bool)], dir: &str, existing: &[String]) -> Vec<String> {
+    items
+        .iter()
+        .filter_map(|(src, _)| {
+            let name = remote_path::file_name(src)?;
+            (*src != remote_path::join(dir, &name) && existing.contains(&name)).then_some(name)
+        })
+        .collect()
+}
+
+/// 按冲突策略规划粘贴到 `dir` 的各项，`existing` 为目标目录的完整名称列表。
+/// 粘贴回原目录的项直接跳过；"保留两者"另起 `name (n).ext`，同批新名称互不重复
+pub fn plan_remote_paste(
+    items: &[(String, bool)],
+    dir: &str,
+    existing: &[String],
+    policy: ConflictPolicy,
+) -> Vec<PasteStep> {
+    let mut taken = existing.to_vec();
+    let mut steps = Vec::new();
+    for (src, is_dir) in items {
+        let name = match remote_path::file_name(src) {
+            Some(n) => n,
+            None => continue,
+        };
+        let dst = remote_path::join(dir, &name);
+        if *src == dst {
+            continue;
+        }
+        let conflict = existing.contains(&name);
+        let (dst, replace) = match policy {
+            _ if !conflict => (dst, false),
+            ConflictPolicy::Skip => continue,
+            ConflictPolicy::Overwrite => (dst, true),
+            ConflictPolicy::KeepBoth => {
+                let free = path_utils::free_name(&name, &taken);
+                taken.push(free.clone());
+                (remote_path::join(dir, &free), false)
+            }
+        };
+        steps.push(PasteStep { src: src.clone(), dst, is_dir: *is_dir, replace });
+    }
+    steps
+}
+
+/// 是否为可预览的图片 (按扩展名判断)
+pub fn is_previewable_image(name: &str) -> bool {
+    let lower = name.to_lowercase();
+    [".png", ".jpg", ".jpeg"].iter().any(|ext| lower.ends_with(ext))
+}
+
+/// 预览和编辑文本文件时使用的编码
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TextEncoding {
+    #[default]
+    Utf8,
+    Gbk,
+    Latin1,
+}
+
+impl TextEncoding {
+    /// 界面上显示和回传的名称
+    pub fn label(&self) -> &'static str {
+        match self {
+            TextEncoding::Utf8 => "UTF-8",
+            TextEncoding::Gbk => "GBK",
+            TextEncoding::Latin1 => "Latin-1",
+        }
+    }
+
+    /// 按名称解析，未知名称按 UTF-8 处理
+    pub fn from_label(label: &str) -> Self {
+        match label {
+            "GBK" => TextEncoding::Gbk,
+            "Latin-1" => TextEncoding::Latin1,
+            _ => TextEncoding::Utf8,
+        }
+    }
+}
+
+/// 按指定编码将文件内容解码为预览文本，二进制内容返回 None。
+/// 无法解码的字节替换为 U+FFFD，不会因个别坏字节而整体失败，仅用于只读预览
+pub fn decode_text_preview(bytes: &[u8], encoding: TextEncoding) -> Option<String> {
+    if bytes.contains(&0) {
+        return None;
+    }
+    let text = match encoding {
+        TextEncoding::Utf8 => String::from_utf8_lossy(bytes).into_owned(),
+        TextEncoding::Gbk => encoding_rs::GBK.decode_without_bom_handling(bytes).0.into_owned(),
+        TextEncoding::Latin1 => bytes.iter().map(|&b| b as char).collect(),
+    };
+    Some(text)
+}
+
+/// 编辑器用的严格解码：二进制内容或含该编码无法识别的字节时报错，
+/// 避免替换字符在保存时写回文件造成损坏 (预览才使用有损解码)
+pub fn decode_text_for_edit(bytes: &[u8], encoding: TextEncoding) -> Result<String> {
+    if bytes.contains(&0) {
+        return Err(anyhow!("二进制文件，无法编辑"));
+    }
+    let decoded = match encoding {
+        TextEncoding::Utf8 => std::str::from_utf8(bytes).ok().map(str::to_string),
+        TextEncoding::Gbk => encoding_rs::GBK
+            .decode_without_bom_handling_and_without_replacement(bytes)
+            .map(|text| text.into_owned()),
+        TextEncoding::Latin1 => Some(bytes.iter().map(|&b| b as char).collect()),
+    };
+    decoded.ok_or_else(|| anyhow!("文件中有无法按 {} 解码的字节，请换用其他编码", encoding.label()))
+}
+
+/// 按指定编码把编辑后的文本编码为字节，含该编码无法表示的字符时报错
+pub fn encode_text(text: &str, encoding: TextEncoding) -> Result<Vec<u8>> {
+    match encoding {
+        TextEncoding::Utf8 => Ok(text.as_bytes().to_vec()),
+        TextEncoding::Gbk => {
+            let (bytes, _, had_errors) = encoding_rs::GBK.encode(text);
+            if had_errors {
+                // 编码器会把无法表示的字符替换为 HTML 实体，这里找出第一个报给用户
+                let mut buf = [0u8; 4];
+                let c = text
+                    .chars()
+                    .find(|c| encoding_rs::GBK.encode(c.encode_utf8(&mut buf)).2)
+                    .unwrap_or(char::REPLACEMENT_CHARACTER);
+                return Err(anyhow!("字符 '{}' 无法用 GBK 编码", c));
+            }
+            Ok(bytes.into_owned())
+        }
+        TextEncoding::Latin1 => text
+            .chars()
+            .map(|c| u8::try_from(c as u32).map_err(|_| anyhow!("字符 '{}' 无法用 Latin-1 编码", c)))
+            .collect(),
+    }
+}
+
+/// 把列目录失败的原始错误转成简短提示
+pub fn describe_list_error(path: &str, error: &str) -> String {
+    let lower = error.to_lowercase();
+    let reason = if lower.contains("permission denied") || lower.contains("sftp(3)") {
+        "权限不足".to_string()
+    } else if lower.contains("no such file") || lower.contains("sftp(2)") {
+        "目录不存在".to_string()
+    } else {
+        error.to_string()
+    };
+    format!("无法打开 {}: {}", path, reason)
+}
+
+/// scp 命令的公共部分：端口和跳板机参数
+fn scp_prefix(server: &ServerConfig) -> String {
+    let mut cmd = String::from("scp");
+    if server.port != 22 {
+        cmd.push_str(&format!(" -P {}", server.port));
+    }
+    if let Some(jump) = server.proxy_jump.as_deref().filter(|j| !j.is_empty()) {
+        cmd.push_str(&format!(" -J {}", jump));
+    }
+    cmd
+}
+
+/// `user@host:path` 形式的 scp 远程参数
+fn scp_target(server: &ServerConfig, path: &str) -> String {
+    format!("{}@{}:{}", server.user, host::bracketed(&server.host), escape_shell_arg(path))
+}
+
+/// 下载远程文件的 scp 命令 (目标位置由用户补充)
+pub fn scp_download_command(server: &ServerConfig, remote_path: &str) -> String {
+    format!("{} {}", scp_prefix(server), scp_target(server, remote_path))
+}
+
+/// 把本地文件上传到远程目录的 scp 命令
+pub fn scp_upload_command(server: &ServerConfig, local_path: &str, remote_dir: &str) -> String {
+    let dir = format!("{}/", remote_dir.trim_end_matches('/'));
+    format!(
+        "{} {} {}",
+        scp_prefix(server),
+        escape_shell_arg(local_path),
+        scp_target(server, &dir)
+    )
+}
+
+/// 删除确认对话框的提示，`to_trash` 表示本地删除会移到回收站
+pub fn delete_confirm_message(count: usize, remote: bool, to_trash: bool) -> String {
+    match (remote, to_trash) {
+        (true, _) => format!("确定要删除远程的以下 {} 个项目吗？此操作不可撤销。", count),
+        (false, true) => format!("确定要将以下 {} 个项目移到回收站吗？", count),
+        (false, false) => format!("确定要删除以下 {} 个项目吗？此操作不可撤销。", count),
+    }
+}
+
+/// 删除失败的汇总：`N 个项目删除失败`，每项附上路径和错误首行
+pub fn delete_failure_summary(failures: &[(String, String)]) -> (String, Vec<String>) {
+    let header = format!("{} 个项目删除失败", failures.len());
+    let lines = failures
+        .iter()
+        .map(|(path, error)| format!("{}: {}", path, error.lines().next().unwrap_or("").trim()))
+        .collect();
+    (header, lines)
+}
+
+/// 路径补全候选数量上限
+pub const PATH_COMPLETION_LIMIT: usize = 20;
+
+/// 把正在输入的路径拆成 (父目录, 名称前缀)，父目录保留末尾分隔符；不含分隔符时返回 None
+pub fn split_partial_path<'a>(partial: &'a str, separators: &[char]) -> Option<(&'a str, &'a str)> {
+    let idx = partial.rfind(separators)?;
+    Some((&partial[..=idx], &partial[idx + 1..]))
+}
+
+/// 从父目录的子目录名中按前缀 (忽略大小写) 筛选补全候选，返回完整路径，
+/// 末尾沿用父目录的分隔符。前缀不以 `.` 开头时不提示隐藏目录
+pub fn path_completions(parent: &str, prefix: &str, dir_names: &[String]) -> Vec<String> {
+    let separator = parent.chars().last().unwrap_or('/');
+    let lower = prefix.to_lowercase();
+    let mut names: Vec<&String> = dir_names
+        .iter()
+        .filter(|n| n.as_str() != "." && n.as_str() != "..")
+        .filter(|n| prefix.starts_with('.') || !n.starts_with('.'))
+        .filter(|n| n.to_lowercase().starts_with(&lower))
+        .collect();
+    names.sort_by_key(|n| n.to_lowercase());
+    names
+        .into_iter()
+        .take(PATH_COMPLETION_LIMIT)
+        .map(|n| format!("{}{}{}", parent, n, separator))
+        .collect()
+}
+
+/// 格式化远程命令的执行结果：命令行、stdout、stderr 和退出码
+pub fn format_command_output(command: &str, stdout: &str, stderr: &str, exit_code: i32) -> String {
+    let mut out = format!("$ {}\n", command);
+    if !stdout.is_empty() {
+        out.push_str(stdout.trim_end());
+        out.push('\n');
+    }
+    if !stderr.is_empty() {
+        out.push_str("[stderr]\n");
+        out.push_str(stderr.trim_end());
+        out.push('\n');
+    }
+    out.push_str(&format!("[退出码 {}]", exit_code));
+    out
+}
+
+/// 权限位显示为八进制加 rwx 形式，如 `0755 (rwxr-xr-x)`
+pub fn format_mode(mode: u32) -> String {
+    let rwx: String = (0..9)
+        .map(|i| {
+            let bit = 1 << (8 - i);
+            match (mode & bit != 0, i % 3) {
+                (false, _) => '-',
+                (true, 0) => 'r',
+                (true, 1) => 'w',
+                (true, _) => 'x',
+            }
+        })
+        .collect();
+    format!("{:04o} ({})", mode & 0o7777, rwx)
+}
+
+fn format_timestamp(secs: u64) -> String {
+    use chrono::TimeZone;
+    chrono::Local
+        .timestamp_opt(secs as i64, 0)
+        .single()
+        .map(|dt| dt.format("%Y-%m-%d %H:%M:%S").to_string())
+        .unwrap_or_default()
+}
+
+/// 远程条目属性对话框的各行 (名称, 值)
+pub fn remote_properties(stat: &RemoteStat) -> Vec<(String, String)> {
+    let kind = match (&stat.link_target, stat.is_dir) {
+        (Some(_), _) => "符号链接",
+        (None, true) => "目录",
+        (None, false) => "文件",
+    };
+    let mut rows = vec![
+        ("路径".to_string(), stat.path.clone()),
+        ("类型".to_string(), kind.to_string()),
+        ("大小".to_string(), format!("{} 字节", stat.size)),
+        ("权限".to_string(), format_mode(stat.mode)),
+        ("属主".to_string(), format!("{}:{}", stat.owner, stat.group)),
+        ("修改时间".to_string(), format_timestamp(stat.mtime)),
+    ];
+    if let Some(target) = &stat.link_target {
+        rows.push(("链接目标".to_string(), target.clone()));
+    }
+    rows
+}
+
+/// 本地条目属性对话框的各行 (名称, 值)，不跟随符号链接
+pub fn local_properties(path: &Path) -> Result<Vec<(String, String)>> {
+    let meta = std::fs::symlink_metadata(path)
+        .map_err(|e| anyhow!("无法读取属性: {}", e))?;
+    let link_target = if meta.file_type().is_symlink() {
+        std::fs::read_link(path).ok().map(|t| t.to_string_lossy().to_string())
+    } else {
+        None
+    };
+    let kind = match (&link_target, meta.is_dir()) {
+        (Some(_), _) => "符号链接",
+        (None, true) => "目录",
+        (None, false) => "文件",
+    };
+    #[cfg(unix)]
+    let permissions = {
+        use std::os::unix::fs::PermissionsExt;
+        format_mode(meta.permissions().mode())
+    };
+    #[cfg(not(unix))]
+    let permissions = if meta.permissions().readonly() { "只读" } else { "可读写" }.to_string();
+    let modified = meta
+        .modified()
+        .ok()
+        .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+        .map(|d| format_timestamp(d.as_secs()))
+        .unwrap_or_default();
+
+    let mut rows = vec![
+        ("路径".to_string(), path.to_string_lossy().to_string()),
+        ("类型".to_string(), kind.to_string()),
+        ("大小".to_string(), format!("{} 字节", meta.len())),
+        ("权限".to_string(), permissions),
+        ("修改时间".to_string(), modified),
+    ];
+    if let Some(target) = link_target {
+        rows.push(("链接目标".to_string(), target));
+    }
+    Ok(rows)
+}
+
+/// 从 `base` 到 `path` 的相对位移：先向上 `ups` 层，再依次进入 `downs`
+fn relative_move<T: PartialEq + Clone>(base: &[T], path: &[T]) -> (usize, Vec<T>) {
+    let common = base.iter().zip(path).take_while(|(a, b)| a == b).count();
+    (base.len() - common, path[common..].to_vec())
+}
+
+fn remote_parts(path: &str) -> Vec<String> {
+    path.split('/').filter(|p| !p.is_empty()).map(str::to_string).collect()
+}
+
+/// 同步浏览：把本地相对起点的位移套用到远程起点，越过远程根目录时返回 None
+pub fn sync_remote_target(local_base: &Path, local_path: &Path, remote_base: &str) -> Option<String> {
+    if local_path.as_os_str().is_empty() {
+        return None;
+    }
+    let base: Vec<_> = local_base.components().collect();
+    let path: Vec<_> = local_path.components().collect();
+    let (ups, downs) = relative_move(&base, &path);
+    let mut parts = remote_parts(remote_base);
+    parts.truncate(parts.len().checked_sub(ups)?);
+    parts.extend(downs.iter().map(|c| c.as_os_str().to_string_lossy().to_string()));
+    Some(format!("/{}", parts.join("/")))
+}
+
+/// 同步浏览：把远程相对起点的位移套用到本地起点，越过本地根目录时返回 None
+pub fn sync_local_target(remote_base: &str, remote_path: &str, local_base: &Path) -> Option<PathBuf> {
+    let (ups, downs) = relative_move(&remote_parts(remote_base), &remote_parts(remote_path));
+    let mut target = local_base.to_path_buf();
+    for _ in 0..ups {
+        if !target.pop() {
+            return None;
+        }
+    }
+    target.extend(downs);
+    Some(target)
+}
+
+/// 翻页键一次移动的行数
+const PAGE_ROWS: usize = 10;
+
+/// 按方向键移动列表的焦点行 (`up` / `down` / `pageup` / `pagedown` / `home` / `end`)，
+/// 尚无焦点时从第一行开始；列表为空或按键无法识别时为 None
+pub fn move_focus(current: Option<usize>, len: usize, key: &str) -> Option<usize> {
+    if len == 0 {
+        return None;
+    }
+    let last = len - 1;
+    let next = match (current.map(|i| i.min(last)), key) {
+        (_, "home") => 0,
+        (_, "end") => last,
+        (None, "up" | "down" | "pageup" | "pagedown") => 0,
+        (Some(i), "up") => i.saturating_sub(1),
+        (Some(i), "down") => (i + 1).min(last),
+        (Some(i), "pageup") => i.saturating_sub(PAGE_ROWS),
+        (Some(i), "pagedown") => (i + PAGE_ROWS).min(last),
+        _ => return None,
+    };
+    Some(next)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_paste_conflicts() {
+        let items = vec![("/a/x.txt".to_string(), false), ("/srv/y".to_string(), true), ("/b/z".to_string(), false)];
+        let existing = vec!["x.txt".to_string(), "y".to_string()];
+        // /srv/y 粘贴回原目录，不算冲突
+        assert_eq!(paste_conflicts(&items, "/srv", &existing), vec!["x.txt"]);
+    }
+
+    #[test]
+    fn test_plan_remote_paste_policies() {
+        let items = vec![("/a/x.txt".to_string(), false), ("/b/x.txt".to_string(), false), ("/a/new".to_string(), true)];
+        let existing = vec!["x.txt".to_string()];
+
+        let skip = plan_remote_paste(&items, "/srv", &existing, ConflictPolicy::Skip);
+        assert_eq!(skip.len(), 1);
+        assert_eq!(skip[0], PasteStep { src: "/a/new".into(), dst: "/srv/new".into(), is_dir: true, replace: false });
+
+        let overwrite = plan_remote_paste(&items, "/srv", &existing, ConflictPolicy::Overwrite);
+        assert!(overwrite[0].replace && overwrite[0].dst == "/srv/x.txt");
+        assert!(!overwrite[2].replace);
+
+        let keep = plan_remote_paste(&items, "/srv", &existing, ConflictPolicy::KeepBoth);
+        let dsts: Vec<&str> = keep.iter().map(|s| s.dst.as_str()).collect();
+        assert_eq!(dsts, vec!["/srv/x (1).txt", "/srv/x (2).txt", "/srv/new"]);
+        assert!(keep.iter().all(|s| !s.replace));
+
+        // 粘贴回原目录的项跳过
+        assert!(plan_remote_paste(&[("/srv/x.txt".to_string(), false)], "/srv", &existing, ConflictPolicy::Overwrite).is_empty());
+    }
+
+    #[test]
+    fn test_format_mode() {
+        assert_eq!(format_mode(0o755), "0755 (rwxr-xr-x)");
+        assert_eq!(format_mode(0o640), "0640 (rw-r-----)");
+        assert_eq!(format_mode(0o100644), "0644 (rw-r--r--)");
+    }
+
+    #[test]
+    fn test_remote_properties() {
+        let stat = RemoteStat {
+            path: "/var/www/current".into(),
+            size: 12,
+            mode: 0o777,
+            owner: "deploy".into(),
+            group: "www".into(),
+            mtime: 0,
+            is_dir: false,
+            link_target: Some("releases/42".into()),
+        };
+        let rows = remote_properties(&stat);
+        assert_eq!(rows[1], ("类型".to_string(), "符号链接".to_string()));
+        assert_eq!(rows[2].1, "12 字节");
+        assert_eq!(rows[4].1, "deploy:www");
+        assert_eq!(rows.last().unwrap().1, "releases/42");
+    }
+
+    #[test]
+    fn test_local_properties() {
+        let path = std::env::temp_dir().join("flick_test_properties.txt");
+        std::fs::write(&path, b"hello").unwrap();
+        let rows = local_properties(&path).unwrap();
+        assert_eq!(rows[1].1, "文件");
+        assert_eq!(rows[2].1, "5 字节");
+        assert!(!rows[4].1.is_empty());
+        std::fs::remove_file(&path).unwrap();
+        assert!(local_properties(&path).is_err());
+    }
+
+    #[test]
+    fn test_split_partial_path() {
+        assert_eq!(split_partial_path("/usr/lo", &['/']), Some(("/usr/", "lo")));
+        assert_eq!(split_partial_path("/var/", &['/']), Some(("/var/", "")));
+        assert_eq!(split_partial_path("/", &['/']), Some(("/", "")));
+        assert_eq!(split_partial_path("C:\\Us", &['/', '\\']), Some(("C:\\", "Us")));
+        assert_eq!(split_partial_path("home", &['/']), None);
+    }
+
+    #[test]
+    fn test_path_completions() {
+        let names: Vec<String> = ["logs", "Local", "lib", ".cache", "bin"].iter().map(|s| s.to_string()).collect();
+        assert_eq!(
+            path_completions("/usr/", "l", &names),
+            vec!["/usr/lib/", "/usr/Local/", "/usr/logs/"]
+        );
+        assert_eq!(path_completions("/usr/", "LO", &names), vec!["/usr/Local/", "/usr/logs/"]);
+        assert_eq!(path_completions("/", "", &names).len(), 4);
+        assert_eq!(path_completions("/", ".", &names), vec!["/.cache/"]);
+        assert!(path_completions("/", "x", &names).is_empty());
+        assert_eq!(path_completions("C:\\", "b", &names), vec!["C:\\bin\\"]);
+    }
+
+    #[test]
+    fn test_is_previewable_image() {
+        assert!(is_previewable_image("a.PNG"));
+        assert!(is_previewable_image("photo.jpeg"));
+        assert!(!is_previewable_image("notes.txt"));
+    }
+
+    #[test]
+    fn test_decode_text_preview() {
+        let utf8 = TextEncoding::Utf8;
+        assert_eq!(decode_text_preview("你好".as_bytes(), utf8).as_deref(), Some("你好"));
+        assert!(decode_text_preview(&[0x89, b'P', b'N', b'G', 0], utf8).is_none());
+        assert_eq!(decode_text_preview(&[b'a', 0xff], utf8).as_deref(), Some("a\u{fffd}"));
+    }
+
+    #[test]
+    fn test_decode_legacy_encodings() {
+        // "你好" 的 GBK 编码
+        let gbk = [0xc4, 0xe3, 0xba, 0xc3];
+        assert_eq!(decode_text_preview(&gbk, TextEncoding::Gbk).as_deref(), Some("你好"));
+        assert_eq!(decode_text_preview(&[b'c', 0xe9], TextEncoding::Latin1).as_deref(), Some("cé"));
+        assert!(decode_text_preview(&[0], TextEncoding::Gbk).is_none());
+    }
+
+    #[test]
+    fn test_encode_text_round_trip() {
+        for encoding in [TextEncoding::Utf8, TextEncoding::Gbk] {
+            let bytes = encode_text("日志 log", encoding).unwrap();
+            assert_eq!(decode_text_preview(&bytes, encoding).as_deref(), Some("日志 log"));
+        }
+        assert_eq!(encode_text("你好", TextEncoding::Gbk).unwrap(), vec![0xc4, 0xe3, 0xba, 0xc3]);
+        assert_eq!(encode_text("café", TextEncoding::Latin1).unwrap(), b"caf\xe9".to_vec());
+    }
+
+    #[test]
+    fn test_decode_text_for_edit() {
+        assert_eq!(decode_text_for_edit("你好".as_bytes(), TextEncoding::Utf8).unwrap(), "你好");
+        assert!(decode_text_for_edit(&[b'a', 0xff], TextEncoding::Utf8).is_err());
+        assert!(decode_text_for_edit(&[b'a', 0], TextEncoding::Latin1).is_err());
+        // "你好" 的 GBK 编码不是合法的 UTF-8，截断的 GBK 双字节也会报错
+        let gbk = [0xc4, 0xe3, 0xba, 0xc3];
+        assert!(decode_text_for_edit(&gbk, TextEncoding::Utf8).is_err());
+        assert_eq!(decode_text_for_edit(&gbk, TextEncoding::Gbk).unwrap(), "你好");
+        assert!(decode_text_for_edit(&gbk[..3], TextEncoding::Gbk).is_err());
+        // BOM 原样保留，保存时随文本写回
+        assert_eq!(decode_text_for_edit(b"\xef\xbb\xbfa", TextEncoding::Utf8).unwrap(), "\u{feff}a");
+    }
+
+    #[test]
+    fn test_encode_text_unmappable() {
+        assert!(encode_text("你好", TextEncoding::Latin1).is_err());
+        assert!(encode_text("😀", TextEncoding::Gbk).is_err());
+    }
+
+    #[test]
+    fn test_text_encoding_labels() {
+        for encoding in [TextEncoding::Utf8, TextEncoding::Gbk, TextEncoding::Latin1] {
+            assert_eq!(TextEncoding::from_label(encoding.label()), encoding);
+        }
+        assert_eq!(TextEncoding::from_label("unknown"), TextEncoding::Utf8);
+    }
+
+    #[test]
+    fn test_default_server_indices() {
+        let servers = vec![
+            ServerConfig::default(),
+            ServerConfig { is_default: true, ..Default::default() },
+            ServerConfig { is_default: true, ..Default::default() },
+        ];
+        assert_eq!(default_server_indices(&servers), vec![1, 2]);
+        assert!(default_server_indices(&servers[..1]).is_empty());
+    }
+
+    fn names(list: &[&str]) -> Vec<String> {
+        list.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn test_plan_bulk_rename() {
+        let selected = names(&["a.jpg", "b.jpg"]);
+        let existing = names(&["a.jpg", "b.jpg", "notes.txt"]);
+        let plan = plan_bulk_rename(&selected, &existing, "img_{n}_{name}.jpg").unwrap();
+        assert_eq!(
+            plan,
+            vec![
+                ("a.jpg".to_string(), "img_1_a.jpg".to_string()),
+                ("b.jpg".to_string(), "img_2_b.jpg".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_plan_bulk_rename_rejects_clobbering() {
+        let selected = names(&["a.jpg", "b.jpg"]);
+        let existing = names(&["a.jpg", "b.jpg", "x_2.jpg"]);
+        // 与未选中的条目重名
+        assert!(plan_bulk_rename(&selected, &existing, "x_{n}.jpg").is_err());
+        // 多个文件生成同一名称
+        assert!(plan_bulk_rename(&selected, &existing, "same.jpg").is_err());
+        // 非法名称
+        assert!(plan_bulk_rename(&selected, &existing, "../{n}").is_err());
+    }
+
+    #[test]
+    fn test_plan_bulk_rename_name_only_template() {
+        let selected = names(&["a.jpeg", "b.jpeg"]);
+        let existing = names(&["a.jpeg", "b.jpeg"]);
+        let plan = plan_bulk_rename(&selected, &existing, "{name}.jpg").unwrap();
+        assert_eq!(plan[1], ("b.jpeg".to_string(), "b.jpg".to_string()));
+        // 去掉扩展名后重名时仍然拒绝
+        let selected = names(&["a.jpeg", "a.png"]);
+        assert!(plan_bulk_rename(&selected, &selected, "{name}.jpg").is_err());
+    }
+
+    #[test]
+    fn test_format_command_output() {
+        assert_eq!(
+            format_command_output("uptime", "up 3 days\n", "", 0),
+            "$ uptime\nup 3 days\n[退出码 0]"
+        );
+        assert_eq!(
+            format_command_output("systemctl restart x", "", "Failed\n", 5),
+            "$ systemctl restart x\n[stderr]\nFailed\n[退出码 5]"
+        );
+    }
+
+    #[test]
+    fn test_delete_failure_summary() {
+        let failures = vec![
+            (
+                "/srv/a".to_string(),
+                "rm: cannot remove '/srv/a/x': Permission denied\nrm: cannot remove '/srv/a/y'".to_string(),
+            ),
+            ("/srv/b".to_string(), "命令退出码 1".to_string()),
+        ];
+        let (header, lines) = delete_failure_summary(&failures);
+        assert_eq!(header, "2 个项目删除失败");
+        assert_eq!(
+            lines,
+            vec![
+                "/srv/a: rm: cannot remove '/srv/a/x': Permission denied",
+                "/srv/b: 命令退出码 1",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_delete_confirm_message() {
+        assert_eq!(
+            delete_confirm_message(2, true, true),
+            "确定要删除远程的以下 2 个项目吗？此操作不可撤销。"
+        );
+        assert_eq!(delete_confirm_message(1, false, true), "确定要将以下 1 个项目移到回收站吗？");
+        assert_eq!(
+            delete_confirm_message(3, false, false),
+            "确定要删除以下 3 个项目吗？此操作不可撤销。"
+        );
+    }
+
+    #[test]
+    fn test_describe_list_error() {
+        assert_eq!(
+            describe_list_error("/root", "读取目录失败: [SFTP(3)] permission denied"),
+            "无法打开 /root: 权限不足"
+        );
+        assert_eq!(
+            describe_list_error("/nope", "命令失败: find: '/nope': No such file or directory"),
+            "无法打开 /nope: 目录不存在"
+        );
+        assert_eq!(describe_list_error("/x", "超时"), "无法打开 /x: 超时");
+    }
+
+    #[test]
+    fn test_scp_commands() {
+        let mut server = ServerConfig {
+            user: "deploy".to_string(),
+            host: "10.0.0.5".to_string(),
+            ..ServerConfig::default()
+        };
+        assert_eq!(
+            scp_download_command(&server, "/var/log/app.log"),
+            "scp deploy@10.0.0.5:'/var/log/app.log'"
+        );
+
+        server.port = 2222;
+        server.host = "::1".to_string();
+        server.proxy_jump = Some("jump@gw".to_string());
+        assert_eq!(
+            scp_upload_command(&server, "/home/me/a b.txt", "/srv"),
+            "scp -P 2222 -J jump@gw '/home/me/a b.txt' deploy@[::1]:'/srv/'"
+        );
+    }
+
+    #[test]
+    fn test_server_tags_and_filter() {
+        let tagged = |name: &str, tag: Option<&str>| ServerConfig {
+            name: name.to_string(),
+            tag: tag.map(str::to_string),
+            ..Default::default()
+        };
+        let servers = vec![
+            tagged("web1", Some("prod")),
+            tagged("dev", None),
+            tagged("stage", Some("staging")),
+            tagged("web2", Some("prod")),
+        ];
+        assert_eq!(server_tags(&servers), vec!["prod", "staging"]);
+        assert_eq!(servers_with_tag(&servers, "prod"), vec![0, 3]);
+        assert_eq!(servers_with_tag(&servers, ""), vec![0, 1, 2, 3]);
+        assert!(servers_with_tag(&servers, "missing").is_empty());
+        assert_eq!(server_label(&servers[0]), "[prod] web1");
+        assert_eq!(server_label(&servers[1]), "dev");
+    }
+
+    #[test]
+    fn test_sync_browse_targets() {
+        let local = Path::new("/home/me/site");
+        assert_eq!(
+            sync_remote_target(local, Path::new("/home/me/site/css/img"), "/var/www").as_deref(),
+            Some("/var/www/css/img")
+        );
+        assert_eq!(sync_remote_target(local, Path::new("/home/me"), "/var/www").as_deref(), Some("/var"));
+        assert_eq!(
+            sync_remote_target(local, Path::new("/home/me/docs"), "/var/www").as_deref(),
+            Some("/var/docs")
+        );
+        assert_eq!(sync_remote_target(local, Path::new("/"), "/srv"), None);
+        assert_eq!(sync_remote_target(local, Path::new(""), "/srv"), None);
+
+        assert_eq!(
+            sync_local_target("/var/www", "/var/www/js", local),
+            Some(PathBuf::from("/home/me/site/js"))
+        );
+        assert_eq!(sync_local_target("/var/www", "/var", local), Some(PathBuf::from("/home/me")));
+        assert_eq!(sync_local_target("/a/b/c/d/e", "/", Path::new("/x")), None);
+    }
+
+    #[test]
+    fn test_move_focus() {
+        assert_eq!(move_focus(None, 0, "down"), None);
+        assert_eq!(move_focus(None, 5, "down"), Some(0));
+        assert_eq!(move_focus(None, 5, "up"), Some(0));
+        assert_eq!(move_focus(Some(2), 5, "up"), Some(1));
+        assert_eq!(move_focus(Some(0), 5, "up"), Some(0));
+        assert_eq!(move_focus(Some(4), 5, "down"), Some(4));
+        assert_eq!(move_focus(Some(9), 5, "up"), Some(3));
+        assert_eq!(move_focus(Some(2), 30, "pagedown"), Some(12));
+        assert_eq!(move_focus(Some(25), 30, "pagedown"), Some(29));
+        assert_eq!(move_focus(Some(5), 30, "pageup"), Some(0));
+        assert_eq!(move_focus(Some(2), 5, "end"), Some(4));
+        assert_eq!(move_focus(None, 5, "home"), Some(0));
+        assert_eq!(move_focus(Some(2), 5, "left"), None);
+    }
+}
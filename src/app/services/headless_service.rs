@@ -0,0 +1,141 @@
+//! 不依赖界面的列表与传输，供命令行无界面模式调用
+
+use std::fs;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+use anyhow::{anyhow, Result};
+use serde::Serialize;
+
+use crate::app::services::quick_upload_service;
+use crate::domain::config::ServerConfig;
+use crate::infra::remote_fs::{self, RemoteEntry};
+use crate::infra::ssh::{FileTransfer, SshUploader};
+use crate::shared::remote_path;
+use crate::shared::target_template::TemplateContext;
+
+/// 单个条目的传输结果
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TransferOutcome {
+    pub source: String,
+    pub target: String,
+    pub is_dir: bool,
+    pub ok: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl TransferOutcome {
+    fn new(source: String, target: String, is_dir: bool, result: Result<()>) -> Self {
+        Self {
+            source,
+            target,
+            is_dir,
+            ok: result.is_ok(),
+            error: result.err().map(|e| e.to_string()),
+        }
+    }
+}
+
+/// 是否全部成功
+pub fn all_ok(outcomes: &[TransferOutcome]) -> bool {
+    outcomes.iter().all(|o| o.ok)
+}
+
+/// 列出远程目录
+pub fn list(config: &ServerConfig, path: &str) -> Result<Vec<RemoteEntry>> {
+    let uploader = SshUploader::connect(config)?;
+    remote_fs::list_dir_sftp(&uploader, path)
+}
+
+/// 把本地文件或目录上传到远程目录，单个条目失败不影响其余条目
+pub fn upload(config: &ServerConfig, sources: &[PathBuf], remote_dir: &str) -> Result<Vec<TransferOutcome>> {
+    let mut uploader = SshUploader::connect(config)?;
+    let ctx = TemplateContext::now(&config.name);
+    let remote_dir = quick_upload_service::resolve_target_dir(&uploader, remote_dir, &ctx)?;
+    let outcomes = sources
+        .iter()
+        .map(|local| {
+            let source = local.to_string_lossy().to_string();
+            let name = match local.file_name() {
+                Some(name) => name.to_string_lossy().to_string(),
+                None => {
+                    let err = anyhow!("无效的文件名: {}", source);
+                    return TransferOutcome::new(source, String::new(), false, Err(err));
+                }
+            };
+            let target = remote_path::join(&remote_dir, &name);
+            let is_dir = local.is_dir();
+            let result = if !local.exists() {
+                Err(anyhow!("本地路径不存在: {}", source))
+            } else if is_dir {
+                uploader.upload_dir(local, Path::new(&target), |_| {})
+            } else {
+                uploader.upload(local, Path::new(&target), |_| {})
+            };
+            TransferOutcome::new(source, target, is_dir, result)
+        })
+        .collect();
+    Ok(outcomes)
+}
+
+/// 把流式输入 (如标准输入) 上传为远程文件 `remote_file`，回调已上传的字节数
+pub fn upload_stream(
+    config: &ServerConfig,
+    reader: &mut impl Read,
+    remote_file: &str,
+    callback: impl Fn(u64),
+) -> Result<TransferOutcome> {
+    let uploader = SshUploader::connect(config)?;
+    let target = remote_fs::expand_tilde(&uploader, remote_file);
+    let result = uploader.upload_reader(reader, Path::new(&target), callback).map(|_| ());
+    Ok(TransferOutcome::new("-".to_string(), target, false, result))
+}
+
+/// 把远程文件或目录下载到本地目录，本地目录不存在时自动创建
+pub fn download(config: &ServerConfig, sources: &[String], local_dir: &Path) -> Result<Vec<TransferOutcome>> {
+    fs::create_dir_all(local_dir).map_err(|e| anyhow!("无法创建本地目录 {:?}: {}", local_dir, e))?;
+    let mut uploader = SshUploader::connect(config)?;
+    let outcomes = sources
+        .iter()
+        .map(|remote| {
+            let source = remote_fs::expand_tilde(&uploader, remote);
+            let name = match remote_path::file_name(&source) {
+                Some(name) => name,
+                None => {
+                    let err = anyhow!("无效的文件名: {}", source);
+                    return TransferOutcome::new(source, String::new(), false, Err(err));
+                }
+            };
+            let target = local_dir.join(name);
+            let (is_dir, result) = match remote_fs::remote_is_dir(&uploader, &source) {
+                Ok(true) => (true, uploader.download_dir(Path::new(&source), &target, |_| {})),
+                Ok(false) => (false, uploader.download(Path::new(&source), &target, |_| {})),
+                Err(e) => (false, Err(e)),
+            };
+            let target = target.to_string_lossy().to_string();
+            TransferOutcome::new(source, target, is_dir, result)
+        })
+        .collect();
+    Ok(outcomes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_outcome_from_result() {
+        let ok = TransferOutcome::new("a".into(), "/tmp/a".into(), false, Ok(()));
+        assert!(ok.ok);
+        assert!(ok.error.is_none());
+
+        let failed = TransferOutcome::new("b".into(), "/tmp/b".into(), true, Err(anyhow!("连接失败")));
+        assert!(!failed.ok);
+        assert_eq!(failed.error.as_deref(), Some("连接失败"));
+
+        assert!(all_ok(std::slice::from_ref(&ok)));
+        assert!(!all_ok(&[ok, failed]));
+        assert!(all_ok(&[]));
+    }
+}
@@ -1,4 +1,6 @@
+pub mod compare_service;
 pub mod explorer_service;
+pub mod headless_service;
 pub mod quick_upload_service;
 pub mod settings_service;
 pub mod transfer_service;
@@ -1,27 +1,229 @@
 use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Sender};
+use std::thread;
 
 use anyhow::{anyhow, Result};
 
 use crate::domain::config::ServerConfig;
+use crate::domain::transfer::{TransferEvent, PROGRESS_INDETERMINATE};
+use crate::infra::remote_fs;
 use crate::infra::ssh::{FileTransfer, SshUploader};
 use crate::shared::path_utils;
+use crate::shared::target_template::{self, TemplateContext};
 
 pub fn validate_upload_path(local_path: &std::path::Path) -> Result<()> {
     path_utils::ensure_file_exists(local_path)
 }
 
-pub fn execute_upload(
-    config: ServerConfig,
-    local_path: PathBuf,
+/// 按名称选择服务器；未指定名称时依次取默认服务器和第一台
+pub fn select_server(servers: &[ServerConfig], name: Option<&str>) -> Result<ServerConfig> {
+    let found = match name {
+        Some(name) => servers
+            .iter()
+            .find(|s| s.name == name)
+            .or_else(|| servers.iter().find(|s| s.name.eq_ignore_ascii_case(name)))
+            .ok_or_else(|| anyhow!("找不到服务器: {}", name))?,
+        None => servers
+            .iter()
+            .find(|s| s.is_default)
+            .or_else(|| servers.first())
+            .ok_or_else(|| anyhow!("尚未配置任何服务器"))?,
+    };
+    Ok(found.clone())
+}
+
+/// 上传到服务器的默认目录，过程通过 events 报告，便于脚本等非 UI 调用方复用；
+/// 连接或解析目标目录失败同样以 `Failed` 事件结束。
+/// 目录模板按 `ctx` 展开，同一批文件传入同一个 `ctx` 才会落到同一目录
+pub fn execute_upload_with_events(
+    config: &ServerConfig,
+    local_path: &Path,
+    ctx: &TemplateContext,
+    events: &Sender<TransferEvent>,
+) -> Result<()> {
+    let prepared = (|| -> Result<(SshUploader, PathBuf)> {
+        let (uploader, target_dir) = prepare_upload(config, ctx)?;
+        Ok((uploader, remote_path_in(&target_dir, local_path)?))
+    })();
+    let (mut uploader, remote_path) = match prepared {
+        Ok(prepared) => prepared,
+        Err(e) => {
+            let _ = events.send(TransferEvent::Failed(e.to_string()));
+            return Err(e);
+        }
+    };
+
+    uploader.upload_with_events(local_path, &remote_path, events)
+}
+
+/// 连接服务器并解析默认目标目录，同一批文件可复用返回的连接和目录
+pub fn prepare_upload(config: &ServerConfig, ctx: &TemplateContext) -> Result<(SshUploader, String)> {
+    let uploader = SshUploader::connect(config)?;
+    let target_dir = resolve_target_dir(&uploader, &config.default_target_dir, ctx)?;
+    Ok((uploader, target_dir))
+}
+
+/// 把本地文件上传到已解析的目标目录下，回调 0.0~1.0 的进度或 `PROGRESS_INDETERMINATE`
+pub fn upload_to_dir(
+    uploader: &mut SshUploader,
+    local_path: &Path,
+    target_dir: &str,
     callback: impl Fn(f32),
 ) -> Result<()> {
-    let mut uploader = SshUploader::connect(&config)?;
+    let remote_path = remote_path_in(target_dir, local_path)?;
+    uploader.upload(local_path, &remote_path, callback)
+}
 
+/// 目标目录下与本地文件同名的远程路径
+fn remote_path_in(target_dir: &str, local_path: &Path) -> Result<PathBuf> {
     let file_name = local_path
         .file_name()
         .ok_or_else(|| anyhow!("无效的文件名"))?;
-    let remote_path = Path::new(&config.default_target_dir).join(file_name);
+    Ok(Path::new(target_dir).join(file_name))
+}
+
+/// 展开目标目录模板和开头的 `~`；含占位符时目录多半尚不存在，先逐级创建
+pub fn resolve_target_dir(uploader: &SshUploader, template: &str, ctx: &TemplateContext) -> Result<String> {
+    let expanded = target_template::expand_target_template(template, ctx);
+    let dir = remote_fs::expand_tilde(uploader, &expanded);
+    if target_template::has_placeholders(template) {
+        uploader.remote_mkdir(Path::new(&dir))?;
+    }
+    Ok(dir)
+}
+
+/// 在 `execute_upload_with_events` 之上把进度事件转换为 0.0~1.0 回调，
+/// 无法获知进度时回调 `PROGRESS_INDETERMINATE`
+pub fn execute_upload(
+    config: ServerConfig,
+    local_path: PathBuf,
+    ctx: &TemplateContext,
+    callback: impl Fn(f32),
+) -> Result<()> {
+    let (tx, rx) = mpsc::channel();
+    thread::scope(|scope| {
+        let handle = scope.spawn(move || execute_upload_with_events(&config, &local_path, ctx, &tx));
+        // 上传线程结束后 tx 被释放，循环随之退出
+        for event in rx {
+            match event {
+                TransferEvent::Progress { bytes, total } if total > 0 => {
+                    callback(bytes as f32 / total as f32);
+                }
+                TransferEvent::Indeterminate => callback(PROGRESS_INDETERMINATE),
+                _ => {}
+            }
+        }
+        handle
+            .join()
+            .unwrap_or_else(|_| Err(anyhow!("上传线程异常退出")))
+    })
+}
+
+/// 同时上传到多台服务器，每台服务器一个线程。
+/// 回调参数为 (服务器下标, 进度)；返回值按输入顺序给出各服务器的名称与结果。
+/// 目录模板的时间取自 `ctx`，服务器名按各自配置替换
+pub fn execute_broadcast_upload(
+    configs: Vec<ServerConfig>,
+    local_path: PathBuf,
+    ctx: &TemplateContext,
+    callback: impl Fn(usize, f32) + Sync,
+) -> Vec<(String, Result<()>)> {
+    let callback = &callback;
+    thread::scope(|scope| {
+        let handles: Vec<_> = configs
+            .into_iter()
+            .enumerate()
+            .map(|(index, config)| {
+                let local_path = local_path.clone();
+                let name = config.name.clone();
+                let ctx = ctx.for_server(&config.name);
+                let handle = scope.spawn(move || {
+                    execute_upload(config, local_path, &ctx, |p| callback(index, p))
+                });
+                (name, handle)
+            })
+            .collect();
+
+        handles
+            .into_iter()
+            .map(|(name, handle)| {
+                let result = handle
+                    .join()
+                    .unwrap_or_else(|_| Err(anyhow!("上传线程异常退出")));
+                (name, result)
+            })
+            .collect()
+    })
+}
+
+/// 汇总多服务器上传结果，用于状态栏显示
+pub fn summarize_broadcast(results: &[(String, Result<()>)]) -> String {
+    let ok = results.iter().filter(|(_, r)| r.is_ok()).count();
+    let mut lines = vec![format!("完成 {}/{} 台服务器", ok, results.len())];
+    for (name, result) in results {
+        match result {
+            Ok(_) => lines.push(format!("✔ {}", name)),
+            Err(e) => lines.push(format!("✘ {}: {}", name, e)),
+        }
+    }
+    lines.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_summarize_broadcast() {
+        let results = vec![
+            ("prod".to_string(), Ok(())),
+            ("test".to_string(), Err(anyhow!("连接超时"))),
+        ];
+        let summary = summarize_broadcast(&results);
+        assert_eq!(summary, "完成 1/2 台服务器\n✔ prod\n✘ test: 连接超时");
+    }
+
+    #[test]
+    fn test_select_server() {
+        let server = |name: &str, is_default: bool| ServerConfig {
+            name: name.to_string(),
+            is_default,
+            ..Default::default()
+        };
+        let servers = vec![server("test", false), server("Prod", true)];
+        assert_eq!(select_server(&servers, Some("test")).unwrap().name, "test");
+        assert_eq!(select_server(&servers, Some("prod")).unwrap().name, "Prod");
+        assert_eq!(select_server(&servers, None).unwrap().name, "Prod");
+        assert!(select_server(&servers, Some("missing")).is_err());
+        assert!(select_server(&[], None).is_err());
+
+        let no_default = vec![server("a", false), server("b", false)];
+        assert_eq!(select_server(&no_default, None).unwrap().name, "a");
+    }
+
+    #[test]
+    fn test_connect_failure_emits_failed() {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        drop(listener);
+        let config = ServerConfig {
+            host: "127.0.0.1".to_string(),
+            port,
+            connect_timeout_secs: 2,
+            ..Default::default()
+        };
+        let (tx, rx) = mpsc::channel();
+        let ctx = TemplateContext::now(&config.name);
+        assert!(execute_upload_with_events(&config, Path::new("a.txt"), &ctx, &tx).is_err());
+        drop(tx);
+        let events: Vec<TransferEvent> = rx.into_iter().collect();
+        assert!(matches!(events.as_slice(), [TransferEvent::Failed(_)]));
+    }
 
-    uploader.upload(&local_path, &remote_path, callback)?;
-    Ok(())
+    #[test]
+    fn test_broadcast_with_no_servers() {
+        let ctx = TemplateContext::now("");
+        let results = execute_broadcast_upload(Vec::new(), PathBuf::from("a.txt"), &ctx, |_, _| {});
+        assert!(results.is_empty());
+    }
 }
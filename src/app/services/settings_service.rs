@@ -1,16 +1,21 @@
+use std::path::Path;
 use std::sync::{Arc, Mutex};
 
 use anyhow::{anyhow, Result};
 
 use crate::domain::config::{AppConfig, ServerConfig};
 use crate::domain::ports::ConfigRepository;
+use crate::infra::ssh::client::{self, PROBE_TIMEOUT};
 use crate::infra::ssh::SshUploader;
 
+/// 同时进行的可达性探测数量
+const PROBE_CONCURRENCY: usize = 8;
+
 pub fn save_server(
     config_state: &Arc<Mutex<AppConfig>>,
     repo: &Arc<dyn ConfigRepository + Send + Sync>,
     index: i32,
-    new_server: ServerConfig,
+    mut new_server: ServerConfig,
 ) -> Result<AppConfig> {
     let mut guard = config_state
         .lock()
@@ -29,6 +34,10 @@ pub fn save_server(
                 s.is_default = false;
             }
         }
+        // 设置页不编辑浏览记录，保留原值
+        new_server.last_remote_path =
+            guard.servers[index as usize].last_remote_path.take();
+        new_server.recent_dirs = std::mem::take(&mut guard.servers[index as usize].recent_dirs);
         guard.servers[index as usize] = new_server;
     }
 
@@ -53,6 +62,115 @@ pub fn delete_server(
     Ok(guard.clone())
 }
 
+/// 复制指定服务器为新条目：名称追加 " (副本)"，不作为默认服务器。
+/// 返回更新后的配置和新条目的下标
+pub fn duplicate_server(
+    config_state: &Arc<Mutex<AppConfig>>,
+    repo: &Arc<dyn ConfigRepository + Send + Sync>,
+    index: i32,
+) -> Result<(AppConfig, usize)> {
+    let mut guard = config_state
+        .lock()
+        .map_err(|_| anyhow!("配置锁定失败"))?;
+
+    let source = usize::try_from(index)
+        .ok()
+        .and_then(|i| guard.servers.get(i))
+        .ok_or_else(|| anyhow!("服务器不存在"))?;
+    let mut copy = source.clone();
+    copy.name = copy_name(&guard.servers, &source.name);
+    copy.is_default = false;
+    copy.last_remote_path = None;
+    guard.servers.push(copy);
+
+    repo.save(&guard)?;
+    Ok((guard.clone(), guard.servers.len() - 1))
+}
+
+/// 修改应用级设置 (设置页"通用"部分) 并保存
+pub fn update_app_settings(
+    config_state: &Arc<Mutex<AppConfig>>,
+    repo: &Arc<dyn ConfigRepository + Send + Sync>,
+    update: impl FnOnce(&mut AppConfig),
+) -> Result<()> {
+    let mut guard = config_state
+        .lock()
+        .map_err(|_| anyhow!("配置锁定失败"))?;
+    update(&mut guard);
+    repo.save(&guard)
+}
+
+/// 副本名称，与已有服务器重名时追加序号
+fn copy_name(servers: &[ServerConfig], name: &str) -> String {
+    let taken = |candidate: &str| servers.iter().any(|s| s.name == candidate);
+    let base = format!("{} (副本)", name);
+    if !taken(&base) {
+        return base;
+    }
+    (2..)
+        .map(|n| format!("{} (副本 {})", name, n))
+        .find(|candidate| !taken(candidate))
+        .unwrap_or(base)
+}
+
+/// 追加导入的服务器，跳过与已有服务器同名的条目
+pub fn import_servers(
+    config_state: &Arc<Mutex<AppConfig>>,
+    repo: &Arc<dyn ConfigRepository + Send + Sync>,
+    servers: Vec<ServerConfig>,
+) -> Result<AppConfig> {
+    let mut guard = config_state
+        .lock()
+        .map_err(|_| anyhow!("配置锁定失败"))?;
+
+    let mut added = 0;
+    for server in servers {
+        if guard.servers.iter().any(|s| s.name == server.name) {
+            continue;
+        }
+        guard.servers.push(server);
+        added += 1;
+    }
+    if added > 0 {
+        repo.save(&guard)?;
+    }
+    Ok(guard.clone())
+}
+
+/// 导出当前配置到文件，可选去掉密码
+pub fn export_config(
+    config_state: &Arc<Mutex<AppConfig>>,
+    repo: &Arc<dyn ConfigRepository + Send + Sync>,
+    path: &Path,
+    strip_passwords: bool,
+) -> Result<()> {
+    let snapshot = config_state
+        .lock()
+        .map_err(|_| anyhow!("配置锁定失败"))?
+        .clone();
+    let snapshot = if strip_passwords {
+        snapshot.without_passwords()
+    } else {
+        snapshot
+    };
+    repo.export_to(&snapshot, path)
+}
+
+/// 从文件导入配置并合并到当前配置
+pub fn import_config(
+    config_state: &Arc<Mutex<AppConfig>>,
+    repo: &Arc<dyn ConfigRepository + Send + Sync>,
+    path: &Path,
+) -> Result<AppConfig> {
+    let imported = repo.import_from(path)?;
+    let mut guard = config_state
+        .lock()
+        .map_err(|_| anyhow!("配置锁定失败"))?;
+    guard.merge_from(imported);
+    repo.save(&guard)?;
+    Ok(guard.clone())
+}
+
 pub fn load_server(
     config_state: &Arc<Mutex<AppConfig>>,
     index: i32,
@@ -65,7 +183,88 @@ pub fn load_server(
     }
 }
 
-pub fn test_connection(server_config: &ServerConfig) -> (Result<()>, String) {
-    let (res, logs) = SshUploader::connect_with_log(server_config);
+/// 测试连接；指定 `timeout_secs` 时本次测试的连接与读写超时都改用该值
+pub fn test_connection(server_config: &ServerConfig, timeout_secs: Option<u64>) -> (Result<()>, String) {
+    let config = with_test_timeout(server_config, timeout_secs);
+    let (res, logs) = SshUploader::connect_with_log(&config);
     (res.map(|_| ()), logs)
 }
+
+/// 探测每台服务器的 host:port 能否建立 TCP 连接，结果与 `servers` 一一对应。
+/// 经跳板机的服务器无法直接探测，结果为 None
+pub fn probe_reachability(servers: &[ServerConfig]) -> Vec<Option<bool>> {
+    let mut results = Vec::with_capacity(servers.len());
+    for chunk in servers.chunks(PROBE_CONCURRENCY) {
+        std::thread::scope(|scope| {
+            let handles: Vec<_> = chunk
+                .iter()
+                .map(|s| scope.spawn(move || probe_one(s)))
+                .collect();
+            results.extend(handles.into_iter().map(|h| h.join().unwrap_or(Some(false))));
+        });
+    }
+    results
+}
+
+fn probe_one(server: &ServerConfig) -> Option<bool> {
+    let via_jump = server.proxy_jump.as_deref().is_some_and(|j| !j.trim().is_empty());
+    if via_jump || server.host.trim().is_empty() {
+        return None;
+    }
+    Some(client::is_reachable(&server.host, server.port, PROBE_TIMEOUT))
+}
+
+fn with_test_timeout(server_config: &ServerConfig, timeout_secs: Option<u64>) -> ServerConfig {
+    let mut config = server_config.clone();
+    if let Some(secs) = timeout_secs.filter(|&s| s > 0) {
+        config.connect_timeout_secs = secs;
+        config.io_timeout_secs = secs;
+    }
+    config
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_probe_reachability() {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let open = listener.local_addr().unwrap().port();
+        let closed = {
+            let l = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+            l.local_addr().unwrap().port()
+        };
+        let server = |port: u16, jump: Option<&str>| ServerConfig {
+            host: "127.0.0.1".into(),
+            port,
+            proxy_jump: jump.map(str::to_string),
+            ..ServerConfig::default()
+        };
+        let servers = vec![server(open, None), server(closed, None), server(open, Some("bastion"))];
+        assert_eq!(probe_reachability(&servers), vec![Some(true), Some(false), None]);
+        assert!(probe_reachability(&[]).is_empty());
+    }
+
+    #[test]
+    fn test_with_test_timeout() {
+        let server = ServerConfig::default();
+        let cfg = with_test_timeout(&server, Some(5));
+        assert_eq!((cfg.connect_timeout_secs, cfg.io_timeout_secs), (5, 5));
+        let cfg = with_test_timeout(&server, None);
+        assert_eq!(cfg.connect_timeout_secs, server.connect_timeout_secs);
+        assert_eq!(cfg.io_timeout_secs, server.io_timeout_secs);
+        let cfg = with_test_timeout(&server, Some(0));
+        assert_eq!(cfg.connect_timeout_secs, server.connect_timeout_secs);
+    }
+
+    #[test]
+    fn test_copy_name() {
+        let server = |name: &str| ServerConfig { name: name.into(), ..Default::default() };
+        let servers = vec![server("web")];
+        assert_eq!(copy_name(&servers, "web"), "web (副本)");
+
+        let servers = vec![server("web"), server("web (副本)"), server("web (副本 2)")];
+        assert_eq!(copy_name(&servers, "web"), "web (副本 3)");
+    }
+}
@@ -1,10 +1,247 @@
-use crate::domain::transfer::TransferStatus;
+use chrono::{DateTime, Local};
+use std::collections::BTreeSet;
 
-pub fn status_to_text(status: &TransferStatus) -> (&'static str, String) {
+use crate::domain::history::HistoryEntry;
+use crate::domain::transfer::{Direction, TransferStatus, TransferTask, DIR_SCAN_PROGRESS};
+
+/// 任务状态转 UI 文本，队列暂停时等待中的任务显示为 "paused"
+pub fn status_to_text(status: &TransferStatus, paused: bool) -> (&'static str, String) {
     match status {
+        TransferStatus::Pending if paused => ("paused", String::new()),
         TransferStatus::Pending => ("pending", String::new()),
         TransferStatus::InProgress => ("progress", String::new()),
         TransferStatus::Completed => ("done", String::new()),
         TransferStatus::Failed(e) => ("failed", e.clone()),
     }
 }
+
+/// 目录任务是否仍处于扫描阶段 (枚举目录树，尚未开始传输文件)
+pub fn is_scanning(task: &TransferTask) -> bool {
+    task.is_dir
+        && task.status == TransferStatus::InProgress
+        && task.progress > 0.0
+        && task.progress < DIR_SCAN_PROGRESS
+}
+
+fn is_unfinished(task: &TransferTask) -> bool {
+    matches!(task.status, TransferStatus::Pending | TransferStatus::InProgress)
+}
+
+/// 跟踪一批传输涉及的任务，队列结束时只统计这一批 (列表中保留的旧任务不计入)
+pub struct DrainTracker {
+    batch: BTreeSet<usize>,
+    /// 不小于该值的任务 ID 是上一批结束后入队的
+    next_id: usize,
+}
+
+impl DrainTracker {
+    /// 以当前队列为起点，已有的任务不属于任何批次
+    pub fn new(tasks: &[TransferTask]) -> Self {
+        Self {
+            batch: BTreeSet::new(),
+            next_id: tasks.iter().map(|t| t.id + 1).max().unwrap_or(0),
+        }
+    }
+
+    /// 每次刷新队列时调用；本批任务全部结束时返回 (成功数, 失败数) 并开始新的一批
+    pub fn observe(&mut self, tasks: &[TransferTask]) -> Option<(usize, usize)> {
+        for task in tasks {
+            // 两次刷新之间就完成的新任务也计入本批，重试的旧任务按重新开始计入
+            if task.id >= self.next_id || is_unfinished(task) {
+                self.batch.insert(task.id);
+            }
+        }
+        self.next_id = self.next_id.max(tasks.iter().map(|t| t.id + 1).max().unwrap_or(0));
+        if self.batch.is_empty() || tasks.iter().any(is_unfinished) {
+            return None;
+        }
+        let batch = std::mem::take(&mut self.batch);
+        let in_batch = |t: &&TransferTask| batch.contains(&t.id);
+        let done = tasks
+            .iter()
+            .filter(in_batch)
+            .filter(|t| t.status == TransferStatus::Completed)
+            .count();
+        let failed = tasks
+            .iter()
+            .filter(in_batch)
+            .filter(|t| matches!(t.status, TransferStatus::Failed(_)))
+            .count();
+        Some((done, failed))
+    }
+}
+
+/// 队列结束通知的正文
+pub fn drain_message(done: usize, failed: usize) -> String {
+    format!("传输完成: {} 成功, {} 失败", done, failed)
+}
+
+/// 剪贴板图片上传时使用的文件名，如 `clipboard_20240102_030405.png`
+pub fn clipboard_image_name(now: &DateTime<Local>) -> String {
+    now.format("clipboard_%Y%m%d_%H%M%S.png").to_string()
+}
+
+/// 由已结束的任务生成历史记录
+pub fn history_entry(task: &TransferTask, server: &str, is_dir: bool) -> HistoryEntry {
+    let direction = match task.direction {
+        Direction::Upload => "upload",
+        Direction::Download => "download",
+    };
+    let error = match &task.status {
+        TransferStatus::Failed(e) => Some(e.clone()),
+        _ => None,
+    };
+    HistoryEntry {
+        timestamp: chrono::Local::now().format("%Y-%m-%d %H:%M:%S").to_string(),
+        direction: direction.to_string(),
+        file_name: task.file_name.clone(),
+        local_path: task.local_path.to_string_lossy().to_string(),
+        remote_path: task.remote_path.clone(),
+        size: task.size,
+        is_dir,
+        duration_secs: task
+            .started_at
+            .map(|t| t.elapsed().as_secs_f64())
+            .unwrap_or(0.0),
+        server: server.to_string(),
+        success: task.status == TransferStatus::Completed,
+        error,
+    }
+}
+
+/// 传输前检查目标空间，`target` 为 "远程" 或 "本地"
+pub fn ensure_free_space(target: &str, required: u64, available: u64) -> anyhow::Result<()> {
+    if required > available {
+        anyhow::bail!("{}空间不足: 需要 {} 字节，可用 {} 字节", target, required, available);
+    }
+    Ok(())
+}
+
+/// 移动前核对目标与源的字节数一致，不一致时不删除源文件
+pub fn ensure_same_size(source: u64, target: u64) -> anyhow::Result<()> {
+    if source != target {
+        anyhow::bail!("大小校验不一致: 源 {} 字节，目标 {} 字节", source, target);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::transfer::{self, TransferQueue};
+    use std::path::PathBuf;
+
+    #[test]
+    fn test_history_entry_from_failed_task() {
+        let mut q = TransferQueue::new();
+        let id = q.enqueue(
+            Direction::Download,
+            PathBuf::from("/local/a.txt"),
+            "/remote/a.txt".into(),
+            "a.txt".into(),
+            42,
+            "prod".into(),
+        );
+        q.mark_failed(id, "timeout".into());
+        let entry = history_entry(&q.get_task(id).unwrap(), "prod", false);
+        assert_eq!(entry.direction, "download");
+        assert_eq!(entry.server, "prod");
+        assert_eq!(entry.size, 42);
+        assert!(!entry.success);
+        assert_eq!(entry.error.as_deref(), Some("timeout"));
+    }
+
+    #[test]
+    fn test_is_scanning() {
+        let mut q = TransferQueue::new();
+        let id = q.enqueue(
+            Direction::Download,
+            PathBuf::from("/local/dir"),
+            "/remote/dir".into(),
+            "dir".into(),
+            0,
+            "prod".into(),
+        );
+        q.mark_dir(id);
+        assert!(!is_scanning(&q.get_task(id).unwrap()));
+        q.update_progress(id, transfer::scan_progress(3));
+        assert!(is_scanning(&q.get_task(id).unwrap()));
+        q.update_progress(id, transfer::dir_progress(0, 5, 0.0));
+        assert!(!is_scanning(&q.get_task(id).unwrap()));
+    }
+
+    #[test]
+    fn test_drain_tracker_counts_per_batch() {
+        let mut q = TransferQueue::new();
+        let enqueue = |q: &mut TransferQueue, i: usize| {
+            q.enqueue(
+                Direction::Upload,
+                PathBuf::from(format!("/local/{}", i)),
+                format!("/remote/{}", i),
+                format!("{}", i),
+                10,
+                "prod".into(),
+            )
+        };
+        let mut tracker = DrainTracker::new(&q.snapshot());
+        let ids: Vec<usize> = (0..3).map(|i| enqueue(&mut q, i)).collect();
+        assert_eq!(tracker.observe(&q.snapshot()), None);
+
+        q.mark_completed(ids[0]);
+        q.mark_completed(ids[1]);
+        q.mark_failed(ids[2], "Permission denied".into());
+        assert_eq!(tracker.observe(&q.snapshot()), Some((2, 1)));
+        assert_eq!(drain_message(2, 1), "传输完成: 2 成功, 1 失败");
+        // 已经全部结束的队列不重复通知
+        assert_eq!(tracker.observe(&q.snapshot()), None);
+
+        // 下一批只统计新任务，两次刷新之间就完成的任务也计入
+        let id = enqueue(&mut q, 3);
+        q.mark_completed(id);
+        assert_eq!(tracker.observe(&q.snapshot()), Some((1, 0)));
+
+        // 重试的失败任务算作新的一批
+        assert!(q.retry(ids[2]));
+        assert_eq!(tracker.observe(&q.snapshot()), None);
+        q.mark_completed(ids[2]);
+        assert_eq!(tracker.observe(&q.snapshot()), Some((1, 0)));
+    }
+
+    #[test]
+    fn test_drain_tracker_ignores_existing_tasks() {
+        let mut q = TransferQueue::new();
+        let id = q.enqueue(
+            Direction::Download,
+            PathBuf::from("/local/a"),
+            "/remote/a".into(),
+            "a".into(),
+            1,
+            "prod".into(),
+        );
+        q.mark_completed(id);
+        let mut tracker = DrainTracker::new(&q.snapshot());
+        assert_eq!(tracker.observe(&q.snapshot()), None);
+    }
+
+    #[test]
+    fn test_ensure_free_space() {
+        assert!(ensure_free_space("远程", 100, 100).is_ok());
+        let err = ensure_free_space("远程", 2048, 1024).unwrap_err().to_string();
+        assert_eq!(err, "远程空间不足: 需要 2048 字节，可用 1024 字节");
+    }
+
+    #[test]
+    fn test_ensure_same_size() {
+        assert!(ensure_same_size(0, 0).is_ok());
+        assert!(ensure_same_size(4096, 4096).is_ok());
+        let err = ensure_same_size(4096, 100).unwrap_err().to_string();
+        assert_eq!(err, "大小校验不一致: 源 4096 字节，目标 100 字节");
+    }
+
+    #[test]
+    fn test_clipboard_image_name() {
+        use chrono::TimeZone;
+        let now = Local.with_ymd_and_hms(2024, 1, 2, 3, 4, 5).unwrap();
+        assert_eq!(clipboard_image_name(&now), "clipboard_20240102_030405.png");
+    }
+}
@@ -1,7 +1,9 @@
 use serde::{Deserialize, Serialize};
 
+use super::transfer::DirArchive;
+
 /// 服务器连接配置
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 pub struct ServerConfig {
     /// 服务器别名 (显示在下拉框中)
     pub name: String,
@@ -17,11 +19,153 @@ pub struct ServerConfig {
     pub password: Option<String>,
     /// 私钥路径 (如果 auth_type 为 key)
     pub key_path: Option<String>,
-    /// 默认上传的目标目录
+    /// 默认上传的目标目录，支持 `{date}`、`{hostname}` 等占位符 (见 `shared::target_template`)
     pub default_target_dir: String,
     /// 是否为默认服务器 (启动时自动选中)
     #[serde(default)]
     pub is_default: bool,
+    /// 跳板机 (格式 `user@host:port`，为空则直连)
+    #[serde(default)]
+    pub proxy_jump: Option<String>,
+    /// 上次浏览的远程目录 (连接时优先恢复)
+    #[serde(default)]
+    pub last_remote_path: Option<String>,
+    /// 最近访问的远程目录，最新的在前
+    #[serde(default)]
+    pub recent_dirs: Vec<String>,
+    /// SFTP 上传先写临时文件再改名，避免中断时留下损坏的目标文件
+    #[serde(default = "default_atomic_upload")]
+    pub atomic_upload: bool,
+    /// 传输后让目标文件保留源文件的修改时间 (尽力而为，失败只写入任务备注)；SCP 方式会一并保留权限位
+    #[serde(default = "default_preserve_mtime")]
+    pub preserve_mtime: bool,
+    /// SFTP 上传文本类文件时先 gzip 压缩，远程再解压 (适合慢速链路)
+    #[serde(default)]
+    pub compress_on_upload: bool,
+    /// TCP 连接超时 (秒)
+    #[serde(default = "default_connect_timeout_secs")]
+    pub connect_timeout_secs: u64,
+    /// 握手及读写超时 (秒)
+    #[serde(default = "default_io_timeout_secs")]
+    pub io_timeout_secs: u64,
+    /// 分组标签 (如 prod / staging)，用于在下拉框中筛选
+    #[serde(default)]
+    pub tag: Option<String>,
+    /// 标记颜色，格式 `#RRGGBB`
+    #[serde(default)]
+    pub color: Option<String>,
+    /// 传输方式: "auto" (先 SCP 失败再 SFTP) | "scp" | "sftp"
+    #[serde(default = "default_transfer_method")]
+    pub transfer_method: String,
+    /// SSH Agent 的 socket 路径 (Windows 下为命名管道)，为空时使用默认 Agent
+    #[serde(default)]
+    pub agent_socket: Option<String>,
+    /// 强制使用系统 ssh/scp 命令 (服务器的 SFTP 子系统不可用或只允许特定命令时)，
+    /// 与认证失败后的自动回退不同，libssh2 认证成功也不使用内置 SFTP
+    #[serde(default)]
+    pub force_native: bool,
+    /// 允许调用系统 ssh/scp/ssh-keygen；关闭后只在进程内通过 libssh2 连接和传输
+    #[serde(default = "default_allow_native_fallback")]
+    pub allow_native_fallback: bool,
+    /// 上传目录时跳过的条目，类似 .gitignore 的规则 (见 `shared::ignore`)
+    #[serde(default = "default_upload_ignore")]
+    pub upload_ignore: Vec<String>,
+    /// 上传目录时远程已有大小与修改时间一致的文件则跳过，关闭时全部覆盖
+    #[serde(default)]
+    pub skip_unchanged: bool,
+}
+
+fn default_transfer_method() -> String {
+    TransferMethod::Auto.as_str().to_string()
+}
+
+/// 文件传输方式
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TransferMethod {
+    /// 先尝试 SCP，失败后回退到 SFTP
+    Auto,
+    Scp,
+    Sftp,
+}
+
+impl TransferMethod {
+    /// 解析配置值，无法识别时视为 Auto
+    pub fn parse(s: &str) -> Self {
+        match s.trim().to_lowercase().as_str() {
+            "scp" => Self::Scp,
+            "sftp" => Self::Sftp,
+            _ => Self::Auto,
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Auto => "auto",
+            Self::Scp => "scp",
+            Self::Sftp => "sftp",
+        }
+    }
+}
+
+/// 批量传输中某个任务失败后的处理方式
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BatchPolicy {
+    /// 其余任务照常传输
+    ContinueOnError,
+    /// 同批任务逐个传输，任一失败即取消尚未开始的任务
+    StopOnError,
+}
+
+impl BatchPolicy {
+    /// 解析配置值，无法识别时视为 ContinueOnError
+    pub fn parse(s: &str) -> Self {
+        match s.trim().to_lowercase().as_str() {
+            "stop" => Self::StopOnError,
+            _ => Self::ContinueOnError,
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::ContinueOnError => "continue",
+            Self::StopOnError => "stop",
+        }
+    }
+}
+
+fn default_batch_policy() -> String {
+    BatchPolicy::ContinueOnError.as_str().to_string()
+}
+
+fn default_atomic_upload() -> bool {
+    true
+}
+
+fn default_preserve_mtime() -> bool {
+    true
+}
+
+fn default_allow_native_fallback() -> bool {
+    true
+}
+
+/// 默认跳过版本库目录和常见的系统/编辑器临时文件
+pub fn default_upload_ignore() -> Vec<String> {
+    [".git/", ".svn/", ".hg/", ".DS_Store", "Thumbs.db", "desktop.ini", "*.tmp", "*.swp", "~$*"]
+        .iter()
+        .map(|p| p.to_string())
+        .collect()
+}
+
+/// 编译时是否包含系统 ssh/scp 回退 (`native-fallback` 特性，默认启用)
+pub const NATIVE_FALLBACK_BUILT: bool = cfg!(feature = "native-fallback");
+
+pub fn default_connect_timeout_secs() -> u64 {
+    10
+}
+
+pub fn default_io_timeout_secs() -> u64 {
+    30
 }
 
 impl Default for ServerConfig {
@@ -36,7 +180,49 @@ impl Default for ServerConfig {
             key_path: None,
             default_target_dir: "/tmp".to_string(),
             is_default: false,
+            proxy_jump: None,
+            last_remote_path: None,
+            recent_dirs: Vec::new(),
+            atomic_upload: default_atomic_upload(),
+            preserve_mtime: default_preserve_mtime(),
+            compress_on_upload: false,
+            connect_timeout_secs: default_connect_timeout_secs(),
+            io_timeout_secs: default_io_timeout_secs(),
+            tag: None,
+            color: None,
+            transfer_method: default_transfer_method(),
+            agent_socket: None,
+            force_native: false,
+            allow_native_fallback: default_allow_native_fallback(),
+            upload_ignore: default_upload_ignore(),
+            skip_unchanged: false,
+        }
+    }
+}
+
+impl ServerConfig {
+    pub fn transfer_method(&self) -> TransferMethod {
+        TransferMethod::parse(&self.transfer_method)
+    }
+
+    /// 能否执行外部 ssh/scp 等命令：需编译时启用且服务器设置允许
+    pub fn native_fallback_allowed(&self) -> bool {
+        NATIVE_FALLBACK_BUILT && self.allow_native_fallback
+    }
+
+    /// 指定的 Agent socket，空白视为未指定
+    pub fn agent_socket(&self) -> Option<&str> {
+        self.agent_socket.as_deref().map(str::trim).filter(|s| !s.is_empty())
+    }
+
+    /// 解析标记颜色，非 `#RRGGBB` 格式时返回 None
+    pub fn color_rgb(&self) -> Option<(u8, u8, u8)> {
+        let hex = self.color.as_deref()?.trim().strip_prefix('#')?;
+        if hex.len() != 6 || !hex.is_ascii() {
+            return None;
         }
+        let channel = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).ok();
+        Some((channel(0)?, channel(2)?, channel(4)?))
     }
 }
 
@@ -49,6 +235,117 @@ pub struct Bookmark {
     pub side: String,
 }
 
+/// 界面偏好 (排序、窗口尺寸、分栏比例)，退出时保存、启动时恢复
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(default)]
+pub struct UiPrefs {
+    pub local_sort_field: String,
+    pub local_sort_ascending: bool,
+    pub remote_sort_field: String,
+    pub remote_sort_ascending: bool,
+    /// 窗口逻辑尺寸，0 表示使用默认值
+    pub window_width: u32,
+    pub window_height: u32,
+    /// 本地/远程面板分栏比例
+    pub split_ratio: f32,
+    /// 文件列表的列设置
+    pub columns: ColumnPrefs,
+    /// 本地面板显示带隐藏/系统属性的文件
+    pub local_show_hidden: bool,
+    /// 远程面板显示 `.` 开头的文件
+    pub remote_show_hidden: bool,
+}
+
+/// 文件列表可选列的显示与宽度 (逻辑像素)
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(default)]
+pub struct ColumnPrefs {
+    pub show_size: bool,
+    pub show_modified: bool,
+    pub size_width: u32,
+    pub modified_width: u32,
+}
+
+impl ColumnPrefs {
+    pub const MIN_WIDTH: u32 = 40;
+    pub const MAX_WIDTH: u32 = 300;
+
+    /// 把列宽限制在可用范围内 (手工改坏的配置也能正常显示)
+    pub fn clamped(&self) -> ColumnPrefs {
+        let clamp = |w: u32| w.clamp(Self::MIN_WIDTH, Self::MAX_WIDTH);
+        ColumnPrefs {
+            size_width: clamp(self.size_width),
+            modified_width: clamp(self.modified_width),
+            ..self.clone()
+        }
+    }
+}
+
+impl Default for ColumnPrefs {
+    fn default() -> Self {
+        Self {
+            show_size: true,
+            show_modified: true,
+            size_width: 60,
+            modified_width: 110,
+        }
+    }
+}
+
+impl Default for UiPrefs {
+    fn default() -> Self {
+        Self {
+            local_sort_field: "name".to_string(),
+            local_sort_ascending: true,
+            remote_sort_field: "name".to_string(),
+            remote_sort_ascending: true,
+            window_width: 0,
+            window_height: 0,
+            split_ratio: 0.5,
+            columns: ColumnPrefs::default(),
+            local_show_hidden: false,
+            remote_show_hidden: true,
+        }
+    }
+}
+
+/// 最近访问目录列表的长度上限
+pub const MAX_RECENT_DIRS: usize = 10;
+
+/// 把目录移到最近访问列表最前面，去重并截断到 `cap` 条
+pub fn push_recent_dir(list: &mut Vec<String>, path: &str, cap: usize) {
+    if path.is_empty() {
+        return;
+    }
+    list.retain(|p| p != path);
+    list.insert(0, path.to_string());
+    list.truncate(cap);
+}
+
+/// 面板的排序方式
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct PaneSort {
+    /// 排序字段: "name" | "size" | "modified"，无法识别时按名称
+    pub field: String,
+    #[serde(default = "default_sort_ascending")]
+    pub ascending: bool,
+}
+
+fn default_sort_ascending() -> bool {
+    true
+}
+
+impl PaneSort {
+    /// 规范化后的排序字段
+    pub fn field(&self) -> &'static str {
+        match self.field.trim().to_lowercase().as_str() {
+            "size" => "size",
+            "modified" => "modified",
+            _ => "name",
+        }
+    }
+}
+
 /// 应用全局配置
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct AppConfig {
@@ -59,6 +356,72 @@ pub struct AppConfig {
     /// 书签列表
     #[serde(default)]
     pub bookmarks: Vec<Bookmark>,
+    /// 文件预览大小上限 (字节)
+    #[serde(default = "default_preview_max_bytes")]
+    pub preview_max_bytes: u64,
+    /// 上次浏览的本地目录
+    #[serde(default)]
+    pub last_local_path: Option<String>,
+    /// 最近访问的本地目录，最新的在前
+    #[serde(default)]
+    pub recent_local_dirs: Vec<String>,
+    /// 界面偏好
+    #[serde(default)]
+    pub ui_prefs: UiPrefs,
+    /// 本地面板启动时固定使用的排序，未设置时沿用上次退出时的排序
+    #[serde(default)]
+    pub default_local_sort: Option<PaneSort>,
+    /// 远程面板启动时固定使用的排序，未设置时沿用上次退出时的排序
+    #[serde(default)]
+    pub default_remote_sort: Option<PaneSort>,
+    /// 连接保活探测间隔 (秒)，0 表示关闭
+    #[serde(default = "default_ping_interval_secs")]
+    pub ping_interval_secs: u64,
+    /// 网络类错误的最大自动重试次数
+    #[serde(default = "default_max_retries")]
+    pub max_retries: u32,
+    /// 本地删除时移到系统回收站，关闭则直接删除
+    #[serde(default = "default_delete_to_trash")]
+    pub delete_to_trash: bool,
+    /// 批量传输出错时的策略："continue" 或 "stop"
+    #[serde(default = "default_batch_policy")]
+    pub batch_policy: String,
+    /// 传输队列全部结束后弹出桌面通知
+    #[serde(default = "default_notify_on_complete")]
+    pub notify_on_complete: bool,
+    /// 通知时播放提示音
+    #[serde(default)]
+    pub notify_sound: bool,
+    /// 下载目录时先在远程用 `tar czf -` 打包再整体传输 (适合大量小文件)
+    #[serde(default)]
+    pub archive_dir_download: bool,
+    /// 打包下载后在本地解包并删除压缩包，关闭时保留 `.tar.gz`
+    #[serde(default = "default_extract_archive")]
+    pub extract_archive: bool,
+}
+
+fn default_extract_archive() -> bool {
+    true
+}
+
+fn default_notify_on_complete() -> bool {
+    true
+}
+
+fn default_preview_max_bytes() -> u64 {
+    1024 * 1024
+}
+
+fn default_ping_interval_secs() -> u64 {
+    15
+}
+
+fn default_max_retries() -> u32 {
+    3
+}
+
+fn default_delete_to_trash() -> bool {
+    true
 }
 
 impl Default for AppConfig {
@@ -67,6 +430,84 @@ impl Default for AppConfig {
             servers: vec![ServerConfig::default()],
             last_selected_index: 0,
             bookmarks: Vec::new(),
+            preview_max_bytes: default_preview_max_bytes(),
+            last_local_path: None,
+            recent_local_dirs: Vec::new(),
+            ui_prefs: UiPrefs::default(),
+            default_local_sort: None,
+            default_remote_sort: None,
+            ping_interval_secs: default_ping_interval_secs(),
+            max_retries: default_max_retries(),
+            delete_to_trash: default_delete_to_trash(),
+            batch_policy: default_batch_policy(),
+            notify_on_complete: default_notify_on_complete(),
+            notify_sound: false,
+            archive_dir_download: false,
+            extract_archive: default_extract_archive(),
+        }
+    }
+}
+
+impl AppConfig {
+    pub fn batch_policy(&self) -> BatchPolicy {
+        BatchPolicy::parse(&self.batch_policy)
+    }
+
+    /// 启动时本地面板的排序 (字段, 是否升序)
+    pub fn startup_local_sort(&self) -> (String, bool) {
+        match &self.default_local_sort {
+            Some(sort) => (sort.field().to_string(), sort.ascending),
+            None => (self.ui_prefs.local_sort_field.clone(), self.ui_prefs.local_sort_ascending),
+        }
+    }
+
+    /// 启动时远程面板的排序 (字段, 是否升序)
+    pub fn startup_remote_sort(&self) -> (String, bool) {
+        match &self.default_remote_sort {
+            Some(sort) => (sort.field().to_string(), sort.ascending),
+            None => (self.ui_prefs.remote_sort_field.clone(), self.ui_prefs.remote_sort_ascending),
+        }
+    }
+
+    /// 新加入的目录下载任务使用的打包方式
+    pub fn dir_archive(&self) -> DirArchive {
+        match (self.archive_dir_download, self.extract_archive) {
+            (false, _) => DirArchive::None,
+            (true, true) => DirArchive::Extract,
+            (true, false) => DirArchive::Keep,
+        }
+    }
+
+    /// 去掉所有服务器密码的副本 (导出时使用)
+    pub fn without_passwords(&self) -> AppConfig {
+        let mut copy = self.clone();
+        for server in &mut copy.servers {
+            if server.password.is_some() {
+                server.password = Some(String::new());
+            }
+        }
+        copy
+    }
+
+    /// 合并另一份配置的服务器和书签：服务器按名称+主机去重，书签按位置+路径去重
+    pub fn merge_from(&mut self, other: AppConfig) {
+        for server in other.servers {
+            let exists = self
+                .servers
+                .iter()
+                .any(|s| s.name == server.name && s.host == server.host);
+            if !exists {
+                self.servers.push(server);
+            }
+        }
+        for bookmark in other.bookmarks {
+            let exists = self
+                .bookmarks
+                .iter()
+                .any(|b| b.side == bookmark.side && b.path == bookmark.path);
+            if !exists {
+                self.bookmarks.push(bookmark);
+            }
         }
     }
 }
@@ -82,6 +523,35 @@ mod tests {
         assert_eq!(cfg.auth_type, "password");
         assert!(cfg.password.is_some());
         assert!(cfg.key_path.is_none());
+        assert!(cfg.atomic_upload);
+    }
+
+    #[test]
+    fn test_native_fallback_allowed() {
+        let mut cfg = ServerConfig::default();
+        assert_eq!(cfg.native_fallback_allowed(), NATIVE_FALLBACK_BUILT);
+        cfg.allow_native_fallback = false;
+        assert!(!cfg.native_fallback_allowed());
+    }
+
+    #[test]
+    fn test_atomic_upload_defaults_to_true_when_missing() {
+        let toml_str = r#"
+            name = "old"
+            host = "10.0.0.1"
+            port = 22
+            user = "root"
+            auth_type = "key"
+            default_target_dir = "/tmp"
+        "#;
+        let cfg: ServerConfig = toml::from_str(toml_str).unwrap();
+        assert!(cfg.atomic_upload);
+        assert!(cfg.preserve_mtime);
+        assert!(cfg.allow_native_fallback);
+        assert_eq!(cfg.upload_ignore, default_upload_ignore());
+        assert!(!cfg.skip_unchanged);
+        assert_eq!(cfg.connect_timeout_secs, 10);
+        assert_eq!(cfg.io_timeout_secs, 30);
     }
 
     #[test]
@@ -101,5 +571,237 @@ mod tests {
         assert_eq!(parsed.servers[0].port, config.servers[0].port);
     }
 
-}
+    #[test]
+    fn test_last_paths_roundtrip() {
+        let mut config = AppConfig {
+            last_local_path: Some("D:\\work".to_string()),
+            recent_local_dirs: vec!["D:\\work".to_string(), "C:\\".to_string()],
+            ..Default::default()
+        };
+        config.servers[0].last_remote_path = Some("/var/www".to_string());
+        config.servers[0].recent_dirs = vec!["/var/www".to_string()];
+        let toml_str = toml::to_string_pretty(&config).unwrap();
+        let parsed: AppConfig = toml::from_str(&toml_str).unwrap();
+        assert_eq!(parsed.last_local_path.as_deref(), Some("D:\\work"));
+        assert_eq!(parsed.servers[0].last_remote_path.as_deref(), Some("/var/www"));
+        assert_eq!(parsed.recent_local_dirs, config.recent_local_dirs);
+        assert_eq!(parsed.servers[0].recent_dirs, vec!["/var/www"]);
+    }
+
+    #[test]
+    fn test_ui_prefs_roundtrip_and_partial() {
+        let mut config = AppConfig::default();
+        config.ui_prefs.remote_sort_field = "size".to_string();
+        config.ui_prefs.remote_sort_ascending = false;
+        config.ui_prefs.window_width = 1280;
+        config.ui_prefs.local_show_hidden = true;
+        config.ui_prefs.remote_show_hidden = false;
+        let toml_str = toml::to_string_pretty(&config).unwrap();
+        let parsed: AppConfig = toml::from_str(&toml_str).unwrap();
+        assert_eq!(parsed.ui_prefs, config.ui_prefs);
+
+        // 旧配置缺少的字段取默认值
+        let partial: UiPrefs = toml::from_str("window_width = 800").unwrap();
+        assert_eq!(partial.window_width, 800);
+        assert_eq!(partial.local_sort_field, "name");
+        assert_eq!(partial.split_ratio, 0.5);
+        assert_eq!(partial.columns, ColumnPrefs::default());
+        assert!(!partial.local_show_hidden);
+        assert!(partial.remote_show_hidden);
+    }
+
+    #[test]
+    fn test_column_prefs_partial_and_clamped() {
+        let prefs: UiPrefs = toml::from_str("[columns]\nshow_modified = false\nsize_width = 5\n").unwrap();
+        assert!(prefs.columns.show_size);
+        assert!(!prefs.columns.show_modified);
+        assert_eq!(prefs.columns.modified_width, 110);
+
+        let clamped = prefs.columns.clamped();
+        assert_eq!(clamped.size_width, ColumnPrefs::MIN_WIDTH);
+        assert!(!clamped.show_modified);
+        let wide = ColumnPrefs { modified_width: 5000, ..ColumnPrefs::default() };
+        assert_eq!(wide.clamped().modified_width, ColumnPrefs::MAX_WIDTH);
+    }
+
+    #[test]
+    fn test_ping_interval_defaults_when_missing() {
+        let toml_str = toml::to_string_pretty(&AppConfig::default()).unwrap();
+        let stripped: String = toml_str
+            .lines()
+            .filter(|l| !l.starts_with("ping_interval_secs"))
+            .collect::<Vec<_>>()
+            .join("\n");
+        let parsed: AppConfig = toml::from_str(&stripped).unwrap();
+        assert_eq!(parsed.ping_interval_secs, 15);
+    }
+
+    #[test]
+    fn test_server_color_rgb() {
+        let mut server = ServerConfig {
+            color: Some("#E53935".to_string()),
+            ..ServerConfig::default()
+        };
+        assert_eq!(server.color_rgb(), Some((0xe5, 0x39, 0x35)));
+        server.color = Some("red".to_string());
+        assert_eq!(server.color_rgb(), None);
+        server.color = Some("#12345".to_string());
+        assert_eq!(server.color_rgb(), None);
+        server.color = None;
+        assert_eq!(server.color_rgb(), None);
+    }
 
+    #[test]
+    fn test_agent_socket() {
+        let mut server = ServerConfig::default();
+        assert_eq!(server.agent_socket(), None);
+        server.agent_socket = Some("  ".into());
+        assert_eq!(server.agent_socket(), None);
+        server.agent_socket = Some(" /run/user/1000/gnupg/S.gpg-agent.ssh ".into());
+        assert_eq!(server.agent_socket(), Some("/run/user/1000/gnupg/S.gpg-agent.ssh"));
+    }
+
+    #[test]
+    fn test_transfer_method() {
+        assert_eq!(TransferMethod::parse("SFTP"), TransferMethod::Sftp);
+        assert_eq!(TransferMethod::parse("scp"), TransferMethod::Scp);
+        assert_eq!(TransferMethod::parse("other"), TransferMethod::Auto);
+
+        // 旧配置没有该字段时为 auto
+        let toml_str = "name = \"a\"\nhost = \"h\"\nport = 22\nuser = \"u\"\nauth_type = \"key\"\ndefault_target_dir = \"/tmp\"\n";
+        let server: ServerConfig = toml::from_str(toml_str).unwrap();
+        assert_eq!(server.transfer_method(), TransferMethod::Auto);
+        assert!(!server.force_native);
+    }
+
+    #[test]
+    fn test_max_retries_defaults_when_missing() {
+        let toml_str = toml::to_string_pretty(&AppConfig::default()).unwrap();
+        let stripped: String = toml_str
+            .lines()
+            .filter(|l| !l.starts_with("max_retries"))
+            .collect::<Vec<_>>()
+            .join("\n");
+        let parsed: AppConfig = toml::from_str(&stripped).unwrap();
+        assert_eq!(parsed.max_retries, 3);
+    }
+
+    #[test]
+    fn test_delete_to_trash_defaults_when_missing() {
+        let toml_str = toml::to_string_pretty(&AppConfig::default()).unwrap();
+        let stripped: String = toml_str
+            .lines()
+            .filter(|l| !l.starts_with("delete_to_trash"))
+            .collect::<Vec<_>>()
+            .join("\n");
+        let parsed: AppConfig = toml::from_str(&stripped).unwrap();
+        assert!(parsed.delete_to_trash);
+    }
+
+    #[test]
+    fn test_batch_policy() {
+        assert_eq!(BatchPolicy::parse("Stop"), BatchPolicy::StopOnError);
+        assert_eq!(BatchPolicy::parse("continue"), BatchPolicy::ContinueOnError);
+        assert_eq!(BatchPolicy::parse("other"), BatchPolicy::ContinueOnError);
+
+        // 旧配置没有该字段时照常继续
+        let toml_str = toml::to_string_pretty(&AppConfig::default()).unwrap();
+        let stripped: String = toml_str
+            .lines()
+            .filter(|l| !l.starts_with("batch_policy"))
+            .collect::<Vec<_>>()
+            .join("\n");
+        let parsed: AppConfig = toml::from_str(&stripped).unwrap();
+        assert_eq!(parsed.batch_policy(), BatchPolicy::ContinueOnError);
+    }
+
+    #[test]
+    fn test_notify_defaults_for_old_config() {
+        let toml_str = toml::to_string_pretty(&AppConfig::default()).unwrap();
+        let stripped: String = toml_str
+            .lines()
+            .filter(|l| !l.starts_with("notify_"))
+            .collect::<Vec<_>>()
+            .join("\n");
+        let parsed: AppConfig = toml::from_str(&stripped).unwrap();
+        assert!(parsed.notify_on_complete);
+        assert!(!parsed.notify_sound);
+    }
+
+    #[test]
+    fn test_startup_sort() {
+        let mut config = AppConfig::default();
+        config.ui_prefs.local_sort_field = "size".to_string();
+        config.ui_prefs.local_sort_ascending = false;
+        assert_eq!(config.startup_local_sort(), ("size".to_string(), false));
+        assert_eq!(config.startup_remote_sort(), ("name".to_string(), true));
+
+        let toml_str = "field = \"Modified\"\n";
+        config.default_remote_sort = Some(toml::from_str(toml_str).unwrap());
+        assert_eq!(config.startup_remote_sort(), ("modified".to_string(), true));
+        config.default_local_sort = Some(PaneSort { field: "bogus".to_string(), ascending: false });
+        assert_eq!(config.startup_local_sort(), ("name".to_string(), false));
+
+        let text = toml::to_string_pretty(&config).unwrap();
+        let parsed: AppConfig = toml::from_str(&text).unwrap();
+        assert_eq!(parsed.default_remote_sort, config.default_remote_sort);
+    }
+
+    #[test]
+    fn test_dir_archive() {
+        let mut config = AppConfig::default();
+        assert_eq!(config.dir_archive(), DirArchive::None);
+        config.archive_dir_download = true;
+        assert_eq!(config.dir_archive(), DirArchive::Extract);
+        config.extract_archive = false;
+        assert_eq!(config.dir_archive(), DirArchive::Keep);
+    }
+
+    #[test]
+    fn test_push_recent_dir() {
+        let mut list = Vec::new();
+        push_recent_dir(&mut list, "/a", 3);
+        push_recent_dir(&mut list, "/b", 3);
+        push_recent_dir(&mut list, "/c", 3);
+        assert_eq!(list, vec!["/c", "/b", "/a"]);
+
+        push_recent_dir(&mut list, "/a", 3);
+        assert_eq!(list, vec!["/a", "/c", "/b"]);
+
+        push_recent_dir(&mut list, "/d", 3);
+        assert_eq!(list, vec!["/d", "/a", "/c"]);
+
+        push_recent_dir(&mut list, "", 3);
+        assert_eq!(list.len(), 3);
+    }
+
+    #[test]
+    fn test_without_passwords() {
+        let config = AppConfig::default();
+        let stripped = config.without_passwords();
+        assert_eq!(stripped.servers[0].password.as_deref(), Some(""));
+        assert_eq!(config.servers[0].password.as_deref(), Some("123456"));
+    }
+
+    #[test]
+    fn test_merge_from_dedups() {
+        let mut config = AppConfig::default();
+        let mut other = AppConfig::default();
+        other.servers.push(ServerConfig {
+            name: "prod".to_string(),
+            ..ServerConfig::default()
+        });
+        let bookmark = Bookmark {
+            name: "logs".to_string(),
+            path: "/var/log".to_string(),
+            side: "remote".to_string(),
+        };
+        config.bookmarks.push(bookmark.clone());
+        other.bookmarks.push(bookmark);
+
+        config.merge_from(other);
+        assert_eq!(config.servers.len(), 2);
+        assert_eq!(config.servers[1].name, "prod");
+        assert_eq!(config.bookmarks.len(), 1);
+    }
+}
@@ -0,0 +1,103 @@
+/// 内存中保留的连接日志条数
+pub const CONNECTION_LOG_LIMIT: usize = 50;
+
+/// 一次连接尝试的日志
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConnectionLogEntry {
+    /// 结束时间 (`%Y-%m-%d %H:%M:%S`)
+    pub timestamp: String,
+    /// 服务器别名
+    pub server: String,
+    /// `user@host:port`
+    pub target: String,
+    pub success: bool,
+    /// `connect_with_log` 产生的逐步日志，失败时末尾附上错误
+    pub log: String,
+}
+
+impl ConnectionLogEntry {
+    /// 带标题行的完整文本，用于复制和导出
+    pub fn to_text(&self) -> String {
+        format!(
+            "[{}] {} ({}) {}\n{}",
+            self.timestamp,
+            self.server,
+            self.target,
+            if self.success { "成功" } else { "失败" },
+            self.log.trim_end()
+        )
+    }
+}
+
+/// 最近的连接日志，超出上限时丢弃最旧的
+#[derive(Debug, Clone, Default)]
+pub struct ConnectionLog {
+    entries: Vec<ConnectionLogEntry>,
+}
+
+impl ConnectionLog {
+    pub const fn new() -> Self {
+        Self { entries: Vec::new() }
+    }
+
+    pub fn push(&mut self, entry: ConnectionLogEntry) {
+        self.entries.push(entry);
+        if self.entries.len() > CONNECTION_LOG_LIMIT {
+            let excess = self.entries.len() - CONNECTION_LOG_LIMIT;
+            self.entries.drain(..excess);
+        }
+    }
+
+    /// 最近的日志在前
+    pub fn recent(&self) -> impl Iterator<Item = &ConnectionLogEntry> {
+        self.entries.iter().rev()
+    }
+
+    /// 全部日志按时间顺序拼接为导出文本
+    pub fn export_text(&self) -> String {
+        self.entries
+            .iter()
+            .map(ConnectionLogEntry::to_text)
+            .collect::<Vec<_>>()
+            .join("\n\n")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(server: &str, success: bool) -> ConnectionLogEntry {
+        ConnectionLogEntry {
+            timestamp: "2024-01-01 00:00:00".into(),
+            server: server.into(),
+            target: "root@10.0.0.1:22".into(),
+            success,
+            log: "开始连接\n握手成功\n".into(),
+        }
+    }
+
+    #[test]
+    fn test_push_keeps_limit_and_recent_order() {
+        let mut log = ConnectionLog::new();
+        for i in 0..CONNECTION_LOG_LIMIT + 3 {
+            log.push(entry(&format!("s{}", i), true));
+        }
+        let recent: Vec<&str> = log.recent().map(|e| e.server.as_str()).collect();
+        assert_eq!(recent.len(), CONNECTION_LOG_LIMIT);
+        assert_eq!(recent[0], format!("s{}", CONNECTION_LOG_LIMIT + 2));
+        assert_eq!(recent[CONNECTION_LOG_LIMIT - 1], "s3");
+    }
+
+    #[test]
+    fn test_export_text() {
+        let mut log = ConnectionLog::new();
+        log.push(entry("web", true));
+        log.push(entry("db", false));
+        assert_eq!(
+            log.export_text(),
+            "[2024-01-01 00:00:00] web (root@10.0.0.1:22) 成功\n开始连接\n握手成功\n\n\
+             [2024-01-01 00:00:00] db (root@10.0.0.1:22) 失败\n开始连接\n握手成功"
+        );
+    }
+}
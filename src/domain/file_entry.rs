@@ -6,3 +6,85 @@ pub struct FileEntryInfo {
     pub size: u64,
     pub modified: String,
 }
+
+/// 按扩展名划分的文件类别，用于列表中的图标颜色
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FileKind {
+    Archive,
+    Image,
+    Code,
+    Document,
+    Executable,
+    Other,
+}
+
+impl FileKind {
+    /// 传给界面的类别名
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            FileKind::Archive => "archive",
+            FileKind::Image => "image",
+            FileKind::Code => "code",
+            FileKind::Document => "document",
+            FileKind::Executable => "executable",
+            FileKind::Other => "other",
+        }
+    }
+}
+
+/// 按扩展名 (不区分大小写) 判断文件类别，`.tar.gz` 等双扩展名按最后一段判断
+pub fn file_kind(name: &str) -> FileKind {
+    const ARCHIVE: &[&str] = &["zip", "tar", "gz", "tgz", "bz2", "xz", "zst", "7z", "rar", "jar", "deb", "rpm"];
+    const IMAGE: &[&str] = &["png", "jpg", "jpeg", "gif", "bmp", "webp", "svg", "ico", "tif", "tiff"];
+    const CODE: &[&str] = &[
+        "rs", "py", "js", "ts", "jsx", "tsx", "go", "java", "c", "h", "cpp", "hpp", "cs", "rb", "php",
+        "sh", "bash", "ps1", "sql", "html", "css", "json", "toml", "yaml", "yml", "xml", "slint", "lua",
+    ];
+    const DOCUMENT: &[&str] = &["txt", "md", "pdf", "doc", "docx", "xls", "xlsx", "ppt", "pptx", "csv", "log", "rtf", "odt"];
+    const EXECUTABLE: &[&str] = &["exe", "msi", "bat", "cmd", "com", "app", "appimage", "bin", "run", "dll", "so"];
+
+    let ext = match name.rfind('.') {
+        Some(dot) if dot > 0 => name[dot + 1..].to_lowercase(),
+        _ => return FileKind::Other,
+    };
+    let ext = ext.as_str();
+    if ARCHIVE.contains(&ext) {
+        FileKind::Archive
+    } else if IMAGE.contains(&ext) {
+        FileKind::Image
+    } else if CODE.contains(&ext) {
+        FileKind::Code
+    } else if DOCUMENT.contains(&ext) {
+        FileKind::Document
+    } else if EXECUTABLE.contains(&ext) {
+        FileKind::Executable
+    } else {
+        FileKind::Other
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_file_kind_by_extension() {
+        assert_eq!(file_kind("backup.tar.gz"), FileKind::Archive);
+        assert_eq!(file_kind("release.ZIP"), FileKind::Archive);
+        assert_eq!(file_kind("photo.JPeG"), FileKind::Image);
+        assert_eq!(file_kind("main.rs"), FileKind::Code);
+        assert_eq!(file_kind("config.yaml"), FileKind::Code);
+        assert_eq!(file_kind("report.pdf"), FileKind::Document);
+        assert_eq!(file_kind("app.log"), FileKind::Document);
+        assert_eq!(file_kind("setup.exe"), FileKind::Executable);
+        assert_eq!(file_kind("data.unknown"), FileKind::Other);
+    }
+
+    #[test]
+    fn test_file_kind_without_extension() {
+        assert_eq!(file_kind("Makefile"), FileKind::Other);
+        assert_eq!(file_kind(".bashrc"), FileKind::Other);
+        assert_eq!(file_kind("trailing."), FileKind::Other);
+        assert_eq!(FileKind::Image.as_str(), "image");
+    }
+}
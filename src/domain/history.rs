@@ -0,0 +1,97 @@
+use serde::{Deserialize, Serialize};
+
+/// 历史记录保留的最大条数
+pub const HISTORY_LIMIT: usize = 500;
+
+/// 一条已结束的传输记录
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct HistoryEntry {
+    /// 结束时间 (`%Y-%m-%d %H:%M:%S`)
+    pub timestamp: String,
+    /// "upload" 或 "download"
+    pub direction: String,
+    pub file_name: String,
+    pub local_path: String,
+    pub remote_path: String,
+    pub size: u64,
+    #[serde(default)]
+    pub is_dir: bool,
+    /// 耗时 (秒)
+    pub duration_secs: f64,
+    /// 服务器别名
+    pub server: String,
+    pub success: bool,
+    #[serde(default)]
+    pub error: Option<String>,
+}
+
+/// 传输历史，按时间顺序追加，超出上限时丢弃最旧的记录
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct TransferHistory {
+    #[serde(default)]
+    pub entries: Vec<HistoryEntry>,
+}
+
+impl TransferHistory {
+    pub fn push(&mut self, entry: HistoryEntry) {
+        self.entries.push(entry);
+        if self.entries.len() > HISTORY_LIMIT {
+            let excess = self.entries.len() - HISTORY_LIMIT;
+            self.entries.drain(..excess);
+        }
+    }
+
+    /// 最近的记录在前
+    pub fn recent(&self) -> impl Iterator<Item = &HistoryEntry> {
+        self.entries.iter().rev()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(name: &str) -> HistoryEntry {
+        HistoryEntry {
+            timestamp: "2024-01-01 00:00:00".into(),
+            direction: "upload".into(),
+            file_name: name.into(),
+            local_path: format!("/local/{}", name),
+            remote_path: format!("/remote/{}", name),
+            size: 1,
+            is_dir: false,
+            duration_secs: 0.5,
+            server: "test".into(),
+            success: true,
+            error: None,
+        }
+    }
+
+    #[test]
+    fn test_push_caps_at_limit() {
+        let mut h = TransferHistory::default();
+        for i in 0..HISTORY_LIMIT + 10 {
+            h.push(entry(&i.to_string()));
+        }
+        assert_eq!(h.entries.len(), HISTORY_LIMIT);
+        assert_eq!(h.entries[0].file_name, "10");
+    }
+
+    #[test]
+    fn test_recent_is_newest_first() {
+        let mut h = TransferHistory::default();
+        h.push(entry("a"));
+        h.push(entry("b"));
+        let names: Vec<_> = h.recent().map(|e| e.file_name.as_str()).collect();
+        assert_eq!(names, vec!["b", "a"]);
+    }
+
+    #[test]
+    fn test_toml_roundtrip() {
+        let mut h = TransferHistory::default();
+        h.push(entry("a"));
+        let text = toml::to_string_pretty(&h).unwrap();
+        let back: TransferHistory = toml::from_str(&text).unwrap();
+        assert_eq!(back.entries, h.entries);
+    }
+}
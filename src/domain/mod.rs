@@ -1,4 +1,6 @@
 pub mod config;
+pub mod connection_log;
 pub mod file_entry;
+pub mod history;
 pub mod ports;
 pub mod transfer;
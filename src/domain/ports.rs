@@ -1,8 +1,28 @@
+use std::path::Path;
+
 use anyhow::Result;
 
 use super::config::AppConfig;
+use super::history::{HistoryEntry, TransferHistory};
+use super::transfer::TransferTask;
 
 pub trait ConfigRepository {
     fn load(&self) -> Result<AppConfig>;
     fn save(&self, config: &AppConfig) -> Result<()>;
+    /// 把配置写到指定文件 (用于迁移到其他机器)
+    fn export_to(&self, config: &AppConfig, path: &Path) -> Result<()>;
+    /// 从指定文件读取配置，不影响当前保存的配置
+    fn import_from(&self, path: &Path) -> Result<AppConfig>;
+}
+
+pub trait HistoryRepository {
+    fn load(&self) -> Result<TransferHistory>;
+    fn append(&self, entry: HistoryEntry) -> Result<()>;
+}
+
+/// 未完成传输任务的持久化，程序异常退出后可恢复
+pub trait QueueRepository {
+    fn load(&self) -> Result<Vec<TransferTask>>;
+    /// 用 tasks 覆盖已保存的任务
+    fn save(&self, tasks: &[TransferTask]) -> Result<()>;
 }
@@ -1,15 +1,49 @@
-use std::path::PathBuf;
-use std::time::Instant;
+use std::collections::{HashMap, VecDeque};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+
+use super::config::BatchPolicy;
+use super::ports::QueueRepository;
 
 /// 传输方向
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum Direction {
     Upload,
     Download,
 }
 
+/// 目录下载的打包方式
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+pub enum DirArchive {
+    /// 逐个文件下载
+    #[default]
+    None,
+    /// 远程打包为 tar.gz 整体下载，在本地解包后删除压缩包
+    Extract,
+    /// 远程打包为 tar.gz 整体下载，保留压缩包
+    Keep,
+}
+
+impl DirArchive {
+    /// 把远程目录下载为本地 `local_dir` 时最终写入的路径：保留压缩包时为 `<目录>.tar.gz`，
+    /// 否则为目录本身 (解包用的压缩包是临时文件，不占用这个名字)
+    pub fn output_path(self, local_dir: &Path) -> PathBuf {
+        match self {
+            DirArchive::Keep => {
+                let mut name = local_dir.as_os_str().to_os_string();
+                name.push(".tar.gz");
+                PathBuf::from(name)
+            }
+            DirArchive::None | DirArchive::Extract => local_dir.to_path_buf(),
+        }
+    }
+}
+
 /// 传输任务状态
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum TransferStatus {
     Pending,
     InProgress,
@@ -19,7 +53,7 @@ pub enum TransferStatus {
 
 /// 单个传输任务
 #[allow(dead_code)]
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TransferTask {
     pub id: usize,
     pub direction: Direction,
@@ -27,15 +61,366 @@ pub struct TransferTask {
     pub remote_path: String,
     pub file_name: String,
     pub size: u64,
+    /// 目标服务器名称
+    pub server: String,
     pub progress: f32,
     pub status: TransferStatus,
+    /// 开始时间，仅用于计算速度与耗时，不写入磁盘
+    #[serde(skip)]
     pub started_at: Option<Instant>,
+    /// 最近的吞吐量采样，用于绘制速度趋势图，不写入磁盘
+    #[serde(skip)]
+    pub throughput: ThroughputSamples,
+    /// 完成后的附加说明 (如同步的传输/跳过数量)
+    #[serde(default)]
+    pub note: String,
+    /// 已自动重试的次数
+    #[serde(default)]
+    pub attempts: u32,
+    /// 是否为目录传输
+    #[serde(default)]
+    pub is_dir: bool,
+    /// 移动操作：传输完全成功后删除源文件
+    #[serde(default)]
+    pub delete_source_on_success: bool,
+    /// 所属批次 (同一次选择加入的任务)，单独加入的任务为 None
+    #[serde(default)]
+    pub batch_id: Option<usize>,
+    /// 目录下载的打包方式
+    #[serde(default)]
+    pub archive: DirArchive,
+}
+
+/// 恢复的上次未完成任务的错误说明，界面上可直接重试
+pub const INTERRUPTED_ERROR: &str = "上次退出时未完成";
+
+/// 批次按 StopOnError 策略取消的任务的错误说明
+pub const BATCH_STOPPED_ERROR: &str = "同批任务失败，已取消";
+
+/// 等待中的任务能否开始
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum StartDecision {
+    Start,
+    /// 队列暂停或同批前面的任务未结束
+    Wait,
+    /// 任务已被取消，不再传输
+    Cancelled,
+}
+
+/// 无法获知真实进度 (如外部 scp 命令) 时回调的特殊值，界面应显示为不确定进度
+pub const PROGRESS_INDETERMINATE: f32 = -1.0;
+
+/// 目录传输扫描阶段的进度上限：进行中的目录任务进度大于 0 且低于该值表示仍在枚举
+pub const DIR_SCAN_PROGRESS: f32 = 0.02;
+
+/// 扫描阶段的进度，随已发现的文件数增长，始终大于 0 且小于 `DIR_SCAN_PROGRESS`
+pub fn scan_progress(discovered: usize) -> f32 {
+    let found = discovered as f32 + 1.0;
+    DIR_SCAN_PROGRESS * found / (found + 100.0)
+}
+
+/// 目录传输的累计进度：扫描结束后按已完成文件数加上当前文件的进度推进，空目录直接完成
+pub fn dir_progress(done_files: usize, total_files: usize, current: f32) -> f32 {
+    if total_files == 0 {
+        return 1.0;
+    }
+    let files = (done_files as f32 + current.clamp(0.0, 1.0)) / total_files as f32;
+    DIR_SCAN_PROGRESS + (1.0 - DIR_SCAN_PROGRESS) * files.min(1.0)
+}
+
+/// 吞吐量趋势图保留的采样数
+pub const THROUGHPUT_SAMPLES: usize = 30;
+
+/// 两次吞吐量采样的最小间隔，更密集的进度回调只更新进度不采样
+pub const THROUGHPUT_INTERVAL: Duration = Duration::from_millis(500);
+
+/// 速度平滑的移动平均窗口
+const SMOOTHING_WINDOW: usize = 3;
+
+/// 最近若干个 (时间, 已传输字节) 采样组成的环形缓冲，用于计算速度变化
+#[derive(Debug, Clone, Default)]
+pub struct ThroughputSamples {
+    samples: VecDeque<(Instant, u64)>,
+}
+
+impl ThroughputSamples {
+    /// 记录一个采样；距上次采样不足 `THROUGHPUT_INTERVAL` 时忽略，
+    /// 字节数回退 (重试后从头开始) 时清空旧采样
+    pub fn record(&mut self, at: Instant, bytes: u64) {
+        if let Some(&(last_at, last_bytes)) = self.samples.back() {
+            if bytes < last_bytes {
+                self.samples.clear();
+            } else if at.saturating_duration_since(last_at) < THROUGHPUT_INTERVAL {
+                return;
+            }
+        }
+        if self.samples.len() == THROUGHPUT_SAMPLES {
+            self.samples.pop_front();
+        }
+        self.samples.push_back((at, bytes));
+    }
+
+    pub fn clear(&mut self) {
+        self.samples.clear();
+    }
+
+    /// 相邻采样之间的速度 (字节/秒)
+    pub fn rates(&self) -> Vec<f64> {
+        self.samples
+            .iter()
+            .zip(self.samples.iter().skip(1))
+            .map(|(&(t0, b0), &(t1, b1))| {
+                let secs = t1.saturating_duration_since(t0).as_secs_f64();
+                if secs > 0.0 {
+                    (b1 - b0) as f64 / secs
+                } else {
+                    0.0
+                }
+            })
+            .collect()
+    }
+
+    /// 按移动平均平滑后的速度序列，消除单次采样的抖动
+    pub fn smoothed_rates(&self) -> Vec<f64> {
+        let rates = self.rates();
+        (0..rates.len())
+            .map(|i| {
+                let window = &rates[i.saturating_sub(SMOOTHING_WINDOW - 1)..=i];
+                window.iter().sum::<f64>() / window.len() as f64
+            })
+            .collect()
+    }
+
+    /// 归一化到 0..=1 的平滑速度序列 (以其中的最大值为 1)，供迷你图使用
+    pub fn sparkline(&self) -> Vec<f32> {
+        let rates = self.smoothed_rates();
+        let max = rates.iter().cloned().fold(0.0, f64::max);
+        if max <= 0.0 {
+            return vec![0.0; rates.len()];
+        }
+        rates.iter().map(|r| (r / max) as f32).collect()
+    }
+}
+
+/// 传输过程中的结构化事件，供 UI 以外的调用方 (脚本、CLI) 订阅
+#[derive(Debug, Clone, PartialEq)]
+pub enum TransferEvent {
+    Started { total: u64 },
+    Progress { bytes: u64, total: u64 },
+    /// 传输进行中但无法报告字节进度
+    Indeterminate,
+    Completed,
+    Failed(String),
+}
+
+impl TransferEvent {
+    /// 把 0.0~1.0 的进度换算为字节进度事件，负值视为不确定进度
+    pub fn progress(fraction: f32, total: u64) -> Self {
+        if fraction < 0.0 {
+            return Self::Indeterminate;
+        }
+        let bytes = (fraction.clamp(0.0, 1.0) as f64 * total as f64).round() as u64;
+        Self::Progress { bytes, total }
+    }
+}
+
+/// 压缩上传统计：原始字节数与实际传输的压缩后字节数
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct CompressionStats {
+    pub original: u64,
+    pub on_wire: u64,
+}
+
+impl CompressionStats {
+    /// 累加多个文件的统计 (目录上传)
+    pub fn merge(self, other: Self) -> Self {
+        Self {
+            original: self.original + other.original,
+            on_wire: self.on_wire + other.on_wire,
+        }
+    }
+}
+
+/// 增量同步比较用的文件信息
+#[derive(Debug, Clone, PartialEq)]
+pub struct SyncFile {
+    /// 相对于同步根目录的路径 (`/` 分隔)
+    pub relative_path: String,
+    pub size: u64,
+    /// 修改时间 (Unix 秒)，未知时为 0
+    pub mtime: u64,
+}
+
+/// 增量同步计划
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SyncPlan {
+    /// 需要上传的相对路径
+    pub uploads: Vec<String>,
+    /// 需要删除的远程相对路径 (仅镜像删除模式)
+    pub deletes: Vec<String>,
+    pub skipped: usize,
+}
+
+/// 增量同步结果统计
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct SyncReport {
+    pub transferred: usize,
+    pub skipped: usize,
+    pub deleted: usize,
+}
+
+impl SyncReport {
+    pub fn summary(&self) -> String {
+        let mut text = format!("传输 {}，跳过 {}", self.transferred, self.skipped);
+        if self.deleted > 0 {
+            text.push_str(&format!("，删除 {}", self.deleted));
+        }
+        text
+    }
+}
+
+/// 判断修改时间时允许的误差 (秒)，兼容 FAT 等只精确到 2 秒的文件系统
+pub const MTIME_TOLERANCE_SECS: u64 = 2;
+
+/// 本地文件是否需要上传：远程缺失、大小不同或本地更新时需要。
+/// 远程 mtime 不早于本地 (允许误差) 即视为未变化：保留修改时间时两者相同，
+/// 不保留时远程为上传时刻，总是晚于本地。
+pub fn should_transfer(local: &SyncFile, remote: Option<&SyncFile>) -> bool {
+    match remote {
+        Some(r) => r.size != local.size || r.mtime + MTIME_TOLERANCE_SECS < local.mtime,
+        None => true,
+    }
+}
+
+/// 对比本地与远程文件列表，按 `should_transfer` 决定上传或跳过
+pub fn plan_sync(local: &[SyncFile], remote: &[SyncFile], mirror_delete: bool) -> SyncPlan {
+    let remote_map: HashMap<&str, &SyncFile> = remote
+        .iter()
+        .map(|f| (f.relative_path.as_str(), f))
+        .collect();
+    let mut plan = SyncPlan::default();
+    for file in local {
+        if should_transfer(file, remote_map.get(file.relative_path.as_str()).copied()) {
+            plan.uploads.push(file.relative_path.clone());
+        } else {
+            plan.skipped += 1;
+        }
+    }
+    if mirror_delete {
+        let local_paths: std::collections::HashSet<&str> =
+            local.iter().map(|f| f.relative_path.as_str()).collect();
+        plan.deletes = remote
+            .iter()
+            .filter(|r| !local_paths.contains(r.relative_path.as_str()))
+            .map(|r| r.relative_path.clone())
+            .collect();
+    }
+    plan
+}
+
+/// 目标已存在时的处理策略
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ConflictPolicy {
+    Overwrite,
+    Skip,
+    /// 以 `name (n).ext` 另存
+    KeepBoth,
+}
+
+impl ConflictPolicy {
+    /// 解析界面传来的策略名 ("overwrite" / "skip" / "keep-both")
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "overwrite" => Some(Self::Overwrite),
+            "skip" => Some(Self::Skip),
+            "keep-both" => Some(Self::KeepBoth),
+            _ => None,
+        }
+    }
+}
+
+/// 目录上传预演中的单个文件
+#[derive(Debug, Clone, PartialEq)]
+pub struct PlannedTransfer {
+    /// 相对于上传根目录的路径 (`/` 分隔)
+    pub relative_path: String,
+    pub local_path: PathBuf,
+    pub remote_path: String,
+    pub size: u64,
+}
+
+/// 传输错误是否值得自动重试
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ErrorKind {
+    /// 连接中断、超时等，重连后可能成功
+    Transient,
+    /// 文件不存在、权限不足等，重试也不会成功
+    Permanent,
+}
+
+/// 按错误信息分类，只有明确的网络类错误才视为可重试
+pub fn classify_error(error: &str) -> ErrorKind {
+    const PERMANENT: &[&str] = &[
+        "no such file", "not found", "permission denied", "不存在", "权限", "空间不足",
+    ];
+    const TRANSIENT: &[&str] = &[
+        "timed out", "timeout", "超时", "connection reset", "reset by peer", "broken pipe",
+        "connection aborted", "连接失败", "unexpected eof", "[session(",
+    ];
+    let lower = error.to_lowercase();
+    if PERMANENT.iter().any(|k| lower.contains(k)) {
+        return ErrorKind::Permanent;
+    }
+    if TRANSIENT.iter().any(|k| lower.contains(k)) {
+        return ErrorKind::Transient;
+    }
+    ErrorKind::Permanent
+}
+
+/// 第 `attempt` 次重试前的等待时间：1s、2s、4s……
+pub fn retry_delay(attempt: u32) -> Duration {
+    Duration::from_secs(1 << attempt.saturating_sub(1).min(6))
+}
+
+/// 待写入存储的未完成任务快照，在队列锁之外写入
+pub struct PendingSave {
+    store: Arc<dyn QueueRepository + Send + Sync>,
+    tasks: Vec<TransferTask>,
+    seq: u64,
+    /// 已写入的最新快照序号，较旧的快照晚到时直接丢弃
+    written: Arc<Mutex<u64>>,
+}
+
+impl PendingSave {
+    /// 写入存储，写入失败不影响传输
+    pub fn write(self) {
+        let mut written = self.written.lock().unwrap_or_else(|e| e.into_inner());
+        if self.seq <= *written {
+            return;
+        }
+        match self.store.save(&self.tasks) {
+            Ok(()) => *written = self.seq,
+            Err(e) => eprintln!("保存传输队列失败: {}", e),
+        }
+    }
 }
 
 /// 传输队列
 pub struct TransferQueue {
     tasks: Vec<TransferTask>,
     next_id: usize,
+    /// 暂停时不再开始新的任务，已在传输的任务继续完成
+    paused: bool,
+    /// 可重试错误的最大自动重试次数
+    max_retries: u32,
+    /// 未完成任务的持久化存储，由调用方定期取走变化写入
+    store: Option<Arc<dyn QueueRepository + Send + Sync>>,
+    /// 上次取走快照后队列是否有变化
+    unsaved: bool,
+    save_seq: u64,
+    written_seq: Arc<Mutex<u64>>,
+    batch_policy: BatchPolicy,
+    next_batch_id: usize,
 }
 
 impl TransferQueue {
@@ -43,7 +428,192 @@ impl TransferQueue {
         Self {
             tasks: Vec::new(),
             next_id: 0,
+            paused: false,
+            max_retries: 3,
+            store: None,
+            unsaved: false,
+            save_seq: 0,
+            written_seq: Arc::new(Mutex::new(0)),
+            batch_policy: BatchPolicy::ContinueOnError,
+            next_batch_id: 0,
+        }
+    }
+
+    pub fn set_store(&mut self, store: Arc<dyn QueueRepository + Send + Sync>) {
+        self.store = Some(store);
+    }
+
+    fn mark_unsaved(&mut self) {
+        self.unsaved = true;
+    }
+
+    /// 取走上次保存后的变化：返回未完成 (等待、进行中、失败) 任务的快照，
+    /// 由调用方在释放队列锁后写入；没有变化或未设置存储时为 None
+    pub fn take_unsaved(&mut self) -> Option<PendingSave> {
+        if !self.unsaved {
+            return None;
+        }
+        let store = self.store.clone()?;
+        self.unsaved = false;
+        self.save_seq += 1;
+        Some(PendingSave {
+            store,
+            tasks: self
+                .tasks
+                .iter()
+                .filter(|t| t.status != TransferStatus::Completed)
+                .cloned()
+                .collect(),
+            seq: self.save_seq,
+            written: self.written_seq.clone(),
+        })
+    }
+
+    /// 立即写入尚未保存的变化 (退出前调用)
+    pub fn persist(&mut self) {
+        if let Some(save) = self.take_unsaved() {
+            save.write();
+        }
+    }
+
+    /// 重新加入上次保存的任务，统一标记为未完成的失败任务，由用户决定是否重试。
+    /// 返回新分配的任务 id
+    pub fn restore(&mut self, tasks: Vec<TransferTask>) -> Vec<usize> {
+        let mut ids = Vec::new();
+        for mut task in tasks {
+            if task.status == TransferStatus::Completed {
+                continue;
+            }
+            task.id = self.next_id;
+            self.next_id += 1;
+            task.progress = 0.0;
+            task.status = TransferStatus::Failed(INTERRUPTED_ERROR.to_string());
+            task.started_at = None;
+            task.throughput.clear();
+            task.attempts = 0;
+            task.note.clear();
+            // 批次号只在本次运行内有效
+            task.batch_id = None;
+            ids.push(task.id);
+            self.tasks.push(task);
+        }
+        if !ids.is_empty() {
+            self.mark_unsaved();
+        }
+        ids
+    }
+
+    /// 恢复后仍未处理的任务 id
+    pub fn interrupted_ids(&self) -> Vec<usize> {
+        self.tasks
+            .iter()
+            .filter(|t| matches!(&t.status, TransferStatus::Failed(e) if e == INTERRUPTED_ERROR))
+            .map(|t| t.id)
+            .collect()
+    }
+
+    /// 从队列中移除任务
+    pub fn remove(&mut self, ids: &[usize]) {
+        self.tasks.retain(|t| !ids.contains(&t.id));
+        self.mark_unsaved();
+    }
+
+    /// 标记任务为目录传输
+    pub fn mark_dir(&mut self, id: usize) {
+        if let Some(task) = self.tasks.iter_mut().find(|t| t.id == id) {
+            task.is_dir = true;
+            self.mark_unsaved();
+        }
+    }
+
+    /// 设置目录下载的打包方式
+    pub fn mark_archive(&mut self, id: usize, archive: DirArchive) {
+        if let Some(task) = self.tasks.iter_mut().find(|t| t.id == id) {
+            task.archive = archive;
+            self.mark_unsaved();
+        }
+    }
+
+    /// 标记任务为移动，完成后删除源文件
+    pub fn mark_move(&mut self, id: usize) {
+        if let Some(task) = self.tasks.iter_mut().find(|t| t.id == id) {
+            task.delete_source_on_success = true;
+            self.mark_unsaved();
+        }
+    }
+
+    pub fn set_max_retries(&mut self, max_retries: u32) {
+        self.max_retries = max_retries;
+    }
+
+    pub fn set_batch_policy(&mut self, policy: BatchPolicy) {
+        self.batch_policy = policy;
+    }
+
+    /// 分配新的批次号
+    pub fn new_batch(&mut self) -> usize {
+        let id = self.next_batch_id;
+        self.next_batch_id += 1;
+        id
+    }
+
+    /// 把任务归入批次
+    pub fn mark_batch(&mut self, id: usize, batch_id: usize) {
+        if let Some(task) = self.tasks.iter_mut().find(|t| t.id == id) {
+            task.batch_id = Some(batch_id);
+            self.mark_unsaved();
+        }
+    }
+
+    pub fn pause(&mut self) {
+        self.paused = true;
+    }
+
+    pub fn resume(&mut self) {
+        self.paused = false;
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    /// 检查任务能否开始，可以时标记为进行中。
+    /// StopOnError 策略下同批任务按加入顺序逐个传输，前面的任务结束后才轮到下一个
+    pub fn try_start(&mut self, id: usize) -> StartDecision {
+        let decision = self.start_decision(id);
+        if decision == StartDecision::Start {
+            if let Some(task) = self.tasks.iter_mut().find(|t| t.id == id) {
+                if task.status == TransferStatus::Pending {
+                    task.status = TransferStatus::InProgress;
+                }
+            }
+        }
+        decision
+    }
+
+    /// 与 `try_start` 相同的判断，但不改变任务状态，用于取连接前先等待
+    pub fn start_decision(&self, id: usize) -> StartDecision {
+        if self.paused {
+            return StartDecision::Wait;
         }
+        let (status, batch_id) = match self.tasks.iter().find(|t| t.id == id) {
+            Some(task) => (task.status.clone(), task.batch_id),
+            None => return StartDecision::Start,
+        };
+        if matches!(status, TransferStatus::Failed(_)) {
+            return StartDecision::Cancelled;
+        }
+        if self.batch_policy == BatchPolicy::StopOnError && batch_id.is_some() {
+            let earlier_running = self.tasks.iter().any(|t| {
+                t.id < id
+                    && t.batch_id == batch_id
+                    && matches!(t.status, TransferStatus::Pending | TransferStatus::InProgress)
+            });
+            if earlier_running {
+                return StartDecision::Wait;
+            }
+        }
+        StartDecision::Start
     }
 
     pub fn enqueue(
@@ -53,6 +623,7 @@ impl TransferQueue {
         remote_path: String,
         file_name: String,
         size: u64,
+        server: String,
     ) -> usize {
         let id = self.next_id;
         self.next_id += 1;
@@ -63,10 +634,19 @@ impl TransferQueue {
             remote_path,
             file_name,
             size,
+            server,
             progress: 0.0,
             status: TransferStatus::Pending,
             started_at: None,
+            throughput: ThroughputSamples::default(),
+            note: String::new(),
+            attempts: 0,
+            is_dir: false,
+            delete_source_on_success: false,
+            batch_id: None,
+            archive: DirArchive::None,
         });
+        self.mark_unsaved();
         id
     }
 
@@ -82,10 +662,15 @@ impl TransferQueue {
     pub fn update_progress(&mut self, id: usize, progress: f32) {
         if let Some(task) = self.tasks.iter_mut().find(|t| t.id == id)
         {
+            let now = Instant::now();
             if task.started_at.is_none() {
-                task.started_at = Some(Instant::now());
+                task.started_at = Some(now);
+            }
+            // 不确定进度时保留已有进度，只标记为进行中
+            if progress >= 0.0 {
+                task.progress = progress;
+                task.throughput.record(now, (progress as f64 * task.size as f64) as u64);
             }
-            task.progress = progress;
             task.status = TransferStatus::InProgress;
         }
     }
@@ -96,15 +681,34 @@ impl TransferQueue {
         {
             task.progress = 1.0;
             task.status = TransferStatus::Completed;
+            self.mark_unsaved();
+        }
+    }
+
+    /// 设置任务的附加说明
+    pub fn set_note(&mut self, id: usize, note: String) {
+        if let Some(task) = self.tasks.iter_mut().find(|t| t.id == id) {
+            task.note = note;
         }
     }
 
-    /// 标记任务失败
+    /// 标记任务失败；StopOnError 策略下同时取消同批尚未开始的任务
     pub fn mark_failed(&mut self, id: usize, error: String) {
-        if let Some(task) = self.tasks.iter_mut().find(|t| t.id == id)
-        {
-            task.status = TransferStatus::Failed(error);
+        let batch_id = match self.tasks.iter_mut().find(|t| t.id == id) {
+            Some(task) => {
+                task.status = TransferStatus::Failed(error);
+                task.batch_id
+            }
+            None => return,
+        };
+        if let (BatchPolicy::StopOnError, Some(batch_id)) = (self.batch_policy, batch_id) {
+            for task in self.tasks.iter_mut() {
+                if task.batch_id == Some(batch_id) && task.status == TransferStatus::Pending {
+                    task.status = TransferStatus::Failed(BATCH_STOPPED_ERROR.to_string());
+                }
+            }
         }
+        self.mark_unsaved();
     }
 
     /// 获取所有任务的快照
@@ -116,6 +720,7 @@ impl TransferQueue {
     pub fn clear_completed(&mut self) {
         self.tasks
             .retain(|t| t.status != TransferStatus::Completed);
+        self.mark_unsaved();
     }
 
     /// 重试失败的任务，重置为 Pending 状态
@@ -125,12 +730,58 @@ impl TransferQueue {
                 task.status = TransferStatus::Pending;
                 task.progress = 0.0;
                 task.started_at = None;
+                task.throughput.clear();
+                task.attempts = 0;
+                self.mark_unsaved();
                 return true;
             }
         }
         false
     }
 
+    /// 把所有失败的任务重置为 Pending，返回被重置的任务 id
+    pub fn retry_all_failed(&mut self) -> Vec<usize> {
+        let mut ids = Vec::new();
+        for task in self.tasks.iter_mut() {
+            if matches!(task.status, TransferStatus::Failed(_)) {
+                task.status = TransferStatus::Pending;
+                task.progress = 0.0;
+                task.started_at = None;
+                task.throughput.clear();
+                task.attempts = 0;
+                ids.push(task.id);
+            }
+        }
+        if !ids.is_empty() {
+            self.mark_unsaved();
+        }
+        ids
+    }
+
+    /// 是否有失败的任务
+    pub fn has_failed(&self) -> bool {
+        self.tasks.iter().any(|t| matches!(t.status, TransferStatus::Failed(_)))
+    }
+
+    /// 传输出错后登记一次自动重试，可重试且未超过上限时返回等待时间
+    pub fn record_retry(&mut self, id: usize, error: &str) -> Option<Duration> {
+        let max_retries = self.max_retries;
+        let task = self.tasks.iter_mut().find(|t| t.id == id)?;
+        if task.attempts >= max_retries || classify_error(error) == ErrorKind::Permanent {
+            return None;
+        }
+        task.attempts += 1;
+        task.progress = 0.0;
+        task.throughput.clear();
+        task.note = format!("第 {} 次重试: {}", task.attempts, error);
+        Some(retry_delay(task.attempts))
+    }
+
+    /// 是否有正在传输的任务
+    pub fn has_active(&self) -> bool {
+        self.tasks.iter().any(|t| t.status == TransferStatus::InProgress)
+    }
+
     /// 根据 id 获取任务的克隆
     pub fn get_task(&self, id: usize) -> Option<TransferTask> {
         self.tasks.iter().find(|t| t.id == id).cloned()
@@ -141,6 +792,14 @@ impl TransferQueue {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_dir_archive_output_path() {
+        let dir = Path::new("/tmp/logs");
+        assert_eq!(DirArchive::None.output_path(dir), dir);
+        assert_eq!(DirArchive::Extract.output_path(dir), dir);
+        assert_eq!(DirArchive::Keep.output_path(dir), Path::new("/tmp/logs.tar.gz"));
+    }
+
     fn make_queue_with_task() -> (TransferQueue, usize) {
         let mut q = TransferQueue::new();
         let id = q.enqueue(
@@ -149,6 +808,7 @@ mod tests {
             "/remote/file.txt".to_string(),
             "file.txt".to_string(),
             1024,
+            "prod".to_string(),
         );
         (q, id)
     }
@@ -156,8 +816,8 @@ mod tests {
     #[test]
     fn test_enqueue_returns_incremental_ids() {
         let mut q = TransferQueue::new();
-        let id0 = q.enqueue(Direction::Upload, PathBuf::from("a"), "r".into(), "a".into(), 0);
-        let id1 = q.enqueue(Direction::Download, PathBuf::from("b"), "r".into(), "b".into(), 0);
+        let id0 = q.enqueue(Direction::Upload, PathBuf::from("a"), "r".into(), "a".into(), 0, "s".into());
+        let id1 = q.enqueue(Direction::Download, PathBuf::from("b"), "r".into(), "b".into(), 0, "s".into());
         assert_eq!(id0, 0);
         assert_eq!(id1, 1);
     }
@@ -177,6 +837,117 @@ mod tests {
         assert_eq!(task.id, id);
     }
 
+    /// 内存中的队列存储，记录最后一次保存的内容
+    #[derive(Default)]
+    struct MemoryStore(std::sync::Mutex<Vec<TransferTask>>);
+
+    impl QueueRepository for MemoryStore {
+        fn load(&self) -> anyhow::Result<Vec<TransferTask>> {
+            Ok(self.0.lock().unwrap().clone())
+        }
+
+        fn save(&self, tasks: &[TransferTask]) -> anyhow::Result<()> {
+            *self.0.lock().unwrap() = tasks.to_vec();
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_persist_keeps_unfinished_tasks() {
+        let store = Arc::new(MemoryStore::default());
+        let mut q = TransferQueue::new();
+        q.set_store(store.clone());
+        let done = q.enqueue(Direction::Upload, PathBuf::from("a"), "/r/a".into(), "a".into(), 1, "s".into());
+        let failed = q.enqueue(Direction::Download, PathBuf::from("b"), "/r/b".into(), "b".into(), 2, "s".into());
+        q.enqueue(Direction::Upload, PathBuf::from("c"), "/r/c".into(), "c".into(), 3, "s".into());
+        // 变化先累积，取走后才写入
+        assert!(store.load().unwrap().is_empty());
+        q.persist();
+        assert_eq!(store.load().unwrap().len(), 3);
+        assert!(q.take_unsaved().is_none());
+
+        q.mark_completed(done);
+        q.mark_failed(failed, "超时".into());
+        q.mark_dir(failed);
+        q.mark_move(failed);
+        q.persist();
+        let saved = store.load().unwrap();
+        let names: Vec<&str> = saved.iter().map(|t| t.file_name.as_str()).collect();
+        assert_eq!(names, vec!["b", "c"]);
+        assert!(saved[0].is_dir);
+        assert!(saved[0].delete_source_on_success);
+        assert!(!saved[1].delete_source_on_success);
+    }
+
+    #[test]
+    fn test_restore_marks_tasks_interrupted() {
+        let (mut old, id) = make_queue_with_task();
+        old.update_progress(id, 0.5);
+        old.record_retry(id, "timed out");
+        let saved = old.snapshot();
+
+        let store = Arc::new(MemoryStore::default());
+        let mut q = TransferQueue::new();
+        q.set_store(store.clone());
+        q.enqueue(Direction::Upload, PathBuf::from("x"), "/r/x".into(), "x".into(), 0, "s".into());
+        let ids = q.restore(saved);
+        assert_eq!(ids, vec![1]);
+        assert_eq!(q.interrupted_ids(), vec![1]);
+
+        let task = q.get_task(1).unwrap();
+        assert_eq!(task.file_name, "file.txt");
+        assert_eq!(task.progress, 0.0);
+        assert_eq!(task.attempts, 0);
+        assert_eq!(task.status, TransferStatus::Failed(INTERRUPTED_ERROR.into()));
+        q.persist();
+        assert_eq!(store.load().unwrap().len(), 2);
+
+        assert!(q.retry(1));
+        assert!(q.interrupted_ids().is_empty());
+        q.remove(&[0, 1]);
+        assert!(q.snapshot().is_empty());
+        q.persist();
+        assert!(store.load().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_stale_save_is_dropped() {
+        let store = Arc::new(MemoryStore::default());
+        let mut q = TransferQueue::new();
+        q.set_store(store.clone());
+        q.enqueue(Direction::Upload, PathBuf::from("a"), "/r/a".into(), "a".into(), 1, "s".into());
+        let older = q.take_unsaved().unwrap();
+        q.enqueue(Direction::Upload, PathBuf::from("b"), "/r/b".into(), "b".into(), 2, "s".into());
+        let newer = q.take_unsaved().unwrap();
+
+        // 较新的快照先写完，晚到的旧快照不能覆盖它
+        newer.write();
+        older.write();
+        assert_eq!(store.load().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_retry_all_failed() {
+        let mut q = TransferQueue::new();
+        let a = q.enqueue(Direction::Upload, PathBuf::from("a"), "r".into(), "a".into(), 0, "s".into());
+        let b = q.enqueue(Direction::Upload, PathBuf::from("b"), "r".into(), "b".into(), 0, "s".into());
+        let c = q.enqueue(Direction::Download, PathBuf::from("c"), "r".into(), "c".into(), 0, "s".into());
+        assert!(!q.has_failed());
+        q.mark_failed(a, "超时".into());
+        q.mark_completed(b);
+        q.update_progress(c, 0.4);
+        q.mark_failed(c, "连接失败".into());
+        assert!(q.has_failed());
+
+        assert_eq!(q.retry_all_failed(), vec![a, c]);
+        assert!(!q.has_failed());
+        let task = q.get_task(c).unwrap();
+        assert_eq!(task.status, TransferStatus::Pending);
+        assert_eq!(task.progress, 0.0);
+        assert_eq!(q.get_task(b).unwrap().status, TransferStatus::Completed);
+        assert!(q.retry_all_failed().is_empty());
+    }
+
     #[test]
     fn test_update_progress() {
         let (mut q, id) = make_queue_with_task();
@@ -184,6 +955,64 @@ mod tests {
         let snap = q.snapshot();
         assert_eq!(snap[0].progress, 0.5);
         assert_eq!(snap[0].status, TransferStatus::InProgress);
+        assert_eq!(snap[0].throughput.samples.len(), 1);
+    }
+
+    #[test]
+    fn test_throughput_rates() {
+        let t0 = Instant::now();
+        let mut s = ThroughputSamples::default();
+        assert!(s.smoothed_rates().is_empty());
+        s.record(t0, 0);
+        s.record(t0 + Duration::from_secs(1), 1000);
+        s.record(t0 + Duration::from_secs(2), 3000);
+        assert_eq!(s.rates(), vec![1000.0, 2000.0]);
+        assert_eq!(s.smoothed_rates(), vec![1000.0, 1500.0]);
+        assert_eq!(s.sparkline(), vec![(1000.0 / 1500.0) as f32, 1.0]);
+    }
+
+    #[test]
+    fn test_throughput_throttle_and_capacity() {
+        let t0 = Instant::now();
+        let mut s = ThroughputSamples::default();
+        s.record(t0, 0);
+        s.record(t0 + Duration::from_millis(100), 50);
+        assert_eq!(s.samples.len(), 1);
+        for i in 1..=THROUGHPUT_SAMPLES as u64 * 2 {
+            s.record(t0 + THROUGHPUT_INTERVAL * i as u32, i * 10);
+        }
+        assert_eq!(s.samples.len(), THROUGHPUT_SAMPLES);
+        assert_eq!(s.rates().len(), THROUGHPUT_SAMPLES - 1);
+    }
+
+    #[test]
+    fn test_throughput_resets_when_bytes_go_back() {
+        let t0 = Instant::now();
+        let mut s = ThroughputSamples::default();
+        s.record(t0, 0);
+        s.record(t0 + Duration::from_secs(1), 500);
+        // 重试后从头开始，即使间隔很短也重新采样
+        s.record(t0 + Duration::from_millis(1100), 10);
+        assert_eq!(s.samples.len(), 1);
+        assert!(s.rates().is_empty());
+    }
+
+    #[test]
+    fn test_throughput_stalled_sparkline() {
+        let t0 = Instant::now();
+        let mut s = ThroughputSamples::default();
+        s.record(t0, 100);
+        s.record(t0 + Duration::from_secs(1), 100);
+        assert_eq!(s.sparkline(), vec![0.0]);
+    }
+
+    #[test]
+    fn test_retry_clears_throughput() {
+        let (mut q, id) = make_queue_with_task();
+        q.update_progress(id, 0.5);
+        q.mark_failed(id, "timeout".to_string());
+        assert!(q.retry(id));
+        assert!(q.get_task(id).unwrap().throughput.samples.is_empty());
     }
 
     #[test]
@@ -206,12 +1035,225 @@ mod tests {
     #[test]
     fn test_clear_completed() {
         let mut q = TransferQueue::new();
-        let id0 = q.enqueue(Direction::Upload, PathBuf::from("a"), "r".into(), "a".into(), 0);
-        let _id1 = q.enqueue(Direction::Upload, PathBuf::from("b"), "r".into(), "b".into(), 0);
+        let id0 = q.enqueue(Direction::Upload, PathBuf::from("a"), "r".into(), "a".into(), 0, "s".into());
+        let _id1 = q.enqueue(Direction::Upload, PathBuf::from("b"), "r".into(), "b".into(), 0, "s".into());
         q.mark_completed(id0);
         q.clear_completed();
         let snap = q.snapshot();
         assert_eq!(snap.len(), 1);
         assert_eq!(snap[0].status, TransferStatus::Pending);
     }
+
+    fn sync_file(path: &str, size: u64, mtime: u64) -> SyncFile {
+        SyncFile { relative_path: path.to_string(), size, mtime }
+    }
+
+    #[test]
+    fn test_plan_sync() {
+        let local = vec![
+            sync_file("same.txt", 10, 100),
+            sync_file("resized.txt", 20, 100),
+            sync_file("newer.txt", 10, 300),
+            sync_file("sub/new.txt", 5, 100),
+        ];
+        let remote = vec![
+            sync_file("same.txt", 10, 200),
+            sync_file("resized.txt", 10, 200),
+            sync_file("newer.txt", 10, 200),
+            sync_file("stale.txt", 1, 200),
+        ];
+
+        let plan = plan_sync(&local, &remote, false);
+        assert_eq!(plan.uploads, vec!["resized.txt", "newer.txt", "sub/new.txt"]);
+        assert_eq!(plan.skipped, 1);
+        assert!(plan.deletes.is_empty());
+
+        let plan = plan_sync(&local, &remote, true);
+        assert_eq!(plan.deletes, vec!["stale.txt"]);
+    }
+
+    #[test]
+    fn test_should_transfer() {
+        let local = sync_file("a.txt", 10, 1000);
+        assert!(should_transfer(&local, None));
+        // 大小相同且远程不早于本地
+        assert!(!should_transfer(&local, Some(&sync_file("a.txt", 10, 1000))));
+        assert!(!should_transfer(&local, Some(&sync_file("a.txt", 10, 5000))));
+        // 误差范围内视为相同
+        assert!(!should_transfer(&local, Some(&sync_file("a.txt", 10, 998))));
+        // 大小不同或本地明显更新
+        assert!(should_transfer(&local, Some(&sync_file("a.txt", 11, 5000))));
+        assert!(should_transfer(&local, Some(&sync_file("a.txt", 10, 997))));
+    }
+
+    #[test]
+    fn test_sync_report_summary() {
+        let report = SyncReport { transferred: 3, skipped: 7, deleted: 0 };
+        assert_eq!(report.summary(), "传输 3，跳过 7");
+        let report = SyncReport { deleted: 2, ..report };
+        assert_eq!(report.summary(), "传输 3，跳过 7，删除 2");
+    }
+
+    #[test]
+    fn test_transfer_event_progress() {
+        assert_eq!(
+            TransferEvent::progress(0.5, 1000),
+            TransferEvent::Progress { bytes: 500, total: 1000 }
+        );
+        assert_eq!(
+            TransferEvent::progress(1.5, 10),
+            TransferEvent::Progress { bytes: 10, total: 10 }
+        );
+        assert_eq!(
+            TransferEvent::progress(PROGRESS_INDETERMINATE, 10),
+            TransferEvent::Indeterminate
+        );
+    }
+
+    #[test]
+    fn test_indeterminate_progress_keeps_value() {
+        let (mut q, id) = make_queue_with_task();
+        q.update_progress(id, 0.3);
+        q.update_progress(id, PROGRESS_INDETERMINATE);
+        let task = q.get_task(id).unwrap();
+        assert_eq!(task.progress, 0.3);
+        assert_eq!(task.status, TransferStatus::InProgress);
+    }
+
+    #[test]
+    fn test_dir_progress_is_cumulative() {
+        assert!(scan_progress(0) > 0.0);
+        assert!(scan_progress(10) > scan_progress(0));
+        assert!(scan_progress(1_000_000) < DIR_SCAN_PROGRESS);
+
+        assert_eq!(dir_progress(0, 4, 0.0), DIR_SCAN_PROGRESS);
+        assert!(dir_progress(1, 4, 0.5) > dir_progress(1, 4, 0.0));
+        assert!(dir_progress(2, 4, 0.0) > dir_progress(1, 4, 0.9));
+        assert_eq!(dir_progress(4, 4, 0.0), 1.0);
+        assert_eq!(dir_progress(3, 4, 1.0), 1.0);
+        // 外部 scp 的不确定进度不会让累计进度倒退
+        assert_eq!(dir_progress(2, 4, PROGRESS_INDETERMINATE), dir_progress(2, 4, 0.0));
+        assert_eq!(dir_progress(0, 0, 0.0), 1.0);
+    }
+
+    #[test]
+    fn test_conflict_policy_parse() {
+        assert_eq!(ConflictPolicy::parse("overwrite"), Some(ConflictPolicy::Overwrite));
+        assert_eq!(ConflictPolicy::parse("skip"), Some(ConflictPolicy::Skip));
+        assert_eq!(ConflictPolicy::parse("keep-both"), Some(ConflictPolicy::KeepBoth));
+        assert_eq!(ConflictPolicy::parse("other"), None);
+    }
+
+    #[test]
+    fn test_pause_resume() {
+        let (mut q, id) = make_queue_with_task();
+        assert!(!q.is_paused());
+        q.pause();
+        assert!(q.is_paused());
+        q.pause();
+        assert_eq!(q.try_start(id), StartDecision::Wait);
+        q.resume();
+        assert!(!q.is_paused());
+        assert_eq!(q.start_decision(id), StartDecision::Start);
+        assert_eq!(q.snapshot()[0].status, TransferStatus::Pending);
+        assert_eq!(q.try_start(id), StartDecision::Start);
+        assert_eq!(q.snapshot()[0].status, TransferStatus::InProgress);
+
+        // 已开始的任务重试时同样受暂停约束
+        q.pause();
+        assert_eq!(q.try_start(id), StartDecision::Wait);
+    }
+
+    #[test]
+    fn test_has_active() {
+        let (mut q, id) = make_queue_with_task();
+        assert!(!q.has_active());
+        q.try_start(id);
+        assert!(q.has_active());
+        q.mark_completed(id);
+        assert!(!q.has_active());
+    }
+
+    fn enqueue_batch(q: &mut TransferQueue, count: usize) -> Vec<usize> {
+        let batch = q.new_batch();
+        (0..count)
+            .map(|i| {
+                let id = q.enqueue(Direction::Upload, PathBuf::from(format!("f{}", i)), "r".into(), "f".into(), 0, "s".into());
+                q.mark_batch(id, batch);
+                id
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_continue_on_error_keeps_batch_running() {
+        let mut q = TransferQueue::new();
+        let ids = enqueue_batch(&mut q, 3);
+        assert_eq!(q.try_start(ids[0]), StartDecision::Start);
+        assert_eq!(q.try_start(ids[1]), StartDecision::Start);
+        q.mark_failed(ids[0], "Permission denied".into());
+        assert_eq!(q.try_start(ids[2]), StartDecision::Start);
+    }
+
+    #[test]
+    fn test_stop_on_error_cancels_rest_of_batch() {
+        let mut q = TransferQueue::new();
+        q.set_batch_policy(BatchPolicy::StopOnError);
+        let ids = enqueue_batch(&mut q, 3);
+        let other = enqueue_batch(&mut q, 1)[0];
+
+        // 同批逐个传输，其他批次不受影响
+        assert_eq!(q.try_start(ids[0]), StartDecision::Start);
+        assert_eq!(q.try_start(ids[1]), StartDecision::Wait);
+        assert_eq!(q.try_start(other), StartDecision::Start);
+
+        q.mark_failed(ids[0], "Permission denied".into());
+        assert_eq!(q.try_start(ids[1]), StartDecision::Cancelled);
+        assert_eq!(q.try_start(ids[2]), StartDecision::Cancelled);
+        assert_eq!(
+            q.get_task(ids[2]).unwrap().status,
+            TransferStatus::Failed(BATCH_STOPPED_ERROR.into())
+        );
+        assert_eq!(q.get_task(other).unwrap().status, TransferStatus::InProgress);
+
+        // 重试后按顺序继续
+        q.retry_all_failed();
+        assert_eq!(q.try_start(ids[0]), StartDecision::Start);
+        assert_eq!(q.try_start(ids[1]), StartDecision::Wait);
+        q.mark_completed(ids[0]);
+        assert_eq!(q.try_start(ids[1]), StartDecision::Start);
+    }
+
+    #[test]
+    fn test_classify_error() {
+        assert_eq!(classify_error("连接失败: Connection reset by peer"), ErrorKind::Transient);
+        assert_eq!(classify_error("[Session(-9)] Timed out waiting on socket"), ErrorKind::Transient);
+        assert_eq!(classify_error("无法打开远程文件: no such file"), ErrorKind::Permanent);
+        assert_eq!(classify_error("Permission denied"), ErrorKind::Permanent);
+        assert_eq!(classify_error("远程空间不足: 需要 1 字节"), ErrorKind::Permanent);
+        assert_eq!(classify_error("未知错误"), ErrorKind::Permanent);
+    }
+
+    #[test]
+    fn test_retry_delay_backoff() {
+        assert_eq!(retry_delay(1), Duration::from_secs(1));
+        assert_eq!(retry_delay(2), Duration::from_secs(2));
+        assert_eq!(retry_delay(3), Duration::from_secs(4));
+    }
+
+    #[test]
+    fn test_record_retry_caps_attempts() {
+        let (mut q, id) = make_queue_with_task();
+        q.set_max_retries(2);
+        assert_eq!(q.record_retry(id, "timeout"), Some(Duration::from_secs(1)));
+        assert_eq!(q.record_retry(id, "timeout"), Some(Duration::from_secs(2)));
+        assert_eq!(q.record_retry(id, "timeout"), None);
+        assert_eq!(q.get_task(id).unwrap().attempts, 2);
+
+        // 手动重试重新计数，永久错误不自动重试
+        q.mark_failed(id, "timeout".into());
+        assert!(q.retry(id));
+        assert_eq!(q.get_task(id).unwrap().attempts, 0);
+        assert_eq!(q.record_retry(id, "permission denied"), None);
+    }
 }
@@ -0,0 +1,58 @@
+//! 读取系统剪贴板中的图片并保存为 PNG
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{anyhow, Context, Result};
+
+/// 把 RGBA 像素编码为 PNG
+fn encode_png(width: usize, height: usize, rgba: &[u8]) -> Result<Vec<u8>> {
+    if width == 0 || height == 0 || rgba.len() != width * height * 4 {
+        return Err(anyhow!("剪贴板图片数据无效"));
+    }
+    let mut bytes = Vec::new();
+    let mut encoder = png::Encoder::new(&mut bytes, width as u32, height as u32);
+    encoder.set_color(png::ColorType::Rgba);
+    encoder.set_depth(png::BitDepth::Eight);
+    let mut writer = encoder.write_header().map_err(|e| anyhow!("PNG 编码失败: {}", e))?;
+    writer.write_image_data(rgba).map_err(|e| anyhow!("PNG 编码失败: {}", e))?;
+    writer.finish().map_err(|e| anyhow!("PNG 编码失败: {}", e))?;
+    Ok(bytes)
+}
+
+/// 把剪贴板中的图片保存为 `dir/name`，剪贴板里没有图片时返回 None
+pub fn save_png(dir: &Path, name: &str) -> Result<Option<PathBuf>> {
+    let mut clipboard = arboard::Clipboard::new().map_err(|e| anyhow!("无法访问剪贴板: {}", e))?;
+    let image = match clipboard.get_image() {
+        Ok(image) => image,
+        Err(arboard::Error::ContentNotAvailable) => return Ok(None),
+        Err(e) => return Err(anyhow!("读取剪贴板图片失败: {}", e)),
+    };
+    let bytes = encode_png(image.width, image.height, &image.bytes)?;
+    fs::create_dir_all(dir).with_context(|| format!("无法创建目录: {:?}", dir))?;
+    let path = dir.join(name);
+    fs::write(&path, bytes).with_context(|| format!("无法写入文件: {:?}", path))?;
+    Ok(Some(path))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encode_png() {
+        let rgba = [255u8, 0, 0, 255, 0, 255, 0, 128];
+        let bytes = encode_png(2, 1, &rgba).unwrap();
+        assert_eq!(&bytes[..8], b"\x89PNG\r\n\x1a\n");
+
+        let decoder = png::Decoder::new(bytes.as_slice());
+        let mut reader = decoder.read_info().unwrap();
+        let mut buf = vec![0; reader.output_buffer_size()];
+        let info = reader.next_frame(&mut buf).unwrap();
+        assert_eq!((info.width, info.height), (2, 1));
+        assert_eq!(&buf[..info.buffer_size()], &rgba);
+
+        assert!(encode_png(2, 2, &rgba).is_err());
+        assert!(encode_png(0, 0, &[]).is_err());
+    }
+}
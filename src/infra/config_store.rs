@@ -1,36 +1,67 @@
 use anyhow::{Context, Result};
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 use crate::domain::config::AppConfig;
 use crate::domain::ports::ConfigRepository;
 
-pub struct TomlConfigStore;
+pub struct TomlConfigStore {
+    /// 指定文件路径，为空时使用配置目录
+    path: Option<PathBuf>,
+}
 
 impl TomlConfigStore {
     pub fn new() -> Self {
-        Self
+        Self { path: None }
+    }
+
+    /// 使用指定文件 (测试用)
+    #[cfg(test)]
+    pub fn with_path(path: PathBuf) -> Self {
+        Self { path: Some(path) }
     }
 
-    fn get_config_path() -> Result<PathBuf> {
+    fn get_config_path(&self) -> Result<PathBuf> {
+        if let Some(path) = &self.path {
+            return Ok(path.clone());
+        }
         let mut path = dirs::config_dir()
             .ok_or_else(|| anyhow::anyhow!("无法获取系统配置目录"))?;
         path.push("flick");
         path.push("server.toml");
         Ok(path)
     }
+
+    /// 读取配置；文件损坏时备份为 `server.toml.bak` 并改用默认配置，
+    /// 同时返回需要提示用户的说明
+    pub fn load_or_recover(&self) -> Result<(AppConfig, Option<String>)> {
+        let error = match self.load() {
+            Ok(config) => return Ok((config, None)),
+            Err(e) => e,
+        };
+        let config_path = self.get_config_path()?;
+        let mut backup = config_path.clone().into_os_string();
+        backup.push(".bak");
+        let backup = PathBuf::from(backup);
+        fs::rename(&config_path, &backup)
+            .with_context(|| format!("{:#}；备份损坏的配置文件失败", error))?;
+
+        let config = AppConfig::default();
+        self.save(&config).with_context(|| "创建默认配置文件失败")?;
+        let warning = format!(
+            "{:#}。原文件已备份为 {:?}，本次使用默认配置启动",
+            error, backup
+        );
+        Ok((config, Some(warning)))
+    }
 }
 
 impl ConfigRepository for TomlConfigStore {
     fn load(&self) -> Result<AppConfig> {
-        let config_path = Self::get_config_path()?;
+        let config_path = self.get_config_path()?;
 
         if config_path.exists() {
-            let content = fs::read_to_string(&config_path)
-                .with_context(|| format!("无法读取配置文件: {:?}", config_path))?;
-            let config: AppConfig = toml::from_str(&content)
-                .with_context(|| "配置文件格式错误，请检查 server.toml")?;
-            Ok(config)
+            read_config(&config_path).with_context(|| "配置文件格式错误，请检查 server.toml")
         } else {
             let config = AppConfig::default();
             self.save(&config)
@@ -40,21 +71,37 @@ impl ConfigRepository for TomlConfigStore {
     }
 
     fn save(&self, config: &AppConfig) -> Result<()> {
-        let config_path = Self::get_config_path()?;
+        write_config(&self.get_config_path()?, config)
+    }
 
-        if let Some(parent) = config_path.parent() {
-            fs::create_dir_all(parent)
-                .with_context(|| format!("无法创建配置目录: {:?}", parent))?;
-        }
+    fn export_to(&self, config: &AppConfig, path: &Path) -> Result<()> {
+        write_config(path, config)
+    }
 
-        let content = toml::to_string_pretty(config)
-            .with_context(|| "序列化配置失败")?;
+    fn import_from(&self, path: &Path) -> Result<AppConfig> {
+        read_config(path).with_context(|| format!("不是有效的配置文件: {:?}", path))
+    }
+}
 
-        fs::write(&config_path, content)
-            .with_context(|| format!("无法写入配置文件: {:?}", config_path))?;
+fn read_config(path: &Path) -> Result<AppConfig> {
+    let content = fs::read_to_string(path)
+        .with_context(|| format!("无法读取配置文件: {:?}", path))?;
+    Ok(toml::from_str(&content)?)
+}
 
-        Ok(())
+fn write_config(path: &Path, config: &AppConfig) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .with_context(|| format!("无法创建配置目录: {:?}", parent))?;
     }
+
+    let content = toml::to_string_pretty(config)
+        .with_context(|| "序列化配置失败")?;
+
+    fs::write(path, content)
+        .with_context(|| format!("无法写入配置文件: {:?}", path))?;
+
+    Ok(())
 }
 
 #[cfg(test)]
@@ -63,8 +110,46 @@ mod tests {
 
     #[test]
     fn test_config_path_not_empty() {
-        let path = TomlConfigStore::get_config_path().unwrap();
+        let path = TomlConfigStore::new().get_config_path().unwrap();
         assert!(path.to_string_lossy().contains("flick"));
         assert!(path.to_string_lossy().contains("server.toml"));
     }
+
+    #[test]
+    fn test_export_import_roundtrip() {
+        let store = TomlConfigStore::new();
+        let path = std::env::temp_dir().join("flick_test_export.toml");
+        let mut config = AppConfig::default();
+        config.servers[0].name = "exported".to_string();
+        store.export_to(&config, &path).unwrap();
+
+        let imported = store.import_from(&path).unwrap();
+        assert_eq!(imported.servers[0].name, "exported");
+
+        fs::write(&path, "not = [valid").unwrap();
+        assert!(store.import_from(&path).is_err());
+        let _ = fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_recover_from_corrupt_config() {
+        let dir = std::env::temp_dir().join("flick_test_corrupt_config");
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("server.toml");
+        let broken = "[[servers]]\nname = \"prod\nhost = ";
+        fs::write(&path, broken).unwrap();
+
+        let store = TomlConfigStore::with_path(path.clone());
+        assert!(store.load().is_err());
+        let (config, warning) = store.load_or_recover().unwrap();
+        assert_eq!(config.servers.len(), AppConfig::default().servers.len());
+        assert!(warning.unwrap().contains("server.toml.bak"));
+        assert_eq!(fs::read_to_string(dir.join("server.toml.bak")).unwrap(), broken);
+
+        // 默认配置已写回，再次读取不再提示
+        let (_, warning) = store.load_or_recover().unwrap();
+        assert!(warning.is_none());
+        let _ = fs::remove_dir_all(&dir);
+    }
 }
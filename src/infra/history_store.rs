@@ -0,0 +1,76 @@
+use anyhow::{Context, Result};
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+use crate::domain::history::{HistoryEntry, TransferHistory};
+use crate::domain::ports::HistoryRepository;
+use crate::shared::panic_guard::lock;
+
+/// 传输历史存储 (`<config_dir>/flick/history.toml`)
+#[derive(Default)]
+pub struct TomlHistoryStore {
+    /// 串行化多个传输线程的读-改-写
+    lock: Mutex<()>,
+}
+
+impl TomlHistoryStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn get_history_path() -> Result<PathBuf> {
+        let mut path = dirs::config_dir()
+            .ok_or_else(|| anyhow::anyhow!("无法获取系统配置目录"))?;
+        path.push("flick");
+        path.push("history.toml");
+        Ok(path)
+    }
+
+    fn read(path: &PathBuf) -> Result<TransferHistory> {
+        if !path.exists() {
+            return Ok(TransferHistory::default());
+        }
+        let content = fs::read_to_string(path)
+            .with_context(|| format!("无法读取传输历史: {:?}", path))?;
+        toml::from_str(&content).with_context(|| "传输历史格式错误")
+    }
+}
+
+impl HistoryRepository for TomlHistoryStore {
+    fn load(&self) -> Result<TransferHistory> {
+        let _guard = lock(&self.lock);
+        Self::read(&Self::get_history_path()?)
+    }
+
+    fn append(&self, entry: HistoryEntry) -> Result<()> {
+        let _guard = lock(&self.lock);
+        let path = Self::get_history_path()?;
+
+        // 历史文件损坏时从头记录，不影响传输本身
+        let mut history = Self::read(&path).unwrap_or_default();
+        history.push(entry);
+
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)
+                .with_context(|| format!("无法创建配置目录: {:?}", parent))?;
+        }
+        let content = toml::to_string_pretty(&history)
+            .with_context(|| "序列化传输历史失败")?;
+        fs::write(&path, content)
+            .with_context(|| format!("无法写入传输历史: {:?}", path))?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_history_path_not_empty() {
+        let path = TomlHistoryStore::get_history_path().unwrap();
+        assert!(path.to_string_lossy().contains("flick"));
+        assert!(path.to_string_lossy().contains("history.toml"));
+    }
+}
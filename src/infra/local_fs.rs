@@ -1,7 +1,10 @@
 use std::fs;
 use std::os::windows::fs::MetadataExt;
 use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::atomic::{AtomicUsize, Ordering};
 
+use anyhow::Context;
 use chrono::{DateTime, Local};
 
 /// 本地文件/目录条目
@@ -11,11 +14,15 @@ pub struct LocalEntry {
     pub is_dir: bool,
     pub size: u64,
     pub modified: String,
+    /// 修改时间 (Unix 秒)，无法获取时为 0
+    pub mtime: u64,
     pub path: PathBuf,
+    /// 按需计算的目录总大小
+    pub dir_size: Option<u64>,
 }
 
-/// 列出目录内容，目录优先排序
-pub fn list_dir(path: &Path) -> anyhow::Result<Vec<LocalEntry>> {
+/// 列出目录内容，目录优先排序；`show_hidden` 为 false 时过滤隐藏文件和系统文件
+pub fn list_dir(path: &Path, show_hidden: bool) -> anyhow::Result<Vec<LocalEntry>> {
     // 特殊处理：如果是空路径，列出所有盘符
     if path.as_os_str().is_empty() {
         return Ok(list_drives());
@@ -32,27 +39,34 @@ pub fn list_dir(path: &Path) -> anyhow::Result<Vec<LocalEntry>> {
         const FILE_ATTRIBUTE_HIDDEN: u32 = 0x2;
         const FILE_ATTRIBUTE_SYSTEM: u32 = 0x4;
         
-        if (attributes & FILE_ATTRIBUTE_HIDDEN != 0) || (attributes & FILE_ATTRIBUTE_SYSTEM != 0) {
+        if !show_hidden
+            && ((attributes & FILE_ATTRIBUTE_HIDDEN != 0) || (attributes & FILE_ATTRIBUTE_SYSTEM != 0))
+        {
             continue;
         }
 
         let name = entry.file_name().to_string_lossy().to_string();
 
-        let modified = metadata
-            .modified()
-            .ok()
+        let modified_time = metadata.modified().ok();
+        let modified = modified_time
             .map(|t| {
                 let dt: DateTime<Local> = t.into();
                 dt.format("%Y-%m-%d %H:%M").to_string()
             })
             .unwrap_or_default();
+        let mtime = modified_time
+            .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
 
         entries.push(LocalEntry {
             name,
             is_dir: metadata.is_dir(),
             size: if metadata.is_dir() { 0 } else { metadata.len() },
             modified,
+            mtime,
             path: entry.path(),
+            dir_size: None,
         });
     }
 
@@ -64,6 +78,215 @@ pub fn list_dir(path: &Path) -> anyhow::Result<Vec<LocalEntry>> {
     Ok(entries)
 }
 
+/// 递归统计目录总大小，不跟随符号链接，无法读取的条目跳过
+pub fn dir_size(path: &Path) -> u64 {
+    let mut total = 0;
+    let mut stack = vec![path.to_path_buf()];
+    while let Some(dir) = stack.pop() {
+        let read = match fs::read_dir(&dir) {
+            Ok(r) => r,
+            Err(_) => continue,
+        };
+        for entry in read.flatten() {
+            let metadata = match fs::symlink_metadata(entry.path()) {
+                Ok(m) => m,
+                Err(_) => continue,
+            };
+            if metadata.is_dir() {
+                stack.push(entry.path());
+            } else if metadata.is_file() {
+                total += metadata.len();
+            }
+        }
+    }
+    total
+}
+
+/// 复制文件或整个目录，不跟随目录符号链接
+pub fn copy_path(src: &Path, dst: &Path) -> anyhow::Result<()> {
+    let metadata = fs::symlink_metadata(src)
+        .with_context(|| format!("无法读取: {:?}", src))?;
+    if !metadata.is_dir() {
+        fs::copy(src, dst).with_context(|| format!("复制失败: {:?}", src))?;
+        return Ok(());
+    }
+    fs::create_dir_all(dst).with_context(|| format!("无法创建目录: {:?}", dst))?;
+    for entry in fs::read_dir(src)? {
+        let entry = entry?;
+        copy_path(&entry.path(), &dst.join(entry.file_name()))?;
+    }
+    Ok(())
+}
+
+/// 移动文件或目录，跨磁盘时改为复制后删除源
+pub fn move_path(src: &Path, dst: &Path) -> anyhow::Result<()> {
+    match fs::rename(src, dst) {
+        Ok(()) => Ok(()),
+        Err(e) if e.kind() == std::io::ErrorKind::CrossesDevices => {
+            copy_path(src, dst)?;
+            remove_path(src)
+        }
+        Err(e) => Err(e).with_context(|| format!("移动失败: {:?}", src)),
+    }
+}
+
+/// 新建空文件，目标已存在时报错而不是清空
+pub fn create_empty_file(path: &Path) -> anyhow::Result<()> {
+    fs::OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(path)
+        .with_context(|| format!("无法新建文件: {:?}", path))?;
+    Ok(())
+}
+
+/// 删除文件或整个目录
+pub fn remove_path(path: &Path) -> anyhow::Result<()> {
+    let result = match fs::symlink_metadata(path) {
+        Ok(m) if m.is_dir() => fs::remove_dir_all(path),
+        _ => fs::remove_file(path),
+    };
+    result.with_context(|| format!("删除失败: {:?}", path))
+}
+
+/// 把文件或目录移到系统回收站
+pub fn move_to_trash(path: &Path) -> anyhow::Result<()> {
+    trash::delete(path).with_context(|| format!("移到回收站失败: {:?}", path))
+}
+
+/// 与 `path` 同目录的临时名，用于先写好新内容再替换
+fn sibling_temp_path(path: &Path, tag: &str) -> PathBuf {
+    static COUNTER: AtomicUsize = AtomicUsize::new(0);
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(format!(
+        ".flick-{}-{}-{}",
+        tag,
+        std::process::id(),
+        COUNTER.fetch_add(1, Ordering::Relaxed)
+    ));
+    path.with_file_name(name)
+}
+
+/// 把 `src` 复制 (或移动) 为 `dst`，调用方需事先确认覆盖。
+/// `dst` 已存在时先把新内容放到同目录的临时名，成功后才替换旧目标；
+/// 中途失败时旧目标保持不变
+pub fn paste_path(src: &Path, dst: &Path, cut: bool) -> anyhow::Result<()> {
+    if src == dst {
+        return Ok(());
+    }
+    if dst.starts_with(src) {
+        anyhow::bail!("不能粘贴到自身的子目录中");
+    }
+    if src.starts_with(dst) {
+        anyhow::bail!("目标是源的上级目录，无法覆盖");
+    }
+    if fs::symlink_metadata(dst).is_err() {
+        return if cut { move_path(src, dst) } else { copy_path(src, dst) };
+    }
+
+    // 剪切时同盘直接改名到临时名，否则复制，源在替换成功后再删除
+    let staged = sibling_temp_path(dst, "paste");
+    let renamed = cut && fs::rename(src, &staged).is_ok();
+    if !renamed {
+        if let Err(e) = copy_path(src, &staged) {
+            let _ = remove_path(&staged);
+            return Err(e);
+        }
+    }
+
+    let backup = sibling_temp_path(dst, "old");
+    let replaced = fs::rename(dst, &backup).and_then(|_| {
+        fs::rename(&staged, dst).inspect_err(|_| {
+            let _ = fs::rename(&backup, dst);
+        })
+    });
+    if let Err(e) = replaced {
+        if renamed {
+            let _ = fs::rename(&staged, src);
+        } else {
+            let _ = remove_path(&staged);
+        }
+        return Err(e).with_context(|| format!("无法替换: {:?}", dst));
+    }
+    remove_path(&backup)?;
+    if cut && !renamed {
+        remove_path(src)?;
+    }
+    Ok(())
+}
+
+/// 用系统默认程序打开文件 (目录则在文件管理器中打开)
+pub fn open_with_default(path: &Path) -> anyhow::Result<()> {
+    open_command(path)
+        .spawn()
+        .map(|_| ())
+        .with_context(|| format!("无法打开: {:?}", path))
+}
+
+/// 在系统文件管理器中显示并选中该条目
+pub fn reveal_in_file_manager(path: &Path) -> anyhow::Result<()> {
+    reveal_command(path)
+        .spawn()
+        .map(|_| ())
+        .with_context(|| format!("无法打开文件管理器: {:?}", path))
+}
+
+#[cfg(windows)]
+fn open_command(path: &Path) -> Command {
+    let mut cmd = Command::new("explorer");
+    cmd.arg(path);
+    cmd
+}
+
+#[cfg(target_os = "macos")]
+fn open_command(path: &Path) -> Command {
+    let mut cmd = Command::new("open");
+    cmd.arg(path);
+    cmd
+}
+
+#[cfg(not(any(windows, target_os = "macos")))]
+fn open_command(path: &Path) -> Command {
+    let mut cmd = Command::new("xdg-open");
+    cmd.arg(path);
+    cmd
+}
+
+#[cfg(windows)]
+fn reveal_command(path: &Path) -> Command {
+    use std::os::windows::process::CommandExt;
+    // explorer 要求 `/select,` 与路径连在一起，路径需自行加引号
+    let mut cmd = Command::new("explorer");
+    cmd.raw_arg(format!("/select,\"{}\"", path.display()));
+    cmd
+}
+
+#[cfg(target_os = "macos")]
+fn reveal_command(path: &Path) -> Command {
+    let mut cmd = Command::new("open");
+    cmd.arg("-R").arg(path);
+    cmd
+}
+
+/// 多数 Linux 文件管理器不支持选中参数，打开所在目录
+#[cfg(not(any(windows, target_os = "macos")))]
+fn reveal_command(path: &Path) -> Command {
+    let mut cmd = Command::new("xdg-open");
+    cmd.arg(path.parent().unwrap_or(path));
+    cmd
+}
+
+/// 查询路径所在磁盘的可用空间 (字节)，取挂载点最长匹配的磁盘，找不到时返回 None
+pub fn available_space(path: &Path) -> Option<u64> {
+    let disks = sysinfo::Disks::new_with_refreshed_list();
+    disks
+        .list()
+        .iter()
+        .filter(|d| path.starts_with(d.mount_point()))
+        .max_by_key(|d| d.mount_point().as_os_str().len())
+        .map(|d| d.available_space())
+}
+
 fn list_drives() -> Vec<LocalEntry> {
     let mut entries = Vec::new();
 
@@ -80,7 +303,9 @@ fn list_drives() -> Vec<LocalEntry> {
             // 无需额外系统依赖，仅用于驱动器列表展示。
             size: 0,
             modified: String::new(),
+            mtime: 0,
             path: mount_point,
+            dir_size: None,
         });
     }
 
@@ -127,13 +352,97 @@ mod tests {
             .status()
             .expect("failed to execute attrib");
 
-        let entries = list_dir(&temp_dir).unwrap();
+        let entries = list_dir(&temp_dir, false).unwrap();
         
         let names: Vec<String> = entries.into_iter().map(|e| e.name).collect();
         assert!(names.contains(&"normal.txt".to_string()));
         assert!(!names.contains(&"hidden.txt".to_string()));
 
+        let entries = list_dir(&temp_dir, true).unwrap();
+        let names: Vec<String> = entries.into_iter().map(|e| e.name).collect();
+        assert!(names.contains(&"hidden.txt".to_string()));
+
         fs::remove_dir_all(&temp_dir).unwrap();
     }
-}
 
+    #[test]
+    fn test_dir_size() {
+        use std::env;
+        use std::io::Write;
+        let temp_dir = env::temp_dir().join("flick_test_dir_size");
+        if temp_dir.exists() {
+            fs::remove_dir_all(&temp_dir).unwrap();
+        }
+        fs::create_dir_all(temp_dir.join("sub")).unwrap();
+        File::create(temp_dir.join("a.txt")).unwrap().write_all(&[0u8; 100]).unwrap();
+        File::create(temp_dir.join("sub").join("b.txt")).unwrap().write_all(&[0u8; 23]).unwrap();
+
+        assert_eq!(dir_size(&temp_dir), 123);
+
+        fs::remove_dir_all(&temp_dir).unwrap();
+    }
+
+    #[test]
+    fn test_create_empty_file_does_not_overwrite() {
+        let dir = std::env::temp_dir().join("flick_test_touch");
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("new.txt");
+        create_empty_file(&path).unwrap();
+        assert_eq!(fs::metadata(&path).unwrap().len(), 0);
+
+        fs::write(&path, "keep").unwrap();
+        assert!(create_empty_file(&path).is_err());
+        assert_eq!(fs::read_to_string(&path).unwrap(), "keep");
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_paste_path_copy_and_move() {
+        let temp_dir = std::env::temp_dir().join("flick_test_paste_path");
+        let _ = fs::remove_dir_all(&temp_dir);
+        fs::create_dir_all(temp_dir.join("src").join("sub")).unwrap();
+        fs::create_dir_all(temp_dir.join("dst")).unwrap();
+        fs::write(temp_dir.join("src").join("sub").join("a.txt"), "a").unwrap();
+        fs::write(temp_dir.join("dst").join("sub"), "old").unwrap();
+
+        // 复制目录并覆盖同名文件
+        let copied = temp_dir.join("dst").join("sub");
+        paste_path(&temp_dir.join("src").join("sub"), &copied, false).unwrap();
+        assert_eq!(fs::read_to_string(copied.join("a.txt")).unwrap(), "a");
+        assert!(temp_dir.join("src").join("sub").exists());
+
+        // 移动后源不再存在
+        let moved = temp_dir.join("moved");
+        paste_path(&temp_dir.join("src").join("sub"), &moved, true).unwrap();
+        assert!(moved.join("a.txt").exists());
+        assert!(!temp_dir.join("src").join("sub").exists());
+
+        // 不能粘贴到自身内部，也不能覆盖自己的上级目录
+        assert!(paste_path(&moved, &moved.join("inner"), false).is_err());
+        assert!(paste_path(&moved.join("a.txt"), &moved, true).is_err());
+        assert!(moved.join("a.txt").exists());
+
+        fs::remove_dir_all(&temp_dir).unwrap();
+    }
+
+    #[test]
+    fn test_paste_path_failure_keeps_target() {
+        let temp_dir = std::env::temp_dir().join("flick_test_paste_keep");
+        let _ = fs::remove_dir_all(&temp_dir);
+        fs::create_dir_all(&temp_dir).unwrap();
+        let dst = temp_dir.join("a.txt");
+        fs::write(&dst, "old").unwrap();
+
+        // 源不存在时复制失败，已有的目标不能被删掉
+        assert!(paste_path(&temp_dir.join("missing.txt"), &dst, false).is_err());
+        assert_eq!(fs::read_to_string(&dst).unwrap(), "old");
+
+        fs::write(temp_dir.join("b.txt"), "new").unwrap();
+        paste_path(&temp_dir.join("b.txt"), &dst, true).unwrap();
+        assert_eq!(fs::read_to_string(&dst).unwrap(), "new");
+        assert_eq!(fs::read_dir(&temp_dir).unwrap().count(), 1);
+
+        fs::remove_dir_all(&temp_dir).unwrap();
+    }
+}
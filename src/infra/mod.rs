@@ -1,4 +1,9 @@
+pub mod clipboard_image;
 pub mod config_store;
+pub mod history_store;
 pub mod local_fs;
+pub mod notifier;
+pub mod queue_store;
 pub mod remote_fs;
 pub mod ssh;
+pub mod ssh_config;
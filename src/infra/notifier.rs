@@ -0,0 +1,22 @@
+//! 系统桌面通知
+
+use notify_rust::Notification;
+
+/// 提示音名称：Windows 为 Toast 内置音效，其他平台为 freedesktop 音效主题名
+#[cfg(windows)]
+const SOUND_NAME: &str = "Default";
+#[cfg(not(windows))]
+const SOUND_NAME: &str = "message-new-instant";
+
+/// 弹出桌面通知，`sound` 为 true 时同时播放提示音
+pub fn show(summary: &str, body: &str, sound: bool) -> anyhow::Result<()> {
+    let mut notification = Notification::new();
+    notification.appname("Flick").summary(summary).body(body);
+    if sound {
+        notification.sound_name(SOUND_NAME);
+    }
+    notification
+        .show()
+        .map_err(|e| anyhow::anyhow!("桌面通知发送失败: {}", e))?;
+    Ok(())
+}
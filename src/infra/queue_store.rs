@@ -0,0 +1,280 @@
+use anyhow::{Context, Result};
+use std::collections::BTreeSet;
+use std::fs::{self, File, OpenOptions, TryLockError};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use crate::domain::ports::QueueRepository;
+use crate::domain::transfer::TransferTask;
+use crate::shared::panic_guard::lock;
+
+/// 旧版本所有实例共用的队列文件名，启动时并入本实例
+const LEGACY_QUEUE_FILE: &str = "queue.json";
+
+/// 未完成传输任务存储 (`<config_dir>/flick/queue-<pid>.json`)。
+/// 每个实例只写自己的文件，并对同名 `.lock` 文件持有排它锁；
+/// 启动时把锁已释放 (实例已退出) 的其他队列文件并入本实例
+pub struct JsonQueueStore {
+    /// 指定存放目录，为空时使用配置目录
+    dir: Option<PathBuf>,
+    /// 本实例的编号 (进程号)
+    instance: u32,
+    /// 本实例持有的锁文件，进程退出时由系统释放
+    instance_lock: Mutex<Option<File>>,
+    /// 串行化多个传输线程的写入
+    lock: Mutex<()>,
+}
+
+impl Default for JsonQueueStore {
+    fn default() -> Self {
+        Self {
+            dir: None,
+            instance: std::process::id(),
+            instance_lock: Mutex::new(None),
+            lock: Mutex::new(()),
+        }
+    }
+}
+
+impl JsonQueueStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 使用指定目录和实例编号 (测试用)
+    #[cfg(test)]
+    pub fn with_dir(dir: PathBuf, instance: u32) -> Self {
+        Self {
+            dir: Some(dir),
+            instance,
+            ..Self::default()
+        }
+    }
+
+    fn queue_dir(&self) -> Result<PathBuf> {
+        if let Some(dir) = &self.dir {
+            return Ok(dir.clone());
+        }
+        let mut path = dirs::config_dir()
+            .ok_or_else(|| anyhow::anyhow!("无法获取系统配置目录"))?;
+        path.push("flick");
+        Ok(path)
+    }
+
+    fn queue_path(&self) -> Result<PathBuf> {
+        Ok(self.queue_dir()?.join(queue_file_name(self.instance)))
+    }
+
+    /// 首次读写前创建并锁定本实例的锁文件
+    fn claim_instance(&self, dir: &Path) -> Result<()> {
+        let mut held = lock(&self.instance_lock);
+        if held.is_some() {
+            return Ok(());
+        }
+        fs::create_dir_all(dir).with_context(|| format!("无法创建配置目录: {:?}", dir))?;
+        let path = dir.join(lock_file_name(self.instance));
+        let file = OpenOptions::new()
+            .create(true)
+            .truncate(false)
+            .write(true)
+            .open(&path)
+            .with_context(|| format!("无法创建队列锁文件: {:?}", path))?;
+        file.try_lock()
+            .map_err(|e| anyhow::anyhow!("无法锁定队列文件 {:?}: {}", path, e))?;
+        *held = Some(file);
+        Ok(())
+    }
+
+    /// 其他实例的编号：锁已释放 (实例已退出) 才返回，锁文件不存在也视为已退出
+    fn orphaned_instances(&self, dir: &Path) -> Result<Vec<u32>> {
+        let mut ids = BTreeSet::new();
+        for entry in fs::read_dir(dir).with_context(|| format!("无法读取目录: {:?}", dir))? {
+            let name = entry?.file_name().to_string_lossy().to_string();
+            if let Some(id) = parse_instance(&name).filter(|&id| id != self.instance) {
+                ids.insert(id);
+            }
+        }
+        Ok(ids
+            .into_iter()
+            .filter(|&id| {
+                let path = dir.join(lock_file_name(id));
+                match OpenOptions::new().write(true).open(&path) {
+                    Ok(file) => !matches!(file.try_lock(), Err(TryLockError::WouldBlock)),
+                    Err(_) => !path.exists(),
+                }
+            })
+            .collect())
+    }
+}
+
+fn queue_file_name(instance: u32) -> String {
+    format!("queue-{}.json", instance)
+}
+
+fn lock_file_name(instance: u32) -> String {
+    format!("queue-{}.lock", instance)
+}
+
+/// 从 `queue-<id>.json` / `queue-<id>.lock` 中取出实例编号
+fn parse_instance(name: &str) -> Option<u32> {
+    let rest = name.strip_prefix("queue-")?;
+    let id = rest
+        .strip_suffix(".json")
+        .or_else(|| rest.strip_suffix(".lock"))?;
+    id.parse().ok()
+}
+
+fn read_tasks(path: &Path) -> Result<Vec<TransferTask>> {
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let content = fs::read_to_string(path)
+        .with_context(|| format!("无法读取传输队列: {:?}", path))?;
+    serde_json::from_str(&content).with_context(|| format!("传输队列格式错误: {:?}", path))
+}
+
+fn write_tasks(path: &Path, tasks: &[TransferTask]) -> Result<()> {
+    // 没有未完成的任务时删除文件，下次启动不再提示
+    if tasks.is_empty() {
+        if path.exists() {
+            fs::remove_file(path)
+                .with_context(|| format!("无法删除传输队列: {:?}", path))?;
+        }
+        return Ok(());
+    }
+    let content = serde_json::to_string(tasks)
+        .with_context(|| "序列化传输队列失败")?;
+    // 先写临时文件再替换，写到一半退出也不会留下损坏的队列文件
+    let temp = path.with_extension("json.tmp");
+    fs::write(&temp, content)
+        .with_context(|| format!("无法写入传输队列: {:?}", temp))?;
+    fs::rename(&temp, path)
+        .with_context(|| format!("无法写入传输队列: {:?}", path))?;
+    Ok(())
+}
+
+impl QueueRepository for JsonQueueStore {
+    /// 读取本实例和已退出实例的任务；并入的任务先写进本实例的文件，再删除原文件
+    fn load(&self) -> Result<Vec<TransferTask>> {
+        let _guard = lock(&self.lock);
+        let dir = self.queue_dir()?;
+        self.claim_instance(&dir)?;
+        let own = self.queue_path()?;
+
+        let mut tasks = read_tasks(&own)?;
+        let mut claimed = vec![dir.join(LEGACY_QUEUE_FILE)];
+        tasks.extend(read_tasks(&claimed[0])?);
+        for id in self.orphaned_instances(&dir)? {
+            let path = dir.join(queue_file_name(id));
+            tasks.extend(read_tasks(&path)?);
+            claimed.push(path);
+            claimed.push(dir.join(lock_file_name(id)));
+        }
+
+        write_tasks(&own, &tasks)?;
+        for path in claimed.iter().filter(|p| p.exists()) {
+            let _ = fs::remove_file(path);
+        }
+        Ok(tasks)
+    }
+
+    fn save(&self, tasks: &[TransferTask]) -> Result<()> {
+        let _guard = lock(&self.lock);
+        let dir = self.queue_dir()?;
+        self.claim_instance(&dir)?;
+        write_tasks(&self.queue_path()?, tasks)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::transfer::{Direction, TransferQueue, TransferStatus};
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(name);
+        let _ = fs::remove_dir_all(&dir);
+        dir
+    }
+
+    fn one_task(remote: &str) -> Vec<TransferTask> {
+        let mut q = TransferQueue::new();
+        let id = q.enqueue(Direction::Download, PathBuf::from("/l/a"), remote.into(), "a".into(), 42, "prod".into());
+        q.update_progress(id, 0.3);
+        q.snapshot()
+    }
+
+    #[test]
+    fn test_queue_path_not_empty() {
+        let path = JsonQueueStore::new().queue_path().unwrap();
+        assert!(path.to_string_lossy().contains("flick"));
+        assert!(path.to_string_lossy().contains(&format!("queue-{}.json", std::process::id())));
+    }
+
+    #[test]
+    fn test_parse_instance() {
+        assert_eq!(parse_instance("queue-42.json"), Some(42));
+        assert_eq!(parse_instance("queue-42.lock"), Some(42));
+        assert_eq!(parse_instance("queue.json"), None);
+        assert_eq!(parse_instance("queue-x.json"), None);
+    }
+
+    #[test]
+    fn test_save_and_load_round_trip() {
+        let dir = temp_dir("flick_test_queue");
+        let store = JsonQueueStore::with_dir(dir.clone(), 1);
+        assert!(store.load().unwrap().is_empty());
+
+        store.save(&one_task("/r/a")).unwrap();
+
+        let loaded = store.load().unwrap();
+        assert_eq!(loaded.len(), 1);
+        assert_eq!(loaded[0].direction, Direction::Download);
+        assert_eq!(loaded[0].remote_path, "/r/a");
+        assert_eq!(loaded[0].size, 42);
+        assert_eq!(loaded[0].status, TransferStatus::InProgress);
+        assert!(loaded[0].started_at.is_none());
+
+        assert!(!dir.join("queue-1.json.tmp").exists());
+
+        store.save(&[]).unwrap();
+        assert!(!dir.join("queue-1.json").exists());
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_instances_do_not_overwrite_each_other() {
+        let dir = temp_dir("flick_test_queue_instances");
+        let first = JsonQueueStore::with_dir(dir.clone(), 1);
+        first.save(&one_task("/r/first")).unwrap();
+
+        // 第一个实例仍在运行，第二个实例既不覆盖也不接管它的任务
+        let second = JsonQueueStore::with_dir(dir.clone(), 2);
+        assert!(second.load().unwrap().is_empty());
+        second.save(&one_task("/r/second")).unwrap();
+        assert_eq!(read_tasks(&dir.join("queue-1.json")).unwrap()[0].remote_path, "/r/first");
+
+        // 第一个实例退出后，下一个启动的实例并入它的任务
+        drop(first);
+        let third = JsonQueueStore::with_dir(dir.clone(), 3);
+        let loaded = third.load().unwrap();
+        assert_eq!(loaded.len(), 1);
+        assert_eq!(loaded[0].remote_path, "/r/first");
+        assert!(!dir.join("queue-1.json").exists());
+        assert!(dir.join("queue-2.json").exists());
+        assert_eq!(read_tasks(&dir.join("queue-3.json")).unwrap().len(), 1);
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_load_merges_legacy_file() {
+        let dir = temp_dir("flick_test_queue_legacy");
+        fs::create_dir_all(&dir).unwrap();
+        write_tasks(&dir.join(LEGACY_QUEUE_FILE), &one_task("/r/legacy")).unwrap();
+
+        let store = JsonQueueStore::with_dir(dir.clone(), 1);
+        assert_eq!(store.load().unwrap()[0].remote_path, "/r/legacy");
+        assert!(!dir.join(LEGACY_QUEUE_FILE).exists());
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
@@ -1,16 +1,49 @@
 use crate::domain::config::ServerConfig;
-use crate::infra::ssh::{AuthMode, SshUploader};
+use crate::infra::ssh::native_fallback::{apply_identity_agent, apply_proxy_jump};
+use crate::infra::ssh::transfer::copy_counting;
+use crate::infra::ssh::{host, open_sftp, AuthMode, SshUploader};
+use crate::shared::remote_path;
 use anyhow::{anyhow, Result};
 use chrono::{Local, TimeZone};
+use serde::Serialize;
+use ssh2::Session;
+use std::io::{Read, Write};
 use std::path::Path;
+use std::process::Stdio;
+use std::thread;
+use std::time::{Duration, Instant};
 
 /// 远程文件/目录条目
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct RemoteEntry {
     pub name: String,
     pub is_dir: bool,
     pub size: u64,
     pub modified: String,
+    /// 修改时间 (Unix 秒)，无法获取时为 0
+    pub mtime: u64,
+    /// 按需计算的目录总大小
+    pub dir_size: Option<u64>,
+    pub is_symlink: bool,
+    /// 符号链接指向的目标（无法读取时为空）
+    pub link_target: String,
+}
+
+/// 单个远程条目的详细属性 (属性对话框用)
+#[derive(Debug, Clone, PartialEq)]
+pub struct RemoteStat {
+    pub path: String,
+    pub size: u64,
+    /// 权限位，如 0o755
+    pub mode: u32,
+    /// 属主与属组：SFTP 只能取得数字 id，原生模式为名称
+    pub owner: String,
+    pub group: String,
+    /// 修改时间 (Unix 秒)，无法获取时为 0
+    pub mtime: u64,
+    pub is_dir: bool,
+    /// 符号链接指向的目标，非符号链接为 None
+    pub link_target: Option<String>,
 }
 
 /// 转义 shell 参数，防止注入攻击
@@ -20,24 +53,46 @@ pub fn escape_shell_arg(arg: &str) -> String {
     format!("'{}'", escaped)
 }
 
+/// 远程用户主目录 (`echo $HOME`)，结果缓存在连接上
+pub fn remote_home(uploader: &SshUploader) -> Result<String> {
+    if let Some(home) = uploader.home_dir_cache().get() {
+        return Ok(home.clone());
+    }
+    let home = remote_exec(uploader, "echo $HOME")?.trim().to_string();
+    if home.is_empty() {
+        return Err(anyhow!("无法获取远程主目录"));
+    }
+    Ok(uploader.home_dir_cache().get_or_init(|| home).clone())
+}
+
+/// 展开远程路径开头的 `~` (SFTP 和带引号的 shell 参数都不会展开)，查询主目录失败时原样返回
+pub fn expand_tilde(uploader: &SshUploader, path: &str) -> String {
+    if !path.starts_with('~') {
+        return path.to_string();
+    }
+    match remote_home(uploader) {
+        Ok(home) => remote_path::expand_tilde(path, &home),
+        Err(_) => path.to_string(),
+    }
+}
+
 /// 通过 SFTP 列出远程目录
 pub fn list_dir_sftp(
     uploader: &SshUploader,
     path: &str,
 ) -> Result<Vec<RemoteEntry>> {
+    let path = expand_tilde(uploader, path);
     if *uploader.auth_mode() == AuthMode::NativeSsh {
-        return list_dir_native(uploader.config(), path);
+        return list_dir_native(uploader.config(), &path);
     }
 
-    let sftp = uploader
-        .session()
-        .sftp()
-        .map_err(|e| anyhow!("SFTP 会话失败: {}", e))?;
+    let sftp = open_sftp(uploader.session())?;
 
     let dir = sftp
-        .readdir(Path::new(path))
+        .readdir(Path::new(&path))
         .map_err(|e| anyhow!("读取目录失败: {}", e))?;
 
+    // readdir 返回的是 lstat 信息，符号链接需再 stat 一次判断目标是否为目录
     let mut entries: Vec<RemoteEntry> = dir
         .into_iter()
         .filter_map(|(p, stat)| {
@@ -45,8 +100,19 @@ pub fn list_dir_sftp(
             if name == "." || name == ".." {
                 return None;
             }
-            let is_dir = stat.is_dir();
+            let is_symlink = stat.file_type().is_symlink();
+            let (is_dir, link_target) = if is_symlink {
+                let target = sftp
+                    .readlink(&p)
+                    .map(|t| t.to_string_lossy().replace('\\', "/"))
+                    .unwrap_or_default();
+                let target_is_dir = sftp.stat(&p).map(|s| s.is_dir()).unwrap_or(false);
+                (target_is_dir, target)
+            } else {
+                (stat.is_dir(), String::new())
+            };
             let size = stat.size.unwrap_or(0);
+            let mtime = stat.mtime.unwrap_or(0);
             let modified = stat
                 .mtime
                 .and_then(|t| {
@@ -60,6 +126,10 @@ pub fn list_dir_sftp(
                 is_dir,
                 size,
                 modified,
+                mtime,
+                dir_size: None,
+                is_symlink,
+                link_target,
             })
         })
         .collect();
@@ -73,41 +143,94 @@ pub fn list_dir_sftp(
     Ok(entries)
 }
 
-/// 通过系统 ssh 命令列出远程目录（NativeSsh 兜底）
-fn list_dir_native(
-    config: &ServerConfig,
-    path: &str,
-) -> Result<Vec<RemoteEntry>> {
-    use std::process::Command;
-
-    let mut cmd = Command::new("ssh");
-    cmd.arg("-o").arg("BatchMode=yes")
-        .arg("-o").arg("StrictHostKeyChecking=no")
-        .arg("-p").arg(config.port.to_string());
-
-    if let Some(key) = &config.key_path {
-        if !key.is_empty() {
-            cmd.arg("-i").arg(key);
-        }
-    }
-
-    #[cfg(windows)]
-    {
-        use std::os::windows::process::CommandExt;
-        cmd.creation_flags(0x08000000);
+/// 读取单个条目的属性 (不跟随符号链接)：SFTP 模式用 lstat，原生模式执行 `stat`
+pub fn stat(uploader: &SshUploader, path: &str) -> Result<RemoteStat> {
+    let path = expand_tilde(uploader, path);
+    if *uploader.auth_mode() == AuthMode::NativeSsh {
+        let arg = escape_shell_arg(&path);
+        let output = remote_exec_native(
+            uploader.config(),
+            &format!("stat -c '%s|%a|%U|%G|%Y|%F' {arg} && (readlink {arg} || true)"),
+        )?;
+        return parse_stat_output(&path, &output).ok_or_else(|| anyhow!("无法解析 stat 输出"));
     }
 
-    cmd.arg(format!("{}@{}", config.user, config.host));
-    cmd.arg(format!("ls -la --time-style=long-iso {}", escape_shell_arg(path)));
+    let sftp = open_sftp(uploader.session())?;
+    let st = sftp
+        .lstat(Path::new(&path))
+        .map_err(|e| anyhow!("读取属性失败: {}", e))?;
+    let link_target = if st.file_type().is_symlink() {
+        sftp.readlink(Path::new(&path))
+            .ok()
+            .map(|t| t.to_string_lossy().replace('\\', "/"))
+    } else {
+        None
+    };
+    Ok(RemoteStat {
+        size: st.size.unwrap_or(0),
+        mode: st.perm.unwrap_or(0) & 0o7777,
+        owner: st.uid.map(|id| id.to_string()).unwrap_or_default(),
+        group: st.gid.map(|id| id.to_string()).unwrap_or_default(),
+        mtime: st.mtime.unwrap_or(0),
+        is_dir: st.is_dir(),
+        link_target,
+        path,
+    })
+}
 
-    let output = cmd.output()?;
-    if !output.status.success() {
-        let stderr = String::from_utf8_lossy(&output.stderr);
-        return Err(anyhow!("ls 失败: {}", stderr.trim()));
+/// 解析 `stat -c '%s|%a|%U|%G|%Y|%F'` 的输出，符号链接的下一行为 readlink 结果
+fn parse_stat_output(path: &str, output: &str) -> Option<RemoteStat> {
+    let mut lines = output.lines();
+    let fields: Vec<&str> = lines.next()?.trim().splitn(6, '|').collect();
+    if fields.len() != 6 {
+        return None;
     }
+    let is_symlink = fields[5] == "symbolic link";
+    Some(RemoteStat {
+        path: path.to_string(),
+        size: fields[0].parse().ok()?,
+        mode: u32::from_str_radix(fields[1], 8).ok()?,
+        owner: fields[2].to_string(),
+        group: fields[3].to_string(),
+        mtime: fields[4].parse().unwrap_or(0),
+        is_dir: fields[5] == "directory",
+        link_target: if is_symlink {
+            lines.next().map(|l| l.trim().to_string())
+        } else {
+            None
+        },
+    })
+}
+
+/// 远程命令统一使用 UTF-8 语言环境，避免非 UTF-8 locale 下文件名被转义或转码
+const NATIVE_LOCALE: &str = "LC_ALL=C.UTF-8";
 
-    let stdout = String::from_utf8_lossy(&output.stdout);
-    let mut entries = parse_ls_output(&stdout);
+/// 通过系统 ssh 命令列出远程目录（NativeSsh 兜底）。
+/// 优先用 `find -printf` 获取与语言环境无关的输出，每条记录以 NUL 结尾，
+/// 文件名中的空格、制表符和换行都能正确解析；不支持时退回 `ls`。
+fn list_dir_native(
+    config: &ServerConfig,
+    path: &str,
+) -> Result<Vec<RemoteEntry>> {
+    let find_cmd = format!(
+        "{} find {} -mindepth 1 -maxdepth 1 -printf '%y\\t%Y\\t%s\\t%T@\\t%l\\t%f\\0'",
+        NATIVE_LOCALE,
+        escape_shell_arg(path)
+    );
+    let mut entries = match remote_exec_native(config, &find_cmd) {
+        Ok(output) => parse_find_listing(&output),
+        // busybox 等精简环境的 find 不支持 -printf
+        Err(_) => {
+            let ls_cmd = format!(
+                "{} ls -la --time-style=long-iso {}",
+                NATIVE_LOCALE,
+                escape_shell_arg(path)
+            );
+            let output = remote_exec_native(config, &ls_cmd)
+                .map_err(|e| anyhow!("ls 失败: {}", e))?;
+            parse_ls_output(&output)
+        }
+    };
 
     entries.sort_by(|a, b| {
         b.is_dir
@@ -120,10 +243,14 @@ fn list_dir_native(
 
 /// 在远程执行 shell 命令的辅助函数
 fn remote_exec(uploader: &SshUploader, command: &str) -> Result<String> {
-    if *uploader.auth_mode() == AuthMode::NativeSsh {
-        return remote_exec_native(uploader.config(), command);
+    exec_with(uploader.session(), uploader.auth_mode(), uploader.config(), command)
+}
+
+fn exec_with(session: &Session, auth_mode: &AuthMode, config: &ServerConfig, command: &str) -> Result<String> {
+    if *auth_mode == AuthMode::NativeSsh {
+        return remote_exec_native(config, command);
     }
-    let mut channel = uploader.session().channel_session()
+    let mut channel = session.channel_session()
         .map_err(|e| anyhow!("创建 channel 失败: {}", e))?;
     channel.exec(command).map_err(|e| anyhow!("执行命令失败: {}", e))?;
     let mut output = String::new();
@@ -136,12 +263,233 @@ fn remote_exec(uploader: &SshUploader, command: &str) -> Result<String> {
     Ok(output)
 }
 
+/// 在远程执行命令，分别返回 stdout、stderr 和退出码 (非零退出码不视为错误)
+pub fn remote_exec_capture(uploader: &SshUploader, command: &str) -> Result<(String, String, i32)> {
+    if *uploader.auth_mode() == AuthMode::NativeSsh {
+        let output = native_ssh_command(uploader.config(), command).output()?;
+        return Ok((
+            String::from_utf8_lossy(&output.stdout).to_string(),
+            String::from_utf8_lossy(&output.stderr).to_string(),
+            output.status.code().unwrap_or(-1),
+        ));
+    }
+    let mut channel = uploader.session().channel_session()
+        .map_err(|e| anyhow!("创建 channel 失败: {}", e))?;
+    channel.exec(command).map_err(|e| anyhow!("执行命令失败: {}", e))?;
+    let mut stdout = String::new();
+    std::io::Read::read_to_string(&mut channel, &mut stdout)?;
+    let mut stderr = String::new();
+    std::io::Read::read_to_string(&mut channel.stderr(), &mut stderr)?;
+    channel.wait_close().ok();
+    let exit = channel.exit_status().unwrap_or(-1);
+    Ok((stdout, stderr, exit))
+}
+
+/// 执行交互输入的命令：stderr 在远端并入 stdout，避免两路输出互相阻塞；
+/// 超过 `timeout` 仍未结束则中止并返回错误。返回合并后的输出和退出码
+pub fn remote_exec_merged(
+    uploader: &SshUploader,
+    command: &str,
+    timeout: Duration,
+) -> Result<(String, i32)> {
+    let merged = format!("{{ {}\n}} 2>&1", command);
+    let timed_out = || anyhow!("命令执行超时 ({} 秒)，已中止", timeout.as_secs());
+    if *uploader.auth_mode() == AuthMode::NativeSsh {
+        let mut child = native_ssh_command(uploader.config(), &merged)
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()?;
+        let stdout = drain_in_background(child.stdout.take());
+        let stderr = drain_in_background(child.stderr.take());
+        let deadline = Instant::now() + timeout;
+        let status = loop {
+            if let Some(status) = child.try_wait()? {
+                break status;
+            }
+            if Instant::now() >= deadline {
+                let _ = child.kill();
+                let _ = child.wait();
+                return Err(timed_out());
+            }
+            thread::sleep(Duration::from_millis(100));
+        };
+        // 本地 ssh 自身的报错 (如连接失败) 附在输出末尾
+        let mut output = stdout.join().unwrap_or_default();
+        output.push_str(&stderr.join().unwrap_or_default());
+        return Ok((output, status.code().unwrap_or(-1)));
+    }
+    let session = uploader.session();
+    let previous = session.timeout();
+    session.set_timeout(timeout.as_millis().min(u32::MAX as u128) as u32);
+    let result = (|| {
+        let mut channel = session.channel_session()
+            .map_err(|e| anyhow!("创建 channel 失败: {}", e))?;
+        channel.exec(&merged).map_err(|e| anyhow!("执行命令失败: {}", e))?;
+        let mut bytes = Vec::new();
+        channel.read_to_end(&mut bytes).map_err(|e| {
+            if e.kind() == std::io::ErrorKind::TimedOut {
+                timed_out()
+            } else {
+                e.into()
+            }
+        })?;
+        channel.wait_close().ok();
+        let exit = channel.exit_status().unwrap_or(-1);
+        Ok((String::from_utf8_lossy(&bytes).into_owned(), exit))
+    })();
+    session.set_timeout(previous);
+    result
+}
+
+/// 在后台线程读尽子进程的一路输出，防止管道写满阻塞子进程
+fn drain_in_background(stream: Option<impl Read + Send + 'static>) -> thread::JoinHandle<String> {
+    thread::spawn(move || {
+        let mut bytes = Vec::new();
+        if let Some(mut stream) = stream {
+            let _ = stream.read_to_end(&mut bytes);
+        }
+        String::from_utf8_lossy(&bytes).into_owned()
+    })
+}
+
+/// `remote_exec_to_writer` 的结果，退出码由调用方解释 (如 tar 的 1 只是警告)
+pub struct StreamOutput {
+    pub exit: i32,
+    pub stderr: String,
+}
+
+/// 保留的 stderr 上限，足够显示错误原因
+const STDERR_LIMIT: usize = 64 * 1024;
+
+/// 在远程执行命令并把 stdout 原样写入 `out` (用于 tar 等二进制输出)，stderr 同时读取；
+/// `on_bytes` 每次写入后收到累计字节数
+pub fn remote_exec_to_writer(
+    uploader: &SshUploader,
+    command: &str,
+    out: &mut impl Write,
+    on_bytes: &dyn Fn(u64),
+) -> Result<StreamOutput> {
+    if *uploader.auth_mode() == AuthMode::NativeSsh {
+        let mut child = native_ssh_command(uploader.config(), command)
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()?;
+        let stderr = drain_in_background(child.stderr.take());
+        let mut stdout = child.stdout.take().ok_or_else(|| anyhow!("无法读取 ssh 输出"))?;
+        let written = copy_counting(&mut stdout, out, on_bytes);
+        if written.is_err() {
+            let _ = child.kill();
+        }
+        let status = child.wait()?;
+        let stderr = stderr.join().unwrap_or_default();
+        written?;
+        return Ok(StreamOutput { exit: status.code().unwrap_or(-1), stderr });
+    }
+    let session = uploader.session();
+    let mut channel = session.channel_session()
+        .map_err(|e| anyhow!("创建 channel 失败: {}", e))?;
+    channel.exec(command).map_err(|e| anyhow!("执行命令失败: {}", e))?;
+    session.set_blocking(false);
+    let result = read_channel_streams(&mut channel, out, on_bytes);
+    session.set_blocking(true);
+    let stderr = result?;
+    channel.wait_close().ok();
+    Ok(StreamOutput { exit: channel.exit_status().unwrap_or(-1), stderr })
+}
+
+/// 在非阻塞模式下交替读取 channel 的 stdout 和 stderr，
+/// 避免 stderr 积压占满窗口后远端停止输出、本端一直等待 stdout
+fn read_channel_streams(
+    channel: &mut ssh2::Channel,
+    out: &mut impl Write,
+    on_bytes: &dyn Fn(u64),
+) -> Result<String> {
+    let mut buf = vec![0u8; 64 * 1024];
+    let mut written = 0u64;
+    let mut stderr = Vec::new();
+    loop {
+        let mut progressed = false;
+        match channel.read(&mut buf) {
+            Ok(0) => {}
+            Ok(n) => {
+                out.write_all(&buf[..n])?;
+                written += n as u64;
+                on_bytes(written);
+                progressed = true;
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::WouldBlock => {}
+            Err(e) => return Err(e.into()),
+        }
+        match channel.stderr().read(&mut buf) {
+            Ok(0) => {}
+            Ok(n) => {
+                let keep = n.min(STDERR_LIMIT.saturating_sub(stderr.len()));
+                stderr.extend_from_slice(&buf[..keep]);
+                progressed = true;
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::WouldBlock => {}
+            Err(e) => return Err(e.into()),
+        }
+        if !progressed {
+            // 远端已发送 EOF 且没有未读数据
+            if channel.eof() {
+                break;
+            }
+            thread::sleep(Duration::from_millis(5));
+        }
+    }
+    out.flush()?;
+    Ok(String::from_utf8_lossy(&stderr).into_owned())
+}
+
+/// 探活用的连接句柄：在持锁时从连接复制出来，释放锁后再发起网络请求
+pub struct PingHandle {
+    session: Session,
+    auth_mode: AuthMode,
+    config: ServerConfig,
+}
+
+impl PingHandle {
+    pub fn new(uploader: &SshUploader) -> Self {
+        Self {
+            session: uploader.session().clone(),
+            auth_mode: uploader.auth_mode().clone(),
+            config: uploader.config().clone(),
+        }
+    }
+}
+
+/// 执行空命令探测连接是否存活，返回往返耗时
+pub fn remote_ping(handle: &PingHandle) -> Result<std::time::Duration> {
+    let started = std::time::Instant::now();
+    exec_with(&handle.session, &handle.auth_mode, &handle.config, "true")?;
+    Ok(started.elapsed())
+}
+
 fn remote_exec_native(config: &ServerConfig, command: &str) -> Result<String> {
+    let stdout = remote_exec_native_bytes(config, command)?;
+    Ok(String::from_utf8_lossy(&stdout).to_string())
+}
+
+/// 通过系统 ssh 执行命令并返回原始 stdout 字节（用于读取二进制内容）
+fn remote_exec_native_bytes(config: &ServerConfig, command: &str) -> Result<Vec<u8>> {
+    let output = native_ssh_command(config, command).output()?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(anyhow!("命令失败: {}", stderr.trim()));
+    }
+    Ok(output.stdout)
+}
+
+/// 构造通过系统 ssh 执行远程命令的 Command
+fn native_ssh_command(config: &ServerConfig, command: &str) -> std::process::Command {
     use std::process::Command;
     let mut cmd = Command::new("ssh");
     cmd.arg("-o").arg("BatchMode=yes")
         .arg("-o").arg("StrictHostKeyChecking=no")
         .arg("-p").arg(config.port.to_string());
+    apply_proxy_jump(&mut cmd, config);
+    apply_identity_agent(&mut cmd, config);
     if let Some(key) = &config.key_path {
         if !key.is_empty() {
             cmd.arg("-i").arg(key);
@@ -152,68 +500,515 @@ fn remote_exec_native(config: &ServerConfig, command: &str) -> Result<String> {
         use std::os::windows::process::CommandExt;
         cmd.creation_flags(0x08000000);
     }
-    cmd.arg(format!("{}@{}", config.user, config.host));
+    cmd.arg(format!("{}@{}", config.user, host::bare_host(&config.host)));
     cmd.arg(command);
-    let output = cmd.output()?;
-    if !output.status.success() {
-        let stderr = String::from_utf8_lossy(&output.stderr);
-        return Err(anyhow!("命令失败: {}", stderr.trim()));
+    cmd
+}
+
+/// 读取远程文件内容，超过 `max_bytes` 时拒绝读取
+pub fn read_file_bytes(
+    uploader: &SshUploader,
+    path: &str,
+    max_bytes: u64,
+) -> Result<Vec<u8>> {
+    let too_large = || anyhow!("文件超过预览上限 ({} 字节)", max_bytes);
+    let path = expand_tilde(uploader, path);
+
+    if *uploader.auth_mode() == AuthMode::NativeSsh {
+        let cmd = format!("head -c {} {}", max_bytes + 1, escape_shell_arg(&path));
+        let bytes = remote_exec_native_bytes(uploader.config(), &cmd)?;
+        if bytes.len() as u64 > max_bytes {
+            return Err(too_large());
+        }
+        return Ok(bytes);
+    }
+
+    let sftp = open_sftp(uploader.session())?;
+    let mut file = sftp
+        .open(Path::new(&path))
+        .map_err(|e| anyhow!("打开远程文件失败: {}", e))?;
+
+    if let Some(size) = file.stat().ok().and_then(|st| st.size) {
+        if size > max_bytes {
+            return Err(too_large());
+        }
+    }
+
+    let mut bytes = Vec::new();
+    std::io::Read::read_to_end(
+        &mut std::io::Read::take(&mut file, max_bytes + 1),
+        &mut bytes,
+    )?;
+    if bytes.len() as u64 > max_bytes {
+        return Err(too_large());
     }
-    Ok(String::from_utf8_lossy(&output.stdout).to_string())
+    Ok(bytes)
+}
+
+/// 将内容写回远程文件：先写临时文件再替换，保留原文件权限。
+/// 目标是符号链接时写回链接指向的文件，不会把链接本身替换成普通文件；
+/// 替换用 `mv -T`，目标被换成目录时报错而不是把临时文件移进目录
+pub fn write_file_bytes(uploader: &SshUploader, path: &str, bytes: &[u8]) -> Result<()> {
+    let path = expand_tilde(uploader, path);
+
+    if *uploader.auth_mode() == AuthMode::NativeSsh {
+        let cmd = format!(
+            "dst=$(readlink -f -- {path}) || dst={path}; tmp=\"$dst.flick-tmp\"; \
+             cat > \"$tmp\" && (chmod --reference=\"$dst\" \"$tmp\" 2>/dev/null; true) \
+             && mv -fT \"$tmp\" \"$dst\"",
+            path = escape_shell_arg(&path),
+        );
+        let mut child = native_ssh_command(uploader.config(), &cmd)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::piped())
+            .spawn()?;
+        if let Some(mut stdin) = child.stdin.take() {
+            stdin.write_all(bytes)?;
+        }
+        let output = child.wait_with_output()?;
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            return Err(anyhow!("写回远程文件失败: {}", stderr.trim()));
+        }
+        return Ok(());
+    }
+
+    let sftp = open_sftp(uploader.session())?;
+    let path = match sftp.lstat(Path::new(&path)) {
+        Ok(st) if st.file_type().is_symlink() => sftp
+            .realpath(Path::new(&path))
+            .map_err(|e| anyhow!("无法解析符号链接 {}: {}", path, e))?
+            .to_string_lossy()
+            .replace('\\', "/"),
+        _ => path,
+    };
+    let temp = format!("{}.flick-tmp", path);
+    let perm = sftp.stat(Path::new(&path)).ok().and_then(|st| st.perm);
+
+    let result = sftp
+        .create(Path::new(&temp))
+        .map_err(|e| anyhow!("创建临时文件失败: {}", e))
+        .and_then(|mut file| {
+            std::io::Write::write_all(&mut file, bytes)?;
+            Ok(())
+        })
+        .and_then(|_| match perm {
+            Some(perm) => {
+                let stat = ssh2::FileStat {
+                    size: None,
+                    uid: None,
+                    gid: None,
+                    perm: Some(perm & 0o7777),
+                    atime: None,
+                    mtime: None,
+                };
+                sftp.setstat(Path::new(&temp), stat)
+                    .map_err(|e| anyhow!("设置文件权限失败: {}", e))
+            }
+            None => Ok(()),
+        })
+        .and_then(|_| {
+            remote_exec(
+                uploader,
+                &format!("mv -fT {} {}", escape_shell_arg(&temp), escape_shell_arg(&path)),
+            )
+            .map(|_| ())
+        });
+    if result.is_err() {
+        let _ = sftp.unlink(Path::new(&temp));
+    }
+    result
 }
 
 /// 在远程创建目录
 pub fn remote_mkdir(uploader: &SshUploader, path: &str) -> Result<()> {
-    let cmd = format!("mkdir -p {}", escape_shell_arg(path));
+    let path = expand_tilde(uploader, path);
+    let cmd = format!("mkdir -p {}", escape_shell_arg(&path));
     remote_exec(uploader, &cmd)?;
     Ok(())
 }
 
+/// 在远程新建空文件；借助 noclobber，目标已存在时失败而不是清空
+pub fn remote_touch(uploader: &SshUploader, path: &str) -> Result<()> {
+    let path = expand_tilde(uploader, path);
+    let cmd = format!("set -C && : > {}", escape_shell_arg(&path));
+    remote_exec(uploader, &cmd).map_err(|e| anyhow!("新建文件失败: {}", e))?;
+    Ok(())
+}
+
+/// 修改权限位 (`chmod <八进制> <path>`)，不递归
+pub fn remote_chmod(uploader: &SshUploader, path: &str, mode: u32) -> Result<()> {
+    let path = expand_tilde(uploader, path);
+    ensure_attr_target(&path)?;
+    if mode > 0o7777 {
+        return Err(anyhow!("无效的权限: {:o}", mode));
+    }
+    let cmd = format!("chmod {:o} {}", mode, escape_shell_arg(&path));
+    run_attr_command(uploader, &cmd, "修改权限")
+}
+
+/// 修改属主 (`chown -h <user[:group]> <path>`)，不递归；符号链接只改链接本身
+pub fn remote_chown(uploader: &SshUploader, path: &str, owner: &str) -> Result<()> {
+    let path = expand_tilde(uploader, path);
+    ensure_attr_target(&path)?;
+    let owner = owner.trim();
+    if !is_valid_owner_spec(owner) {
+        return Err(anyhow!("无效的属主: {}", owner));
+    }
+    let cmd = format!("chown -h {} {}", escape_shell_arg(owner), escape_shell_arg(&path));
+    run_attr_command(uploader, &cmd, "修改属主")
+}
+
+/// 执行 chmod/chown，失败时把命令的错误输出带入错误信息
+fn run_attr_command(uploader: &SshUploader, cmd: &str, action: &str) -> Result<()> {
+    let (_, stderr, code) =
+        remote_exec_capture(uploader, cmd).map_err(|e| anyhow!("{}失败: {}", action, e))?;
+    if code != 0 {
+        return Err(anyhow!("{}失败 (退出码 {}): {}", action, code, stderr.trim()));
+    }
+    Ok(())
+}
+
+/// 拒绝对根目录等路径修改权限或属主
+fn ensure_attr_target(path: &str) -> Result<()> {
+    let normalized = remote_path::normalize(path);
+    if normalized == "/" || normalized == "." || path.trim().is_empty() {
+        return Err(anyhow!("不允许修改该路径的属性: {}", path));
+    }
+    Ok(())
+}
+
+/// `user`、`user:group` 或 `:group`，名称只允许字母、数字和 `._-`，不能以 `-` 开头
+fn is_valid_owner_spec(spec: &str) -> bool {
+    let valid_name = |name: &str| {
+        !name.is_empty()
+            && !name.starts_with('-')
+            && name.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '_' | '-'))
+    };
+    match spec.split_once(':') {
+        Some(("", group)) => valid_name(group),
+        Some((user, group)) => valid_name(user) && valid_name(group),
+        None => valid_name(spec),
+    }
+}
+
 /// 删除远程文件或目录
 pub fn remote_remove(uploader: &SshUploader, path: &str, is_dir: bool) -> Result<()> {
-    let cmd = if is_dir {
-        format!("rm -rf {}", escape_shell_arg(path))
-    } else {
-        format!("rm -f {}", escape_shell_arg(path))
-    };
-    remote_exec(uploader, &cmd)?;
+    run_remove(uploader, path, is_dir, false)
+}
+
+/// 以 `sudo -n` 删除，仅在远程账户可免密 sudo 时成功
+pub fn remote_remove_sudo(uploader: &SshUploader, path: &str, is_dir: bool) -> Result<()> {
+    run_remove(uploader, path, is_dir, true)
+}
+
+fn run_remove(uploader: &SshUploader, path: &str, is_dir: bool, sudo: bool) -> Result<()> {
+    let path = expand_tilde(uploader, path);
+    let (_, stderr, code) = remote_exec_capture(uploader, &remove_command(&path, is_dir, sudo))?;
+    if code != 0 {
+        // rm -rf 遇到无权限的文件会继续删除其余文件，stderr 中逐个列出失败项
+        let detail = stderr.trim();
+        return Err(if detail.is_empty() {
+            anyhow!("命令退出码 {}", code)
+        } else {
+            anyhow!("{}", detail)
+        });
+    }
     Ok(())
 }
 
+fn remove_command(path: &str, is_dir: bool, sudo: bool) -> String {
+    format!(
+        "{}{} {}",
+        if sudo { "sudo -n " } else { "" },
+        if is_dir { "rm -rf" } else { "rm -f" },
+        escape_shell_arg(path)
+    )
+}
+
 /// 重命名远程文件或目录
 pub fn remote_rename(uploader: &SshUploader, old_path: &str, new_path: &str) -> Result<()> {
-    let cmd = format!("mv {} {}", escape_shell_arg(old_path), escape_shell_arg(new_path));
+    let old_path = expand_tilde(uploader, old_path);
+    let new_path = expand_tilde(uploader, new_path);
+    // -T：新路径是已有目录时替换它 (非空则报错)，而不是把源移进该目录
+    let cmd = format!("mv -T {} {}", escape_shell_arg(&old_path), escape_shell_arg(&new_path));
     remote_exec(uploader, &cmd)?;
     Ok(())
 }
 
-/// 解析 `ls -la --time-style=long-iso` 输出
+/// 远程路径是否已存在 (含指向无效目标的符号链接)
+pub fn remote_exists(uploader: &SshUploader, path: &str) -> Result<bool> {
+    let path = escape_shell_arg(&expand_tilde(uploader, path));
+    let cmd = format!("test -e {p} || test -L {p}", p = path);
+    match remote_exec_capture(uploader, &cmd)? {
+        (_, _, 0) => Ok(true),
+        (_, _, 1) => Ok(false),
+        (_, stderr, code) => Err(anyhow!("检查路径失败 (退出码 {}): {}", code, stderr.trim())),
+    }
+}
+
+/// 远程路径是否为目录 (跟随符号链接)
+pub fn remote_is_dir(uploader: &SshUploader, path: &str) -> Result<bool> {
+    let cmd = format!("test -d {}", escape_shell_arg(&expand_tilde(uploader, path)));
+    match remote_exec_capture(uploader, &cmd)? {
+        (_, _, 0) => Ok(true),
+        (_, _, 1) => Ok(false),
+        (_, stderr, code) => Err(anyhow!("检查路径失败 (退出码 {}): {}", code, stderr.trim())),
+    }
+}
+/// 在一条命令中依次执行多个重命名，任一失败即停止；与单个重命名一样用 `-T`，不会把源移进同名目录
+/// 在一条命令中依次执行多个重命名，任一失败即停止
+pub fn remote_rename_batch(uploader: &SshUploader, renames: &[(String, String)]) -> Result<()> {
+    if renames.is_empty() {
+        return Ok(());
+    }
+    let cmd = renames
+        .iter()
+        .map(|(old, new)| {
+            format!(
+                "mv -T {} {}",
+                escape_shell_arg(&expand_tilde(uploader, old)),
+                escape_shell_arg(&expand_tilde(uploader, new))
+            )
+        })
+        .collect::<Vec<_>>()
+        .join(" && ");
+    remote_exec(uploader, &cmd)?;
+    Ok(())
+}
+
+/// 计算远程目录总大小 (字节)
+pub fn remote_dir_size(uploader: &SshUploader, path: &str) -> Result<u64> {
+    let path = expand_tilde(uploader, path);
+    let cmd = format!("du -sb {}", escape_shell_arg(&path));
+    let output = remote_exec(uploader, &cmd)?;
+    parse_du_output(&output).ok_or_else(|| anyhow!("无法解析 du 输出: {}", output.trim()))
+}
+
+/// 统计远程文件或目录下所有普通文件的字节数之和，不跟随符号链接
+pub fn remote_file_bytes(uploader: &SshUploader, path: &str) -> Result<u64> {
+    let path = expand_tilde(uploader, path);
+    let cmd = format!("find {} -type f -printf '%s\\n'", escape_shell_arg(&path));
+    let output = remote_exec(uploader, &cmd)?;
+    parse_size_lines(&output).ok_or_else(|| anyhow!("无法解析文件大小: {}", output.trim()))
+}
+
+/// 累加每行一个的字节数
+fn parse_size_lines(output: &str) -> Option<u64> {
+    output.lines().map(|l| l.trim().parse::<u64>().ok()).sum()
+}
+
+/// 解析 `du -sb` 输出的字节数
+fn parse_du_output(output: &str) -> Option<u64> {
+    output.split_whitespace().next()?.parse().ok()
+}
+
+/// 查询远程目录所在文件系统的可用空间 (字节)
+pub fn remote_free_space(uploader: &SshUploader, path: &str) -> Result<u64> {
+    let path = expand_tilde(uploader, path);
+    let cmd = format!("df -B1 -P {}", escape_shell_arg(&path));
+    let output = remote_exec(uploader, &cmd)?;
+    parse_df_output(&output).ok_or_else(|| anyhow!("无法解析 df 输出: {}", output.trim()))
+}
+
+/// 解析 `df -B1 -P` 输出第二行的可用字节数 (第 4 列)
+fn parse_df_output(output: &str) -> Option<u64> {
+    parse_df_usage(output).map(|(available, _)| available)
+}
+
+/// 查询远程目录所在文件系统的 (可用, 总计) 字节数
+pub fn remote_disk_usage(uploader: &SshUploader, path: &str) -> Result<(u64, u64)> {
+    let path = expand_tilde(uploader, path);
+    let cmd = format!("df -B1 -P {}", escape_shell_arg(&path));
+    let output = remote_exec(uploader, &cmd)?;
+    parse_df_usage(&output).ok_or_else(|| anyhow!("无法解析 df 输出: {}", output.trim()))
+}
+
+/// 解析 `df -B1 -P` 输出第二行的 (可用, 总计) 字节数 (第 4、2 列)
+fn parse_df_usage(output: &str) -> Option<(u64, u64)> {
+    let columns: Vec<&str> = output.lines().nth(1)?.split_whitespace().collect();
+    let total = columns.get(1)?.parse().ok()?;
+    let available = columns.get(3)?.parse().ok()?;
+    Some((available, total))
+}
+
+/// 复制远程文件或目录
+pub fn remote_copy(uploader: &SshUploader, src: &str, dst: &str, is_dir: bool) -> Result<()> {
+    let src = expand_tilde(uploader, src);
+    let dst = expand_tilde(uploader, dst);
+    let cmd = if is_dir {
+        format!("cp -r {} {}", escape_shell_arg(&src), escape_shell_arg(&dst))
+    } else {
+        format!("cp {} {}", escape_shell_arg(&src), escape_shell_arg(&dst))
+    };
+    remote_exec(uploader, &cmd)?;
+    Ok(())
+}
+
+/// 递归搜索结果数量上限，避免在大目录树上卡住界面
+pub const FIND_RESULT_LIMIT: usize = 1000;
+
+/// 在 root 下递归搜索文件名匹配 pattern 的条目 (忽略大小写)，返回条目名为完整路径。
+/// pattern 不含通配符时按包含匹配处理。
+pub fn remote_find(uploader: &SshUploader, root: &str, pattern: &str) -> Result<Vec<RemoteEntry>> {
+    let pattern = if pattern.contains(['*', '?', '[']) {
+        pattern.to_string()
+    } else {
+        format!("*{}*", pattern)
+    };
+    let cmd = format!(
+        "find {} -mindepth 1 -iname {} -printf '%y\\t%s\\t%TY-%Tm-%Td %TH:%TM\\t%p\\n' 2>/dev/null | head -n {}",
+        escape_shell_arg(&expand_tilde(uploader, root)),
+        escape_shell_arg(&pattern),
+        FIND_RESULT_LIMIT
+    );
+    let output = remote_exec(uploader, &cmd)?;
+    Ok(parse_find_output(&output))
+}
+
+/// 把列表中显示的 `YYYY-MM-DD HH:MM` 按本地时区还原为 Unix 秒，无法解析时为 0
+fn parse_display_mtime(modified: &str) -> u64 {
+    chrono::NaiveDateTime::parse_from_str(modified, "%Y-%m-%d %H:%M")
+        .ok()
+        .and_then(|naive| Local.from_local_datetime(&naive).earliest())
+        .map(|dt| dt.timestamp().max(0) as u64)
+        .unwrap_or(0)
+}
+
+/// 解析 `find -printf '%y\t%s\t%TY-%Tm-%Td %TH:%TM\t%p\n'` 输出
+fn parse_find_output(output: &str) -> Vec<RemoteEntry> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let mut parts = line.splitn(4, '\t');
+            let kind = parts.next()?;
+            let size: u64 = parts.next()?.parse().unwrap_or(0);
+            let modified = parts.next()?;
+            let path = parts.next()?;
+            if path.is_empty() {
+                return None;
+            }
+            Some(RemoteEntry {
+                name: path.to_string(),
+                is_dir: kind == "d",
+                size,
+                modified: modified.to_string(),
+                mtime: parse_display_mtime(modified),
+                dir_size: None,
+                is_symlink: kind == "l",
+                link_target: String::new(),
+            })
+        })
+        .collect()
+}
+
+/// 解析 `find -printf '%y\t%Y\t%s\t%T@\t%l\t%f\0'` 输出 (单层目录列表)，
+/// 记录以 NUL 分隔，文件名位于最后一列，可以包含制表符和换行
+fn parse_find_listing(output: &str) -> Vec<RemoteEntry> {
+    output
+        .split('\0')
+        .filter_map(|record| {
+            let mut parts = record.splitn(6, '\t');
+            let kind = parts.next()?;
+            let target_kind = parts.next()?;
+            let size: u64 = parts.next()?.parse().ok()?;
+            let mtime: f64 = parts.next()?.parse().ok()?;
+            let link_target = parts.next()?;
+            let name = parts.next()?;
+            if name.is_empty() || name == "." || name == ".." {
+                return None;
+            }
+            let is_symlink = kind == "l";
+            // %Y 为链接目标的类型，指向目录的链接可直接进入
+            let is_dir = kind == "d" || (is_symlink && target_kind == "d");
+            let modified = Local
+                .timestamp_opt(mtime as i64, 0)
+                .single()
+                .map(|dt| dt.format("%Y-%m-%d %H:%M").to_string())
+                .unwrap_or_default();
+            Some(RemoteEntry {
+                name: name.to_string(),
+                is_dir,
+                size,
+                modified,
+                mtime: mtime as u64,
+                dir_size: None,
+                is_symlink,
+                link_target: link_target.to_string(),
+            })
+        })
+        .collect()
+}
+
+/// 校验 `ls -l` 的权限列，如 `drwxr-xr-x`、`-rw-r--r--.`、`lrwxrwxrwx@`
+fn is_ls_perms(s: &str) -> bool {
+    let chars: Vec<char> = s.chars().collect();
+    if chars.len() < 10 || chars.len() > 11 {
+        return false;
+    }
+    if !"-dlcbps".contains(chars[0]) {
+        return false;
+    }
+    if !chars[1..10].iter().all(|c| "rwxsStTl-".contains(*c)) {
+        return false;
+    }
+    chars.len() == 10 || ".+@".contains(chars[10])
+}
+
+fn is_iso_date(s: &str) -> bool {
+    let b = s.as_bytes();
+    b.len() == 10
+        && b[4] == b'-'
+        && b[7] == b'-'
+        && b.iter().enumerate().all(|(i, c)| i == 4 || i == 7 || c.is_ascii_digit())
+}
+
+fn is_hh_mm(s: &str) -> bool {
+    let b = s.as_bytes();
+    b.len() == 5
+        && b[2] == b':'
+        && b.iter().enumerate().all(|(i, c)| i == 2 || c.is_ascii_digit())
+}
+
+/// 解析 `ls -la --time-style=long-iso` 输出。
+/// 以日期/时间列定位字段，不依赖用户/组的列数；格式不符的行直接跳过。
 fn parse_ls_output(output: &str) -> Vec<RemoteEntry> {
     output
         .lines()
         .filter_map(|line| {
             let parts: Vec<&str> =
                 line.split_whitespace().collect();
-            if parts.len() < 8 {
-                return None;
-            }
-            let perms = parts[0];
-            if perms == "total" {
+            let perms = *parts.first()?;
+            if !is_ls_perms(perms) {
                 return None;
             }
+            let date_idx = (2..parts.len().saturating_sub(2))
+                .find(|&i| is_iso_date(parts[i]) && is_hh_mm(parts[i + 1]))?;
+            let size: u64 = parts[date_idx - 1].parse().ok()?;
             let is_dir = perms.starts_with('d');
-            let size: u64 = parts[4].parse().unwrap_or(0);
-            let date = parts[5];
-            let time = parts[6];
-            let name = parts[7..].join(" ");
+            let is_symlink = perms.starts_with('l');
+            let date = parts[date_idx];
+            let time = parts[date_idx + 1];
+            let full_name = parts[date_idx + 2..].join(" ");
+            // ls 无法区分链接目标类型，native 模式下符号链接一律按文件处理
+            let (name, link_target) = match full_name.split_once(" -> ") {
+                Some((name, target)) if is_symlink => (name.to_string(), target.to_string()),
+                _ => (full_name, String::new()),
+            };
             if name == "." || name == ".." {
                 return None;
             }
+            let modified = format!("{} {}", date, time);
             Some(RemoteEntry {
                 name,
                 is_dir,
                 size,
-                modified: format!("{} {}", date, time),
+                mtime: parse_display_mtime(&modified),
+                modified,
+                dir_size: None,
+                is_symlink,
+                link_target,
             })
         })
         .collect()
@@ -223,6 +1018,13 @@ fn parse_ls_output(output: &str) -> Vec<RemoteEntry> {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_remove_command() {
+        assert_eq!(remove_command("/tmp/a b", true, false), "rm -rf '/tmp/a b'");
+        assert_eq!(remove_command("/tmp/f", false, false), "rm -f '/tmp/f'");
+        assert_eq!(remove_command("/root/x", true, true), "sudo -n rm -rf '/root/x'");
+    }
+
     #[test]
     fn test_escape_shell_arg_simple() {
         assert_eq!(escape_shell_arg("/tmp/test"), "'/tmp/test'");
@@ -282,5 +1084,155 @@ mod tests {
         assert_eq!(entries.len(), 1);
         assert_eq!(entries[0].name, "my file name.txt");
     }
-}
 
+    #[test]
+    fn test_parse_du_output() {
+        assert_eq!(parse_du_output("123456\t/var/log\n"), Some(123456));
+        assert_eq!(parse_du_output("du: cannot access"), None);
+        assert_eq!(parse_du_output(""), None);
+    }
+
+    #[test]
+    fn test_parse_size_lines() {
+        assert_eq!(parse_size_lines("10\n20\n 5\n"), Some(35));
+        assert_eq!(parse_size_lines(""), Some(0));
+        assert_eq!(parse_size_lines("10\nfind: permission denied\n"), None);
+    }
+
+    #[test]
+    fn test_parse_df_output() {
+        let output = "Filesystem     1-blocks       Used   Available Capacity Mounted on\n\
+                      /dev/sda1    52710469632 20254629888 29752213504      41% /\n";
+        assert_eq!(parse_df_output(output), Some(29752213504));
+        assert_eq!(parse_df_output("df: /nope: No such file or directory\n"), None);
+        assert_eq!(parse_df_output(""), None);
+    }
+
+    #[test]
+    fn test_owner_spec_and_target_guard() {
+        assert!(is_valid_owner_spec("www-data"));
+        assert!(is_valid_owner_spec("deploy:www"));
+        assert!(is_valid_owner_spec(":www"));
+        assert!(is_valid_owner_spec("1000:1000"));
+        assert!(!is_valid_owner_spec(""));
+        assert!(!is_valid_owner_spec("deploy:"));
+        assert!(!is_valid_owner_spec("-R"));
+        assert!(!is_valid_owner_spec("root; rm -rf /"));
+        assert!(!is_valid_owner_spec("a:b:c"));
+
+        assert!(ensure_attr_target("/var/www").is_ok());
+        assert!(ensure_attr_target("/").is_err());
+        assert!(ensure_attr_target("//").is_err());
+        assert!(ensure_attr_target("/var/..").is_err());
+        assert!(ensure_attr_target("").is_err());
+    }
+
+    #[test]
+    fn test_parse_stat_output() {
+        let st = parse_stat_output("/etc/hosts", "220|644|root|wheel|1700000000|regular file\n").unwrap();
+        assert_eq!(st.size, 220);
+        assert_eq!(st.mode, 0o644);
+        assert_eq!(st.owner, "root");
+        assert_eq!(st.group, "wheel");
+        assert_eq!(st.mtime, 1700000000);
+        assert!(!st.is_dir);
+        assert_eq!(st.link_target, None);
+
+        let st = parse_stat_output("/lib", "7|777|root|root|1|symbolic link\nusr/lib\n").unwrap();
+        assert_eq!(st.link_target.as_deref(), Some("usr/lib"));
+
+        let st = parse_stat_output("/srv", "4096|2775|www|www|1|directory\n").unwrap();
+        assert!(st.is_dir);
+        assert_eq!(st.mode, 0o2775);
+
+        assert_eq!(parse_stat_output("/x", "stat: cannot stat"), None);
+    }
+
+    #[test]
+    fn test_parse_df_usage() {
+        let output = "Filesystem     1-blocks       Used   Available Capacity Mounted on\n\
+                      /dev/sda1    52710469632 20254629888 29752213504      41% /\n";
+        assert_eq!(parse_df_usage(output), Some((29752213504, 52710469632)));
+        assert_eq!(parse_df_usage("Filesystem 1-blocks\n/dev/sda1 -\n"), None);
+        assert_eq!(parse_df_usage(""), None);
+    }
+
+    #[test]
+    fn test_parse_ls_output_symlink() {
+        let output = "lrwxrwxrwx 1 root root   11 2024-01-15 10:30 my link -> /var/log\n\
+                      -rw-r--r-- 1 root root 1024 2024-01-15 10:30 a -> b.txt\n";
+        let entries = parse_ls_output(output);
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].name, "my link");
+        assert!(entries[0].is_symlink);
+        assert!(!entries[0].is_dir);
+        assert_eq!(entries[0].link_target, "/var/log");
+        // 非链接文件名中的 " -> " 保持原样
+        assert_eq!(entries[1].name, "a -> b.txt");
+        assert!(!entries[1].is_symlink);
+        assert!(entries[1].link_target.is_empty());
+    }
+
+    #[test]
+    fn test_parse_find_output() {
+        let output = "d\t4096\t2024-01-15 10:30\t/srv/app/logs\n\
+                      f\t1024\t2024-01-16 08:05\t/srv/app/logs/app log.txt\n\
+                      broken line\n";
+        let entries = parse_find_output(output);
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].name, "/srv/app/logs");
+        assert!(entries[0].is_dir);
+        assert_eq!(entries[1].name, "/srv/app/logs/app log.txt");
+        assert_eq!(entries[1].size, 1024);
+        assert_eq!(entries[1].modified, "2024-01-16 08:05");
+        assert!(entries[1].mtime > entries[0].mtime);
+    }
+
+    #[test]
+    fn test_parse_display_mtime() {
+        let mtime = parse_display_mtime("2024-01-15 10:30");
+        let shown = Local.timestamp_opt(mtime as i64, 0).unwrap().format("%Y-%m-%d %H:%M").to_string();
+        assert_eq!(shown, "2024-01-15 10:30");
+        assert_eq!(parse_display_mtime(""), 0);
+        assert_eq!(parse_display_mtime("Jan 15 10:30"), 0);
+    }
+
+    #[test]
+    fn test_parse_ls_output_skips_malformed_lines() {
+        let output = "insgesamt 8\n\
+            drwxr-xr-x 2 root root 4096 Jan 15 10:30 localized\n\
+            -rw-r--r-- 1 root root 12K 2024-01-15 10:30 human.txt\n\
+            -rw-r--r--. 1 root 2048 2024-01-15 09:00 no-group.txt\n";
+        let entries = parse_ls_output(output);
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].name, "no-group.txt");
+        assert_eq!(entries[0].size, 2048);
+    }
+
+    #[test]
+    fn test_parse_find_listing() {
+        let output = "d\td\t4096\t1705311000.1234567890\t\tsubdir\0\
+                      f\tf\t1234\t1705311000.0000000000\t\tmy file.txt\0\
+                      l\td\t8\t1705311000.0000000000\t/var/log\tlogs\0\
+                      garbage\0";
+        let entries = parse_find_listing(output);
+        assert_eq!(entries.len(), 3);
+        assert!(entries[0].is_dir);
+        assert_eq!(entries[1].name, "my file.txt");
+        assert_eq!(entries[1].size, 1234);
+        assert!(!entries[1].modified.is_empty());
+        assert!(entries[2].is_symlink);
+        assert!(entries[2].is_dir);
+        assert_eq!(entries[2].link_target, "/var/log");
+    }
+
+    #[test]
+    fn test_parse_find_listing_unusual_names() {
+        let output = "f\tf\t10\t1705311000.0\t\t报告 2024.txt\0\
+                      d\td\t4096\t1705311000.0\t\t照片\0\
+                      f\tf\t3\t1705311000.0\t\tline one\nline two\0\
+                      f\tf\t5\t1705311000.0\t\ttab\tname\0";
+        let names: Vec<String> = parse_find_listing(output).into_iter().map(|e| e.name).collect();
+        assert_eq!(names, vec!["报告 2024.txt", "照片", "line one\nline two", "tab\tname"]);
+    }
+}
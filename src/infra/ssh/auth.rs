@@ -1,4 +1,8 @@
-use anyhow::Result;
+use std::ffi::{CStr, CString};
+use std::ptr;
+
+use anyhow::{anyhow, Result};
+use libssh2_sys as raw;
 use ssh2::Session;
 
 use crate::domain::config::ServerConfig;
@@ -14,3 +18,124 @@ pub fn try_auth_with_password(
         anyhow::bail!("密码为空")
     }
 }
+
+/// 通过 SSH Agent 认证，逐个尝试 Agent 中的身份直到服务器接受。
+/// `socket` 指定时连接该 Agent，否则使用默认 Agent (Windows 上 libssh2 会先尝试 Pageant)。
+/// 每个身份的尝试结果写入 `logs`
+pub fn try_auth_with_agent(
+    session: &Session,
+    user: &str,
+    socket: Option<&str>,
+    logs: &mut String,
+) -> Result<()> {
+    if let Some(socket) = socket {
+        return try_auth_with_agent_socket(session, user, socket, logs);
+    }
+    let mut agent = session.agent()?;
+    agent.connect().map_err(|e| anyhow!("无法连接 SSH Agent: {}", e))?;
+    agent.list_identities()?;
+    let identities = agent.identities()?;
+    if identities.is_empty() {
+        anyhow::bail!("SSH Agent 中没有可用的身份");
+    }
+
+    for identity in &identities {
+        let comment = match identity.comment() {
+            "" => "(无注释)",
+            c => c,
+        };
+        match agent.userauth(user, identity) {
+            Ok(()) => {
+                push_log(logs, format!("Agent 身份匹配: {}", comment));
+                let _ = agent.disconnect();
+                return Ok(());
+            }
+            Err(e) => push_log(logs, format!("Agent 身份未被接受: {} ({})", comment, e)),
+        }
+    }
+    let _ = agent.disconnect();
+    Err(anyhow!("已尝试 Agent 中的 {} 个身份，均未被接受", identities.len()))
+}
+
+/// 通过指定 socket 的 Agent 认证。ssh2 没有暴露 `libssh2_agent_set_identity_path`，
+/// 这里直接调用 libssh2，不必改写进程的 `SSH_AUTH_SOCK` 环境变量
+fn try_auth_with_agent_socket(
+    session: &Session,
+    user: &str,
+    socket: &str,
+    logs: &mut String,
+) -> Result<()> {
+    let path = CString::new(socket).map_err(|_| anyhow!("Agent socket 路径无效: {}", socket))?;
+    let user = CString::new(user).map_err(|_| anyhow!("用户名无效"))?;
+    // 持有 session 锁直到 agent 释放，期间没有其他调用能使用这个 session
+    let mut sess = session.raw();
+    let sess_ptr: *mut raw::LIBSSH2_SESSION = &mut *sess;
+    let error = |rc| ssh2::Error::from_session_error_raw(sess_ptr, rc);
+
+    // SAFETY: sess_ptr 在锁保护下有效；agent 及其身份列表由 RawAgent 在函数返回前释放，
+    // identity 只在两次 get_identity 之间使用
+    unsafe {
+        let agent = RawAgent(raw::libssh2_agent_init(sess_ptr));
+        if agent.0.is_null() {
+            return Err(anyhow!("无法初始化 SSH Agent"));
+        }
+        raw::libssh2_agent_set_identity_path(agent.0, path.as_ptr());
+        let rc = raw::libssh2_agent_connect(agent.0);
+        if rc != 0 {
+            return Err(anyhow!("无法连接 SSH Agent ({}): {}", socket, error(rc)));
+        }
+        let rc = raw::libssh2_agent_list_identities(agent.0);
+        if rc != 0 {
+            return Err(anyhow!("读取 Agent 身份失败: {}", error(rc)));
+        }
+
+        let mut tried = 0;
+        let mut prev: *mut raw::libssh2_agent_publickey = ptr::null_mut();
+        loop {
+            let mut identity = ptr::null_mut();
+            match raw::libssh2_agent_get_identity(agent.0, &mut identity, prev) {
+                0 => {}
+                1 => break,
+                rc => return Err(anyhow!("读取 Agent 身份失败: {}", error(rc))),
+            }
+            prev = identity;
+            tried += 1;
+            let comment = match (*identity).comment {
+                c if c.is_null() => String::new(),
+                c => CStr::from_ptr(c).to_string_lossy().into_owned(),
+            };
+            let comment = if comment.is_empty() { "(无注释)".to_string() } else { comment };
+            match raw::libssh2_agent_userauth(agent.0, user.as_ptr(), identity) {
+                0 => {
+                    push_log(logs, format!("Agent 身份匹配: {}", comment));
+                    return Ok(());
+                }
+                rc => push_log(logs, format!("Agent 身份未被接受: {} ({})", comment, error(rc))),
+            }
+        }
+        if tried == 0 {
+            anyhow::bail!("SSH Agent 中没有可用的身份");
+        }
+        Err(anyhow!("已尝试 Agent 中的 {} 个身份，均未被接受", tried))
+    }
+}
+
+/// libssh2 原生 Agent 句柄，drop 时断开并释放
+struct RawAgent(*mut raw::LIBSSH2_AGENT);
+
+impl Drop for RawAgent {
+    fn drop(&mut self) {
+        if !self.0.is_null() {
+            // SAFETY: 句柄由 libssh2_agent_init 创建，只释放一次
+            unsafe {
+                raw::libssh2_agent_disconnect(self.0);
+                raw::libssh2_agent_free(self.0);
+            }
+        }
+    }
+}
+
+fn push_log(logs: &mut String, line: String) {
+    logs.push_str(&line);
+    logs.push('\n');
+}
@@ -1,11 +1,15 @@
-use crate::domain::config::ServerConfig;
+use crate::domain::config::{ServerConfig, TransferMethod, NATIVE_FALLBACK_BUILT};
+use crate::domain::transfer::{CompressionStats, PlannedTransfer, SyncReport, TransferEvent};
 use anyhow::{anyhow, Result};
-use ssh2::Session;
+use ssh2::{Session, Sftp};
+use std::cell::{Cell, OnceCell};
 use std::net::{TcpStream, ToSocketAddrs};
 use std::path::Path;
+use std::sync::mpsc::Sender;
 use std::time::Duration;
 
-use super::{auth, native_fallback};
+use super::key_format::{self, KeyStrategy};
+use super::{auth, connection_log, host, jump, native_fallback};
 
 /// 文件传输接口 (方便未来扩展 FTP/S3)
 pub trait FileTransfer {
@@ -40,6 +44,44 @@ pub trait FileTransfer {
         local_dir: &Path,
         callback: impl Fn(f32),
     ) -> Result<()>;
+
+    /// 预演目录上传：只遍历不传输，返回将要上传的文件列表
+    fn plan_upload_dir(
+        &self,
+        local_dir: &Path,
+        remote_dir: &Path,
+    ) -> Result<Vec<PlannedTransfer>>;
+
+    /// 增量同步目录：只上传新增或变化的文件，`mirror_delete` 时删除本地已不存在的远程文件
+    fn sync_dir(
+        &mut self,
+        local_dir: &Path,
+        remote_dir: &Path,
+        mirror_delete: bool,
+        callback: impl Fn(f32),
+    ) -> Result<SyncReport>;
+
+    /// 上传文件并通过 channel 报告结构化事件；接收端已关闭时事件被丢弃，不影响传输
+    fn upload_with_events(
+        &mut self,
+        local_path: &Path,
+        remote_path: &Path,
+        events: &Sender<TransferEvent>,
+    ) -> Result<()>
+    where
+        Self: Sized,
+    {
+        let total = std::fs::metadata(local_path).map(|m| m.len()).unwrap_or(0);
+        let _ = events.send(TransferEvent::Started { total });
+        let result = self.upload(local_path, remote_path, |p| {
+            let _ = events.send(TransferEvent::progress(p, total));
+        });
+        let _ = events.send(match &result {
+            Ok(()) => TransferEvent::Completed,
+            Err(e) => TransferEvent::Failed(e.to_string()),
+        });
+        result
+    }
 }
 
 /// 认证模式标记
@@ -51,18 +93,96 @@ pub enum AuthMode {
     NativeSsh,
 }
 
+/// 实际使用的传输方式：系统 ssh/scp 模式下 session 未认证，只能走 SCP；
+/// 不允许执行外部命令时只能走 SFTP
+pub fn effective_transfer_method(configured: TransferMethod, mode: &AuthMode, native_allowed: bool) -> TransferMethod {
+    match mode {
+        AuthMode::LibSsh2 if !native_allowed => TransferMethod::Sftp,
+        AuthMode::LibSsh2 => configured,
+        AuthMode::NativeSsh => TransferMethod::Scp,
+    }
+}
+
+/// 建立 SFTP 会话，失败时保留 libssh2 的原始错误，并提示可改用系统 ssh/scp
+pub fn open_sftp(session: &Session) -> Result<Sftp> {
+    session
+        .sftp()
+        .map_err(|e| anyhow!("SFTP 子系统初始化失败: {} ({})", e, sftp_failure_hint(NATIVE_FALLBACK_BUILT)))
+}
+
+/// SFTP 初始化失败的处理建议；未编译系统 ssh/scp 回退时不提示强制使用它
+fn sftp_failure_hint(native_built: bool) -> &'static str {
+    if native_built {
+        "服务器可能未启用 SFTP 或限制了可执行的命令，可在服务器设置中勾选“强制使用系统 ssh/scp”"
+    } else {
+        "服务器可能未启用 SFTP 或限制了可执行的命令"
+    }
+}
+
 /// SSH/SFTP 上传器
 pub struct SshUploader {
     session: Session,
     _tcp: TcpStream, // 保持 TCP 连接存活
     config: ServerConfig, // 保存配置以便使用 SCP
     auth_mode: AuthMode,
+    /// 远程主目录缓存，用于展开 `~`
+    home_dir: OnceCell<String>,
+    /// 压缩上传的累计统计，由调用方取走
+    compression_stats: Cell<Option<CompressionStats>>,
+    /// 最近一次文件传输实际使用的方式及原因，由调用方取走
+    method_note: Cell<Option<String>>,
+}
+
+
+/// libssh2 操作是否因 `set_timeout` 超时而失败
+fn is_timeout(e: &ssh2::Error) -> bool {
+    e.code() == ssh2::ErrorCode::Session(-9)
+}
+
+/// 端口可达性预检的超时
+pub const PROBE_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// 目标端口能否在超时内建立 TCP 连接
+pub fn is_reachable(host: &str, port: u16, timeout: Duration) -> bool {
+    probe_port(host, port, timeout).is_ok()
+}
+
+/// 快速探测目标端口是否可连接，用于服务器列表的在线状态
+fn probe_port(host: &str, port: u16, timeout: Duration) -> std::io::Result<()> {
+    let addr = host::socket_addr(host, port)
+        .to_socket_addrs()?
+        .next()
+        .ok_or_else(|| std::io::Error::new(std::io::ErrorKind::AddrNotAvailable, "无法解析地址"))?;
+    TcpStream::connect_timeout(&addr, timeout).map(|_| ())
 }
 
+/// TCP 连接错误是否表示主机不可达或端口未开放 (而非超时等其他原因)
+fn is_unreachable(kind: std::io::ErrorKind) -> bool {
+    use std::io::ErrorKind::*;
+    matches!(kind, ConnectionRefused | HostUnreachable | NetworkUnreachable | AddrNotAvailable)
+}
 
 impl SshUploader {
-    /// 建立 SSH 连接 (带日志)
+    fn new(session: Session, tcp: TcpStream, config: &ServerConfig, auth_mode: AuthMode) -> Self {
+        Self {
+            session,
+            _tcp: tcp,
+            config: config.clone(),
+            auth_mode,
+            home_dir: OnceCell::new(),
+            compression_stats: Cell::new(None),
+            method_note: Cell::new(None),
+        }
+    }
+
+    /// 建立 SSH 连接 (带日志)，每次尝试都记入连接日志
     pub fn connect_with_log(config: &ServerConfig) -> (Result<Self>, String) {
+        let (result, logs) = Self::try_connect(config);
+        connection_log::record(config, &result, &logs);
+        (result, logs)
+    }
+
+    fn try_connect(config: &ServerConfig) -> (Result<Self>, String) {
         let mut logs = String::new();
         
         macro_rules! log {
@@ -73,22 +193,68 @@ impl SshUploader {
         }
 
         log!("开始连接到 {}:{} (User: {})...", config.host, config.port, config.user);
+        let native_allowed = config.native_fallback_allowed();
+        if !native_allowed {
+            log!("已禁用系统 ssh/scp 回退，仅使用内置库");
+        }
+
+        let connect_timeout = Duration::from_secs(config.connect_timeout_secs);
+        let jump_spec = config
+            .proxy_jump
+            .as_deref()
+            .map(str::trim)
+            .filter(|s| !s.is_empty());
 
-        let tcp = match format!("{}:{}", config.host, config.port)
-            .to_socket_addrs()
-            .and_then(|mut addrs| {
-                addrs
-                    .next()
-                    .ok_or_else(|| std::io::Error::new(std::io::ErrorKind::AddrNotAvailable, "无法解析地址"))
-                    .and_then(|addr| TcpStream::connect_timeout(&addr, Duration::from_secs(10)))
-            }) {
-            Ok(s) => {
-                log!("TCP 连接成功");
-                s
+        let tcp = if let Some(spec) = jump_spec {
+            let jump_host = match jump::parse_jump_host(spec) {
+                Ok(j) => j,
+                Err(e) => {
+                    log!("跳板机配置无效: {}", e);
+                    return (Err(e), logs);
+                }
+            };
+            log!("经跳板机 {} 连接...", spec);
+            match jump::connect_via_jump(config, &jump_host, connect_timeout, &mut logs) {
+                Ok(s) => {
+                    log!("[目标] 隧道已建立 → {}:{}", config.host, config.port);
+                    s
+                }
+                Err(e) => {
+                    log!("跳板机连接失败: {:#}", e);
+                    return (Err(e.context("跳板机连接失败")), logs);
+                }
             }
-            Err(e) => {
-                log!("TCP 连接失败: {}", e);
-                return (Err(anyhow::Error::new(e).context("TCP 连接失败")), logs);
+        } else {
+            match host::socket_addr(&config.host, config.port)
+                .to_socket_addrs()
+                .and_then(|mut addrs| {
+                    addrs
+                        .next()
+                        .ok_or_else(|| std::io::Error::new(std::io::ErrorKind::AddrNotAvailable, "无法解析地址"))
+                        .and_then(|addr| TcpStream::connect_timeout(&addr, connect_timeout))
+                }) {
+                Ok(s) => {
+                    log!("TCP 连接成功");
+                    s
+                }
+                Err(e) if e.kind() == std::io::ErrorKind::TimedOut => {
+                    log!("TCP 连接超时: {} 秒内未建立连接 (连接超时)", config.connect_timeout_secs);
+                    return (Err(anyhow::Error::new(e).context("TCP 连接超时")), logs);
+                }
+                Err(e) if is_unreachable(e.kind()) => {
+                    log!("主机不可达/端口未开放: {}", e);
+                    return (
+                        Err(anyhow::Error::new(e).context(format!(
+                            "主机不可达/端口未开放 ({}:{})",
+                            config.host, config.port
+                        ))),
+                        logs,
+                    );
+                }
+                Err(e) => {
+                    log!("TCP 连接失败: {}", e);
+                    return (Err(anyhow::Error::new(e).context("TCP 连接失败")), logs);
+                }
             }
         };
 
@@ -108,9 +274,12 @@ impl SshUploader {
             }
         };
         session.set_tcp_stream(tcp_clone);
-        session.set_timeout(30_000);
+        session.set_timeout((config.io_timeout_secs * 1000) as u32);
         
         if let Err(e) = session.handshake() {
+            if is_timeout(&e) {
+                log!("SSH 握手超时: {} 秒内无响应 (读写超时)", config.io_timeout_secs);
+            }
             log!("SSH 握手失败: {}", e);
             return (Err(anyhow::Error::new(e).context("SSH 握手失败")), logs);
         }
@@ -133,37 +302,91 @@ impl SshUploader {
             "key" => {
                 log!("尝试密钥认证...");
                 let mut authenticated = false;
+                // 内置库无法使用该密钥时跳过后续尝试，直接走系统 ssh
+                let mut native_only = false;
                 
                 // 1. 显式指定
                 if let Some(path_str) = &config.key_path {
                     if !path_str.is_empty() {
                         log!("尝试指定密钥: {}", path_str);
-                        match session.userauth_pubkey_file(&config.user, None, Path::new(path_str), None) {
-                            Ok(_) => {
-                                log!("指定密钥认证成功");
-                                authenticated = true;
+                        let key_path = Path::new(path_str);
+                        let strategy = match key_format::plan(key_path, native_allowed) {
+                            Ok((strategy, description)) => {
+                                log!("密钥格式: {}", description);
+                                strategy
                             }
                             Err(e) => {
-                                log!("指定密钥认证失败: {}", e);
+                                log!("无法识别密钥格式: {}", e);
+                                KeyStrategy::LibSsh2
+                            }
+                        };
+                        match strategy {
+                            KeyStrategy::LibSsh2 => {
+                                match session.userauth_pubkey_file(&config.user, None, key_path, None) {
+                                    Ok(_) => {
+                                        log!("指定密钥认证成功");
+                                        authenticated = true;
+                                    }
+                                    Err(e) => {
+                                        log!("指定密钥认证失败: {}", e);
+                                    }
+                                }
+                            }
+                            KeyStrategy::ConvertToPem | KeyStrategy::NativeOnly if !native_allowed => {
+                                log!("内置库不支持该密钥，且已禁用系统 ssh/ssh-keygen，无法继续");
+                                native_only = true;
+                            }
+                            KeyStrategy::ConvertToPem => {
+                                log!("内置库不支持该格式，转换为临时 PEM 副本...");
+                                match key_format::convert_to_pem(key_path) {
+                                    Ok(pem) => {
+                                        let result = session.userauth_pubkey_file(&config.user, None, pem.path(), None);
+                                        // 认证结束立即删除未加密的副本，不等连接结束
+                                        drop(pem);
+                                        match result {
+                                            Ok(_) => {
+                                                log!("PEM 副本认证成功");
+                                                authenticated = true;
+                                            }
+                                            Err(e) => {
+                                                log!("PEM 副本认证失败: {}", e);
+                                            }
+                                        }
+                                    }
+                                    Err(e) => {
+                                        log!("{}", e);
+                                    }
+                                }
+                            }
+                            KeyStrategy::NativeOnly => {
+                                log!("内置库不支持该密钥，跳过内置认证，直接使用系统 ssh");
+                                native_only = true;
                             }
                         }
                     }
                 }
 
-                if !authenticated {
-                    log!("尝试 SSH Agent...");
-                    match session.userauth_agent(&config.user) {
+                if !authenticated && !native_only {
+                    match config.agent_socket() {
+                        Some(socket) => {
+                            log!("尝试 SSH Agent ({})...", socket);
+                        }
+                        None => {
+                            log!("尝试 SSH Agent...");
+                        }
+                    }
+                    match auth::try_auth_with_agent(&session, &config.user, config.agent_socket(), &mut logs) {
                         Ok(_) => {
                             log!("SSH Agent 认证成功");
-                             authenticated = true;
+                            authenticated = true;
                         }
                         Err(e) => {
-                            log!("SSH Agent 认证失败/跳过: {}", e); 
+                            log!("SSH Agent 认证失败/跳过: {}", e);
                         }
                     }
                 }
 
-                if !authenticated {
+                if !authenticated && !native_only {
                     log!("尝试自动探测 .ssh 目录...");
                     if let Some(home) = dirs::home_dir() {
                         let ssh_dir = home.join(".ssh");
@@ -200,6 +423,8 @@ impl SshUploader {
 
                 if authenticated {
                     Ok(())
+                } else if native_only {
+                    Err(anyhow!("内置库不支持该密钥格式"))
                 } else {
                     log!("所有密钥尝试均失败");
                     Err(anyhow!("密钥认证全数失败"))
@@ -209,10 +434,32 @@ impl SshUploader {
         };
 
         match auth_result {
+            Ok(_) if session.authenticated() && config.force_native && !native_allowed => {
+                log!("已禁用系统 ssh/scp，无法按服务器设置强制使用");
+                (Err(anyhow!("已设置强制使用系统 ssh/scp，但该服务器不允许执行外部命令")), logs)
+            }
+            Err(e) if !native_allowed => {
+                log!("认证流程结束: {}", e);
+                log!("已禁用系统 ssh/scp 回退，不再尝试系统 ssh");
+                (Err(e), logs)
+            }
             Ok(_) => {
-                if session.authenticated() {
+                if session.authenticated() && config.force_native {
+                    log!("最终认证状态: 已连接");
+                    log!("已按服务器设置强制使用系统 ssh/scp，检查系统 ssh...");
+                    match native_fallback::perform_native_ssh_check(config) {
+                        Ok(msg) => {
+                            log!("✅ 原生 SSH 测试成功: {}", msg);
+                            (Ok(Self::new(session, tcp, config, AuthMode::NativeSsh)), logs)
+                        }
+                        Err(e) => {
+                            log!("❌ 原生 SSH 失败: {}", e);
+                            (Err(anyhow!("已设置强制使用系统 ssh/scp，但系统 ssh 无法连接: {}", e)), logs)
+                        }
+                    }
+                } else if session.authenticated() {
                     log!("最终认证状态: 已连接");
-                    (Ok(Self { session, _tcp: tcp, config: config.clone(), auth_mode: AuthMode::LibSsh2 }), logs)
+                    (Ok(Self::new(session, tcp, config, AuthMode::LibSsh2)), logs)
                 } else {
                     log!("Session 标记为未认证");
                     (Err(anyhow!("认证未通过")), logs)
@@ -231,7 +478,7 @@ impl SshUploader {
                         log!("💡 当前可以正常使用文件上传功能(将使用系统 scp 命令)");
                         
                         // 返回成功状态,允许上传操作继续
-                        (Ok(Self { session, _tcp: tcp, config: config.clone(), auth_mode: AuthMode::NativeSsh }), logs)
+                        (Ok(Self::new(session, tcp, config, AuthMode::NativeSsh)), logs)
                     }
                     Err(nt_e) => {
                         log!("❌ 原生 SSH 也失败: {}", nt_e);
@@ -265,6 +512,44 @@ impl SshUploader {
         &self.config
     }
 
+    /// 本连接实际使用的传输方式
+    pub fn transfer_method(&self) -> TransferMethod {
+        effective_transfer_method(
+            self.config.transfer_method(),
+            &self.auth_mode,
+            self.config.native_fallback_allowed(),
+        )
+    }
+
+    /// 累加一次压缩上传的统计
+    pub fn record_compression(&self, stats: CompressionStats) {
+        let total = match self.compression_stats.get() {
+            Some(prev) => prev.merge(stats),
+            None => stats,
+        };
+        self.compression_stats.set(Some(total));
+    }
+
+    /// 取走并清空压缩上传统计，没有发生压缩上传时为 None
+    pub fn take_compression_stats(&self) -> Option<CompressionStats> {
+        self.compression_stats.take()
+    }
+
+    /// 记录最近一次传输使用的方式说明
+    pub fn record_method_note(&self, note: String) {
+        self.method_note.set(Some(note));
+    }
+
+    /// 取走传输方式说明，没有记录时为 None
+    pub fn take_method_note(&self) -> Option<String> {
+        self.method_note.take()
+    }
+
+    /// 远程主目录缓存 (每个连接只查询一次)
+    pub fn home_dir_cache(&self) -> &OnceCell<String> {
+        &self.home_dir
+    }
+
     /// 在远程创建目录（递归）
     pub fn remote_mkdir(&self, path: &Path) -> Result<()> {
         let path_str = path.to_string_lossy().replace('\\', "/");
@@ -279,6 +564,8 @@ impl SshUploader {
             cmd.arg("-o").arg("BatchMode=yes")
                 .arg("-o").arg("StrictHostKeyChecking=no")
                 .arg("-p").arg(self.config.port.to_string());
+            native_fallback::apply_proxy_jump(&mut cmd, &self.config);
+            native_fallback::apply_identity_agent(&mut cmd, &self.config);
             if let Some(key) = &self.config.key_path {
                 if !key.is_empty() {
                     cmd.arg("-i").arg(key);
@@ -289,7 +576,7 @@ impl SshUploader {
                 use std::os::windows::process::CommandExt;
                 cmd.creation_flags(0x08000000);
             }
-            cmd.arg(format!("{}@{}", self.config.user, self.config.host));
+            cmd.arg(format!("{}@{}", self.config.user, host::bare_host(&self.config.host)));
             cmd.arg(format!("mkdir -p '{}'", path_str.replace('\'', "'\\''")));
             let _ = cmd.output();
         }
@@ -297,5 +584,107 @@ impl SshUploader {
     }
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::TcpListener;
+    use std::sync::mpsc;
 
+    /// 只回调进度的假传输实现
+    struct FakeTransfer {
+        fail: bool,
+    }
 
+    impl FileTransfer for FakeTransfer {
+        fn upload(&mut self, _: &Path, _: &Path, callback: impl Fn(f32)) -> Result<()> {
+            callback(0.5);
+            if self.fail {
+                return Err(anyhow!("磁盘已满"));
+            }
+            callback(1.0);
+            Ok(())
+        }
+
+        fn download(&mut self, _: &Path, _: &Path, _: impl Fn(f32)) -> Result<()> {
+            Ok(())
+        }
+
+        fn upload_dir(&mut self, _: &Path, _: &Path, _: impl Fn(f32)) -> Result<()> {
+            Ok(())
+        }
+
+        fn download_dir(&mut self, _: &Path, _: &Path, _: impl Fn(f32)) -> Result<()> {
+            Ok(())
+        }
+
+        fn plan_upload_dir(&self, _: &Path, _: &Path) -> Result<Vec<PlannedTransfer>> {
+            Ok(Vec::new())
+        }
+
+        fn sync_dir(&mut self, _: &Path, _: &Path, _: bool, _: impl Fn(f32)) -> Result<SyncReport> {
+            Ok(SyncReport::default())
+        }
+    }
+
+    #[test]
+    fn test_upload_with_events() {
+        let file = std::env::temp_dir().join("flick_test_events.bin");
+        std::fs::write(&file, [0u8; 100]).unwrap();
+
+        let (tx, rx) = mpsc::channel();
+        let mut ok = FakeTransfer { fail: false };
+        ok.upload_with_events(&file, Path::new("/tmp/x"), &tx).unwrap();
+        let events: Vec<_> = rx.try_iter().collect();
+        assert_eq!(
+            events,
+            vec![
+                TransferEvent::Started { total: 100 },
+                TransferEvent::Progress { bytes: 50, total: 100 },
+                TransferEvent::Progress { bytes: 100, total: 100 },
+                TransferEvent::Completed,
+            ]
+        );
+
+        let mut failing = FakeTransfer { fail: true };
+        assert!(failing.upload_with_events(&file, Path::new("/tmp/x"), &tx).is_err());
+        let last = rx.try_iter().last().unwrap();
+        assert_eq!(last, TransferEvent::Failed("磁盘已满".to_string()));
+
+        let _ = std::fs::remove_file(&file);
+    }
+
+    #[test]
+    fn test_probe_port() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        assert!(probe_port("127.0.0.1", port, PROBE_TIMEOUT).is_ok());
+        drop(listener);
+        assert!(probe_port("127.0.0.1", port, PROBE_TIMEOUT).is_err());
+    }
+
+    #[test]
+    fn test_sftp_failure_hint() {
+        assert!(sftp_failure_hint(true).contains("强制使用系统 ssh/scp"));
+        assert!(!sftp_failure_hint(false).contains("系统 ssh"));
+    }
+
+    #[test]
+    fn test_is_unreachable() {
+        use std::io::ErrorKind;
+        assert!(is_unreachable(ErrorKind::ConnectionRefused));
+        assert!(is_unreachable(ErrorKind::HostUnreachable));
+        assert!(!is_unreachable(ErrorKind::TimedOut));
+        assert!(!is_unreachable(ErrorKind::PermissionDenied));
+    }
+
+    #[test]
+    fn test_effective_transfer_method() {
+        use TransferMethod::*;
+        assert_eq!(effective_transfer_method(Sftp, &AuthMode::LibSsh2, true), Sftp);
+        assert_eq!(effective_transfer_method(Auto, &AuthMode::LibSsh2, true), Auto);
+        assert_eq!(effective_transfer_method(Sftp, &AuthMode::NativeSsh, true), Scp);
+        assert_eq!(effective_transfer_method(Auto, &AuthMode::NativeSsh, true), Scp);
+        assert_eq!(effective_transfer_method(Auto, &AuthMode::LibSsh2, false), Sftp);
+        assert_eq!(effective_transfer_method(Scp, &AuthMode::LibSsh2, false), Sftp);
+    }
+}
@@ -0,0 +1,57 @@
+//! 进程内的连接日志：所有经过 `connect_with_log` 的连接尝试 (浏览、连接池、测试连接) 都记录在这里
+
+use std::sync::Mutex;
+
+use anyhow::Result;
+use chrono::Local;
+
+use crate::domain::config::ServerConfig;
+use crate::domain::connection_log::{ConnectionLog, ConnectionLogEntry};
+use crate::shared::panic_guard::lock;
+
+use super::host;
+
+static LOG: Mutex<ConnectionLog> = Mutex::new(ConnectionLog::new());
+
+/// 记录一次连接尝试，失败时把错误追加到日志末尾
+pub fn record<T>(config: &ServerConfig, result: &Result<T>, log: &str) {
+    let mut log = log.trim_end().to_string();
+    if let Err(e) = result {
+        log.push_str(&format!("\n错误: {:#}", e));
+    }
+    lock(&LOG).push(ConnectionLogEntry {
+        timestamp: Local::now().format("%Y-%m-%d %H:%M:%S").to_string(),
+        server: config.name.clone(),
+        target: format!("{}@{}:{}", config.user, host::bracketed(&config.host), config.port),
+        success: result.is_ok(),
+        log,
+    });
+}
+
+/// 当前保留的全部连接日志
+pub fn snapshot() -> ConnectionLog {
+    lock(&LOG).clone()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::anyhow;
+
+    #[test]
+    fn test_record_appends_error() {
+        let config = ServerConfig {
+            name: "flick_test_connection_log".into(),
+            host: "10.0.0.9".into(),
+            port: 2222,
+            ..ServerConfig::default()
+        };
+        record::<()>(&config, &Err(anyhow!("认证未通过")), "开始连接\n");
+
+        let log = snapshot();
+        let entry = log.recent().find(|e| e.server == config.name).unwrap();
+        assert!(!entry.success);
+        assert_eq!(entry.target, "root@10.0.0.9:2222");
+        assert_eq!(entry.log, "开始连接\n错误: 认证未通过");
+    }
+}
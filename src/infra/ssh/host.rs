@@ -0,0 +1,64 @@
+//! 主机地址处理：IPv6 字面量在 socket 地址和 scp 目标中需要加方括号
+
+/// 去掉首尾空白及用户可能输入的方括号，得到可直接交给 ssh 命令的主机名
+pub fn bare_host(host: &str) -> &str {
+    let host = host.trim();
+    host.strip_prefix('[')
+        .and_then(|h| h.strip_suffix(']'))
+        .unwrap_or(host)
+}
+
+/// 是否为 IPv6 字面量 (含冒号即视为 IPv6，兼容 `fe80::1%eth0` 这类带 zone 的写法)
+pub fn is_ipv6(host: &str) -> bool {
+    bare_host(host).contains(':')
+}
+
+/// IPv6 加方括号，其余原样返回
+pub fn bracketed(host: &str) -> String {
+    let bare = bare_host(host);
+    if is_ipv6(bare) {
+        format!("[{}]", bare)
+    } else {
+        bare.to_string()
+    }
+}
+
+/// 拼接 `host:port`，可用于 `to_socket_addrs`
+pub fn socket_addr(host: &str, port: u16) -> String {
+    format!("{}:{}", bracketed(host), port)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::ToSocketAddrs;
+
+    #[test]
+    fn test_ipv4() {
+        assert!(!is_ipv6("192.168.1.1"));
+        assert_eq!(socket_addr("192.168.1.1", 22), "192.168.1.1:22");
+        assert!(socket_addr("192.168.1.1", 22).to_socket_addrs().is_ok());
+    }
+
+    #[test]
+    fn test_ipv6_loopback() {
+        assert!(is_ipv6("::1"));
+        assert_eq!(bare_host("::1"), "::1");
+        assert_eq!(socket_addr("::1", 22), "[::1]:22");
+        assert!(socket_addr("::1", 22).to_socket_addrs().is_ok());
+    }
+
+    #[test]
+    fn test_ipv6_with_brackets() {
+        assert_eq!(bare_host(" [2001:db8::1] "), "2001:db8::1");
+        assert_eq!(bracketed("[2001:db8::1]"), "[2001:db8::1]");
+        assert_eq!(socket_addr("[2001:db8::1]", 2222), "[2001:db8::1]:2222");
+    }
+
+    #[test]
+    fn test_hostname() {
+        assert!(!is_ipv6("example.com"));
+        assert_eq!(bare_host("example.com"), "example.com");
+        assert_eq!(socket_addr("example.com", 22), "example.com:22");
+    }
+}
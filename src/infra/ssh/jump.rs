@@ -0,0 +1,317 @@
+use anyhow::{anyhow, Context, Result};
+use ssh2::{Channel, Session};
+use std::io::{ErrorKind, Read, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs};
+use std::path::Path;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::domain::config::ServerConfig;
+
+use super::{auth, host};
+
+/// 跳板机地址 (`user@host:port`)
+#[derive(Debug, Clone, PartialEq)]
+pub struct JumpHost {
+    pub user: Option<String>,
+    pub host: String,
+    pub port: u16,
+}
+
+/// 解析 `user@host:port` 格式的跳板机配置，用户名和端口可省略。
+/// IPv6 地址带端口时写作 `user@[2001:db8::1]:22`
+pub fn parse_jump_host(spec: &str) -> Result<JumpHost> {
+    let spec = spec.trim();
+    if spec.is_empty() {
+        anyhow::bail!("跳板机地址为空");
+    }
+
+    let (user, rest) = match spec.rsplit_once('@') {
+        Some((u, r)) if !u.is_empty() => (Some(u.to_string()), r),
+        Some((_, r)) => (None, r),
+        None => (None, spec),
+    };
+
+    let (host, port) = match rest.strip_prefix('[').and_then(|r| r.split_once(']')) {
+        Some((h, "")) => (h.to_string(), 22),
+        Some((h, p)) => {
+            let p = p.strip_prefix(':').unwrap_or(p);
+            let port = p
+                .parse::<u16>()
+                .map_err(|_| anyhow!("跳板机端口无效: {}", p))?;
+            (h.to_string(), port)
+        }
+        // 未加方括号的 IPv6 地址无法携带端口
+        None if rest.matches(':').count() > 1 => (rest.to_string(), 22),
+        None => match rest.rsplit_once(':') {
+            Some((h, p)) => {
+            let port = p
+                .parse::<u16>()
+                .map_err(|_| anyhow!("跳板机端口无效: {}", p))?;
+            (h.to_string(), port)
+            }
+            None => (rest.to_string(), 22),
+        },
+    };
+
+    if host.is_empty() {
+        anyhow::bail!("跳板机主机为空");
+    }
+
+    Ok(JumpHost { user, host, port })
+}
+
+/// 经跳板机建立到目标服务器的 TCP 隧道
+///
+/// libssh2 的 `set_tcp_stream` 只接受真实 socket，因此这里在本地回环地址上
+/// 开一个一次性监听端口，只接受来自本进程客户端端口的连接，
+/// 之后由后台线程把它和跳板机的 direct-tcpip channel 双向转发。
+pub fn connect_via_jump(
+    config: &ServerConfig,
+    jump: &JumpHost,
+    timeout: Duration,
+    logs: &mut String,
+) -> Result<TcpStream> {
+    let jump_user = jump.user.clone().unwrap_or_else(|| config.user.clone());
+    push_log(logs, format!("[跳板] 连接 {}@{}:{}...", jump_user, jump.host, jump.port));
+
+    let addr = host::socket_addr(&jump.host, jump.port)
+        .to_socket_addrs()
+        .with_context(|| "无法解析跳板机地址")?
+        .next()
+        .ok_or_else(|| anyhow!("无法解析跳板机地址"))?;
+    let tcp = TcpStream::connect_timeout(&addr, timeout)
+        .with_context(|| "跳板机 TCP 连接失败")?;
+    push_log(logs, "[跳板] TCP 连接成功".to_string());
+
+    let mut session = Session::new().with_context(|| "跳板机 Session 创建失败")?;
+    session.set_tcp_stream(tcp);
+    session.set_timeout((config.io_timeout_secs * 1000) as u32);
+    session.handshake().with_context(|| "跳板机 SSH 握手失败")?;
+    push_log(logs, "[跳板] SSH 握手成功".to_string());
+
+    authenticate_jump(&session, config, &jump_user, logs)?;
+    push_log(logs, "[跳板] 认证成功".to_string());
+
+    let channel = session
+        .channel_direct_tcpip(host::bare_host(&config.host), config.port, None)
+        .map_err(|e| anyhow!("跳板机无法转发到 {}:{}: {}", config.host, config.port, e))?;
+    push_log(
+        logs,
+        format!("[跳板] 已打开到 {}:{} 的转发通道", config.host, config.port),
+    );
+
+    let listener = TcpListener::bind("127.0.0.1:0")
+        .with_context(|| "无法创建本地转发端口")?;
+    let local_addr = listener.local_addr()?;
+
+    // 回环连接由内核完成握手，无需等待 accept；失败时监听端口随函数返回关闭
+    let stream = TcpStream::connect_timeout(&local_addr, timeout)
+        .with_context(|| "连接本地转发端口失败")?;
+    let peer = accept_peer(&listener, stream.local_addr()?, timeout)?;
+    drop(listener);
+
+    thread::spawn(move || pump(session, channel, peer));
+    Ok(stream)
+}
+
+/// 在超时前接受来自 `expected` 的连接，其他本地进程抢先连入的一律断开
+fn accept_peer(listener: &TcpListener, expected: SocketAddr, timeout: Duration) -> Result<TcpStream> {
+    listener.set_nonblocking(true)?;
+    let deadline = Instant::now() + timeout;
+    loop {
+        match listener.accept() {
+            Ok((stream, addr)) if addr == expected => {
+                stream.set_nonblocking(false)?;
+                return Ok(stream);
+            }
+            Ok(_) => {}
+            Err(e) if e.kind() == ErrorKind::WouldBlock => {
+                if Instant::now() >= deadline {
+                    anyhow::bail!("等待本地转发连接超时");
+                }
+                thread::sleep(Duration::from_millis(5));
+            }
+            Err(e) => return Err(e).context("本地转发端口接受连接失败"),
+        }
+    }
+}
+
+fn push_log(logs: &mut String, line: String) {
+    logs.push_str(&line);
+    logs.push('\n');
+}
+
+/// 跳板机认证：密码 → 指定密钥 → SSH Agent
+fn authenticate_jump(
+    session: &Session,
+    config: &ServerConfig,
+    user: &str,
+    logs: &mut String,
+) -> Result<()> {
+    if config.auth_type == "password" {
+        if let Some(pwd) = &config.password {
+            match session.userauth_password(user, pwd) {
+                Ok(_) => return Ok(()),
+                Err(e) => push_log(logs, format!("[跳板] 密码认证失败: {}", e)),
+            }
+        }
+    }
+
+    if let Some(key) = &config.key_path {
+        if !key.is_empty() {
+            match session.userauth_pubkey_file(user, None, Path::new(key), None) {
+                Ok(_) => return Ok(()),
+                Err(e) => push_log(logs, format!("[跳板] 密钥认证失败: {}", e)),
+            }
+        }
+    }
+
+    match auth::try_auth_with_agent(session, user, config.agent_socket(), logs) {
+        Ok(_) if session.authenticated() => Ok(()),
+        Ok(_) => Err(anyhow!("跳板机认证未通过")),
+        Err(e) => {
+            push_log(logs, format!("[跳板] SSH Agent 认证失败: {}", e));
+            Err(anyhow!("跳板机认证失败"))
+        }
+    }
+}
+
+/// 转发空闲时轮询间隔的上限，有数据时立即恢复到最短间隔
+const MAX_IDLE_WAIT: Duration = Duration::from_millis(50);
+
+/// 连续空闲时轮询间隔翻倍，直到 `MAX_IDLE_WAIT`
+fn next_idle_wait(current: Duration) -> Duration {
+    (current * 2).min(MAX_IDLE_WAIT)
+}
+
+/// 在本地 socket 与跳板机 channel 之间双向转发，任一端关闭即结束
+fn pump(session: Session, mut channel: Channel, mut stream: TcpStream) {
+    session.set_blocking(false);
+    if stream.set_nonblocking(true).is_err() {
+        return;
+    }
+
+    let mut buf = vec![0u8; 32 * 1024];
+    let mut idle_wait = Duration::from_millis(1);
+    loop {
+        let mut idle = true;
+
+        match stream.read(&mut buf) {
+            Ok(0) => break,
+            Ok(n) => {
+                idle = false;
+                if write_all_retry(&mut channel, &buf[..n]).is_err() {
+                    break;
+                }
+            }
+            Err(e) if e.kind() == ErrorKind::WouldBlock => {}
+            Err(_) => break,
+        }
+
+        match channel.read(&mut buf) {
+            Ok(0) if channel.eof() => break,
+            Ok(0) => {}
+            Ok(n) => {
+                idle = false;
+                if write_all_retry(&mut stream, &buf[..n]).is_err() {
+                    break;
+                }
+            }
+            Err(e) if e.kind() == ErrorKind::WouldBlock => {}
+            Err(_) => break,
+        }
+
+        if idle {
+            thread::sleep(idle_wait);
+            idle_wait = next_idle_wait(idle_wait);
+        } else {
+            idle_wait = Duration::from_millis(1);
+        }
+    }
+
+    let _ = channel.close();
+}
+
+fn write_all_retry(w: &mut impl Write, mut data: &[u8]) -> std::io::Result<()> {
+    let mut wait = Duration::from_millis(1);
+    while !data.is_empty() {
+        match w.write(data) {
+            Ok(0) => return Err(ErrorKind::WriteZero.into()),
+            Ok(n) => {
+                data = &data[n..];
+                wait = Duration::from_millis(1);
+            }
+            Err(e) if e.kind() == ErrorKind::WouldBlock => {
+                thread::sleep(wait);
+                wait = next_idle_wait(wait);
+            }
+            Err(e) => return Err(e),
+        }
+    }
+    w.flush().or_else(|e| {
+        if e.kind() == ErrorKind::WouldBlock {
+            Ok(())
+        } else {
+            Err(e)
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_full_spec() {
+        let j = parse_jump_host("admin@bastion.example.com:2222").unwrap();
+        assert_eq!(j.user.as_deref(), Some("admin"));
+        assert_eq!(j.host, "bastion.example.com");
+        assert_eq!(j.port, 2222);
+    }
+
+    #[test]
+    fn test_parse_default_port_and_user() {
+        let j = parse_jump_host("bastion").unwrap();
+        assert_eq!(j.user, None);
+        assert_eq!(j.host, "bastion");
+        assert_eq!(j.port, 22);
+    }
+
+    #[test]
+    fn test_parse_ipv6() {
+        let j = parse_jump_host("root@[2001:db8::1]:2222").unwrap();
+        assert_eq!(j.host, "2001:db8::1");
+        assert_eq!(j.port, 2222);
+        let j = parse_jump_host("::1").unwrap();
+        assert_eq!(j.host, "::1");
+        assert_eq!(j.port, 22);
+    }
+
+    #[test]
+    fn test_accept_peer_rejects_other_clients() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let intruder = TcpStream::connect(addr).unwrap();
+        let ours = TcpStream::connect(addr).unwrap();
+
+        let peer = accept_peer(&listener, ours.local_addr().unwrap(), Duration::from_secs(2)).unwrap();
+        assert_eq!(peer.peer_addr().unwrap(), ours.local_addr().unwrap());
+        drop(intruder);
+
+        let err = accept_peer(&listener, ours.local_addr().unwrap(), Duration::from_millis(20));
+        assert!(err.is_err());
+    }
+
+    #[test]
+    fn test_next_idle_wait() {
+        assert_eq!(next_idle_wait(Duration::from_millis(1)), Duration::from_millis(2));
+        assert_eq!(next_idle_wait(Duration::from_millis(40)), MAX_IDLE_WAIT);
+    }
+
+    #[test]
+    fn test_parse_invalid_port() {
+        assert!(parse_jump_host("root@bastion:abc").is_err());
+        assert!(parse_jump_host("").is_err());
+    }
+}
@@ -1,6 +1,12 @@
 pub mod auth;
 pub mod client;
+pub mod connection_log;
+pub mod host;
+pub mod jump;
+pub mod key_format;
 pub mod native_fallback;
+pub mod pool;
 pub mod transfer;
 
-pub use client::{AuthMode, FileTransfer, SshUploader};
+pub use client::{open_sftp, AuthMode, FileTransfer, SshUploader};
+pub use pool::{ConnectionPool, PooledConnection};
@@ -1,10 +1,27 @@
 use anyhow::{anyhow, Result};
+use std::process::Command;
 
 use crate::domain::config::ServerConfig;
 
-pub fn perform_native_ssh_check(config: &ServerConfig) -> Result<String> {
-    use std::process::Command;
+use super::host;
+
+/// 为系统 ssh/scp 命令追加跳板机参数 (`-J`)
+pub fn apply_proxy_jump(cmd: &mut Command, config: &ServerConfig) {
+    if let Some(jump) = &config.proxy_jump {
+        if !jump.trim().is_empty() {
+            cmd.arg("-J").arg(jump.trim());
+        }
+    }
+}
 
+/// 服务器指定了 Agent socket 时让系统 ssh/scp 使用同一个 Agent (`IdentityAgent`)
+pub fn apply_identity_agent(cmd: &mut Command, config: &ServerConfig) {
+    if let Some(socket) = config.agent_socket() {
+        cmd.arg("-o").arg(format!("IdentityAgent={}", socket));
+    }
+}
+
+pub fn perform_native_ssh_check(config: &ServerConfig) -> Result<String> {
     let verify = Command::new("ssh").arg("-V").output();
     if verify.is_err() {
         return Err(anyhow!("系统中未找到 ssh 命令"));
@@ -15,6 +32,8 @@ pub fn perform_native_ssh_check(config: &ServerConfig) -> Result<String> {
        .arg("-o").arg("StrictHostKeyChecking=no")
        .arg("-p").arg(config.port.to_string())
        .arg("-T");
+    apply_proxy_jump(&mut cmd, config);
+    apply_identity_agent(&mut cmd, config);
 
     if config.auth_type == "key" {
         if let Some(path) = &config.key_path {
@@ -24,7 +43,7 @@ pub fn perform_native_ssh_check(config: &ServerConfig) -> Result<String> {
         }
     }
 
-    cmd.arg(format!("{}@{}", config.user, config.host));
+    cmd.arg(format!("{}@{}", config.user, host::bare_host(&config.host)));
     cmd.arg("exit 0");
 
     #[cfg(windows)]
@@ -0,0 +1,261 @@
+use anyhow::Result;
+use std::collections::HashMap;
+use std::io::Read;
+use std::ops::{Deref, DerefMut};
+use std::sync::{Condvar, Mutex};
+
+use crate::domain::config::ServerConfig;
+use crate::shared::panic_guard::lock;
+
+use super::{AuthMode, SshUploader};
+
+/// 每台服务器同时存在的连接上限 (使用中和空闲的合计)，
+/// 一批任务同时开始时超出的任务等待有连接归还，而不是各自新建连接
+const MAX_CONNECTIONS_PER_SERVER: usize = 4;
+
+/// 按服务器复用已认证的连接，避免每个传输任务都重新握手
+///
+/// 任务开始时 `acquire` 取出一条空闲连接 (没有则新建，已达上限则等待归还)，得到的
+/// [`PooledConnection`] 在离开作用域时自动归还；传输失败时调用 `discard` 丢弃，以免把坏连接留在池里。
+/// 取出空闲连接前先执行一条空命令确认连接仍然可用，已断开或建立后服务器设置有修改的直接淘汰。
+#[derive(Default)]
+pub struct ConnectionPool {
+    state: Mutex<PoolState>,
+    /// 有连接归还或关闭时通知等待的任务
+    returned: Condvar,
+}
+
+#[derive(Default)]
+struct PoolState {
+    idle: HashMap<String, Vec<SshUploader>>,
+    /// 各服务器已建立且尚未关闭的连接数
+    open: HashMap<String, usize>,
+}
+
+impl ConnectionPool {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn acquire(&self, config: &ServerConfig) -> Result<PooledConnection<'_>> {
+        let key = pool_key(config);
+        let uploader = loop {
+            match self.take_slot(&key) {
+                Some(uploader) if same_settings(uploader.config(), config) && is_alive(&uploader) => {
+                    break uploader
+                }
+                // 服务器设置已修改或连接已断开：关闭后按当前设置重建
+                Some(_) => self.close_slot(&key),
+                None => match SshUploader::connect(config) {
+                    Ok(uploader) => break uploader,
+                    Err(e) => {
+                        self.close_slot(&key);
+                        return Err(e);
+                    }
+                },
+            }
+        };
+        Ok(PooledConnection {
+            pool: self,
+            uploader: Some(uploader),
+        })
+    }
+
+    /// 优先取空闲连接；没有空闲连接且未达上限时占一个新建名额并返回 None，否则等待归还
+    fn take_slot(&self, key: &str) -> Option<SshUploader> {
+        let mut state = lock(&self.state);
+        loop {
+            if let Some(uploader) = state.idle.get_mut(key).and_then(|v| v.pop()) {
+                return Some(uploader);
+            }
+            let open = state.open.entry(key.to_string()).or_default();
+            if *open < MAX_CONNECTIONS_PER_SERVER {
+                *open += 1;
+                return None;
+            }
+            state = self.returned.wait(state).unwrap_or_else(|e| e.into_inner());
+        }
+    }
+
+    /// 连接关闭 (丢弃、失效或新建失败) 后释放其名额
+    fn close_slot(&self, key: &str) {
+        if let Some(open) = lock(&self.state).open.get_mut(key) {
+            *open = open.saturating_sub(1);
+        }
+        self.returned.notify_all();
+    }
+
+    fn release(&self, uploader: SshUploader) {
+        let key = pool_key(uploader.config());
+        lock(&self.state).idle.entry(key).or_default().push(uploader);
+        self.returned.notify_all();
+    }
+
+    /// 断开服务器时丢弃其所有空闲连接
+    pub fn clear(&self, config: &ServerConfig) {
+        let key = pool_key(config);
+        let removed = {
+            let mut state = lock(&self.state);
+            let removed = state.idle.remove(&key).unwrap_or_default();
+            if let Some(open) = state.open.get_mut(&key) {
+                *open = open.saturating_sub(removed.len());
+            }
+            removed
+        };
+        // 在锁外关闭连接
+        drop(removed);
+        self.returned.notify_all();
+    }
+}
+
+/// 从池中取出的连接，drop 时归还到池中
+pub struct PooledConnection<'a> {
+    pool: &'a ConnectionPool,
+    uploader: Option<SshUploader>,
+}
+
+impl PooledConnection<'_> {
+    /// 丢弃连接而不归还 (传输出错后连接状态不可信)
+    pub fn discard(mut self) {
+        if let Some(uploader) = self.uploader.take() {
+            self.pool.close_slot(&pool_key(uploader.config()));
+        }
+    }
+}
+
+impl Deref for PooledConnection<'_> {
+    type Target = SshUploader;
+
+    fn deref(&self) -> &SshUploader {
+        self.uploader.as_ref().expect("连接已丢弃")
+    }
+}
+
+impl DerefMut for PooledConnection<'_> {
+    fn deref_mut(&mut self) -> &mut SshUploader {
+        self.uploader.as_mut().expect("连接已丢弃")
+    }
+}
+
+impl Drop for PooledConnection<'_> {
+    fn drop(&mut self) {
+        if let Some(uploader) = self.uploader.take() {
+            self.pool.release(uploader);
+        }
+    }
+}
+
+/// 按用户、主机、端口和跳板机区分连接，同名但地址不同的服务器不会混用
+fn pool_key(config: &ServerConfig) -> String {
+    format!(
+        "{}@{}:{}|{}",
+        config.user,
+        config.host,
+        config.port,
+        config.proxy_jump.as_deref().unwrap_or("")
+    )
+}
+
+/// 连接建立时捕获的设置与当前设置是否一致；只影响界面的字段 (名称、标签、最近目录等) 不参与比较
+fn same_settings(captured: &ServerConfig, current: &ServerConfig) -> bool {
+    let strip = |config: &ServerConfig| ServerConfig {
+        name: String::new(),
+        is_default: false,
+        last_remote_path: None,
+        recent_dirs: Vec::new(),
+        tag: None,
+        color: None,
+        ..config.clone()
+    };
+    strip(captured) == strip(current)
+}
+
+/// 空闲期间服务器可能已断开，执行一次空命令检查；
+/// 原生模式每次操作都会启动新的 ssh 进程，无需检查
+fn is_alive(uploader: &SshUploader) -> bool {
+    match uploader.auth_mode() {
+        AuthMode::LibSsh2 => exec_true(uploader).is_ok(),
+        AuthMode::NativeSsh => true,
+    }
+}
+
+fn exec_true(uploader: &SshUploader) -> Result<()> {
+    let mut channel = uploader.session().channel_session()?;
+    channel.exec("true")?;
+    let mut output = String::new();
+    channel.read_to_string(&mut output)?;
+    channel.wait_close()?;
+    match channel.exit_status()? {
+        0 => Ok(()),
+        code => Err(anyhow::anyhow!("探测命令退出码 {}", code)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+    use std::thread;
+    use std::time::Duration;
+
+    #[test]
+    fn test_take_slot_waits_at_limit() {
+        let pool = Arc::new(ConnectionPool::new());
+        for _ in 0..MAX_CONNECTIONS_PER_SERVER {
+            assert!(pool.take_slot("a").is_none());
+        }
+        let waiting = {
+            let pool = pool.clone();
+            thread::spawn(move || pool.take_slot("a").is_none())
+        };
+        thread::sleep(Duration::from_millis(50));
+        assert!(!waiting.is_finished());
+
+        // 其他服务器不受影响
+        assert!(pool.take_slot("b").is_none());
+
+        pool.close_slot("a");
+        assert!(waiting.join().unwrap());
+    }
+
+    #[test]
+    fn test_pool_key_distinguishes_servers() {
+        let a = ServerConfig::default();
+        assert_eq!(pool_key(&a), pool_key(&ServerConfig::default()));
+
+        let b = ServerConfig {
+            port: 2222,
+            ..Default::default()
+        };
+        assert_ne!(pool_key(&a), pool_key(&b));
+
+        let c = ServerConfig {
+            proxy_jump: Some("bastion".into()),
+            ..Default::default()
+        };
+        assert_ne!(pool_key(&a), pool_key(&c));
+    }
+
+    #[test]
+    fn test_same_settings_ignores_ui_fields() {
+        let a = ServerConfig::default();
+        let browsed = ServerConfig {
+            last_remote_path: Some("/srv".into()),
+            recent_dirs: vec!["/srv".into()],
+            ..Default::default()
+        };
+        assert!(same_settings(&a, &browsed));
+
+        let atomic = ServerConfig {
+            atomic_upload: !a.atomic_upload,
+            ..Default::default()
+        };
+        assert!(!same_settings(&a, &atomic));
+
+        let key = ServerConfig {
+            key_path: Some("~/.ssh/other".into()),
+            ..Default::default()
+        };
+        assert!(!same_settings(&a, &key));
+    }
+}
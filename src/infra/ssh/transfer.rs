@@ -1,13 +1,28 @@
 use anyhow::{anyhow, Context, Result};
-use ssh2::Session;
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use ssh2::{FileStat, Session, Sftp};
 use std::fs::File;
+use std::collections::HashMap;
 use std::io::{Read, Write};
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
-use crate::domain::config::ServerConfig;
+use crate::domain::config::{ServerConfig, TransferMethod};
+use crate::domain::transfer::{self, CompressionStats, DirArchive, PlannedTransfer, SyncFile, SyncReport};
 use crate::infra::remote_fs;
+use crate::shared::ignore::IgnoreRules;
+use crate::shared::remote_path;
+use crate::shared::throttle::Throttle;
 
-use super::{FileTransfer, SshUploader};
+use super::host;
+use super::native_fallback::{apply_identity_agent, apply_proxy_jump};
+use super::{open_sftp, AuthMode, FileTransfer, SshUploader};
+
+/// SFTP 读写缓冲区大小
+pub const SFTP_BUFFER_SIZE: usize = 256 * 1024;
 
 pub fn ensure_scp_available() -> Result<()> {
     use std::process::Command;
@@ -19,7 +34,12 @@ pub fn ensure_scp_available() -> Result<()> {
 }
 
 pub fn build_remote_target(config: &ServerConfig, remote_path: &Path) -> String {
-    format!("{}@{}:{}", config.user, config.host, remote_path.to_string_lossy())
+    format!(
+        "{}@{}:{}",
+        config.user,
+        host::bracketed(&config.host),
+        remote_path.to_string_lossy()
+    )
 }
 
 fn upload_via_scp(
@@ -40,6 +60,12 @@ fn upload_via_scp(
         .arg("StrictHostKeyChecking=no")
         .arg("-o")
         .arg("BatchMode=yes");
+    if config.preserve_mtime {
+        // scp -p 同时保留权限位 (设置项说明中已注明)，没有只保留时间的选项
+        cmd.arg("-p");
+    }
+    apply_proxy_jump(&mut cmd, config);
+    apply_identity_agent(&mut cmd, config);
 
     if let Some(key_path) = &config.key_path {
         if !key_path.is_empty() {
@@ -57,7 +83,8 @@ fn upload_via_scp(
     cmd.arg(local_path);
     cmd.arg(build_remote_target(config, remote_path));
 
-    callback(0.1);
+    // scp 的进度条只输出到终端，无法解析，运行期间报告不确定进度
+    callback(transfer::PROGRESS_INDETERMINATE);
 
     let output = cmd.output().with_context(|| "无法执行 scp 命令")?;
 
@@ -70,17 +97,226 @@ fn upload_via_scp(
     }
 }
 
-fn upload_via_sftp(
+/// 展开远程路径开头的 `~`
+fn expand_remote(uploader: &SshUploader, remote_path: &Path) -> PathBuf {
+    let raw = remote_path.to_string_lossy().replace('\\', "/");
+    PathBuf::from(remote_fs::expand_tilde(uploader, &raw))
+}
+
+/// 说明实际使用的传输方式及原因；SCP 无法报告进度，一并提示
+fn method_note(used: TransferMethod, configured: TransferMethod, native: bool, scp_error: Option<&anyhow::Error>) -> String {
+    match used {
+        TransferMethod::Scp => {
+            let reason = if native {
+                "系统 ssh 模式下无法使用 SFTP"
+            } else if configured == TransferMethod::Scp {
+                "服务器设置指定 SCP"
+            } else {
+                "自动模式优先 SCP，改选 SFTP 可显示进度"
+            };
+            format!("经 SCP 传输 (无进度)：{}", reason)
+        }
+        _ => match scp_error {
+            Some(e) => {
+                let error = format!("{:#}", e);
+                format!("经 SFTP 传输：SCP 失败 ({})", error.lines().next().unwrap_or("").trim())
+            }
+            None if configured == TransferMethod::Sftp => "经 SFTP 传输：服务器设置指定 SFTP".to_string(),
+            None => "经 SFTP 传输：已禁用系统 scp".to_string(),
+        },
+    }
+}
+
+/// 在远程路径后追加后缀
+fn path_with_suffix(remote_path: &Path, suffix: &str) -> PathBuf {
+    let mut name = remote_path.as_os_str().to_os_string();
+    name.push(suffix);
+    PathBuf::from(name)
+}
+
+/// 原子上传使用的临时文件路径
+fn atomic_temp_path(remote_path: &Path) -> PathBuf {
+    path_with_suffix(remote_path, ".flick-tmp")
+}
+
+/// 压缩上传、打包下载等中间文件使用的唯一临时路径，含进程号和随机后缀，
+/// 不会与用户的同名文件或其他实例的传输冲突
+fn unique_temp_path(remote_path: &Path, suffix: &str) -> PathBuf {
+    static COUNTER: AtomicUsize = AtomicUsize::new(0);
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.subsec_nanos())
+        .unwrap_or(0);
+    path_with_suffix(
+        remote_path,
+        &format!(
+            ".flick-{}-{:08x}{}{}",
+            std::process::id(),
+            nanos,
+            COUNTER.fetch_add(1, Ordering::Relaxed),
+            suffix
+        ),
+    )
+}
+
+/// 远程路径转为 shell 参数 (统一 `/` 分隔并转义)
+fn shell_path(path: &Path) -> String {
+    remote_fs::escape_shell_arg(&path.to_string_lossy().replace('\\', "/"))
+}
+
+/// 在 session 上执行命令，退出码非 0 时返回带输出的错误。
+/// stderr 在远端并入 stdout，失败原因不会丢失
+fn exec_checked(session: &Session, command: &str, context: &str) -> Result<()> {
+    let mut channel = session.channel_session()?;
+    channel.exec(&format!("{{ {}; }} 2>&1", command))?;
+    let mut output = String::new();
+    let _ = channel.read_to_string(&mut output);
+    channel.wait_close().ok();
+    match channel.exit_status() {
+        Ok(0) => Ok(()),
+        _ => Err(anyhow!("{}: {}", context, output.trim())),
+    }
+}
+
+/// 目标是符号链接时解析为实际文件，替换链接指向的文件而不是链接本身
+fn resolve_target(sftp: &Sftp, path: &Path) -> Result<PathBuf> {
+    match sftp.lstat(path) {
+        Ok(stat) if stat.file_type().is_symlink() => sftp
+            .realpath(path)
+            .with_context(|| format!("无法解析符号链接目标: {:?}", path)),
+        _ => Ok(path.to_path_buf()),
+    }
+}
+
+/// 用 `mv -fT` 以临时文件替换目标 (SFTP v3 的 rename 不允许覆盖已存在的文件)。
+/// 目标已存在时先把它的权限复制到临时文件，符号链接则替换其指向的文件
+fn rename_over(session: &Session, sftp: &Sftp, from: &Path, to: &Path) -> Result<()> {
+    let target = resolve_target(sftp, to)?;
+    if let Some(perm) = sftp.stat(&target).ok().and_then(|stat| stat.perm) {
+        let stat = FileStat {
+            size: None,
+            uid: None,
+            gid: None,
+            perm: Some(perm & 0o7777),
+            atime: None,
+            mtime: None,
+        };
+        sftp.setstat(from, stat)
+            .with_context(|| format!("无法设置临时文件权限: {:?}", from))?;
+    }
+    exec_checked(
+        session,
+        &format!("mv -fT {} {}", shell_path(from), shell_path(&target)),
+        "重命名临时文件失败",
+    )
+}
+
+/// 已经是压缩格式的扩展名，再 gzip 几乎没有收益
+const COMPRESSED_EXTENSIONS: &[&str] = &[
+    "zip", "gz", "tgz", "bz2", "xz", "7z", "rar", "zst", "png", "jpg", "jpeg", "gif", "webp",
+    "mp3", "mp4", "mkv",
+];
+
+/// 是否值得在上传时压缩
+fn is_compressible(path: &Path) -> bool {
+    match path.extension() {
+        Some(ext) => {
+            let ext = ext.to_string_lossy().to_lowercase();
+            !COMPRESSED_EXTENSIONS.contains(&ext.as_str())
+        }
+        None => true,
+    }
+}
+
+/// 统计写入字节数的包装器，用于得到压缩后的实际传输大小
+struct CountingWriter<W> {
+    inner: W,
+    count: u64,
+}
+
+impl<W: Write> Write for CountingWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        let n = self.inner.write(buf)?;
+        self.count += n as u64;
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+/// 压缩写入唯一命名的临时 `.gz` 后在远程解压到目标；atomic 时先解压到临时文件再替换目标。
+/// 无论成败都会删除远程的中间文件。保留修改时间失败不影响结果，与统计一起返回说明
+fn upload_compressed_via_sftp(
     session: &Session,
     local_path: &Path,
     remote_path: &Path,
+    config: &ServerConfig,
     callback: impl Fn(f32),
-) -> Result<()> {
+) -> Result<(CompressionStats, Option<String>)> {
     let mut local_file = File::open(local_path)
         .with_context(|| format!("无法打开本地文件: {:?}", local_path))?;
     let metadata = local_file.metadata()?;
     let total_size = metadata.len();
+    let times = times_stat(&metadata).filter(|_| config.preserve_mtime);
+
+    ensure_remote_parent(session, remote_path)?;
+    let sftp = open_sftp(session)?;
+    let set_times = |path: &Path| match &times {
+        Some(stat) => sftp
+            .setstat(path, stat.clone())
+            .with_context(|| format!("无法设置远程文件修改时间: {:?}", path)),
+        None => Ok(()),
+    };
+
+    let gz_path = unique_temp_path(remote_path, ".gz");
+    // atomic 时解压到临时文件，成功后再替换目标
+    let unpacked = if config.atomic_upload {
+        unique_temp_path(remote_path, "")
+    } else {
+        remote_path.to_path_buf()
+    };
+    let unpack = format!(
+        "gunzip -c {gz} > {dst}; s=$?; rm -f {gz}; exit $s",
+        gz = shell_path(&gz_path),
+        dst = shell_path(&unpacked)
+    );
+
+    let result = sftp
+        .create(&gz_path)
+        .with_context(|| format!("无法在远程创建文件: {:?}", gz_path))
+        .and_then(|remote_file| {
+            let writer = CountingWriter { inner: remote_file, count: 0 };
+            let mut encoder = GzEncoder::new(writer, Compression::default());
+            copy_with_progress(&mut local_file, &mut encoder, total_size, &callback)?;
+            Ok(encoder.finish()?.count)
+        })
+        .and_then(|on_wire| {
+            exec_checked(session, &unpack, "远程解压失败")?;
+            Ok(CompressionStats {
+                original: total_size,
+                on_wire,
+            })
+        });
+    if result.is_err() {
+        let _ = sftp.unlink(&gz_path);
+        if unpacked != remote_path {
+            let _ = sftp.unlink(&unpacked);
+        }
+    }
+    let stats = result?;
+    let warning = times_warning(set_times(&unpacked));
+    if unpacked != remote_path {
+        cleanup_on_error(rename_over(session, &sftp, &unpacked, remote_path), || {
+            let _ = sftp.unlink(&unpacked);
+        })?;
+    }
+    Ok((stats, warning))
+}
 
+/// 确保远程父目录存在
+fn ensure_remote_parent(session: &Session, remote_path: &Path) -> Result<()> {
     if let Some(parent) = remote_path.parent() {
         let mut channel = session.channel_session()?;
         let parent_str = parent.to_string_lossy();
@@ -88,41 +324,109 @@ fn upload_via_sftp(
         let _ = channel.exec(&format!("mkdir -p \"{}\"", parent_unix));
         let _ = channel.wait_close();
     }
+    Ok(())
+}
 
-    let sftp = session.sftp().with_context(|| "无法建立 SFTP 会话")?;
-
-    let mut remote_file = sftp
-        .create(remote_path)
-        .with_context(|| format!("无法在远程创建文件: {:?}", remote_path))?;
+/// SFTP 上传；保留修改时间失败不影响结果，返回给调用方写入说明
+fn upload_via_sftp(
+    session: &Session,
+    local_path: &Path,
+    remote_path: &Path,
+    config: &ServerConfig,
+    callback: impl Fn(f32),
+) -> Result<Option<String>> {
+    let mut local_file = File::open(local_path)
+        .with_context(|| format!("无法打开本地文件: {:?}", local_path))?;
+    let metadata = local_file.metadata()?;
+    let total_size = metadata.len();
+    let times = times_stat(&metadata).filter(|_| config.preserve_mtime);
 
-    let mut buffer = [0u8; 8192];
-    let mut transferred = 0u64;
+    ensure_remote_parent(session, remote_path)?;
 
-    loop {
-        let bytes_read = local_file.read(&mut buffer)?;
-        if bytes_read == 0 {
-            break;
-        }
-        remote_file.write_all(&buffer[..bytes_read])?;
+    let sftp = open_sftp(session)?;
+    let set_times = |path: &Path| match &times {
+        Some(stat) => sftp
+            .setstat(path, stat.clone())
+            .with_context(|| format!("无法设置远程文件修改时间: {:?}", path)),
+        None => Ok(()),
+    };
 
-        transferred += bytes_read as u64;
-        if total_size > 0 {
-            let progress = transferred as f32 / total_size as f32;
-            callback(progress);
-        }
+    if !config.atomic_upload {
+        let mut remote_file = sftp
+            .create(remote_path)
+            .with_context(|| format!("无法在远程创建文件: {:?}", remote_path))?;
+        let result = copy_with_progress(&mut local_file, &mut remote_file, total_size, &callback);
+        // 先关闭句柄再删除写了一半的目标文件
+        drop(remote_file);
+        cleanup_on_error(result, || {
+            let _ = sftp.unlink(remote_path);
+        })?;
+        return Ok(times_warning(set_times(remote_path)));
     }
 
-    callback(1.0);
-    Ok(())
+    // 先完整写入临时文件，成功后再替换目标；失败时清理临时文件
+    let temp_path = atomic_temp_path(remote_path);
+    let result = sftp
+        .create(&temp_path)
+        .with_context(|| format!("无法在远程创建文件: {:?}", temp_path))
+        .and_then(|mut remote_file| {
+            copy_with_progress(&mut local_file, &mut remote_file, total_size, &callback)
+        })
+        .and_then(|_| {
+            let warning = times_warning(set_times(&temp_path));
+            rename_over(session, &sftp, &temp_path, remote_path).map(|_| warning)
+        });
+    cleanup_on_error(result, || {
+        let _ = sftp.unlink(&temp_path);
+    })
 }
 
+/// 本地文件的访问/修改时间转为只含时间的 SFTP 属性，读取不到修改时间时为 None
+fn times_stat(metadata: &std::fs::Metadata) -> Option<FileStat> {
+    let secs = |time: std::io::Result<std::time::SystemTime>| {
+        time.ok()?.duration_since(std::time::UNIX_EPOCH).ok().map(|d| d.as_secs())
+    };
+    let mtime = secs(metadata.modified())?;
+    Some(FileStat {
+        size: None,
+        uid: None,
+        gid: None,
+        perm: None,
+        atime: Some(secs(metadata.accessed()).unwrap_or(mtime)),
+        mtime: Some(mtime),
+    })
+}
+
+/// 把远程文件的修改时间写到本地文件
+fn set_local_mtime(local_path: &Path, mtime: u64) -> Result<()> {
+    filetime::set_file_mtime(local_path, filetime::FileTime::from_unix_time(mtime as i64, 0))
+        .with_context(|| format!("无法设置本地文件修改时间: {:?}", local_path))
+}
+
+/// 保留修改时间是尽力而为：失败时记录日志，返回写入任务说明的文字
+fn times_warning(result: Result<()>) -> Option<String> {
+    let e = result.err()?;
+    eprintln!("{:#}", e);
+    Some(format!("未能保留修改时间: {}", e.root_cause()))
+}
+
+/// 出错时执行清理 (删除不完整的目标文件)，原样返回结果
+fn cleanup_on_error<T>(result: Result<T>, cleanup: impl FnOnce()) -> Result<T> {
+    if result.is_err() {
+        cleanup();
+    }
+    result
+}
+
+/// SFTP 下载；与上传相同，保留修改时间失败时返回说明而不报错
 fn download_via_sftp(
     session: &Session,
     remote_path: &Path,
     local_path: &Path,
+    preserve_mtime: bool,
     callback: impl Fn(f32),
-) -> Result<()> {
-    let sftp = session.sftp().with_context(|| "无法建立 SFTP 会话")?;
+) -> Result<Option<String>> {
+    let sftp = open_sftp(session)?;
 
     let mut remote_file = sftp
         .open(remote_path)
@@ -139,20 +443,38 @@ fn download_via_sftp(
     let mut local_file = File::create(local_path)
         .with_context(|| format!("无法创建本地文件: {:?}", local_path))?;
 
-    let mut buffer = [0u8; 8192];
+    copy_with_progress(&mut remote_file, &mut local_file, total_size, &callback)?;
+    drop(local_file);
+    Ok(match stat.mtime {
+        Some(mtime) if preserve_mtime => times_warning(set_local_mtime(local_path, mtime)),
+        _ => None,
+    })
+}
+
+/// 按 `SFTP_BUFFER_SIZE` 分块复制，进度回调经过节流
+fn copy_with_progress(
+    reader: &mut impl Read,
+    writer: &mut impl Write,
+    total_size: u64,
+    callback: &impl Fn(f32),
+) -> Result<()> {
+    let mut buffer = vec![0u8; SFTP_BUFFER_SIZE];
     let mut transferred = 0u64;
+    let throttle = Throttle::for_progress();
 
     loop {
-        let bytes_read = remote_file.read(&mut buffer)?;
+        let bytes_read = reader.read(&mut buffer)?;
         if bytes_read == 0 {
             break;
         }
-        local_file.write_all(&buffer[..bytes_read])?;
+        writer.write_all(&buffer[..bytes_read])?;
 
         transferred += bytes_read as u64;
         if total_size > 0 {
             let progress = transferred as f32 / total_size as f32;
-            callback(progress);
+            if throttle.ready(progress) {
+                callback(progress);
+            }
         }
     }
 
@@ -160,6 +482,34 @@ fn download_via_sftp(
     Ok(())
 }
 
+/// 总大小未知时的分块复制，按时间节流回调已复制的字节数，返回总字节数
+///
+/// 流式上传和远程命令输出写入本地 (remote_fs) 共用
+pub(crate) fn copy_counting(reader: &mut impl Read, writer: &mut impl Write, callback: &dyn Fn(u64)) -> Result<u64> {
+    let mut buffer = vec![0u8; SFTP_BUFFER_SIZE];
+    let mut transferred = 0u64;
+    // 没有比例可比较，只按间隔放行
+    let throttle = Throttle::new(f32::INFINITY, Duration::from_millis(100));
+
+    loop {
+        let bytes_read = match reader.read(&mut buffer) {
+            Ok(0) => break,
+            Ok(n) => n,
+            Err(e) if e.kind() == std::io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e).context("读取输入失败"),
+        };
+        writer.write_all(&buffer[..bytes_read])?;
+        transferred += bytes_read as u64;
+        if throttle.ready(0.0) {
+            callback(transferred);
+        }
+    }
+
+    writer.flush()?;
+    callback(transferred);
+    Ok(transferred)
+}
+
 fn download_via_scp(
     config: &ServerConfig,
     remote_path: &Path,
@@ -183,6 +533,12 @@ fn download_via_scp(
         .arg("StrictHostKeyChecking=no")
         .arg("-o")
         .arg("BatchMode=yes");
+    if config.preserve_mtime {
+        // 与上传相同，-p 也会让本地文件沿用远程的权限位
+        cmd.arg("-p");
+    }
+    apply_proxy_jump(&mut cmd, config);
+    apply_identity_agent(&mut cmd, config);
 
     if let Some(key_path) = &config.key_path {
         if !key_path.is_empty() {
@@ -200,7 +556,8 @@ fn download_via_scp(
     cmd.arg(build_remote_target(config, remote_path));
     cmd.arg(local_path);
 
-    callback(0.1);
+    // scp 的进度条只输出到终端，无法解析，运行期间报告不确定进度
+    callback(transfer::PROGRESS_INDETERMINATE);
 
     let output = cmd.output().with_context(|| "无法执行 scp 命令")?;
 
@@ -213,71 +570,382 @@ fn download_via_scp(
     }
 }
 
+/// 递归上传目录，跳过 `ignore` 匹配的条目；`relative` 为相对上传根目录的路径。
+/// 返回因未变化而跳过的文件数
 fn upload_dir_recursive(
     uploader: &mut SshUploader,
     local_dir: &Path,
     remote_dir: &Path,
+    relative: &str,
+    ignore: &IgnoreRules,
     callback: &dyn Fn(f32),
-) -> Result<()> {
+) -> Result<usize> {
     uploader.remote_mkdir(remote_dir)?;
 
-    let entries: Vec<_> = std::fs::read_dir(local_dir)
-        .with_context(|| format!("无法读取本地目录: {:?}", local_dir))?
-        .filter_map(|e| e.ok())
-        .collect();
+    let entries = read_local_dir_filtered(local_dir, relative, ignore)?;
+    // 服务器设置允许时，远程已有的同名文件大小与修改时间一致则跳过；列目录失败时全部上传
+    let remote_files: HashMap<String, SyncFile> = if uploader.config().skip_unchanged {
+        remote_fs::list_dir_sftp(uploader, &remote_dir.to_string_lossy().replace('\\', "/"))
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|e| !e.is_dir)
+            .map(|e| {
+                let file = SyncFile { relative_path: e.name.clone(), size: e.size, mtime: e.mtime };
+                (e.name, file)
+            })
+            .collect()
+    } else {
+        HashMap::new()
+    };
 
     let total = entries.len();
+    let mut skipped = 0;
     for (i, entry) in entries.iter().enumerate() {
         let path = entry.path();
         let name = entry.file_name();
         let remote_child = remote_dir.join(&name);
 
         if path.is_dir() {
-            upload_dir_recursive(uploader, &path, &remote_child, callback)?;
+            let rel = join_relative(relative, &name.to_string_lossy());
+            skipped += upload_dir_recursive(uploader, &path, &remote_child, &rel, ignore, callback)?;
         } else {
-            uploader.upload(&path, &remote_child, callback)?;
+            let name = name.to_string_lossy().to_string();
+            let local = local_sync_file(name.clone(), &entry.metadata()?);
+            if transfer::should_transfer(&local, remote_files.get(&name)) {
+                uploader.upload(&path, &remote_child, callback)?;
+            } else {
+                skipped += 1;
+            }
         }
 
         if total > 0 {
             callback((i + 1) as f32 / total as f32);
         }
     }
+    Ok(skipped)
+}
+
+fn read_local_dir(local_dir: &Path) -> Result<Vec<std::fs::DirEntry>> {
+    Ok(std::fs::read_dir(local_dir)
+        .with_context(|| format!("无法读取本地目录: {:?}", local_dir))?
+        .filter_map(|e| e.ok())
+        .collect())
+}
+
+/// 列出本地目录，去掉忽略规则匹配的条目
+fn read_local_dir_filtered(
+    local_dir: &Path,
+    relative: &str,
+    ignore: &IgnoreRules,
+) -> Result<Vec<std::fs::DirEntry>> {
+    let mut entries = read_local_dir(local_dir)?;
+    entries.retain(|e| {
+        let rel = join_relative(relative, &e.file_name().to_string_lossy());
+        !ignore.is_ignored(&rel, e.path().is_dir())
+    });
+    Ok(entries)
+}
+
+/// 拼接 `/` 分隔的相对路径，根为空串
+fn join_relative(relative: &str, name: &str) -> String {
+    if relative.is_empty() {
+        name.to_string()
+    } else {
+        format!("{}/{}", relative, name)
+    }
+}
+
+/// 与 `upload_dir_recursive` 相同的遍历顺序，只收集文件不传输
+fn plan_dir_recursive(
+    local_dir: &Path,
+    remote_dir: &Path,
+    relative: &str,
+    ignore: &IgnoreRules,
+    plan: &mut Vec<PlannedTransfer>,
+) -> Result<()> {
+    for entry in read_local_dir_filtered(local_dir, relative, ignore)? {
+        let path = entry.path();
+        let name = entry.file_name().to_string_lossy().to_string();
+        let remote_child = remote_dir.join(&name);
+        let rel = join_relative(relative, &name);
+
+        if path.is_dir() {
+            plan_dir_recursive(&path, &remote_child, &rel, ignore, plan)?;
+        } else {
+            let size = entry.metadata().map(|m| m.len()).unwrap_or(0);
+            plan.push(PlannedTransfer {
+                relative_path: rel,
+                local_path: path,
+                remote_path: remote_child.to_string_lossy().replace('\\', "/"),
+                size,
+            });
+        }
+    }
+    Ok(())
+}
+
+/// 递归收集本地文件的相对路径、大小和修改时间
+fn collect_local_files(
+    local_dir: &Path,
+    relative: &str,
+    ignore: &IgnoreRules,
+    out: &mut Vec<SyncFile>,
+) -> Result<()> {
+    for entry in read_local_dir_filtered(local_dir, relative, ignore)? {
+        let path = entry.path();
+        let name = entry.file_name().to_string_lossy().to_string();
+        let rel = join_relative(relative, &name);
+        if path.is_dir() {
+            collect_local_files(&path, &rel, ignore, out)?;
+            continue;
+        }
+        out.push(local_sync_file(rel, &entry.metadata()?));
+    }
+    Ok(())
+}
+
+/// 由本地文件元数据生成比较用的文件信息
+fn local_sync_file(relative_path: String, metadata: &std::fs::Metadata) -> SyncFile {
+    let mtime = metadata
+        .modified()
+        .ok()
+        .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    SyncFile {
+        relative_path,
+        size: metadata.len(),
+        mtime,
+    }
+}
+
+/// 递归收集远程文件，指向目录的符号链接不跟随；
+/// 忽略规则匹配的条目不收集，镜像删除时也就不会删掉它们
+fn collect_remote_files(
+    uploader: &SshUploader,
+    remote_dir: &str,
+    relative: &str,
+    ignore: &IgnoreRules,
+    out: &mut Vec<SyncFile>,
+) -> Result<()> {
+    for entry in remote_fs::list_dir_sftp(uploader, remote_dir)? {
+        let rel = join_relative(relative, &entry.name);
+        if ignore.is_ignored(&rel, entry.is_dir) {
+            continue;
+        }
+        if entry.is_dir {
+            if !entry.is_symlink {
+                let child = remote_path::join(remote_dir, &entry.name);
+                collect_remote_files(uploader, &child, &rel, ignore, out)?;
+            }
+            continue;
+        }
+        out.push(SyncFile {
+            relative_path: rel,
+            size: entry.size,
+            mtime: entry.mtime,
+        });
+    }
     Ok(())
 }
 
+/// 递归下载远程目录：先枚举整棵树 (期间报告扫描进度)，再按文件数报告累计进度。
+/// 指向文件的符号链接按目标内容下载；指向目录的符号链接不跟随，避免循环链接导致无限递归。
 fn download_dir_recursive(
     uploader: &mut SshUploader,
     remote_dir: &Path,
     local_dir: &Path,
     callback: &dyn Fn(f32),
+) -> Result<()> {
+    let mut files = Vec::new();
+    scan_remote_dir(uploader, remote_dir, local_dir, &mut files, callback)?;
+
+    let total = files.len();
+    for (i, (remote_child, local_child)) in files.iter().enumerate() {
+        uploader.download(remote_child, local_child, |p| {
+            callback(transfer::dir_progress(i, total, p))
+        })?;
+        callback(transfer::dir_progress(i + 1, total, 0.0));
+    }
+    if total == 0 {
+        callback(1.0);
+    }
+    Ok(())
+}
+
+/// 枚举远程目录树，创建对应的本地目录并收集待下载的 (远程, 本地) 文件路径
+fn scan_remote_dir(
+    uploader: &SshUploader,
+    remote_dir: &Path,
+    local_dir: &Path,
+    files: &mut Vec<(PathBuf, PathBuf)>,
+    callback: &dyn Fn(f32),
 ) -> Result<()> {
     std::fs::create_dir_all(local_dir)
         .with_context(|| format!("无法创建本地目录: {:?}", local_dir))?;
+    callback(transfer::scan_progress(files.len()));
 
     let remote_str = remote_dir.to_string_lossy().replace('\\', "/");
-    let entries = remote_fs::list_dir_sftp(uploader, &remote_str)?;
-
-    let total = entries.len();
-    for (i, entry) in entries.iter().enumerate() {
+    for entry in remote_fs::list_dir_sftp(uploader, &remote_str)? {
         let remote_child = remote_dir.join(&entry.name);
         let local_child = local_dir.join(&entry.name);
 
+        if entry.is_symlink && entry.is_dir {
+            continue;
+        }
+
         if entry.is_dir {
-            download_dir_recursive(
-                uploader,
-                &remote_child,
-                &local_child,
-                callback,
-            )?;
+            scan_remote_dir(uploader, &remote_child, &local_child, files, callback)?;
         } else {
-            uploader.download(&remote_child, &local_child, callback)?;
+            files.push((remote_child, local_child));
         }
+    }
+    Ok(())
+}
 
-        if total > 0 {
-            callback((i + 1) as f32 / total as f32);
+/// tar 以退出码 1 结束时的任务说明，只取 stderr 的第一行
+fn tar_warning_note(stderr: &str) -> String {
+    let mut lines = stderr.lines().map(str::trim).filter(|l| !l.is_empty());
+    match (lines.next(), lines.next()) {
+        (Some(first), Some(_)) => format!("打包时部分文件有变化或无法读取: {} 等", first),
+        (Some(first), None) => format!("打包时部分文件有变化或无法读取: {}", first),
+        (None, _) => "打包时部分文件有变化或无法读取".to_string(),
+    }
+}
+
+/// 把 tar.gz 解包到 `dest`，条目路径中的 `..` 与绝对路径由 tar 库拒绝
+fn extract_tar_gz(archive: &Path, dest: &Path) -> Result<()> {
+    std::fs::create_dir_all(dest).with_context(|| format!("无法创建本地目录: {:?}", dest))?;
+    let file = File::open(archive).with_context(|| format!("无法打开压缩包: {:?}", archive))?;
+    tar::Archive::new(GzDecoder::new(file))
+        .unpack(dest)
+        .with_context(|| format!("解包失败: {:?}", archive))
+}
+
+impl SshUploader {
+    fn note_method(&self, used: TransferMethod, scp_error: Option<&anyhow::Error>) {
+        let native = *self.auth_mode() == AuthMode::NativeSsh;
+        self.record_method_note(method_note(used, self.config().transfer_method(), native, scp_error));
+    }
+
+    /// 传输成功时记录方式说明，并附上保留修改时间失败等警告
+    fn finish_note(
+        &self,
+        used: TransferMethod,
+        scp_error: Option<&anyhow::Error>,
+        result: Result<Option<String>>,
+    ) -> Result<()> {
+        let warning = result?;
+        self.note_method(used, scp_error);
+        if let Some(warning) = warning {
+            self.append_note(warning);
         }
+        Ok(())
+    }
+
+    /// 在已有的传输方式说明后追加一条，一起显示在任务备注中
+    fn append_note(&self, note: String) {
+        let notes: Vec<String> = self
+            .take_method_note()
+            .into_iter()
+            .chain(std::iter::once(note))
+            .collect();
+        self.record_method_note(notes.join("，"));
+    }
+
+    /// 把 reader (如标准输入) 经 SFTP 流式写入远程文件，不落地临时文件。
+    /// 大小未知，回调已写入的字节数；返回总字节数
+    pub fn upload_reader(
+        &self,
+        reader: &mut impl Read,
+        remote_path: &Path,
+        callback: impl Fn(u64),
+    ) -> Result<u64> {
+        if *self.auth_mode() != AuthMode::LibSsh2 {
+            return Err(anyhow!("当前为系统 ssh 模式，无法使用 SFTP 流式上传"));
+        }
+        let remote_path = expand_remote(self, remote_path);
+        let session = self.session();
+        ensure_remote_parent(session, &remote_path)?;
+        let sftp = open_sftp(session)?;
+
+        let target = if self.config().atomic_upload {
+            atomic_temp_path(&remote_path)
+        } else {
+            remote_path.clone()
+        };
+        let result = sftp
+            .create(&target)
+            .with_context(|| format!("无法在远程创建文件: {:?}", target))
+            .and_then(|mut remote_file| copy_counting(reader, &mut remote_file, &callback));
+        let result = match result {
+            Ok(bytes) if target != remote_path => {
+                rename_over(session, &sftp, &target, &remote_path).map(|_| bytes)
+            }
+            other => other,
+        };
+        cleanup_on_error(result, || {
+            let _ = sftp.unlink(&target);
+        })
+    }
+
+    /// 整目录打包下载：远程 `tar czf -` 的输出流式写入本地压缩包。
+    /// `extract` 时写入临时压缩包，解包到 `local_dir` 后删除；否则保留为 `<目标目录>.tar.gz`。
+    /// 已有的同名目录或压缩包会被覆盖，调用方需事先按冲突策略处理。
+    /// tar 退出码为 1 (打包期间文件有变化或无法读取) 时压缩包仍可用，只在说明中提示。
+    /// 进度按远程目录的未压缩大小估算
+    pub fn download_dir_archive(
+        &self,
+        remote_dir: &Path,
+        local_dir: &Path,
+        extract: bool,
+        callback: impl Fn(f32),
+    ) -> Result<()> {
+        let remote_dir = expand_remote(self, remote_dir);
+        let estimate = remote_fs::remote_dir_size(self, &remote_dir.to_string_lossy()).unwrap_or(0);
+        let archive = if extract {
+            unique_temp_path(local_dir, ".tar.gz")
+        } else {
+            DirArchive::Keep.output_path(local_dir)
+        };
+        if let Some(parent) = archive.parent() {
+            std::fs::create_dir_all(parent)
+                .with_context(|| format!("无法创建本地目录: {:?}", parent))?;
+        }
+        let mut file = File::create(&archive)
+            .with_context(|| format!("无法创建本地文件: {:?}", archive))?;
+
+        let throttle = Throttle::for_progress();
+        let command = format!("tar czf - -C {} .", shell_path(&remote_dir));
+        let result = remote_fs::remote_exec_to_writer(self, &command, &mut file, &|written| {
+            // 压缩后的字节数小于原始大小，留出解包的余量
+            let progress = if estimate > 0 { (written as f32 / estimate as f32).min(0.95) } else { 0.0 };
+            if throttle.ready(progress) {
+                callback(progress);
+            }
+        })
+        .and_then(|output| match output.exit {
+            0 => Ok(None),
+            1 => Ok(Some(output.stderr)),
+            code => Err(anyhow!("tar 退出码 {}: {}", code, output.stderr.trim())),
+        });
+        drop(file);
+        let warning = cleanup_on_error(result, || {
+            let _ = std::fs::remove_file(&archive);
+        })
+        .context("远程打包下载失败")?;
+
+        if extract {
+            let extracted = extract_tar_gz(&archive, local_dir);
+            let _ = std::fs::remove_file(&archive);
+            extracted?;
+        }
+        if let Some(warning) = warning {
+            self.record_method_note(tar_warning_note(&warning));
+        }
+        callback(1.0);
+        Ok(())
     }
-    Ok(())
 }
 
 impl FileTransfer for SshUploader {
@@ -287,17 +955,47 @@ impl FileTransfer for SshUploader {
         remote_path: &Path,
         callback: impl Fn(f32),
     ) -> Result<()> {
-        match upload_via_scp(self.config(), local_path, remote_path, &callback)
+        let remote_path = &expand_remote(self, remote_path);
+        let method = self.transfer_method();
+        if self.config().compress_on_upload
+            && method != TransferMethod::Scp
+            && *self.auth_mode() == AuthMode::LibSsh2
+            && is_compressible(local_path)
         {
-            Ok(_) => Ok(()),
-            Err(scp_err) => upload_via_sftp(
+            let (stats, warning) = upload_compressed_via_sftp(
                 self.session(),
                 local_path,
                 remote_path,
-                callback,
-            )
-            .with_context(|| format!("SCP 和 SFTP 均失败。SCP 错误: {}", scp_err)),
+                self.config(),
+                &callback,
+            )?;
+            self.record_compression(stats);
+            self.record_method_note("经 SFTP 压缩传输：服务器设置开启了上传压缩".to_string());
+            if let Some(warning) = warning {
+                self.append_note(warning);
+            }
+            return Ok(());
         }
+        let config = self.config();
+        let (used, scp_err, result) = match method {
+            TransferMethod::Scp => {
+                (method, None, upload_via_scp(config, local_path, remote_path, callback).map(|_| None))
+            }
+            TransferMethod::Sftp => (
+                method,
+                None,
+                upload_via_sftp(self.session(), local_path, remote_path, config, callback),
+            ),
+            TransferMethod::Auto => match upload_via_scp(config, local_path, remote_path, &callback) {
+                Ok(_) => (TransferMethod::Scp, None, Ok(None)),
+                Err(scp_err) => {
+                    let result = upload_via_sftp(self.session(), local_path, remote_path, config, callback)
+                        .with_context(|| format!("SCP 和 SFTP 均失败。SCP 错误: {}", scp_err));
+                    (TransferMethod::Sftp, Some(scp_err), result)
+                }
+            },
+        };
+        self.finish_note(used, scp_err.as_ref(), result)
     }
 
     fn download(
@@ -306,21 +1004,28 @@ impl FileTransfer for SshUploader {
         local_path: &Path,
         callback: impl Fn(f32),
     ) -> Result<()> {
-        match download_via_scp(
-            self.config(),
-            remote_path,
-            local_path,
-            &callback,
-        ) {
-            Ok(_) => Ok(()),
-            Err(scp_err) => download_via_sftp(
-                self.session(),
-                remote_path,
-                local_path,
-                callback,
-            )
-            .with_context(|| format!("SCP 和 SFTP 均失败。SCP 错误: {}", scp_err)),
-        }
+        let remote_path = &expand_remote(self, remote_path);
+        let preserve = self.config().preserve_mtime;
+        let method = self.transfer_method();
+        let (used, scp_err, result) = match method {
+            TransferMethod::Scp => {
+                (method, None, download_via_scp(self.config(), remote_path, local_path, callback).map(|_| None))
+            }
+            TransferMethod::Sftp => (
+                method,
+                None,
+                download_via_sftp(self.session(), remote_path, local_path, preserve, callback),
+            ),
+            TransferMethod::Auto => match download_via_scp(self.config(), remote_path, local_path, &callback) {
+                Ok(_) => (TransferMethod::Scp, None, Ok(None)),
+                Err(scp_err) => {
+                    let result = download_via_sftp(self.session(), remote_path, local_path, preserve, callback)
+                        .with_context(|| format!("SCP 和 SFTP 均失败。SCP 错误: {}", scp_err));
+                    (TransferMethod::Sftp, Some(scp_err), result)
+                }
+            },
+        };
+        self.finish_note(used, scp_err.as_ref(), result)
     }
 
     fn upload_dir(
@@ -329,7 +1034,13 @@ impl FileTransfer for SshUploader {
         remote_dir: &Path,
         callback: impl Fn(f32),
     ) -> Result<()> {
-        upload_dir_recursive(self, local_dir, remote_dir, &callback)
+        let remote_dir = expand_remote(self, remote_dir);
+        let ignore = IgnoreRules::new(&self.config().upload_ignore);
+        let skipped = upload_dir_recursive(self, local_dir, &remote_dir, "", &ignore, &callback)?;
+        if skipped > 0 {
+            self.append_note(format!("跳过未变化的文件 {}", skipped));
+        }
+        Ok(())
     }
 
     fn download_dir(
@@ -338,6 +1049,377 @@ impl FileTransfer for SshUploader {
         local_dir: &Path,
         callback: impl Fn(f32),
     ) -> Result<()> {
-        download_dir_recursive(self, remote_dir, local_dir, &callback)
+        let remote_dir = expand_remote(self, remote_dir);
+        download_dir_recursive(self, &remote_dir, local_dir, &callback)
+    }
+
+    fn plan_upload_dir(
+        &self,
+        local_dir: &Path,
+        remote_dir: &Path,
+    ) -> Result<Vec<PlannedTransfer>> {
+        let mut plan = Vec::new();
+        let ignore = IgnoreRules::new(&self.config().upload_ignore);
+        plan_dir_recursive(local_dir, &expand_remote(self, remote_dir), "", &ignore, &mut plan)?;
+        Ok(plan)
+    }
+
+    fn sync_dir(
+        &mut self,
+        local_dir: &Path,
+        remote_dir: &Path,
+        mirror_delete: bool,
+        callback: impl Fn(f32),
+    ) -> Result<SyncReport> {
+        let remote_root = expand_remote(self, remote_dir)
+            .to_string_lossy()
+            .replace('\\', "/");
+        remote_fs::remote_mkdir(self, &remote_root)?;
+
+        // 两侧都去掉忽略的路径：既不上传，也不会被镜像删除
+        let ignore = IgnoreRules::new(&self.config().upload_ignore);
+        let mut local = Vec::new();
+        collect_local_files(local_dir, "", &ignore, &mut local)?;
+        let mut remote = Vec::new();
+        collect_remote_files(self, &remote_root, "", &ignore, &mut remote)?;
+        let plan = transfer::plan_sync(&local, &remote, mirror_delete);
+
+        // 先建好上传文件所需的目录 (scp 不会自动创建父目录)
+        let mut parents: Vec<&str> = plan
+            .uploads
+            .iter()
+            .filter_map(|rel| rel.rsplit_once('/').map(|(parent, _)| parent))
+            .collect();
+        parents.sort_unstable();
+        parents.dedup();
+        for parent in parents {
+            remote_fs::remote_mkdir(self, &remote_path::join(&remote_root, parent))?;
+        }
+
+        let total = plan.uploads.len() + plan.deletes.len();
+        let mut done = 0;
+        for rel in &plan.uploads {
+            let remote_file = remote_path::join(&remote_root, rel);
+            self.upload(&local_dir.join(rel), Path::new(&remote_file), |_| {})?;
+            done += 1;
+            callback(done as f32 / total as f32);
+        }
+        for rel in &plan.deletes {
+            remote_fs::remote_remove(self, &remote_path::join(&remote_root, rel), false)?;
+            done += 1;
+            callback(done as f32 / total as f32);
+        }
+        callback(1.0);
+
+        Ok(SyncReport {
+            transferred: plan.uploads.len(),
+            skipped: plan.skipped,
+            deleted: plan.deletes.len(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+    use std::io::Cursor;
+
+    #[test]
+    fn test_tar_warning_note() {
+        assert_eq!(
+            tar_warning_note("tar: ./a.log: file changed as we read it\n"),
+            "打包时部分文件有变化或无法读取: tar: ./a.log: file changed as we read it"
+        );
+        assert!(tar_warning_note("tar: a\ntar: b\n").ends_with("tar: a 等"));
+        assert_eq!(tar_warning_note(""), "打包时部分文件有变化或无法读取");
+    }
+
+    #[test]
+    fn test_times_warning() {
+        assert_eq!(times_warning(Ok(())), None);
+        let err = Err(anyhow::anyhow!("Permission denied")).context("无法设置远程文件修改时间");
+        assert_eq!(times_warning(err).as_deref(), Some("未能保留修改时间: Permission denied"));
+    }
+
+    #[test]
+    fn test_extract_tar_gz() {
+        let dir = std::env::temp_dir().join("flick_test_extract_tar_gz");
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let dest = dir.join("logs");
+        let archive = DirArchive::Keep.output_path(&dest);
+        assert_eq!(archive, dir.join("logs.tar.gz"));
+
+        let mut builder = tar::Builder::new(GzEncoder::new(File::create(&archive).unwrap(), Compression::fast()));
+        for (name, body) in [("./a.txt", "alpha"), ("./sub/b.txt", "beta")] {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(body.len() as u64);
+            header.set_mode(0o644);
+            header.set_cksum();
+            builder.append_data(&mut header, name, body.as_bytes()).unwrap();
+        }
+        builder.into_inner().unwrap().finish().unwrap();
+
+        extract_tar_gz(&archive, &dest).unwrap();
+        assert_eq!(std::fs::read_to_string(dest.join("a.txt")).unwrap(), "alpha");
+        assert_eq!(std::fs::read_to_string(dest.join("sub").join("b.txt")).unwrap(), "beta");
+
+        std::fs::write(&archive, b"not a tarball").unwrap();
+        assert!(extract_tar_gz(&archive, &dest).is_err());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_copy_with_progress_throttles_callbacks() {
+        let data = vec![7u8; SFTP_BUFFER_SIZE * 4 + 10];
+        let mut reader = Cursor::new(data.clone());
+        let mut writer = Vec::new();
+        let calls = Cell::new(0);
+        let last = Cell::new(0.0f32);
+
+        copy_with_progress(&mut reader, &mut writer, data.len() as u64, &|p| {
+            calls.set(calls.get() + 1);
+            last.set(p);
+        })
+        .unwrap();
+
+        assert_eq!(writer, data);
+        assert_eq!(last.get(), 1.0);
+        // 每个缓冲块最多一次，加上最后的 1.0
+        assert!(calls.get() <= 6);
+    }
+
+    #[test]
+    fn test_copy_counting_reports_total() {
+        let data = vec![7u8; SFTP_BUFFER_SIZE * 2 + 10];
+        let mut out = Vec::new();
+        let last = std::cell::Cell::new(0u64);
+        let total = copy_counting(&mut data.as_slice(), &mut out, &|n| last.set(n)).unwrap();
+        assert_eq!(total, data.len() as u64);
+        assert_eq!(last.get(), total);
+        assert_eq!(out, data);
+
+        let mut empty: &[u8] = &[];
+        assert_eq!(copy_counting(&mut empty, &mut Vec::new(), &|_| {}).unwrap(), 0);
+    }
+
+    #[test]
+    fn test_copy_with_progress_skips_tiny_steps() {
+        // 每次只读 1 字节的 reader，总量 10000 字节：不应回调上万次
+        struct OneByte(Cursor<Vec<u8>>);
+        impl Read for OneByte {
+            fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+                let n = buf.len().min(1);
+                self.0.read(&mut buf[..n])
+            }
+        }
+        let mut reader = OneByte(Cursor::new(vec![0u8; 10_000]));
+        let mut writer = Vec::new();
+        let calls = Cell::new(0);
+
+        copy_with_progress(&mut reader, &mut writer, 10_000, &|_| {
+            calls.set(calls.get() + 1);
+        })
+        .unwrap();
+
+        assert!(calls.get() <= 201, "回调次数过多: {}", calls.get());
+    }
+
+    #[test]
+    fn test_cleanup_on_write_error() {
+        struct FailingWriter(usize);
+        impl Write for FailingWriter {
+            fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+                if self.0 == 0 {
+                    return Err(std::io::Error::new(std::io::ErrorKind::BrokenPipe, "断开"));
+                }
+                self.0 -= 1;
+                Ok(buf.len())
+            }
+            fn flush(&mut self) -> std::io::Result<()> {
+                Ok(())
+            }
+        }
+
+        let data = vec![1u8; SFTP_BUFFER_SIZE * 3];
+        let cleaned = Cell::new(false);
+        let result = copy_with_progress(
+            &mut Cursor::new(data.clone()),
+            &mut FailingWriter(1),
+            data.len() as u64,
+            &|_| {},
+        );
+        assert!(cleanup_on_error(result, || cleaned.set(true)).is_err());
+        assert!(cleaned.get());
+
+        // 成功时不清理
+        let cleaned = Cell::new(false);
+        let result = copy_with_progress(&mut Cursor::new(data.clone()), &mut Vec::new(), 0, &|_| {});
+        assert!(cleanup_on_error(result, || cleaned.set(true)).is_ok());
+        assert!(!cleaned.get());
+    }
+
+    #[test]
+    fn test_plan_dir_recursive() {
+        let root = std::env::temp_dir().join("flick_test_plan_dir");
+        if root.exists() {
+            std::fs::remove_dir_all(&root).unwrap();
+        }
+        std::fs::create_dir_all(root.join("sub")).unwrap();
+        std::fs::write(root.join("a.txt"), b"hello").unwrap();
+        std::fs::write(root.join("sub").join("b.bin"), [0u8; 10]).unwrap();
+
+        let mut plan = Vec::new();
+        plan_dir_recursive(&root, Path::new("/srv/app"), "", &IgnoreRules::default(), &mut plan).unwrap();
+        plan.sort_by(|a, b| a.relative_path.cmp(&b.relative_path));
+
+        assert_eq!(plan.len(), 2);
+        assert_eq!(plan[0].relative_path, "a.txt");
+        assert_eq!(plan[0].size, 5);
+        assert_eq!(plan[1].relative_path, "sub/b.bin");
+        assert_eq!(plan[1].remote_path, "/srv/app/sub/b.bin");
+        assert_eq!(plan[1].size, 10);
+
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_plan_dir_skips_ignored() {
+        let root = std::env::temp_dir().join("flick_test_plan_ignore");
+        if root.exists() {
+            std::fs::remove_dir_all(&root).unwrap();
+        }
+        std::fs::create_dir_all(root.join(".git").join("objects")).unwrap();
+        std::fs::create_dir_all(root.join("src")).unwrap();
+        std::fs::write(root.join(".git").join("HEAD"), b"ref").unwrap();
+        std::fs::write(root.join("src").join("main.rs"), b"fn main() {}").unwrap();
+        std::fs::write(root.join("src").join("edit.swp"), b"x").unwrap();
+
+        let ignore = IgnoreRules::new(&[".git/", "*.swp"]);
+        let mut plan = Vec::new();
+        plan_dir_recursive(&root, Path::new("/srv/app"), "", &ignore, &mut plan).unwrap();
+
+        let paths: Vec<&str> = plan.iter().map(|p| p.relative_path.as_str()).collect();
+        assert_eq!(paths, vec!["src/main.rs"]);
+
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_collect_local_files() {
+        let root = std::env::temp_dir().join("flick_test_collect_local");
+        if root.exists() {
+            std::fs::remove_dir_all(&root).unwrap();
+        }
+        std::fs::create_dir_all(root.join("sub")).unwrap();
+        std::fs::write(root.join("a.txt"), b"hello").unwrap();
+        std::fs::write(root.join("sub").join("b.bin"), [0u8; 3]).unwrap();
+
+        let mut files = Vec::new();
+        collect_local_files(&root, "", &IgnoreRules::default(), &mut files).unwrap();
+        files.sort_by(|a, b| a.relative_path.cmp(&b.relative_path));
+
+        assert_eq!(files.len(), 2);
+        assert_eq!(files[0].relative_path, "a.txt");
+        assert_eq!(files[0].size, 5);
+        assert!(files[0].mtime > 0);
+        assert_eq!(files[1].relative_path, "sub/b.bin");
+
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_collect_local_files_skips_ignored() {
+        let root = std::env::temp_dir().join("flick_test_collect_ignore");
+        if root.exists() {
+            std::fs::remove_dir_all(&root).unwrap();
+        }
+        std::fs::create_dir_all(root.join("node_modules").join("x")).unwrap();
+        std::fs::write(root.join("node_modules").join("x").join("i.js"), b"1").unwrap();
+        std::fs::write(root.join("app.js"), b"1").unwrap();
+        std::fs::write(root.join("app.log"), b"1").unwrap();
+
+        let ignore = IgnoreRules::new(&["node_modules/", "*.log"]);
+        let mut local = Vec::new();
+        collect_local_files(&root, "", &ignore, &mut local).unwrap();
+        let paths: Vec<&str> = local.iter().map(|f| f.relative_path.as_str()).collect();
+        assert_eq!(paths, vec!["app.js"]);
+
+        // 远程侧同样不收集忽略的文件，镜像删除不会删到它们
+        let plan = transfer::plan_sync(&local, &[], true);
+        assert_eq!(plan.uploads, vec!["app.js".to_string()]);
+        assert!(plan.deletes.is_empty());
+
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_mtime_roundtrip() {
+        let path = std::env::temp_dir().join("flick_test_mtime");
+        std::fs::write(&path, b"data").unwrap();
+        set_local_mtime(&path, 1_600_000_000).unwrap();
+
+        let stat = times_stat(&std::fs::metadata(&path).unwrap()).unwrap();
+        assert_eq!(stat.mtime, Some(1_600_000_000));
+        assert!(stat.atime.is_some());
+        assert!(stat.size.is_none() && stat.perm.is_none());
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_is_compressible() {
+        assert!(is_compressible(Path::new("/var/log/app.log")));
+        assert!(is_compressible(Path::new("Makefile")));
+        assert!(!is_compressible(Path::new("dist/bundle.ZIP")));
+        assert!(!is_compressible(Path::new("photo.jpg")));
+    }
+
+    #[test]
+    fn test_gzip_through_counting_writer() {
+
+        let data = "line of log text\n".repeat(1000);
+        let writer = CountingWriter { inner: Vec::new(), count: 0 };
+        let mut encoder = GzEncoder::new(writer, Compression::default());
+        copy_with_progress(&mut Cursor::new(data.as_bytes()), &mut encoder, data.len() as u64, &|_| {})
+            .unwrap();
+        let writer = encoder.finish().unwrap();
+
+        assert_eq!(writer.count, writer.inner.len() as u64);
+        assert!(writer.count < data.len() as u64 / 10);
+        let mut decoded = String::new();
+        GzDecoder::new(&writer.inner[..]).read_to_string(&mut decoded).unwrap();
+        assert_eq!(decoded, data);
+    }
+
+    #[test]
+    fn test_method_note() {
+        use TransferMethod::*;
+        assert_eq!(method_note(Scp, Scp, true, None), "经 SCP 传输 (无进度)：系统 ssh 模式下无法使用 SFTP");
+        assert_eq!(method_note(Scp, Scp, false, None), "经 SCP 传输 (无进度)：服务器设置指定 SCP");
+        assert_eq!(method_note(Scp, Auto, false, None), "经 SCP 传输 (无进度)：自动模式优先 SCP，改选 SFTP 可显示进度");
+        assert_eq!(method_note(Sftp, Sftp, false, None), "经 SFTP 传输：服务器设置指定 SFTP");
+        assert_eq!(method_note(Sftp, Auto, false, None), "经 SFTP 传输：已禁用系统 scp");
+        let err = anyhow!("SCP 上传失败: scp: not found\nmore");
+        assert_eq!(method_note(Sftp, Auto, false, Some(&err)), "经 SFTP 传输：SCP 失败 (SCP 上传失败: scp: not found)");
+    }
+
+    #[test]
+    fn test_atomic_temp_path() {
+        assert_eq!(
+            atomic_temp_path(Path::new("/etc/app/config.toml")),
+            PathBuf::from("/etc/app/config.toml.flick-tmp")
+        );
+    }
+
+    #[test]
+    fn test_unique_temp_path() {
+        let target = Path::new("/srv/data.csv");
+        let a = unique_temp_path(target, ".gz");
+        let b = unique_temp_path(target, ".gz");
+        assert_ne!(a, b);
+        let name = a.to_string_lossy();
+        assert!(name.starts_with(&format!("/srv/data.csv.flick-{}-", std::process::id())));
+        assert!(name.ends_with(".gz"));
     }
 }
@@ -0,0 +1,309 @@
+//! 解析 OpenSSH 的 `~/.ssh/config`，把其中的 Host 导入为服务器配置
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+
+use crate::domain::config::ServerConfig;
+
+/// Include 最大嵌套层数，防止循环引用
+const MAX_INCLUDE_DEPTH: usize = 8;
+
+/// config 中的一个具体主机 (不含通配符)，未出现的选项为 None
+#[derive(Debug, Clone, PartialEq)]
+pub struct SshHostEntry {
+    pub alias: String,
+    pub host_name: Option<String>,
+    pub user: Option<String>,
+    pub port: Option<u16>,
+    pub identity_file: Option<String>,
+}
+
+impl SshHostEntry {
+    /// 实际连接的主机，未写 HostName 时即为别名
+    pub fn host(&self) -> &str {
+        self.host_name.as_deref().unwrap_or(&self.alias)
+    }
+
+    /// 端口，未写时为 22
+    pub fn port(&self) -> u16 {
+        self.port.unwrap_or(22)
+    }
+
+    /// 转为服务器配置，有 IdentityFile 时使用密钥认证
+    pub fn to_server_config(&self) -> ServerConfig {
+        let has_key = self.identity_file.is_some();
+        ServerConfig {
+            name: self.alias.clone(),
+            host: self.host().to_string(),
+            port: self.port(),
+            user: self.user.clone().unwrap_or_else(|| "root".to_string()),
+            auth_type: if has_key { "key" } else { "password" }.to_string(),
+            password: if has_key { None } else { Some(String::new()) },
+            key_path: self.identity_file.clone(),
+            ..ServerConfig::default()
+        }
+    }
+}
+
+/// 默认的 `~/.ssh/config` 路径
+pub fn default_path() -> Option<PathBuf> {
+    dirs::home_dir().map(|home| home.join(".ssh").join("config"))
+}
+
+/// 读取并解析 config 文件，Include 的相对路径相对于该文件所在目录
+pub fn load(path: &Path) -> Result<Vec<SshHostEntry>> {
+    let text = fs::read_to_string(path)
+        .with_context(|| format!("无法读取 SSH config: {:?}", path))?;
+    Ok(parse(&text, path.parent().unwrap_or(Path::new("."))))
+}
+
+/// 解析 config 文本，返回具体主机列表
+pub fn parse(text: &str, base_dir: &Path) -> Vec<SshHostEntry> {
+    let mut hosts = Vec::new();
+    parse_into(text, base_dir, 0, &mut hosts);
+    hosts
+}
+
+fn parse_into(text: &str, base_dir: &Path, depth: usize, hosts: &mut Vec<SshHostEntry>) {
+    // 当前 Host 块中的具体主机在 hosts 里的下标；Match 块或通配符块为空
+    let mut current: Vec<usize> = Vec::new();
+
+    for line in text.lines() {
+        let (key, value) = match split_directive(line) {
+            Some(kv) => kv,
+            None => continue,
+        };
+        match key.as_str() {
+            "host" => {
+                current.clear();
+                for alias in value.split_whitespace() {
+                    if is_pattern(alias) {
+                        continue;
+                    }
+                    // 重复出现的主机合并到已有条目，未设置的选项继续补充
+                    if let Some(i) = hosts.iter().position(|h| h.alias == alias) {
+                        current.push(i);
+                        continue;
+                    }
+                    current.push(hosts.len());
+                    hosts.push(SshHostEntry {
+                        alias: alias.to_string(),
+                        host_name: None,
+                        user: None,
+                        port: None,
+                        identity_file: None,
+                    });
+                }
+            }
+            "match" => current.clear(),
+            "include" => {
+                if depth >= MAX_INCLUDE_DEPTH {
+                    continue;
+                }
+                for pattern in value.split_whitespace() {
+                    for file in expand_include(pattern, base_dir) {
+                        if let Ok(text) = fs::read_to_string(&file) {
+                            parse_into(&text, base_dir, depth + 1, hosts);
+                        }
+                    }
+                }
+            }
+            // 同一主机的选项以第一次出现的为准，与 OpenSSH 一致
+            _ => {
+                for &i in &current {
+                    let host = &mut hosts[i];
+                    match key.as_str() {
+                        "hostname" if host.host_name.is_none() => {
+                            host.host_name = Some(value.clone());
+                        }
+                        "user" if host.user.is_none() => host.user = Some(value.clone()),
+                        "port" if host.port.is_none() => host.port = value.parse().ok(),
+                        "identityfile" if host.identity_file.is_none() => {
+                            host.identity_file = Some(expand_home(&value));
+                        }
+                        _ => {}
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// 拆出指令名 (小写) 和值，支持 `Key value` 与 `Key=value`，忽略空行和注释
+fn split_directive(line: &str) -> Option<(String, String)> {
+    let line = line.trim();
+    if line.is_empty() || line.starts_with('#') {
+        return None;
+    }
+    let split_at = line.find(|c: char| c.is_whitespace() || c == '=')?;
+    let key = line[..split_at].to_lowercase();
+    let value = line[split_at..]
+        .trim_start_matches(|c: char| c.is_whitespace() || c == '=')
+        .trim()
+        .trim_matches('"')
+        .to_string();
+    if value.is_empty() {
+        return None;
+    }
+    Some((key, value))
+}
+
+/// 含通配符或取反的 Host 模式不是具体主机
+fn is_pattern(alias: &str) -> bool {
+    alias.contains(['*', '?', '!'])
+}
+
+fn expand_home(path: &str) -> String {
+    match (path.strip_prefix("~/"), dirs::home_dir()) {
+        (Some(rest), Some(home)) => home.join(rest).to_string_lossy().to_string(),
+        _ => path.to_string(),
+    }
+}
+
+/// 展开 Include 路径，文件名部分支持 `*` 通配，结果按名称排序
+fn expand_include(pattern: &str, base_dir: &Path) -> Vec<PathBuf> {
+    let expanded = PathBuf::from(expand_home(pattern));
+    let path = if expanded.is_absolute() {
+        expanded
+    } else {
+        base_dir.join(expanded)
+    };
+    let name = path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+    if !name.contains('*') {
+        return vec![path];
+    }
+    let dir = match path.parent() {
+        Some(d) => d,
+        None => return Vec::new(),
+    };
+    let (prefix, suffix) = name.split_once('*').unwrap_or((&name, ""));
+    let mut files: Vec<PathBuf> = fs::read_dir(dir)
+        .map(|read| {
+            read.flatten()
+                .map(|e| e.path())
+                .filter(|p| {
+                    let n = p.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+                    p.is_file() && n.starts_with(prefix) && n.ends_with(suffix)
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    files.sort();
+    files
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_basic_hosts() {
+        let text = "
+# 注释
+Host web
+    HostName 10.0.0.5
+    User deploy
+    Port 2222
+    IdentityFile /keys/web
+
+Host db db-alias
+    HostName=db.internal
+";
+        let hosts = parse(text, Path::new("/nonexistent"));
+        assert_eq!(hosts.len(), 3);
+        assert_eq!(hosts[0].alias, "web");
+        assert_eq!(hosts[0].host(), "10.0.0.5");
+        assert_eq!(hosts[0].user.as_deref(), Some("deploy"));
+        assert_eq!(hosts[0].port(), 2222);
+        assert_eq!(hosts[0].identity_file.as_deref(), Some("/keys/web"));
+        assert_eq!(hosts[1].alias, "db");
+        assert_eq!(hosts[2].alias, "db-alias");
+        assert_eq!(hosts[2].host(), "db.internal");
+    }
+
+    #[test]
+    fn test_defaults_when_missing() {
+        let hosts = parse("Host bare\n  User root\n", Path::new("/nonexistent"));
+        assert_eq!(hosts[0].host(), "bare");
+        assert_eq!(hosts[0].port(), 22);
+        assert!(hosts[0].identity_file.is_none());
+
+        let hosts = parse("Host bad-port\n  Port abc\n", Path::new("/nonexistent"));
+        assert_eq!(hosts[0].port(), 22);
+    }
+
+    #[test]
+    fn test_skip_wildcards_and_match() {
+        let text = "
+Host *
+    User everyone
+Host *.corp !skip gw
+    User admin
+Match host other
+    User ignored
+";
+        let hosts = parse(text, Path::new("/nonexistent"));
+        assert_eq!(hosts.len(), 1);
+        assert_eq!(hosts[0].alias, "gw");
+        assert_eq!(hosts[0].user.as_deref(), Some("admin"));
+    }
+
+    #[test]
+    fn test_first_value_wins() {
+        let text = "Host a\n  User first\nHost a\n  User second\n  Port 2022\n";
+        let hosts = parse(text, Path::new("/nonexistent"));
+        assert_eq!(hosts.len(), 1);
+        assert_eq!(hosts[0].user.as_deref(), Some("first"));
+        assert_eq!(hosts[0].port(), 2022);
+    }
+
+    #[test]
+    fn test_include() {
+        let dir = std::env::temp_dir().join("flick_test_ssh_config");
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(dir.join("conf.d")).unwrap();
+        fs::write(dir.join("conf.d").join("a.conf"), "Host inc-a\n  Port 2200\n").unwrap();
+        fs::write(dir.join("conf.d").join("b.conf"), "Host inc-b\n").unwrap();
+        fs::write(dir.join("extra"), "Host extra\n").unwrap();
+        fs::write(dir.join("config"), "Include conf.d/*.conf extra\nHost main\n").unwrap();
+
+        let hosts = load(&dir.join("config")).unwrap();
+        let aliases: Vec<&str> = hosts.iter().map(|h| h.alias.as_str()).collect();
+        assert_eq!(aliases, vec!["inc-a", "inc-b", "extra", "main"]);
+        assert_eq!(hosts[0].port(), 2200);
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_include_cycle_terminates() {
+        let dir = std::env::temp_dir().join("flick_test_ssh_config_cycle");
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("config"), "Include config\nHost loop\n").unwrap();
+        let hosts = load(&dir.join("config")).unwrap();
+        assert_eq!(hosts.len(), 1);
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_to_server_config() {
+        let entry = SshHostEntry {
+            alias: "web".into(),
+            host_name: Some("10.0.0.5".into()),
+            user: None,
+            port: Some(2222),
+            identity_file: Some("/keys/web".into()),
+        };
+        let cfg = entry.to_server_config();
+        assert_eq!(cfg.name, "web");
+        assert_eq!(cfg.host, "10.0.0.5");
+        assert_eq!(cfg.port, 2222);
+        assert_eq!(cfg.user, "root");
+        assert_eq!(cfg.auth_type, "key");
+        assert_eq!(cfg.key_path.as_deref(), Some("/keys/web"));
+        assert!(cfg.password.is_none());
+    }
+}
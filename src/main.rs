@@ -7,34 +7,55 @@ mod infra;
 mod presentation;
 mod shared;
 
-use slint::{ModelRc, SharedString, VecModel};
+use clap::Parser;
+use slint::SharedString;
 use std::sync::{Arc, Mutex};
 
+use app::cli::{self, Args};
 use app::context::AppContext;
 use domain::config::AppConfig;
+use shared::panic_guard::lock;
 
 slint::include_modules!();
 
-#[derive(Debug, Default)]
-struct Args {
-    file: Option<String>,
-}
-
-fn parse_args() -> Args {
-    let file = std::env::args_os()
-        .nth(1)
-        .map(|s| s.to_string_lossy().into_owned());
-    Args { file }
-}
-
 fn main() -> anyhow::Result<()> {
-    let args = parse_args();
+    let args = Args::parse();
     let context = AppContext::bootstrap()?;
+    if let Some(warning) = &context.startup_warning {
+        eprintln!("{}", warning);
+    }
+
+    // 子命令：不显示界面，输出 JSON 后以对应退出码结束
+    if let Some(command) = &args.command {
+        let config = lock(&context.config).clone();
+        std::process::exit(cli::run_headless(&config, command));
+    }
+
+    // `--now`: 不显示界面，直接上传后退出
+    if args.now {
+        let config = lock(&context.config).clone();
+        return cli::upload_now(&config, &args);
+    }
 
     let ui = AppWindow::new()?;
 
     // 初始化服务器列表
     init_ui_state(&ui, &context.config, &args);
+    if let Some(warning) = &context.startup_warning {
+        ui.set_global_error(SharedString::from(warning));
+    }
+
+    // 上次异常退出时未完成的传输，浏览模式下询问是否继续
+    let interrupted = lock(&context.transfer_queue).interrupted_ids().len();
+    if interrupted > 0 && args.files.is_empty() {
+        ui.set_confirm_title(SharedString::from("恢复传输"));
+        ui.set_confirm_message(SharedString::from(format!(
+            "发现 {} 个上次未完成的传输任务，是否继续？取消将丢弃这些任务。",
+            interrupted
+        )));
+        ui.set_confirm_action(SharedString::from("resume-transfers"));
+        ui.set_show_confirm(true);
+    }
 
     // 绑定回调
     presentation::slint::settings_bindings::bind(
@@ -42,17 +63,24 @@ fn main() -> anyhow::Result<()> {
         context.config.clone(),
         context.config_repo.clone(),
     );
+    presentation::slint::connection_log_bindings::bind(&ui);
     presentation::slint::quick_upload_bindings::bind(
         &ui,
         context.config.clone(),
+        context.transfer_queue.clone(),
     );
-    presentation::slint::explorer::bind(
+    // 传输列表同步定时器需存活到窗口关闭
+    let _transfer_sync = presentation::slint::explorer::bind(
         &ui,
         context.config,
         context.config_repo,
+        context.history_repo,
+        context.transfer_queue.clone(),
     );
 
     ui.run()?;
+    // 定时保存之后的最后变化在退出前写入
+    lock(&context.transfer_queue).persist();
     Ok(())
 }
 
@@ -66,24 +94,35 @@ fn init_ui_state(
         Err(_) => return,
     };
 
-    // 服务器列表
-    let servers: Vec<SharedString> = guard
-        .servers
-        .iter()
-        .map(|s| SharedString::from(&s.name))
-        .collect();
-    ui.set_servers(ModelRc::new(VecModel::from(servers)));
-
-    // 默认选中服务器
-    let default_idx = guard
-        .servers
-        .iter()
-        .position(|s| s.is_default)
-        .unwrap_or(0);
+    // 服务器列表 (可达性状态点由绑定回调后的后台探测补上)
+    presentation::slint::settings_bindings::refresh_server_list(ui, &guard, &Default::default());
+
+    // 恢复上次的窗口尺寸
+    let prefs = &guard.ui_prefs;
+    if prefs.window_width > 0 && prefs.window_height > 0 {
+        ui.window().set_size(slint::LogicalSize::new(
+            prefs.window_width as f32,
+            prefs.window_height as f32,
+        ));
+    }
+
+    // 默认选中服务器，并在浏览模式下自动连接
+    let defaults = app::services::explorer_service::default_server_indices(&guard.servers);
+    if defaults.len() > 1 {
+        eprintln!(
+            "警告: 有 {} 个服务器被标记为默认，将自动连接第一个",
+            defaults.len()
+        );
+    }
+    let default_idx = defaults.first().copied().unwrap_or(0);
+    let auto_connect = !defaults.is_empty() && args.files.is_empty();
     let ui_handle = ui.as_weak();
     slint::invoke_from_event_loop(move || {
         if let Some(ui) = ui_handle.upgrade() {
             ui.set_remote_server_index(default_idx as i32);
+            if auto_connect {
+                ui.invoke_remote_connect(default_idx as i32);
+            }
         }
     })
     .unwrap();
@@ -101,13 +140,16 @@ fn init_ui_state(
     };
     ui.set_ssh_key_hint(SharedString::from(ssh_hint));
 
-    // 命令行文件参数 → 快速上传模式
-    if let Some(path_str) = &args.file {
+    // 命令行文件参数 → 快速上传模式，通配符展开为多个文件
+    if !args.files.is_empty() {
         ui.set_quick_upload_mode(true);
-        let display = shared::path_utils::normalize_path(path_str)
-            .map(|p| p.to_string_lossy().to_string())
-            .unwrap_or_else(|_| path_str.clone());
-        ui.set_file_path(SharedString::from(display));
+        match shared::path_utils::expand_file_args(&args.files) {
+            Ok(files) => presentation::slint::quick_upload_bindings::stage_files(ui, &files),
+            Err(e) => {
+                ui.set_file_path(SharedString::from(args.files.join(" ")));
+                ui.set_status_log(SharedString::from(format!("错误: {}", e)));
+            }
+        }
 
         // 默认目标目录
         if let Some(first) = guard.servers.first() {
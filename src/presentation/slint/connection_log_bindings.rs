@@ -0,0 +1,71 @@
+use slint::{ComponentHandle, Model, ModelRc, SharedString, VecModel};
+
+use crate::infra::ssh::connection_log;
+use crate::AppWindow;
+use crate::ConnectionLogItem;
+
+pub fn bind(ui: &AppWindow) {
+    bind_open(ui);
+    bind_copy(ui);
+    bind_export(ui);
+}
+
+/// 打开日志面板时读取当前保留的连接日志，最近的在前
+fn bind_open(ui: &AppWindow) {
+    let ui_handle = ui.as_weak();
+    ui.on_open_connection_logs(move || {
+        let ui = match ui_handle.upgrade() {
+            Some(ui) => ui,
+            None => return,
+        };
+        let log = connection_log::snapshot();
+        let items: Vec<ConnectionLogItem> = log
+            .recent()
+            .map(|e| ConnectionLogItem {
+                timestamp: SharedString::from(&e.timestamp),
+                server: SharedString::from(&e.server),
+                target: SharedString::from(&e.target),
+                success: e.success,
+                text: SharedString::from(e.to_text()),
+            })
+            .collect();
+        ui.set_connection_logs(ModelRc::new(VecModel::from(items)));
+        ui.set_connection_log_index(0);
+        ui.set_show_connection_logs(true);
+    });
+}
+
+/// 复制面板中显示的某条日志
+fn bind_copy(ui: &AppWindow) {
+    let ui_handle = ui.as_weak();
+    ui.on_copy_connection_log(move |index| {
+        let ui = match ui_handle.upgrade() {
+            Some(ui) => ui,
+            None => return,
+        };
+        if let Some(item) = ui.get_connection_logs().row_data(index as usize) {
+            ui.invoke_copy_to_clipboard(item.text);
+        }
+    });
+}
+
+/// 把全部连接日志导出为文本文件，便于附在问题反馈中
+fn bind_export(ui: &AppWindow) {
+    let ui_handle = ui.as_weak();
+    ui.on_export_connection_logs(move || {
+        let path = match rfd::FileDialog::new()
+            .add_filter("Text", &["txt"])
+            .set_file_name("flick-connection-log.txt")
+            .save_file()
+        {
+            Some(p) => p,
+            None => return,
+        };
+        let text = connection_log::snapshot().export_text();
+        if let Err(e) = std::fs::write(&path, text) {
+            if let Some(ui) = ui_handle.upgrade() {
+                ui.set_global_error(format!("导出连接日志失败: {}", e).into());
+            }
+        }
+    });
+}
@@ -0,0 +1,89 @@
+use slint::{ComponentHandle, ModelRc, SharedString, VecModel};
+use std::sync::{Arc, Mutex};
+
+use crate::app::services::compare_service::{self, DiffItem, DiffSide};
+use crate::infra::local_fs;
+use crate::infra::remote_fs;
+use crate::shared::panic_guard::lock;
+use crate::AppWindow;
+use crate::CompareItem;
+
+use super::format_size;
+use super::local_bindings::LocalState;
+use super::remote_bindings::{self, RemoteState};
+
+pub(crate) fn bind(ui: &AppWindow, local_state: Arc<Mutex<LocalState>>, remote_state: Arc<Mutex<RemoteState>>) {
+    let ui_handle = ui.as_weak();
+    ui.on_compare_dirs(move || {
+        if let Some(ui) = ui_handle.upgrade() {
+            compare(&ui, &local_state, &remote_state);
+        }
+    });
+}
+
+/// 重新列出两侧当前目录 (不受名称和快捷过滤影响) 并显示比较结果
+fn compare(ui: &AppWindow, local_state: &Arc<Mutex<LocalState>>, remote_state: &Arc<Mutex<RemoteState>>) {
+    let (local_path, show_hidden) = {
+        let s = lock(local_state);
+        (s.current_path.clone(), s.show_hidden)
+    };
+    if local_path.as_os_str().is_empty() {
+        ui.set_global_error(SharedString::from("请先在本地面板进入一个目录"));
+        return;
+    }
+    let local_entries = match local_fs::list_dir(&local_path, show_hidden) {
+        Ok(entries) => entries,
+        Err(e) => {
+            ui.set_global_error(SharedString::from(format!("读取本地目录失败: {:#}", e)));
+            return;
+        }
+    };
+
+    let s = lock(remote_state);
+    let uploader = match &s.uploader {
+        Some(u) => u,
+        None => return,
+    };
+    let remote_path = s.current_path.clone();
+    let remote_entries = match remote_fs::list_dir_sftp(uploader, &remote_path) {
+        Ok(mut entries) => {
+            remote_bindings::retain_shown(&mut entries, s.show_hidden);
+            entries
+        }
+        Err(e) => {
+            drop(s);
+            ui.set_global_error(SharedString::from(format!("读取远程目录失败: {:#}", e)));
+            return;
+        }
+    };
+    drop(s);
+
+    let diff = compare_service::diff_dirs(&local_entries, &remote_entries);
+    let all: Vec<CompareItem> = diff.items.iter().map(to_compare_item).collect();
+    let differences: Vec<CompareItem> = diff.differences().map(to_compare_item).collect();
+    ui.set_compare_items(ModelRc::new(VecModel::from(all)));
+    ui.set_compare_diff_items(ModelRc::new(VecModel::from(differences)));
+    ui.set_compare_local_path(SharedString::from(local_path.to_string_lossy().as_ref()));
+    ui.set_compare_remote_path(SharedString::from(&remote_path));
+    ui.set_compare_summary(SharedString::from(diff.summary()));
+    ui.set_show_compare(true);
+}
+
+fn to_compare_item(item: &DiffItem) -> CompareItem {
+    CompareItem {
+        name: SharedString::from(&item.name),
+        status: SharedString::from(item.status.as_str()),
+        is_dir: item.is_dir(),
+        local_info: SharedString::from(side_info(item.local.as_ref())),
+        remote_info: SharedString::from(side_info(item.remote.as_ref())),
+    }
+}
+
+/// 一侧的大小和修改时间，不存在时为空
+fn side_info(side: Option<&DiffSide>) -> String {
+    match side {
+        Some(s) if s.is_dir => format!("目录  {}", s.modified),
+        Some(s) => format!("{}  {}", format_size(s.size, false), s.modified),
+        None => String::new(),
+    }
+}
@@ -1,13 +1,25 @@
 use slint::{ComponentHandle, Model, ModelRc, SharedString, VecModel};
+use std::cell::RefCell;
 use std::collections::HashSet;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
 use std::sync::{Arc, Mutex};
+use std::thread;
 
+use crate::app::services::explorer_service;
+use crate::domain::config::{push_recent_dir, AppConfig, MAX_RECENT_DIRS};
+use crate::domain::transfer::ConflictPolicy;
 use crate::infra::local_fs;
+use crate::shared::panic_guard::lock;
+use crate::shared::path_utils;
+use crate::shared::name_filter::{FilterMode, NameFilter};
+use crate::shared::quick_filter::{self, QuickFilter};
+use crate::shared::throttle::Debouncer;
+use crate::presentation::slint::mapper;
 use crate::AppWindow;
 use crate::FileEntry;
 
-use super::format_size;
+use super::{entry_kind, format_entry_size, name_highlight};
 
 /// 本地文件浏览器状态
 pub(crate) struct LocalState {
@@ -17,13 +29,51 @@ pub(crate) struct LocalState {
     pub sort_field: String,
     pub sort_ascending: bool,
     pub filter_text: String,
+    pub filter_mode: FilterMode,
+    /// 按大小/修改时间的快捷过滤
+    pub quick_filter: QuickFilter,
+    /// 显示带隐藏/系统属性的文件
+    pub show_hidden: bool,
     pub last_clicked_index: Option<usize>,
+    /// 键盘导航的焦点行
+    pub focused_index: Option<usize>,
+    /// 等待确认覆盖的重命名 (原路径, 新路径)
+    pub pending_rename: Option<(PathBuf, PathBuf)>,
+    /// 复制/剪切的本地路径
+    pub clipboard: Vec<PathBuf>,
+    pub clipboard_cut: bool,
+    /// 最近访问的目录，最新的在前
+    pub recent_dirs: Vec<String>,
+}
+
+impl LocalState {
+    /// 当前的文件名过滤器，未输入过滤文本时为 None
+    pub(crate) fn name_filter(&self) -> Option<NameFilter> {
+        (!self.filter_text.is_empty()).then(|| NameFilter::new(self.filter_mode, &self.filter_text))
+    }
 }
 
 pub(crate) fn default_start_dir() -> PathBuf {
     PathBuf::from("")
 }
 
+fn to_file_entry(e: &local_fs::LocalEntry, selected: bool, filter: Option<&NameFilter>) -> FileEntry {
+    let (name_head, name_match, name_tail) = name_highlight(&e.name, filter);
+    FileEntry {
+        name: SharedString::from(&e.name),
+        is_dir: e.is_dir,
+        kind: entry_kind(&e.name, e.is_dir),
+        is_symlink: false,
+        link_target: SharedString::new(),
+        size: SharedString::from(format_entry_size(e.size, e.is_dir, e.dir_size)),
+        modified: SharedString::from(&e.modified),
+        selected,
+        name_head,
+        name_match,
+        name_tail,
+    }
+}
+
 fn sort_local_entries(entries: &mut Vec<local_fs::LocalEntry>, field: &str, ascending: bool) {
     entries.sort_by(|a, b| {
         let dir_ord = b.is_dir.cmp(&a.is_dir);
@@ -31,7 +81,7 @@ fn sort_local_entries(entries: &mut Vec<local_fs::LocalEntry>, field: &str, asce
             return dir_ord;
         }
         let ord = match field {
-            "size" => a.size.cmp(&b.size),
+            "size" => a.dir_size.unwrap_or(a.size).cmp(&b.dir_size.unwrap_or(b.size)),
             "modified" => a.modified.cmp(&b.modified),
             _ => a.name.to_lowercase().cmp(&b.name.to_lowercase()),
         };
@@ -40,42 +90,59 @@ fn sort_local_entries(entries: &mut Vec<local_fs::LocalEntry>, field: &str, asce
 }
 
 pub(crate) fn refresh_local(ui: &AppWindow, state: &Arc<Mutex<LocalState>>) {
-    let s = state.lock().unwrap();
+    let s = lock(state);
     let path = s.current_path.clone();
     let selected = s.selected_indices.clone();
     let sort_field = s.sort_field.clone();
     let sort_asc = s.sort_ascending;
-    let filter = s.filter_text.clone();
+    let name_filter = s.name_filter();
+    let quick = s.quick_filter;
+    let show_hidden = s.show_hidden;
     drop(s);
 
-    let mut entries = match local_fs::list_dir(&path) {
+    let mut entries = match local_fs::list_dir(&path, show_hidden) {
         Ok(e) => e,
-        Err(_) => return,
+        Err(_) => {
+            // 路径是文件 (如手动输入或书签指向文件)：进入所在目录并选中它
+            if let Some(parent) = path.parent().filter(|_| path.is_file()) {
+                lock(state).current_path = parent.to_path_buf();
+                refresh_local(ui, state);
+                select_local_entry(ui, state, &path);
+            }
+            return;
+        }
     };
 
     sort_local_entries(&mut entries, &sort_field, sort_asc);
 
-    if !filter.is_empty() {
-        let lower = filter.to_lowercase();
-        entries.retain(|e| e.name.to_lowercase().contains(&lower));
+    if let Some(name_filter) = &name_filter {
+        entries.retain(|e| name_filter.matches(&e.name));
+    }
+    if quick != QuickFilter::None {
+        let now = quick_filter::unix_now();
+        entries.retain(|e| quick.matches(e.is_dir, e.size, e.mtime, now));
+    }
+
+    let mut s = lock(state);
+    for e in entries.iter_mut().filter(|e| e.is_dir) {
+        e.dir_size = s
+            .cached_entries
+            .iter()
+            .find(|old| old.path == e.path)
+            .and_then(|old| old.dir_size);
     }
 
     let file_entries: Vec<FileEntry> = entries
         .iter()
         .enumerate()
-        .map(|(i, e)| FileEntry {
-            name: SharedString::from(&e.name),
-            is_dir: e.is_dir,
-            size: SharedString::from(format_size(e.size, e.is_dir)),
-            modified: SharedString::from(&e.modified),
-            selected: selected.contains(&i),
-        })
+        .map(|(i, e)| to_file_entry(e, selected.contains(&i), name_filter.as_ref()))
         .collect();
 
-    let mut s = state.lock().unwrap();
     s.cached_entries = entries;
     let file_count = s.cached_entries.len() as i32;
     let selected_count = s.selected_indices.len() as i32;
+    push_recent_dir(&mut s.recent_dirs, &path.to_string_lossy(), MAX_RECENT_DIRS);
+    let recent = super::recent_dirs_model(&s.recent_dirs);
     drop(s);
 
     let display_path = if path.as_os_str().is_empty() {
@@ -88,23 +155,266 @@ pub(crate) fn refresh_local(ui: &AppWindow, state: &Arc<Mutex<LocalState>>) {
     ui.set_local_files(ModelRc::new(VecModel::from(file_entries)));
     ui.set_local_file_count(file_count);
     ui.set_local_selected_count(selected_count);
+    ui.set_local_recent_dirs(recent);
+    ui.set_local_focused_row(-1);
 }
 
-pub(crate) fn bind(ui: &AppWindow, local_state: Arc<Mutex<LocalState>>) {
+/// 只选中当前列表中路径为 `path` 的条目并设为焦点行，条目被过滤时不做处理
+fn select_local_entry(ui: &AppWindow, state: &Arc<Mutex<LocalState>>, path: &Path) {
+    let mut s = lock(state);
+    let index = match s.cached_entries.iter().position(|e| e.path == path) {
+        Some(i) => i,
+        None => return,
+    };
+    s.selected_indices = HashSet::from([index]);
+    s.focused_index = Some(index);
+    s.last_clicked_index = Some(index);
+    drop(s);
+
+    super::select_only_row(&ui.get_local_files(), index);
+    ui.set_local_selected_count(1);
+    ui.set_local_focused_row(index as i32);
+}
+
+pub(crate) fn bind(ui: &AppWindow, config: Arc<Mutex<AppConfig>>, local_state: Arc<Mutex<LocalState>>) {
     refresh_local(ui, &local_state);
 
     bind_local_navigate(ui, local_state.clone());
+    bind_local_path_complete(ui, local_state.clone());
     bind_local_go_up(ui, local_state.clone());
+    bind_local_key(ui, local_state.clone());
     bind_local_file_clicked(ui, local_state.clone());
     bind_local_double_click(ui, local_state.clone());
+    bind_local_open(ui, local_state.clone());
+    bind_local_properties(ui, local_state.clone());
     bind_local_refresh(ui, local_state.clone());
     bind_local_select_all(ui, local_state.clone());
     bind_local_mkdir(ui, local_state.clone());
-    bind_local_delete_selected(ui, local_state.clone());
+    bind_local_touch(ui, local_state.clone());
+    bind_local_delete_selected(ui, config, local_state.clone());
     bind_local_rename(ui, local_state.clone());
+    bind_local_bulk_rename(ui, local_state.clone());
+    bind_local_clipboard(ui, local_state.clone());
     bind_local_sort_changed(ui, local_state.clone());
     bind_local_file_clicked_ex(ui, local_state.clone());
-    bind_local_filter_changed(ui, local_state);
+    bind_local_calc_dir_size(ui, local_state.clone());
+    bind_local_filter_changed(ui, local_state.clone());
+    bind_local_quick_filter_changed(ui, local_state.clone());
+    bind_local_show_hidden_changed(ui, local_state.clone());
+    bind_local_filter_mode_changed(ui, local_state);
+}
+
+/// 粘贴的 (源, 目标) 路径
+type PastePairs = Vec<(PathBuf, PathBuf)>;
+
+/// 本地复制/剪切/粘贴：粘贴到当前目录，同名冲突时弹出覆盖/跳过/保留两者对话框
+fn bind_local_clipboard(ui: &AppWindow, state: Arc<Mutex<LocalState>>) {
+    let ui_handle = ui.as_weak();
+    let st = state.clone();
+    ui.on_local_copy_selected(move || {
+        set_clipboard(&st, &ui_handle, false);
+    });
+
+    let ui_handle = ui.as_weak();
+    let st = state.clone();
+    ui.on_local_cut_selected(move || {
+        set_clipboard(&st, &ui_handle, true);
+    });
+
+    // 等待冲突对话框决定的粘贴
+    let pending: Rc<RefCell<Option<PastePairs>>> = Rc::new(RefCell::new(None));
+
+    let ui_handle = ui.as_weak();
+    let st = state.clone();
+    let pending_clone = pending.clone();
+    ui.on_local_paste(move || {
+        let ui = match ui_handle.upgrade() {
+            Some(ui) => ui,
+            None => return,
+        };
+        let (pairs, cut) = {
+            let s = lock(&st);
+            let cut = s.clipboard_cut;
+            let pairs: PastePairs = s
+                .clipboard
+                .iter()
+                .filter_map(|src| {
+                    let dst = s.current_path.join(src.file_name()?);
+                    // 复制到原目录时自动改名，剪切到原目录则什么也不做
+                    match (&dst == src, cut) {
+                        (true, true) => None,
+                        (true, false) => Some((src.clone(), path_utils::keep_both_path(&dst))),
+                        _ => Some((src.clone(), dst)),
+                    }
+                })
+                .collect();
+            (pairs, cut)
+        };
+        if pairs.is_empty() {
+            return;
+        }
+
+        let conflicts: Vec<String> = pairs
+            .iter()
+            .filter(|(_, dst)| std::fs::symlink_metadata(dst).is_ok())
+            .filter_map(|(_, dst)| dst.file_name().map(|n| n.to_string_lossy().to_string()))
+            .collect();
+        if conflicts.is_empty() {
+            paste_local(&ui, &st, pairs, cut);
+            return;
+        }
+
+        let shown: Vec<&str> = conflicts.iter().take(3).map(String::as_str).collect();
+        let more = if conflicts.len() > shown.len() { " 等" } else { "" };
+        ui.set_conflict_message(SharedString::from(format!(
+            "当前目录已存在 {} 个同名项: {}{}",
+            conflicts.len(),
+            shown.join(", "),
+            more
+        )));
+        ui.set_conflict_action(SharedString::from("local-paste"));
+        ui.set_show_conflict(true);
+        *pending_clone.borrow_mut() = Some(pairs);
+    });
+
+    let ui_handle = ui.as_weak();
+    let pending_clone = pending.clone();
+    ui.on_local_paste_resolved(move |policy| {
+        let pairs = match pending_clone.borrow_mut().take() {
+            Some(p) => p,
+            None => return,
+        };
+        let policy = match ConflictPolicy::parse(&policy) {
+            Some(p) => p,
+            None => return,
+        };
+        let pairs: PastePairs = pairs
+            .into_iter()
+            .filter_map(|(src, dst)| {
+                if std::fs::symlink_metadata(&dst).is_err() {
+                    return Some((src, dst));
+                }
+                match policy {
+                    ConflictPolicy::Overwrite => Some((src, dst)),
+                    ConflictPolicy::Skip => None,
+                    ConflictPolicy::KeepBoth => {
+                        let dst = path_utils::keep_both_path(&dst);
+                        Some((src, dst))
+                    }
+                }
+            })
+            .collect();
+        if let Some(ui) = ui_handle.upgrade() {
+            let cut = lock(&state).clipboard_cut;
+            paste_local(&ui, &state, pairs, cut);
+        }
+    });
+
+    ui.on_local_paste_cancelled(move || {
+        pending.borrow_mut().take();
+    });
+}
+
+fn set_clipboard(state: &Arc<Mutex<LocalState>>, ui_handle: &slint::Weak<AppWindow>, cut: bool) {
+    let mut s = lock(state);
+    let paths: Vec<PathBuf> = s
+        .selected_indices
+        .iter()
+        .filter_map(|&i| s.cached_entries.get(i))
+        .map(|e| e.path.clone())
+        .collect();
+    if paths.is_empty() {
+        return;
+    }
+    s.clipboard = paths;
+    s.clipboard_cut = cut;
+    let count = s.clipboard.len() as i32;
+    drop(s);
+
+    if let Some(ui) = ui_handle.upgrade() {
+        ui.set_local_clipboard_count(count);
+    }
+}
+
+/// 依次执行粘贴，剪切完成后清空剪贴板，最后刷新列表
+fn paste_local(
+    ui: &AppWindow,
+    state: &Arc<Mutex<LocalState>>,
+    pairs: PastePairs,
+    cut: bool,
+) {
+    let errors: Vec<String> = pairs
+        .iter()
+        .filter_map(|(src, dst)| local_fs::paste_path(src, dst, cut).err().map(|e| format!("{:#}", e)))
+        .collect();
+
+    let mut s = lock(state);
+    if cut {
+        s.clipboard.clear();
+        s.clipboard_cut = false;
+    }
+    s.selected_indices.clear();
+    let count = s.clipboard.len() as i32;
+    drop(s);
+
+    ui.set_local_clipboard_count(count);
+    if !errors.is_empty() {
+        ui.set_global_error(format!("粘贴失败: {}", errors.join("; ")).into());
+    }
+    refresh_local(ui, state);
+}
+
+/// 在后台线程计算选中本地目录的总大小，完成后只更新对应行
+fn bind_local_calc_dir_size(ui: &AppWindow, state: Arc<Mutex<LocalState>>) {
+    let ui_handle = ui.as_weak();
+    ui.on_local_calc_dir_size(move || {
+        let targets: Vec<PathBuf> = {
+            let s = lock(&state);
+            s.selected_indices
+                .iter()
+                .filter_map(|&i| s.cached_entries.get(i))
+                .filter(|e| e.is_dir)
+                .map(|e| e.path.clone())
+                .collect()
+        };
+        if targets.is_empty() {
+            return;
+        }
+
+        let state = state.clone();
+        let ui_handle = ui_handle.clone();
+        thread::spawn(move || {
+            for path in targets {
+                let total = local_fs::dir_size(&path);
+                let state = state.clone();
+                let ui_handle = ui_handle.clone();
+                let _ = slint::invoke_from_event_loop(move || {
+                    let mut s = lock(&state);
+                    let idx = match s.cached_entries.iter().position(|e| e.path == path) {
+                        Some(i) => i,
+                        None => return,
+                    };
+                    s.cached_entries[idx].dir_size = Some(total);
+                    let entry = s.cached_entries[idx].clone();
+                    let selected = s.selected_indices.contains(&idx);
+                    drop(s);
+
+                    if let Some(ui) = ui_handle.upgrade() {
+                        let model = ui.get_local_files();
+                        if let Some(mut row) = model.row_data(idx) {
+                            row.size = SharedString::from(format_entry_size(
+                                entry.size,
+                                entry.is_dir,
+                                entry.dir_size,
+                            ));
+                            row.selected = selected;
+                            model.set_row_data(idx, row);
+                        }
+                    }
+                });
+            }
+        });
+    });
 }
 
 fn bind_local_navigate(ui: &AppWindow, state: Arc<Mutex<LocalState>>) {
@@ -116,7 +426,7 @@ fn bind_local_navigate(ui: &AppWindow, state: Arc<Mutex<LocalState>>) {
             PathBuf::from(path_str.as_str())
         };
         if let Some(ui) = ui_handle.upgrade() {
-            let mut s = state.lock().unwrap();
+            let mut s = lock(&state);
             s.current_path = path.clone();
             s.selected_indices.clear();
             drop(s);
@@ -125,11 +435,66 @@ fn bind_local_navigate(ui: &AppWindow, state: Arc<Mutex<LocalState>>) {
     });
 }
 
+fn bind_local_path_complete(ui: &AppWindow, state: Arc<Mutex<LocalState>>) {
+    let ui_handle = ui.as_weak();
+    let debouncer = Debouncer::new();
+    ui.on_local_path_complete(move |partial| {
+        let show_hidden = lock(&state).show_hidden;
+        super::request_path_completions(
+            &debouncer,
+            &partial,
+            &['/', '\\'],
+            move |parent| {
+                local_fs::list_dir(Path::new(parent), show_hidden)
+                    .unwrap_or_default()
+                    .into_iter()
+                    .filter(|e| e.is_dir)
+                    .map(|e| e.name)
+                    .collect()
+            },
+            &ui_handle,
+            |ui, completions| ui.set_local_path_completions(completions),
+        );
+    });
+}
+
+/// 方向键移动焦点行并只选中该行，回车按双击打开
+fn bind_local_key(ui: &AppWindow, state: Arc<Mutex<LocalState>>) {
+    let ui_handle = ui.as_weak();
+    ui.on_local_key(move |key| {
+        let ui = match ui_handle.upgrade() {
+            Some(ui) => ui,
+            None => return,
+        };
+        let mut s = lock(&state);
+        let current = super::focus_start(s.focused_index, &s.selected_indices);
+        if key == "enter" {
+            drop(s);
+            if let Some(index) = current {
+                ui.invoke_local_file_double_clicked(index as i32);
+            }
+            return;
+        }
+        let next = match explorer_service::move_focus(current, s.cached_entries.len(), &key) {
+            Some(next) => next,
+            None => return,
+        };
+        s.focused_index = Some(next);
+        s.last_clicked_index = Some(next);
+        s.selected_indices = HashSet::from([next]);
+        drop(s);
+
+        super::select_only_row(&ui.get_local_files(), next);
+        ui.set_local_selected_count(1);
+        ui.set_local_focused_row(next as i32);
+    });
+}
+
 fn bind_local_go_up(ui: &AppWindow, state: Arc<Mutex<LocalState>>) {
     let ui_handle = ui.as_weak();
     ui.on_local_go_up(move || {
         if let Some(ui) = ui_handle.upgrade() {
-            let mut s = state.lock().unwrap();
+            let mut s = lock(&state);
             if let Some(parent) = s.current_path.parent() {
                 let parent = parent.to_path_buf();
                 s.current_path = parent.clone();
@@ -153,7 +518,7 @@ fn bind_local_file_clicked(ui: &AppWindow, state: Arc<Mutex<LocalState>>) {
     let ui_handle = ui.as_weak();
     ui.on_local_file_clicked(move |index| {
         if let Some(ui) = ui_handle.upgrade() {
-            let mut s = state.lock().unwrap();
+            let mut s = lock(&state);
             let idx = index as usize;
 
             let is_selected = if s.selected_indices.contains(&idx) {
@@ -161,20 +526,18 @@ fn bind_local_file_clicked(ui: &AppWindow, state: Arc<Mutex<LocalState>>) {
                 false
             } else {
                 s.selected_indices.insert(idx);
+                s.focused_index = Some(idx);
                 true
             };
 
-            if let Some(entry) = s.cached_entries.get(idx).cloned() {
+            if idx < s.cached_entries.len() {
                 let sel_count = s.selected_indices.len() as i32;
                 drop(s);
-                let file_entry = FileEntry {
-                    name: SharedString::from(&entry.name),
-                    is_dir: entry.is_dir,
-                    size: SharedString::from(format_size(entry.size, entry.is_dir)),
-                    modified: SharedString::from(&entry.modified),
-                    selected: is_selected,
-                };
-                ui.get_local_files().set_row_data(idx, file_entry);
+                let model = ui.get_local_files();
+                if let Some(mut row) = model.row_data(idx) {
+                    row.selected = is_selected;
+                    model.set_row_data(idx, row);
+                }
                 ui.set_local_selected_count(sel_count);
             }
         }
@@ -185,22 +548,80 @@ fn bind_local_double_click(ui: &AppWindow, state: Arc<Mutex<LocalState>>) {
     let ui_handle = ui.as_weak();
     ui.on_local_file_double_clicked(move |index| {
         if let Some(ui) = ui_handle.upgrade() {
-            let s = state.lock().unwrap();
+            let s = lock(&state);
             if let Some(entry) = s.cached_entries.get(index as usize) {
                 if entry.is_dir {
                     let new_path = entry.path.clone();
                     drop(s);
-                    let mut s = state.lock().unwrap();
+                    let mut s = lock(&state);
                     s.current_path = new_path;
                     s.selected_indices.clear();
                     drop(s);
                     refresh_local(&ui, &state);
+                } else if let Err(e) = local_fs::open_with_default(&entry.path) {
+                    ui.set_global_error(format!("{:#}", e).into());
                 }
             }
         }
     });
 }
 
+/// 右键菜单：用默认程序打开，或在系统文件管理器中显示
+fn bind_local_open(ui: &AppWindow, state: Arc<Mutex<LocalState>>) {
+    let ui_handle = ui.as_weak();
+    let st = state.clone();
+    ui.on_local_open(move |index| {
+        open_entry(&ui_handle, &st, index, local_fs::open_with_default);
+    });
+
+    let ui_handle = ui.as_weak();
+    ui.on_local_reveal(move |index| {
+        open_entry(&ui_handle, &state, index, local_fs::reveal_in_file_manager);
+    });
+}
+
+/// 右键菜单"属性"
+fn bind_local_properties(ui: &AppWindow, state: Arc<Mutex<LocalState>>) {
+    let ui_handle = ui.as_weak();
+    ui.on_local_show_properties(move |index| {
+        let (path, name) = match lock(&state).cached_entries.get(index as usize) {
+            Some(e) => (e.path.clone(), e.name.clone()),
+            None => return,
+        };
+        let ui = match ui_handle.upgrade() {
+            Some(ui) => ui,
+            None => return,
+        };
+        match explorer_service::local_properties(&path) {
+            Ok(rows) => {
+                ui.set_properties_title(format!("{} 的属性", name).into());
+                ui.set_properties_rows(mapper::to_property_rows(&rows));
+                ui.set_properties_editable(false);
+                ui.set_properties_error(SharedString::new());
+                ui.set_show_properties(true);
+            }
+            Err(e) => ui.set_global_error(format!("{:#}", e).into()),
+        }
+    });
+}
+
+fn open_entry(
+    ui_handle: &slint::Weak<AppWindow>,
+    state: &Arc<Mutex<LocalState>>,
+    index: i32,
+    action: fn(&Path) -> anyhow::Result<()>,
+) {
+    let path = match lock(state).cached_entries.get(index as usize) {
+        Some(e) => e.path.clone(),
+        None => return,
+    };
+    if let Err(e) = action(&path) {
+        if let Some(ui) = ui_handle.upgrade() {
+            ui.set_global_error(format!("{:#}", e).into());
+        }
+    }
+}
+
 fn bind_local_refresh(ui: &AppWindow, state: Arc<Mutex<LocalState>>) {
     let ui_handle = ui.as_weak();
     ui.on_local_refresh(move || {
@@ -214,7 +635,7 @@ fn bind_local_select_all(ui: &AppWindow, state: Arc<Mutex<LocalState>>) {
     let ui_handle = ui.as_weak();
     ui.on_local_select_all(move || {
         if let Some(ui) = ui_handle.upgrade() {
-            let mut s = state.lock().unwrap();
+            let mut s = lock(&state);
             let total = s.cached_entries.len();
             if s.selected_indices.len() == total {
                 s.selected_indices.clear();
@@ -231,7 +652,7 @@ fn bind_local_mkdir(ui: &AppWindow, state: Arc<Mutex<LocalState>>) {
     let ui_handle = ui.as_weak();
     ui.on_local_mkdir(move |dir_name| {
         if let Some(ui) = ui_handle.upgrade() {
-            let s = state.lock().unwrap();
+            let s = lock(&state);
             if s.current_path.as_os_str().is_empty() {
                 return;
             }
@@ -241,7 +662,7 @@ fn bind_local_mkdir(ui: &AppWindow, state: Arc<Mutex<LocalState>>) {
                 eprintln!("创建目录失败: {}", e);
                 return;
             }
-            let mut s = state.lock().unwrap();
+            let mut s = lock(&state);
             s.selected_indices.clear();
             drop(s);
             refresh_local(&ui, &state);
@@ -249,20 +670,59 @@ fn bind_local_mkdir(ui: &AppWindow, state: Arc<Mutex<LocalState>>) {
     });
 }
 
-fn bind_local_delete_selected(ui: &AppWindow, state: Arc<Mutex<LocalState>>) {
+/// 新建空文件，同名条目已存在时自动编号
+fn bind_local_touch(ui: &AppWindow, state: Arc<Mutex<LocalState>>) {
+    let ui_handle = ui.as_weak();
+    ui.on_local_touch(move |file_name| {
+        if let Some(ui) = ui_handle.upgrade() {
+            let s = lock(&state);
+            if s.current_path.as_os_str().is_empty() {
+                return;
+            }
+            let mut new_file = s.current_path.join(file_name.as_str());
+            drop(s);
+            if new_file.exists() {
+                new_file = path_utils::keep_both_path(&new_file);
+            }
+            if let Err(e) = local_fs::create_empty_file(&new_file) {
+                ui.set_global_error(SharedString::from(e.to_string()));
+                return;
+            }
+            lock(&state).selected_indices.clear();
+            refresh_local(&ui, &state);
+        }
+    });
+}
+
+/// 删除前在确认框中列出全部待删除路径
+fn bind_local_delete_selected(
+    ui: &AppWindow,
+    config: Arc<Mutex<AppConfig>>,
+    state: Arc<Mutex<LocalState>>,
+) {
     let ui_handle = ui.as_weak();
     ui.on_local_delete_selected(move || {
         if let Some(ui) = ui_handle.upgrade() {
-            let s = state.lock().unwrap();
-            let count = s.selected_indices.len();
+            let s = lock(&state);
+            let mut paths: Vec<SharedString> = s
+                .selected_indices
+                .iter()
+                .filter_map(|&i| s.cached_entries.get(i))
+                .map(|e| SharedString::from(e.path.to_string_lossy().as_ref()))
+                .collect();
             drop(s);
-            if count == 0 {
+            if paths.is_empty() {
                 return;
             }
+            paths.sort();
+            let to_trash = lock(&config).delete_to_trash;
             ui.set_confirm_title(SharedString::from("确认删除"));
-            ui.set_confirm_message(SharedString::from(
-                format!("确定要删除选中的 {} 个项目吗？此操作不可撤销。", count),
-            ));
+            ui.set_confirm_message(SharedString::from(explorer_service::delete_confirm_message(
+                paths.len(),
+                false,
+                to_trash,
+            )));
+            ui.set_confirm_details(ModelRc::new(VecModel::from(paths)));
             ui.set_confirm_action(SharedString::from("local-delete"));
             ui.set_show_confirm(true);
         }
@@ -273,30 +733,116 @@ fn bind_local_rename(ui: &AppWindow, state: Arc<Mutex<LocalState>>) {
     let ui_handle = ui.as_weak();
     ui.on_local_rename(move |index, new_name| {
         if let Some(ui) = ui_handle.upgrade() {
-            let s = state.lock().unwrap();
+            let s = lock(&state);
             let entry = match s.cached_entries.get(index as usize) {
                 Some(e) => e,
                 None => return,
             };
+            if new_name.is_empty() || entry.name == new_name.as_str() {
+                return;
+            }
             let old_path = entry.path.clone();
-            let new_path = old_path.parent().map(|p| p.join(new_name.as_str()));
+            let new_path = match old_path.parent() {
+                Some(p) => p.join(new_name.as_str()),
+                None => return,
+            };
             drop(s);
-            if let Some(new_path) = new_path {
-                if let Err(e) = std::fs::rename(&old_path, &new_path) {
-                    eprintln!("重命名失败: {}", e);
-                    return;
-                }
+            if target_is_other_entry(&old_path, &new_path) {
+                lock(&state).pending_rename = Some((old_path, new_path));
+                ui.set_confirm_title(SharedString::from("确认覆盖"));
+                ui.set_confirm_message(SharedString::from(format!(
+                    "「{}」已存在，重命名会用当前条目替换它 (目录无法替换)。确定继续吗？",
+                    new_name
+                )));
+                ui.set_confirm_action(SharedString::from("local-rename-overwrite"));
+                ui.set_show_confirm(true);
+                return;
             }
-            refresh_local(&ui, &state);
+            rename_local(&ui, &state, &old_path, &new_path);
         }
     });
 }
 
+/// 新路径已被另一个条目占用；大小写不敏感的文件系统上只改大小写时指向的是同一个文件
+fn target_is_other_entry(old_path: &Path, new_path: &Path) -> bool {
+    if std::fs::symlink_metadata(new_path).is_err() {
+        return false;
+    }
+    match (std::fs::canonicalize(old_path), std::fs::canonicalize(new_path)) {
+        (Ok(a), Ok(b)) => a != b,
+        _ => true,
+    }
+}
+
+/// 执行本地重命名并刷新列表
+pub(crate) fn rename_local(
+    ui: &AppWindow,
+    state: &Arc<Mutex<LocalState>>,
+    old_path: &Path,
+    new_path: &Path,
+) {
+    if let Err(e) = std::fs::rename(old_path, new_path) {
+        ui.set_global_error(format!("重命名失败: {}", e).into());
+        return;
+    }
+    refresh_local(ui, state);
+}
+
+/// 按模板批量重命名选中的本地条目，读目录和重命名在后台线程执行
+fn bind_local_bulk_rename(ui: &AppWindow, state: Arc<Mutex<LocalState>>) {
+    let ui_handle = ui.as_weak();
+    ui.on_local_bulk_rename(move |template| {
+        let (dir, selected) = {
+            let s = lock(&state);
+            let mut indices: Vec<usize> = s.selected_indices.iter().copied().collect();
+            indices.sort_unstable();
+            let names: Vec<String> = indices
+                .iter()
+                .filter_map(|&i| s.cached_entries.get(i))
+                .map(|e| e.name.clone())
+                .collect();
+            (s.current_path.clone(), names)
+        };
+
+        let ui_h = ui_handle.clone();
+        let st = state.clone();
+        let template = template.to_string();
+        thread::spawn(move || {
+            let error = match std::fs::read_dir(&dir) {
+                Ok(rd) => {
+                    let existing: Vec<String> = rd
+                        .filter_map(|e| e.ok())
+                        .map(|e| e.file_name().to_string_lossy().to_string())
+                        .collect();
+                    match explorer_service::plan_bulk_rename(&selected, &existing, &template) {
+                        Ok(plan) => plan.into_iter().find_map(|(old, new)| {
+                            std::fs::rename(dir.join(&old), dir.join(&new))
+                                .err()
+                                .map(|e| format!("重命名 {} 失败: {}", old, e))
+                        }),
+                        Err(e) => Some(format!("批量重命名失败: {}", e)),
+                    }
+                }
+                Err(e) => Some(format!("读取目录失败: {}", e)),
+            };
+
+            let _ = slint::invoke_from_event_loop(move || {
+                if let Some(ui) = ui_h.upgrade() {
+                    if let Some(error) = error {
+                        ui.set_global_error(error.into());
+                    }
+                    refresh_local(&ui, &st);
+                }
+            });
+        });
+    });
+}
+
 fn bind_local_sort_changed(ui: &AppWindow, state: Arc<Mutex<LocalState>>) {
     let ui_handle = ui.as_weak();
     ui.on_local_sort_changed(move |field| {
         if let Some(ui) = ui_handle.upgrade() {
-            let mut s = state.lock().unwrap();
+            let mut s = lock(&state);
             if s.sort_field == field.as_str() {
                 s.sort_ascending = !s.sort_ascending;
             } else {
@@ -313,7 +859,7 @@ fn bind_local_file_clicked_ex(ui: &AppWindow, state: Arc<Mutex<LocalState>>) {
     let ui_handle = ui.as_weak();
     ui.on_local_file_clicked_ex(move |index, ctrl, shift| {
         if let Some(ui) = ui_handle.upgrade() {
-            let mut s = state.lock().unwrap();
+            let mut s = lock(&state);
             let idx = index as usize;
             let total = s.cached_entries.len();
             if idx >= total {
@@ -358,19 +904,14 @@ fn bind_local_file_clicked_ex(ui: &AppWindow, state: Arc<Mutex<LocalState>>) {
 
             let selected = s.selected_indices.clone();
             let entries = s.cached_entries.clone();
+            let name_filter = s.name_filter();
             let sel_count = selected.len() as i32;
             drop(s);
 
             let ui_entries: Vec<FileEntry> = entries
                 .iter()
                 .enumerate()
-                .map(|(i, e)| FileEntry {
-                    name: SharedString::from(&e.name),
-                    is_dir: e.is_dir,
-                    size: SharedString::from(format_size(e.size, e.is_dir)),
-                    modified: SharedString::from(&e.modified),
-                    selected: selected.contains(&i),
-                })
+                .map(|(i, e)| to_file_entry(e, selected.contains(&i), name_filter.as_ref()))
                 .collect();
             ui.set_local_files(ModelRc::new(VecModel::from(ui_entries)));
             ui.set_local_selected_count(sel_count);
@@ -382,7 +923,7 @@ fn bind_local_filter_changed(ui: &AppWindow, state: Arc<Mutex<LocalState>>) {
     let ui_handle = ui.as_weak();
     ui.on_local_filter_changed(move |text| {
         if let Some(ui) = ui_handle.upgrade() {
-            let mut s = state.lock().unwrap();
+            let mut s = lock(&state);
             s.filter_text = text.to_string();
             s.selected_indices.clear();
             drop(s);
@@ -391,3 +932,41 @@ fn bind_local_filter_changed(ui: &AppWindow, state: Arc<Mutex<LocalState>>) {
     });
 }
 
+fn bind_local_quick_filter_changed(ui: &AppWindow, state: Arc<Mutex<LocalState>>) {
+    let ui_handle = ui.as_weak();
+    ui.on_local_quick_filter_changed(move |key| {
+        if let Some(ui) = ui_handle.upgrade() {
+            let mut s = lock(&state);
+            s.quick_filter = QuickFilter::from_key(key.as_str());
+            s.selected_indices.clear();
+            drop(s);
+            refresh_local(&ui, &state);
+        }
+    });
+}
+
+fn bind_local_show_hidden_changed(ui: &AppWindow, state: Arc<Mutex<LocalState>>) {
+    let ui_handle = ui.as_weak();
+    ui.on_local_show_hidden_changed(move |show| {
+        if let Some(ui) = ui_handle.upgrade() {
+            let mut s = lock(&state);
+            s.show_hidden = show;
+            s.selected_indices.clear();
+            drop(s);
+            refresh_local(&ui, &state);
+        }
+    });
+}
+
+fn bind_local_filter_mode_changed(ui: &AppWindow, state: Arc<Mutex<LocalState>>) {
+    let ui_handle = ui.as_weak();
+    ui.on_local_filter_mode_changed(move |mode| {
+        if let Some(ui) = ui_handle.upgrade() {
+            let mut s = lock(&state);
+            s.filter_mode = FilterMode::from_key(mode.as_str());
+            s.selected_indices.clear();
+            drop(s);
+            refresh_local(&ui, &state);
+        }
+    });
+}
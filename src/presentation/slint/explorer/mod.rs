@@ -1,15 +1,32 @@
-use slint::{ComponentHandle, ModelRc, SharedString, VecModel};
-use std::collections::HashSet;
+use slint::{ComponentHandle, Model, ModelRc, SharedString, Timer, VecModel};
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
+use std::rc::Rc;
 use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
 
-use crate::app::services::explorer_service;
-use crate::domain::config::{AppConfig, Bookmark};
-use crate::domain::ports::ConfigRepository;
+use crate::app::services::explorer_service::{self, BrowsePosition};
+use crate::domain::config::{AppConfig, Bookmark, UiPrefs};
+use crate::domain::file_entry;
+use crate::domain::ports::{ConfigRepository, HistoryRepository};
 use crate::domain::transfer::TransferQueue;
+use crate::infra::local_fs;
+use crate::infra::remote_fs;
+use crate::infra::ssh::ConnectionPool;
+use crate::presentation::slint::mapper;
+use crate::shared::panic_guard::lock;
+use crate::shared::name_filter::{FilterMode, NameFilter};
+use crate::shared::quick_filter::QuickFilter;
+use crate::shared::remote_path;
+use crate::shared::throttle::Debouncer;
 use crate::AppWindow;
+use crate::FileEntry;
 use crate::BookmarkEntry;
 
 pub mod bookmarks_bindings;
+pub mod compare_bindings;
 pub mod local_bindings;
 pub mod remote_bindings;
 pub mod transfer_bindings;
@@ -35,19 +52,156 @@ pub(crate) fn format_size(bytes: u64, is_dir: bool) -> String {
     format!("{:.1} TB", size)
 }
 
+/// 条目类别，目录为 "dir"，文件按扩展名划分
+pub(crate) fn entry_kind(name: &str, is_dir: bool) -> SharedString {
+    if is_dir {
+        return SharedString::from("dir");
+    }
+    SharedString::from(file_entry::file_kind(name).as_str())
+}
+
+/// 格式化条目大小，目录已计算过总大小时显示该值
+pub(crate) fn format_entry_size(size: u64, is_dir: bool, dir_size: Option<u64>) -> String {
+    match dir_size {
+        Some(total) => format_size(total, false),
+        None => format_size(size, is_dir),
+    }
+}
+
+/// 把名称拆成 (前段, 匹配段, 后段) 供列表加粗匹配部分；
+/// 只在最后一段路径 (深度搜索的文件名) 中查找，无过滤或未找到时匹配段为空
+pub(crate) fn name_highlight(
+    name: &str,
+    filter: Option<&NameFilter>,
+) -> (SharedString, SharedString, SharedString) {
+    let base_start = name.rfind('/').map(|i| i + 1).unwrap_or(0);
+    match filter.and_then(|f| f.find(&name[base_start..])) {
+        Some(range) => {
+            let (start, end) = (base_start + range.start, base_start + range.end);
+            (
+                SharedString::from(&name[..start]),
+                SharedString::from(&name[start..end]),
+                SharedString::from(&name[end..]),
+            )
+        }
+        None => (SharedString::new(), SharedString::new(), SharedString::new()),
+    }
+}
+
+/// 键盘导航的起点：仍被选中的焦点行，否则为最靠前的选中行
+pub(crate) fn focus_start(focused: Option<usize>, selected: &HashSet<usize>) -> Option<usize> {
+    focused
+        .filter(|i| selected.contains(i))
+        .or_else(|| selected.iter().min().copied())
+}
+
+/// 只选中 `index` 一行，同步界面模型中各行的选中标记
+pub(crate) fn select_only_row(model: &ModelRc<FileEntry>, index: usize) {
+    for i in 0..model.row_count() {
+        if let Some(mut row) = model.row_data(i) {
+            if row.selected != (i == index) {
+                row.selected = i == index;
+                model.set_row_data(i, row);
+            }
+        }
+    }
+}
+
+/// 最近访问目录列表转为界面模型
+pub(crate) fn recent_dirs_model(dirs: &[String]) -> ModelRc<SharedString> {
+    ModelRc::new(VecModel::from(
+        dirs.iter().map(SharedString::from).collect::<Vec<_>>(),
+    ))
+}
+
+/// 路径补全的防抖等待时间
+const PATH_COMPLETE_DEBOUNCE: Duration = Duration::from_millis(150);
+
+/// 防抖后在后台用 `list_dirs` 列出父目录的子目录名并生成补全候选，结果由 `apply` 写回界面；
+/// 输入不含分隔符时立即清空候选
+pub(crate) fn request_path_completions(
+    debouncer: &Debouncer,
+    partial: &str,
+    separators: &[char],
+    list_dirs: impl FnOnce(&str) -> Vec<String> + Send + 'static,
+    ui_handle: &slint::Weak<AppWindow>,
+    apply: fn(&AppWindow, ModelRc<SharedString>),
+) {
+    let ticket = debouncer.next_ticket();
+    let (parent, prefix) = match explorer_service::split_partial_path(partial, separators) {
+        Some((parent, prefix)) => (parent.to_string(), prefix.to_string()),
+        None => {
+            if let Some(ui) = ui_handle.upgrade() {
+                apply(&ui, ModelRc::default());
+            }
+            return;
+        }
+    };
+    let debouncer = debouncer.clone();
+    let ui_h = ui_handle.clone();
+    thread::spawn(move || {
+        thread::sleep(PATH_COMPLETE_DEBOUNCE);
+        if !debouncer.is_latest(ticket) {
+            return;
+        }
+        let completions = explorer_service::path_completions(&parent, &prefix, &list_dirs(&parent));
+        let _ = slint::invoke_from_event_loop(move || {
+            if !debouncer.is_latest(ticket) {
+                return;
+            }
+            if let Some(ui) = ui_h.upgrade() {
+                let items: Vec<SharedString> = completions.iter().map(SharedString::from).collect();
+                apply(&ui, ModelRc::new(VecModel::from(items)));
+            }
+        });
+    });
+}
+
+/// 绑定浏览器全部回调；返回传输列表的同步定时器，调用方需持有到窗口关闭
 pub fn bind(
     ui: &AppWindow,
     config: Arc<Mutex<AppConfig>>,
     repo: Arc<dyn ConfigRepository + Send + Sync>,
-) {
+    history: Arc<dyn HistoryRepository + Send + Sync>,
+    transfer_queue: Arc<Mutex<TransferQueue>>,
+) -> Timer {
+    let start_dir = config
+        .lock()
+        .unwrap()
+        .last_local_path
+        .clone()
+        .map(std::path::PathBuf::from)
+        .filter(|p| p.is_dir())
+        .unwrap_or_else(local_bindings::default_start_dir);
+    let (prefs, (local_sort, local_ascending), (remote_sort, remote_ascending)) = {
+        let c = lock(&config);
+        (c.ui_prefs.clone(), c.startup_local_sort(), c.startup_remote_sort())
+    };
+    ui.set_local_sort_field(SharedString::from(&local_sort));
+    ui.set_local_sort_ascending(local_ascending);
+    ui.set_remote_sort_field(SharedString::from(&remote_sort));
+    ui.set_remote_sort_ascending(remote_ascending);
+    ui.set_split_ratio(prefs.split_ratio.clamp(0.2, 0.8));
+    ui.set_file_columns(mapper::to_file_columns(&prefs.columns));
+    ui.set_local_show_hidden(prefs.local_show_hidden);
+    ui.set_remote_show_hidden(prefs.remote_show_hidden);
+
     let local_state = Arc::new(Mutex::new(LocalState {
-        current_path: local_bindings::default_start_dir(),
+        current_path: start_dir,
         selected_indices: HashSet::new(),
         cached_entries: Vec::new(),
-        sort_field: "name".to_string(),
-        sort_ascending: true,
+        sort_field: local_sort,
+        sort_ascending: local_ascending,
         filter_text: String::new(),
+        filter_mode: FilterMode::Substring,
+        quick_filter: QuickFilter::None,
+        show_hidden: prefs.local_show_hidden,
         last_clicked_index: None,
+        focused_index: None,
+        pending_rename: None,
+        clipboard: Vec::new(),
+        clipboard_cut: false,
+        recent_dirs: lock(&config).recent_local_dirs.clone(),
     }));
 
     let remote_state = Arc::new(Mutex::new(RemoteState {
@@ -55,30 +209,79 @@ pub fn bind(
         uploader: None,
         selected_indices: HashSet::new(),
         cached_entries: Vec::new(),
-        sort_field: "name".to_string(),
-        sort_ascending: true,
+        sort_field: remote_sort,
+        sort_ascending: remote_ascending,
         filter_text: String::new(),
+        filter_mode: FilterMode::Substring,
+        quick_filter: QuickFilter::None,
+        show_hidden: prefs.remote_show_hidden,
+        deep_search: false,
         last_clicked_index: None,
+        focused_index: None,
+        clipboard: Vec::new(),
+        clipboard_cut: false,
+        pending_paste: None,
+        visible_count: remote_bindings::REMOTE_PAGE_SIZE,
+        pending_rename: None,
+        pending_sudo_delete: Vec::new(),
+        properties_path: None,
+        recent_dirs: Vec::new(),
+        file_encodings: HashMap::new(),
     }));
 
-    let transfer_queue = Arc::new(Mutex::new(TransferQueue::new()));
+    let pool = Arc::new(ConnectionPool::new());
 
     // 本地回调
-    local_bindings::bind(ui, local_state.clone());
+    local_bindings::bind(ui, config.clone(), local_state.clone());
 
     // 远程回调
-    remote_bindings::bind(ui, config.clone(), remote_state.clone());
+    remote_bindings::bind(
+        ui,
+        config.clone(),
+        repo.clone(),
+        remote_state.clone(),
+        pool.clone(),
+    );
+
+    // 连接保活与延迟显示
+    remote_bindings::start_keepalive(
+        ui,
+        config.clone(),
+        remote_state.clone(),
+        transfer_queue.clone(),
+    );
 
     // 传输队列回调
-    transfer_bindings::bind(
+    let transfer_sync = transfer_bindings::bind(
         ui,
+        config.clone(),
         local_state.clone(),
         remote_state.clone(),
         transfer_queue,
+        history,
+        pool,
     );
 
+    // 目录比较
+    compare_bindings::bind(ui, local_state.clone(), remote_state.clone());
+
     // 确认对话框回调
-    bind_confirm_accepted(ui, local_state.clone(), remote_state.clone());
+    bind_confirm_accepted(ui, config.clone(), local_state.clone(), remote_state.clone());
+
+    // 复制路径到剪贴板
+    bind_copy_paths(ui, local_state.clone(), remote_state.clone());
+
+
+    bind_sync_browse(ui, local_state.clone(), remote_state.clone());
+
+    // 关闭窗口时记住浏览位置和界面偏好
+    bind_remember_paths_on_close(
+        ui,
+        config.clone(),
+        repo.clone(),
+        local_state.clone(),
+        remote_state.clone(),
+    );
 
     // 书签回调
     bind_bookmarks(
@@ -89,10 +292,233 @@ pub fn bind(
         remote_state,
     );
     refresh_bookmarks(ui, &config);
+    transfer_sync
+}
+
+fn bind_remember_paths_on_close(
+    ui: &AppWindow,
+    config: Arc<Mutex<AppConfig>>,
+    repo: Arc<dyn ConfigRepository + Send + Sync>,
+    local_state: Arc<Mutex<LocalState>>,
+    remote_state: Arc<Mutex<RemoteState>>,
+) {
+    let ui_handle = ui.as_weak();
+    ui.window().on_close_requested(move || {
+        // 界面偏好只更新内存，随后由 remember_paths 一并写回
+        if let Some(ui) = ui_handle.upgrade() {
+            let prefs = collect_ui_prefs(&ui, &local_state, &remote_state);
+            if let Ok(mut guard) = config.lock() {
+                guard.ui_prefs = prefs;
+            }
+        }
+        let local = local_state.lock().ok().map(|s| BrowsePosition {
+            path: s.current_path.to_string_lossy().to_string(),
+            recent: s.recent_dirs.clone(),
+        });
+        let remote = remote_state.lock().ok().and_then(|s| {
+            s.uploader.as_ref().map(|u| {
+                let position = BrowsePosition {
+                    path: s.current_path.clone(),
+                    recent: s.recent_dirs.clone(),
+                };
+                (u.config().clone(), position)
+            })
+        });
+        if let Err(e) = explorer_service::remember_paths(
+            &config,
+            &repo,
+            local,
+            remote.as_ref().map(|(cfg, position)| (cfg, position.clone())),
+        ) {
+            eprintln!("保存浏览位置失败: {}", e);
+        }
+        slint::CloseRequestResponse::HideWindow
+    });
+}
+
+/// 同步浏览的起点：开启时两侧所在的目录
+struct SyncBases {
+    local: PathBuf,
+    remote: String,
+}
+
+/// 同步浏览：一侧目录变化后，按相对起点的位移让另一侧跟随；对应目录不存在时只在状态栏提示
+fn bind_sync_browse(
+    ui: &AppWindow,
+    local_state: Arc<Mutex<LocalState>>,
+    remote_state: Arc<Mutex<RemoteState>>,
+) {
+    let bases: Rc<RefCell<Option<SyncBases>>> = Rc::new(RefCell::new(None));
+
+    let b = bases.clone();
+    let ls = local_state.clone();
+    let rs = remote_state.clone();
+    ui.on_sync_browse_changed(move |enabled| {
+        *b.borrow_mut() = enabled.then(|| SyncBases {
+            local: lock(&ls).current_path.clone(),
+            remote: lock(&rs).current_path.clone(),
+        });
+    });
+
+    let ui_handle = ui.as_weak();
+    let b = bases.clone();
+    let ls = local_state.clone();
+    let rs = remote_state.clone();
+    ui.on_local_path_changed(move || {
+        let ui = match ui_handle.upgrade() {
+            Some(ui) => ui,
+            None => return,
+        };
+        let bases = b.borrow();
+        let bases = match bases.as_ref() {
+            Some(bases) => bases,
+            None => return,
+        };
+        let local = lock(&ls).current_path.clone();
+        let target = match explorer_service::sync_remote_target(&bases.local, &local, &bases.remote) {
+            Some(target) => target,
+            None => {
+                ui.set_remote_status("同步浏览: 远程没有对应的位置".into());
+                return;
+            }
+        };
+        let mut s = lock(&rs);
+        if s.current_path == target {
+            return;
+        }
+        let exists = match &s.uploader {
+            Some(uploader) => remote_fs::stat(uploader, &target)
+                .map(|st| st.is_dir || st.link_target.is_some())
+                .unwrap_or(false),
+            None => return,
+        };
+        if !exists {
+            drop(s);
+            ui.set_remote_status(format!("同步浏览: 远程目录不存在 {}", target).into());
+            return;
+        }
+        s.selected_indices.clear();
+        drop(s);
+        remote_bindings::refresh_remote_dir(&rs, &ui_handle, &target);
+    });
+
+    let ui_handle = ui.as_weak();
+    ui.on_remote_path_changed(move || {
+        let ui = match ui_handle.upgrade() {
+            Some(ui) => ui,
+            None => return,
+        };
+        let bases = bases.borrow();
+        let bases = match bases.as_ref() {
+            Some(bases) => bases,
+            None => return,
+        };
+        let remote = lock(&remote_state).current_path.clone();
+        let target = match explorer_service::sync_local_target(&bases.remote, &remote, &bases.local) {
+            Some(target) => target,
+            None => {
+                ui.set_remote_status("同步浏览: 本地没有对应的位置".into());
+                return;
+            }
+        };
+        let mut s = lock(&local_state);
+        if s.current_path == target {
+            return;
+        }
+        if !target.is_dir() {
+            drop(s);
+            ui.set_remote_status(format!("同步浏览: 本地目录不存在 {}", target.display()).into());
+            return;
+        }
+        s.current_path = target;
+        s.selected_indices.clear();
+        drop(s);
+        local_bindings::refresh_local(&ui, &local_state);
+    });
+}
+
+fn collect_ui_prefs(
+    ui: &AppWindow,
+    local_state: &Arc<Mutex<LocalState>>,
+    remote_state: &Arc<Mutex<RemoteState>>,
+) -> UiPrefs {
+    let mut prefs = UiPrefs::default();
+    if let Ok(s) = local_state.lock() {
+        prefs.local_sort_field = s.sort_field.clone();
+        prefs.local_sort_ascending = s.sort_ascending;
+        prefs.local_show_hidden = s.show_hidden;
+    }
+    if let Ok(s) = remote_state.lock() {
+        prefs.remote_sort_field = s.sort_field.clone();
+        prefs.remote_sort_ascending = s.sort_ascending;
+        prefs.remote_show_hidden = s.show_hidden;
+    }
+    let window = ui.window();
+    let size = window.size().to_logical(window.scale_factor());
+    prefs.window_width = size.width.round() as u32;
+    prefs.window_height = size.height.round() as u32;
+    prefs.split_ratio = ui.get_split_ratio();
+    prefs.columns = mapper::from_file_columns(&ui.get_file_columns());
+    prefs
+}
+
+/// 右键菜单复制完整路径，scp 形式需要已连接服务器
+fn bind_copy_paths(
+    ui: &AppWindow,
+    local_state: Arc<Mutex<LocalState>>,
+    remote_state: Arc<Mutex<RemoteState>>,
+) {
+    let ui_handle = ui.as_weak();
+    let rs = remote_state.clone();
+    ui.on_copy_remote_path(move |index, scp| {
+        let s = lock(&rs);
+        let entry = match s.cached_entries.get(index as usize) {
+            Some(e) => e,
+            None => return,
+        };
+        let full_path = remote_path::join(&s.current_path, &entry.name);
+        let text = match (&s.uploader, scp) {
+            (Some(u), true) => explorer_service::scp_download_command(u.config(), &full_path),
+            _ => full_path,
+        };
+        drop(s);
+        if let Some(ui) = ui_handle.upgrade() {
+            ui.invoke_copy_to_clipboard(SharedString::from(text));
+        }
+    });
+
+    let ui_handle = ui.as_weak();
+    ui.on_copy_local_path(move |index, scp| {
+        let path = {
+            let s = lock(&local_state);
+            match s.cached_entries.get(index as usize) {
+                Some(e) => e.path.to_string_lossy().to_string(),
+                None => return,
+            }
+        };
+        let ui = match ui_handle.upgrade() {
+            Some(ui) => ui,
+            None => return,
+        };
+        let text = if scp {
+            let s = lock(&remote_state);
+            match &s.uploader {
+                Some(u) => explorer_service::scp_upload_command(u.config(), &path, &s.current_path),
+                None => {
+                    ui.set_global_error("请先连接远程服务器，再复制 scp 路径".into());
+                    return;
+                }
+            }
+        } else {
+            path
+        };
+        ui.invoke_copy_to_clipboard(SharedString::from(text));
+    });
 }
 
 fn bind_confirm_accepted(
     ui: &AppWindow,
+    config: Arc<Mutex<AppConfig>>,
     local_state: Arc<Mutex<LocalState>>,
     remote_state: Arc<Mutex<RemoteState>>,
 ) {
@@ -102,44 +528,74 @@ fn bind_confirm_accepted(
             let action = ui.get_confirm_action().to_string();
             match action.as_str() {
                 "local-delete" => {
-                    do_local_delete(&ui, &local_state);
+                    let to_trash = lock(&config).delete_to_trash;
+                    do_local_delete(&ui, &local_state, to_trash);
                 }
                 "remote-delete" => {
                     do_remote_delete(&ui, &remote_state);
                 }
+                "remote-delete-sudo" => {
+                    do_remote_delete_sudo(&ui, &remote_state);
+                }
+                "upload-selected" => {
+                    ui.invoke_upload_selected_confirmed();
+                }
+                "local-rename-overwrite" => {
+                    let pending = lock(&local_state).pending_rename.take();
+                    if let Some((old_path, new_path)) = pending {
+                        local_bindings::rename_local(&ui, &local_state, &old_path, &new_path);
+                    }
+                }
+                "remote-rename-overwrite" => {
+                    let pending = lock(&remote_state).pending_rename.take();
+                    if let Some((old_path, new_path)) = pending {
+                        remote_bindings::rename_remote(&ui, &remote_state, &old_path, &new_path);
+                    }
+                }
+                "remote-reconnect" => {
+                    ui.invoke_remote_connect(remote_bindings::selected_server_index(&ui));
+                }
+                "resume-transfers" => {
+                    ui.invoke_resume_transfers(true);
+                }
                 _ => {}
             }
         }
     });
 }
 
-fn do_local_delete(ui: &AppWindow, state: &Arc<Mutex<LocalState>>) {
-    let s = state.lock().unwrap();
+/// 删除选中的本地条目，`to_trash` 时移到系统回收站以便恢复
+fn do_local_delete(ui: &AppWindow, state: &Arc<Mutex<LocalState>>, to_trash: bool) {
+    let s = lock(state);
     let to_delete: Vec<_> = s
         .selected_indices
         .iter()
         .filter_map(|&i| s.cached_entries.get(i))
-        .map(|e| (e.path.clone(), e.is_dir))
+        .map(|e| e.path.clone())
         .collect();
     drop(s);
-    for (path, is_dir) in &to_delete {
-        let result = if *is_dir {
-            std::fs::remove_dir_all(path)
+    let mut failed = Vec::new();
+    for path in &to_delete {
+        let result = if to_trash {
+            local_fs::move_to_trash(path)
         } else {
-            std::fs::remove_file(path)
+            local_fs::remove_path(path)
         };
         if let Err(e) = result {
-            eprintln!("删除失败 {:?}: {}", path, e);
+            failed.push((path.to_string_lossy().to_string(), format!("{:#}", e)));
         }
     }
-    let mut s = state.lock().unwrap();
+    if !failed.is_empty() {
+        ui.set_global_error(SharedString::from(failure_text(&failed)));
+    }
+    let mut s = lock(state);
     s.selected_indices.clear();
     drop(s);
     local_bindings::refresh_local(ui, state);
 }
 
 fn do_remote_delete(ui: &AppWindow, state: &Arc<Mutex<RemoteState>>) {
-    let s = state.lock().unwrap();
+    let s = lock(state);
     let uploader = match &s.uploader {
         Some(u) => u,
         None => return,
@@ -150,29 +606,70 @@ fn do_remote_delete(ui: &AppWindow, state: &Arc<Mutex<RemoteState>>) {
         .iter()
         .filter_map(|&i| s.cached_entries.get(i))
         .map(|e| {
-            let full = if current.ends_with('/') {
-                format!("{}{}", current, e.name)
-            } else {
-                format!("{}/{}", current, e.name)
-            };
+            let full = remote_path::join(&current, &e.name);
             (full, e.is_dir)
         })
         .collect();
+    let mut failed = Vec::new();
+    let mut retry = Vec::new();
     for (path, is_dir) in &to_delete {
         if let Err(e) = crate::infra::remote_fs::remote_remove(uploader, path, *is_dir) {
-            eprintln!("删除失败 {}: {}", path, e);
+            failed.push((path.clone(), format!("{:#}", e)));
+            retry.push((path.clone(), *is_dir));
         }
     }
     drop(s);
-    let mut s = state.lock().unwrap();
+    let mut s = lock(state);
     s.selected_indices.clear();
+    s.pending_sudo_delete = retry;
+    drop(s);
+    let ui_weak = ui.as_weak();
+    remote_bindings::refresh_remote_dir(state, &ui_weak, &current);
+    if !failed.is_empty() {
+        // 多为权限不足，询问是否以 sudo 重试
+        let (header, lines) = explorer_service::delete_failure_summary(&failed);
+        ui.set_confirm_title(SharedString::from("删除失败"));
+        ui.set_confirm_message(SharedString::from(format!(
+            "{}，是否以 sudo 重试？(需要远程账户可免密 sudo)",
+            header
+        )));
+        let details: Vec<SharedString> = lines.into_iter().map(SharedString::from).collect();
+        ui.set_confirm_details(ModelRc::new(VecModel::from(details)));
+        ui.set_confirm_action(SharedString::from("remote-delete-sudo"));
+        ui.set_show_confirm(true);
+    }
+}
+
+/// 以 sudo 重试上次删除失败的远程条目，仍失败的显示在错误栏
+fn do_remote_delete_sudo(ui: &AppWindow, state: &Arc<Mutex<RemoteState>>) {
+    let mut s = lock(state);
+    let pending = std::mem::take(&mut s.pending_sudo_delete);
+    let uploader = match &s.uploader {
+        Some(u) => u,
+        None => return,
+    };
+    let current = s.current_path.clone();
+    let mut failed = Vec::new();
+    for (path, is_dir) in &pending {
+        if let Err(e) = crate::infra::remote_fs::remote_remove_sudo(uploader, path, *is_dir) {
+            failed.push((path.clone(), format!("{:#}", e)));
+        }
+    }
     drop(s);
     let ui_weak = ui.as_weak();
     remote_bindings::refresh_remote_dir(state, &ui_weak, &current);
+    if !failed.is_empty() {
+        ui.set_global_error(SharedString::from(failure_text(&failed)));
+    }
+}
+
+fn failure_text(failures: &[(String, String)]) -> String {
+    let (header, lines) = explorer_service::delete_failure_summary(failures);
+    format!("{}:\n{}", header, lines.join("\n"))
 }
 
-fn refresh_bookmarks(ui: &AppWindow, config: &Arc<Mutex<AppConfig>>) {
-    let cfg = config.lock().unwrap();
+pub(crate) fn refresh_bookmarks(ui: &AppWindow, config: &Arc<Mutex<AppConfig>>) {
+    let cfg = lock(config);
     let entries: Vec<BookmarkEntry> = cfg
         .bookmarks
         .iter()
@@ -198,7 +695,7 @@ fn bind_bookmarks(
     let repo_add = repo.clone();
     let ui_add = ui.as_weak();
     ui.on_add_bookmark(move |name, path, side| {
-        let mut cfg = cfg_add.lock().unwrap();
+        let mut cfg = lock(&cfg_add);
         // 避免重复
         if explorer_service::dedup_bookmark(
             &cfg.bookmarks,
@@ -229,7 +726,7 @@ fn bind_bookmarks(
     let repo_rm = repo;
     let ui_rm = ui.as_weak();
     ui.on_remove_bookmark(move |index| {
-        let mut cfg = cfg_rm.lock().unwrap();
+        let mut cfg = lock(&cfg_rm);
         let idx = index as usize;
         if idx < cfg.bookmarks.len() {
             cfg.bookmarks.remove(idx);
@@ -250,7 +747,7 @@ fn bind_bookmarks(
     let cfg_goto = config;
     let ui_goto = ui.as_weak();
     ui.on_goto_bookmark(move |index| {
-        let cfg = cfg_goto.lock().unwrap();
+        let cfg = lock(&cfg_goto);
         let idx = index as usize;
         let bm = match cfg.bookmarks.get(idx) {
             Some(b) => b.clone(),
@@ -260,7 +757,7 @@ fn bind_bookmarks(
 
         match bm.side.as_str() {
             "local" => {
-                let mut s = local_state.lock().unwrap();
+                let mut s = lock(&local_state);
                 s.current_path = std::path::PathBuf::from(&bm.path);
                 s.selected_indices.clear();
                 drop(s);
@@ -269,7 +766,7 @@ fn bind_bookmarks(
                 }
             }
             "remote" => {
-                let mut s = remote_state.lock().unwrap();
+                let mut s = lock(&remote_state);
                 s.selected_indices.clear();
                 drop(s);
                 if let Some(ui) = ui_goto.upgrade() {
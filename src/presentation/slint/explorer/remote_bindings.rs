@@ -1,15 +1,28 @@
-use slint::{ComponentHandle, Model, ModelRc, SharedString, VecModel};
-use std::collections::HashSet;
+use slint::{ComponentHandle, Image, Model, ModelRc, SharedString, Timer, TimerMode, VecModel};
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
+use std::rc::Rc;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
 use std::sync::{Arc, Mutex};
 use std::thread;
+use std::time::Duration;
 
-use crate::domain::config::AppConfig;
+use crate::app::services::explorer_service::{self, BrowsePosition, TextEncoding};
+use crate::domain::config::{push_recent_dir, AppConfig, ServerConfig, MAX_RECENT_DIRS};
+use crate::domain::ports::ConfigRepository;
+use crate::domain::transfer::{ConflictPolicy, TransferQueue};
 use crate::infra::remote_fs;
-use crate::infra::ssh::SshUploader;
+use crate::infra::ssh::{ConnectionPool, SshUploader};
+use crate::shared::name_filter::{FilterMode, NameFilter};
+use crate::shared::quick_filter::{self, QuickFilter};
+use crate::shared::panic_guard::lock;
+use crate::shared::throttle::Debouncer;
+use crate::shared::{path_utils, remote_path};
+use crate::presentation::slint::mapper;
 use crate::AppWindow;
 use crate::FileEntry;
 
-use super::format_size;
+use super::{entry_kind, format_entry_size, format_size, name_highlight};
 
 /// 远程文件浏览器状态
 pub(crate) struct RemoteState {
@@ -21,26 +34,89 @@ pub(crate) struct RemoteState {
     pub sort_field: String,
     pub sort_ascending: bool,
     pub filter_text: String,
+    pub filter_mode: FilterMode,
+    /// 按大小/修改时间的快捷过滤
+    pub quick_filter: QuickFilter,
+    /// 显示 `.` 开头的隐藏文件
+    pub show_hidden: bool,
+    /// 开启后搜索会递归匹配子目录，条目名为相对当前目录的路径
+    pub deep_search: bool,
     pub last_clicked_index: Option<usize>,
+    /// 键盘导航的焦点行
+    pub focused_index: Option<usize>,
+    /// 复制/剪切的远程路径及是否为目录
+    pub clipboard: Vec<(String, bool)>,
+    pub clipboard_cut: bool,
+    /// 有同名项、等待冲突对话框选择策略的粘贴
+    pub pending_paste: Option<PendingPaste>,
+    /// 列表中已渲染的条目数，超大目录分页显示
+    pub visible_count: usize,
+    /// 等待确认覆盖的重命名 (原路径, 新路径)
+    pub pending_rename: Option<(String, String)>,
+    /// 删除失败、等待确认以 sudo 重试的路径及是否为目录
+    pub pending_sudo_delete: Vec<(String, bool)>,
+    /// 属性对话框当前显示的远程路径
+    pub properties_path: Option<String>,
+    /// 当前服务器最近访问的目录，最新的在前
+    pub recent_dirs: Vec<String>,
+    /// 为单个文件手动选择的预览/编辑编码，按完整路径记录，未记录的按 UTF-8
+    pub file_encodings: HashMap<String, TextEncoding>,
 }
 
+/// 一次远程粘贴：目标目录、剪贴板中的各项 (路径, 是否为目录) 及是否为剪切
+pub(crate) struct PendingPaste {
+    pub dir: String,
+    pub items: Vec<(String, bool)>,
+    pub cut: bool,
+}
+
+impl RemoteState {
+    /// 当前的文件名过滤器，未输入过滤文本时为 None
+    pub(crate) fn name_filter(&self) -> Option<NameFilter> {
+        (!self.filter_text.is_empty()).then(|| NameFilter::new(self.filter_mode, &self.filter_text))
+    }
+}
+
+/// 远程列表每页渲染的条目数
+pub(crate) const REMOTE_PAGE_SIZE: usize = 500;
+
+/// 只转换前 `limit` 个条目，排序和选中下标仍基于完整列表
 fn remote_entries_to_ui(
     entries: &[remote_fs::RemoteEntry],
     selected: &HashSet<usize>,
+    limit: usize,
+    filter: Option<&NameFilter>,
 ) -> Vec<FileEntry> {
     entries
         .iter()
+        .take(limit)
         .enumerate()
-        .map(|(i, e)| FileEntry {
-            name: SharedString::from(&e.name),
-            is_dir: e.is_dir,
-            size: SharedString::from(format_size(e.size, e.is_dir)),
-            modified: SharedString::from(&e.modified),
-            selected: selected.contains(&i),
+        .map(|(i, e)| {
+            let (name_head, name_match, name_tail) = name_highlight(&e.name, filter);
+            FileEntry {
+                name: SharedString::from(&e.name),
+                is_dir: e.is_dir,
+                kind: entry_kind(&e.name, e.is_dir),
+                is_symlink: e.is_symlink,
+                link_target: SharedString::from(&e.link_target),
+                size: SharedString::from(format_entry_size(e.size, e.is_dir, e.dir_size)),
+                modified: SharedString::from(&e.modified),
+                selected: selected.contains(&i),
+                name_head,
+                name_match,
+                name_tail,
+            }
         })
         .collect()
 }
 
+/// 隐藏文件开关关闭时去掉 `.` 开头的条目 (深度搜索的条目按最后一段判断)
+pub(crate) fn retain_shown(entries: &mut Vec<remote_fs::RemoteEntry>, show_hidden: bool) {
+    if !show_hidden {
+        entries.retain(|e| !e.name.rsplit('/').next().unwrap_or(&e.name).starts_with('.'));
+    }
+}
+
 fn sort_remote_entries(entries: &mut Vec<remote_fs::RemoteEntry>, field: &str, ascending: bool) {
     entries.sort_by(|a, b| {
         let dir_ord = b.is_dir.cmp(&a.is_dir);
@@ -48,7 +124,7 @@ fn sort_remote_entries(entries: &mut Vec<remote_fs::RemoteEntry>, field: &str, a
             return dir_ord;
         }
         let ord = match field {
-            "size" => a.size.cmp(&b.size),
+            "size" => a.dir_size.unwrap_or(a.size).cmp(&b.dir_size.unwrap_or(b.size)),
             "modified" => a.modified.cmp(&b.modified),
             _ => a.name.to_lowercase().cmp(&b.name.to_lowercase()),
         };
@@ -56,12 +132,45 @@ fn sort_remote_entries(entries: &mut Vec<remote_fs::RemoteEntry>, field: &str, a
     });
 }
 
+/// 递归搜索 root 子树，条目名转换为相对 root 的路径，
+/// 这样下载、删除等操作仍可按 `当前目录/条目名` 拼接完整路径
+fn deep_search_entries(
+    uploader: &SshUploader,
+    root: &str,
+    mode: FilterMode,
+    filter: &str,
+) -> Vec<remote_fs::RemoteEntry> {
+    // 正则无法交给 find，先取全部再在本地过滤
+    let pattern = match mode {
+        FilterMode::Regex => "*",
+        _ => filter,
+    };
+    let prefix = if root.ends_with('/') {
+        root.to_string()
+    } else {
+        format!("{}/", root)
+    };
+    match remote_fs::remote_find(uploader, root, pattern) {
+        Ok(entries) => entries
+            .into_iter()
+            .filter_map(|mut e| {
+                e.name = e.name.strip_prefix(&prefix)?.to_string();
+                Some(e)
+            })
+            .collect(),
+        Err(e) => {
+            eprintln!("递归搜索失败: {}", e);
+            Vec::new()
+        }
+    }
+}
+
 pub(crate) fn refresh_remote_dir(
     state: &Arc<Mutex<RemoteState>>,
     ui_handle: &slint::Weak<AppWindow>,
     path: &str,
 ) {
-    let s = state.lock().unwrap();
+    let s = lock(state);
     let uploader = match &s.uploader {
         Some(u) => u,
         None => return,
@@ -70,54 +179,295 @@ pub(crate) fn refresh_remote_dir(
     let sort_field = s.sort_field.clone();
     let sort_asc = s.sort_ascending;
     let filter = s.filter_text.clone();
+    let filter_mode = s.filter_mode;
+    let quick = s.quick_filter;
+    let show_hidden = s.show_hidden;
+    let deep_search = s.deep_search && !filter.is_empty();
 
-    let mut entries =
-        remote_fs::list_dir_sftp(uploader, path).unwrap_or_default();
+    let mut entries = if deep_search {
+        deep_search_entries(uploader, path, filter_mode, &filter)
+    } else {
+        match remote_fs::list_dir_sftp(uploader, path) {
+            Ok(entries) => entries,
+            Err(e) => {
+                // 路径是文件 (如手动输入或书签指向文件)：进入所在目录并选中它
+                let file = remote_path::split_parent(path).filter(|_| is_remote_file(uploader, path));
+                drop(s);
+                if let Some((parent, name)) = file {
+                    refresh_remote_dir(state, ui_handle, &parent);
+                    select_remote_entry(state, ui_handle, &name);
+                    return;
+                }
+                // 保留原有列表，只提示错误
+                if let Some(ui) = ui_handle.upgrade() {
+                    ui.set_remote_status(SharedString::from(explorer_service::describe_list_error(
+                        path,
+                        &e.to_string(),
+                    )));
+                }
+                return;
+            }
+        }
+    };
     sort_remote_entries(&mut entries, &sort_field, sort_asc);
 
-    if !filter.is_empty() {
-        let lower = filter.to_lowercase();
-        entries.retain(|e| e.name.to_lowercase().contains(&lower));
+    let name_filter = s.name_filter();
+    if let Some(name_filter) = &name_filter {
+        entries.retain(|e| {
+            let base = e.name.rsplit('/').next().unwrap_or(&e.name);
+            name_filter.matches(base)
+        });
+    }
+    retain_shown(&mut entries, show_hidden);
+    if quick != QuickFilter::None {
+        let now = quick_filter::unix_now();
+        entries.retain(|e| quick.matches(e.is_dir, e.size, e.mtime, now));
     }
 
-    let ui_entries = remote_entries_to_ui(&entries, &selected);
     let path_owned = path.to_string();
     drop(s);
 
-    let mut s = state.lock().unwrap();
+    let mut s = lock(state);
+    if s.current_path == path_owned {
+        for e in entries.iter_mut().filter(|e| e.is_dir) {
+            e.dir_size = s
+                .cached_entries
+                .iter()
+                .find(|old| old.name == e.name)
+                .and_then(|old| old.dir_size);
+        }
+    }
+    if s.current_path != path_owned {
+        s.visible_count = REMOTE_PAGE_SIZE;
+    }
     s.current_path = path_owned.clone();
+    push_recent_dir(&mut s.recent_dirs, &path_owned, MAX_RECENT_DIRS);
+    let recent = super::recent_dirs_model(&s.recent_dirs);
     s.cached_entries = entries;
+    let ui_entries =
+        remote_entries_to_ui(&s.cached_entries, &selected, s.visible_count, name_filter.as_ref());
     let file_count = s.cached_entries.len() as i32;
+    let hidden_count = (s.cached_entries.len() - ui_entries.len()) as i32;
     let selected_count = s.selected_indices.len() as i32;
     drop(s);
 
     if let Some(ui) = ui_handle.upgrade() {
         ui.set_remote_path(SharedString::from(&path_owned));
         ui.set_remote_files(ModelRc::new(VecModel::from(ui_entries)));
+        ui.set_remote_hidden_count(hidden_count);
         ui.set_remote_file_count(file_count);
         ui.set_remote_selected_count(selected_count);
+        ui.set_remote_status(SharedString::new());
+        ui.set_remote_recent_dirs(recent);
+        ui.set_remote_focused_row(-1);
     }
 }
 
+/// 路径是否为文件 (或指向文件的符号链接)，无法读取属性时按不是文件处理
+fn is_remote_file(uploader: &SshUploader, path: &str) -> bool {
+    match remote_fs::stat(uploader, path) {
+        Ok(st) if st.is_dir => false,
+        Ok(st) if st.link_target.is_some() => {
+            remote_fs::remote_is_dir(uploader, path).map(|is_dir| !is_dir).unwrap_or(false)
+        }
+        Ok(_) => true,
+        Err(_) => false,
+    }
+}
+
+/// 只选中当前列表中名为 `name` 的条目并设为焦点行，条目被过滤或未显示时不做处理
+fn select_remote_entry(state: &Arc<Mutex<RemoteState>>, ui_handle: &slint::Weak<AppWindow>, name: &str) {
+    let mut s = lock(state);
+    let shown = s.cached_entries.len().min(s.visible_count);
+    let index = match s.cached_entries[..shown].iter().position(|e| e.name == name) {
+        Some(i) => i,
+        None => return,
+    };
+    s.selected_indices = HashSet::from([index]);
+    s.focused_index = Some(index);
+    s.last_clicked_index = Some(index);
+    drop(s);
+
+    if let Some(ui) = ui_handle.upgrade() {
+        super::select_only_row(&ui.get_remote_files(), index);
+        ui.set_remote_selected_count(1);
+        ui.set_remote_focused_row(index as i32);
+    }
+}
+
+/// 当前目录所在文件系统的剩余/总空间文本，查询或解析失败时为空 (隐藏指示)
+fn disk_usage_text(uploader: &SshUploader, path: &str) -> String {
+    match remote_fs::remote_disk_usage(uploader, path) {
+        Ok((available, total)) => format!(
+            "剩余 {} / 共 {}",
+            format_size(available, false),
+            format_size(total, false)
+        ),
+        Err(e) => {
+            eprintln!("查询磁盘空间失败: {}", e);
+            String::new()
+        }
+    }
+}
+
+/// 后台重新查询远程磁盘空间并更新状态栏
+pub(crate) fn refresh_disk_usage(state: &Arc<Mutex<RemoteState>>, ui_handle: &slint::Weak<AppWindow>) {
+    let st = state.clone();
+    let ui_h = ui_handle.clone();
+    thread::spawn(move || {
+        let text = {
+            let s = lock(&st);
+            match &s.uploader {
+                Some(uploader) => disk_usage_text(uploader, &s.current_path),
+                None => return,
+            }
+        };
+        let _ = slint::invoke_from_event_loop(move || {
+            if let Some(ui) = ui_h.upgrade().filter(|ui| ui.get_remote_connected()) {
+                ui.set_remote_disk_usage(SharedString::from(&text));
+            }
+        });
+    });
+}
+
 pub(crate) fn bind(
     ui: &AppWindow,
     config: Arc<Mutex<AppConfig>>,
+    repo: Arc<dyn ConfigRepository + Send + Sync>,
     remote_state: Arc<Mutex<RemoteState>>,
+    pool: Arc<ConnectionPool>,
 ) {
-    bind_remote_connect(ui, config, remote_state.clone());
-    bind_remote_disconnect(ui, remote_state.clone());
+    bind_remote_connect(ui, config.clone(), remote_state.clone());
+    bind_server_tag_filter(ui, config.clone());
+    bind_remote_disconnect(ui, config.clone(), repo, remote_state.clone(), pool);
     bind_remote_navigate(ui, remote_state.clone());
+    bind_remote_path_complete(ui, remote_state.clone());
     bind_remote_go_up(ui, remote_state.clone());
+    bind_remote_key(ui, remote_state.clone());
     bind_remote_file_clicked(ui, remote_state.clone());
     bind_remote_double_click(ui, remote_state.clone());
     bind_remote_refresh(ui, remote_state.clone());
     bind_remote_select_all(ui, remote_state.clone());
+    bind_remote_show_more(ui, remote_state.clone());
     bind_remote_mkdir(ui, remote_state.clone());
+    bind_remote_touch(ui, remote_state.clone());
     bind_remote_delete_selected(ui, remote_state.clone());
     bind_remote_rename(ui, remote_state.clone());
+    bind_remote_bulk_rename(ui, remote_state.clone());
+    bind_remote_clipboard(ui, remote_state.clone());
+    bind_remote_calc_dir_size(ui, remote_state.clone());
+    bind_remote_properties(ui, remote_state.clone());
+    bind_remote_change_attrs(ui, remote_state.clone());
     bind_remote_sort_changed(ui, remote_state.clone());
     bind_remote_file_clicked_ex(ui, remote_state.clone());
-    bind_remote_filter_changed(ui, remote_state);
+    bind_remote_filter_changed(ui, remote_state.clone());
+    bind_remote_filter_mode_changed(ui, remote_state.clone());
+    bind_remote_quick_filter_changed(ui, remote_state.clone());
+    bind_remote_show_hidden_changed(ui, remote_state.clone());
+    bind_remote_deep_search_changed(ui, remote_state.clone());
+    bind_remote_preview(ui, config.clone(), remote_state.clone());
+    bind_remote_editor(ui, config, remote_state.clone());
+    bind_remote_command(ui, remote_state);
+}
+
+/// 连接期间定时探测会话，显示往返延迟；探测失败时断开并提示重连。传输进行中暂停探测
+pub(crate) fn start_keepalive(
+    ui: &AppWindow,
+    config: Arc<Mutex<AppConfig>>,
+    state: Arc<Mutex<RemoteState>>,
+    queue: Arc<Mutex<TransferQueue>>,
+) {
+    let interval = lock(&config).ping_interval_secs;
+    if interval == 0 {
+        return;
+    }
+
+    let ui_handle = ui.as_weak();
+    let in_flight = Arc::new(AtomicBool::new(false));
+    let timer = Timer::default();
+    timer.start(TimerMode::Repeated, Duration::from_secs(interval), move || {
+        let connected = ui_handle.upgrade().map(|ui| ui.get_remote_connected()).unwrap_or(false);
+        if !connected || lock(&queue).has_active() {
+            return;
+        }
+        if in_flight.swap(true, Ordering::SeqCst) {
+            return;
+        }
+
+        let st = state.clone();
+        let ui_h = ui_handle.clone();
+        let in_flight = in_flight.clone();
+        thread::spawn(move || {
+            // 只在持锁时复制连接句柄，网络往返期间不占用 RemoteState
+            let handle = lock(&st).uploader.as_ref().map(remote_fs::PingHandle::new);
+            let result = handle.as_ref().map(remote_fs::remote_ping);
+            in_flight.store(false, Ordering::SeqCst);
+            let result = match result {
+                Some(r) => r,
+                None => return,
+            };
+            let _ = slint::invoke_from_event_loop(move || {
+                let ui = match ui_h.upgrade() {
+                    Some(ui) => ui,
+                    None => return,
+                };
+                match result {
+                    Ok(rtt) => ui.set_remote_latency_ms(rtt.as_millis().min(i32::MAX as u128) as i32),
+                    Err(e) => {
+                        ui.invoke_remote_disconnect();
+                        ui.set_remote_status(SharedString::from(format!("连接已断开: {}", e)));
+                        ui.set_confirm_title(SharedString::from("连接已断开"));
+                        ui.set_confirm_message(SharedString::from("与远程服务器的连接已断开，是否重新连接？"));
+                        ui.set_confirm_action(SharedString::from("remote-reconnect"));
+                        ui.set_show_confirm(true);
+                    }
+                }
+            });
+        });
+    });
+    std::mem::forget(timer);
+}
+
+/// 按当前标签筛选服务器下拉框，筛选后的位置通过 remote-server-indices 映射回配置下标
+pub(crate) fn apply_server_filter(ui: &AppWindow, servers: &[ServerConfig]) {
+    let previous = selected_server_index(ui);
+    let mut tag = ui.get_server_tag_filter().to_string();
+    if !explorer_service::server_tags(servers).contains(&tag) {
+        tag.clear();
+        ui.set_server_tag_filter(SharedString::new());
+    }
+    let indices = explorer_service::servers_with_tag(servers, &tag);
+    let labels: Vec<SharedString> = indices
+        .iter()
+        .map(|&i| SharedString::from(explorer_service::server_label(&servers[i])))
+        .collect();
+    let indices: Vec<i32> = indices.into_iter().map(|i| i as i32).collect();
+    // 尽量保持原来选中的服务器
+    let position = indices.iter().position(|&i| i == previous).unwrap_or(0);
+    ui.set_remote_servers(ModelRc::new(VecModel::from(labels)));
+    ui.set_remote_server_indices(ModelRc::new(VecModel::from(indices)));
+    ui.set_remote_server_index(position as i32);
+}
+
+/// 下拉框当前选中项对应的配置下标，没有可选服务器时为 -1
+pub(crate) fn selected_server_index(ui: &AppWindow) -> i32 {
+    let position = ui.get_remote_server_index();
+    if position < 0 {
+        return -1;
+    }
+    ui.get_remote_server_indices()
+        .row_data(position as usize)
+        .unwrap_or(-1)
+}
+
+fn bind_server_tag_filter(ui: &AppWindow, config: Arc<Mutex<AppConfig>>) {
+    let ui_handle = ui.as_weak();
+    ui.on_filter_servers_by_tag(move |tag| {
+        if let Some(ui) = ui_handle.upgrade() {
+            ui.set_server_tag_filter(tag);
+            apply_server_filter(&ui, &lock(&config).servers);
+        }
+    });
 }
 
 fn bind_remote_connect(
@@ -127,7 +477,7 @@ fn bind_remote_connect(
 ) {
     let ui_handle = ui.as_weak();
     ui.on_remote_connect(move |server_index| {
-        let config_guard = config.lock().unwrap();
+        let config_guard = lock(&config);
         if server_index < 0
             || server_index as usize >= config_guard.servers.len()
         {
@@ -148,23 +498,62 @@ fn bind_remote_connect(
             let (result, _logs) =
                 SshUploader::connect_with_log(&server_config);
 
-            let default_dir =
-                server_config.default_target_dir.clone();
+            let last_dir = server_config
+                .last_remote_path
+                .clone()
+                .filter(|p| !p.is_empty());
+            let mut recent_dirs = server_config.recent_dirs.clone();
 
             match result {
                 Ok(uploader) => {
-                    let entries = remote_fs::list_dir_sftp(
-                        &uploader, &default_dir,
-                    )
-                    .unwrap_or_default();
+                    // 认证完成后读取大目录可能较慢，先更新状态
+                    let ui_progress = ui_h.clone();
+                    let _ = slint::invoke_from_event_loop(move || {
+                        if let Some(ui) = ui_progress.upgrade() {
+                            ui.set_remote_status("正在读取目录...".into());
+                        }
+                    });
+
+                    // 优先恢复上次浏览的目录，失败则回到默认目录
+                    let restored = last_dir.and_then(|dir| {
+                        remote_fs::list_dir_sftp(&uploader, &dir)
+                            .ok()
+                            .map(|entries| (dir, entries))
+                    });
+                    let mut list_error = String::new();
+                    let (start_dir, entries) = match restored {
+                        Some(r) => r,
+                        None => {
+                            let dir = server_config.default_target_dir.clone();
+                            let entries = match remote_fs::list_dir_sftp(&uploader, &dir) {
+                                Ok(entries) => entries,
+                                Err(e) => {
+                                    list_error =
+                                        explorer_service::describe_list_error(&dir, &e.to_string());
+                                    Vec::new()
+                                }
+                            };
+                            (dir, entries)
+                        }
+                    };
 
-                    let ui_entries = remote_entries_to_ui(&entries, &HashSet::new());
+                    let mut entries = entries;
+                    retain_shown(&mut entries, lock(&st).show_hidden);
+                    let disk_usage = disk_usage_text(&uploader, &start_dir);
+                    let ui_entries =
+                        remote_entries_to_ui(&entries, &HashSet::new(), REMOTE_PAGE_SIZE, None);
+                    let hidden_count = (entries.len() - ui_entries.len()) as i32;
 
-                    let mut s = st.lock().unwrap();
-                    s.current_path = default_dir.clone();
+                    push_recent_dir(&mut recent_dirs, &start_dir, MAX_RECENT_DIRS);
+                    let mut s = lock(&st);
+                    s.current_path = start_dir.clone();
+                    s.recent_dirs = recent_dirs.clone();
                     s.uploader = Some(uploader);
+                    // 编码选择按路径记录，换了服务器后不再适用
+                    s.file_encodings.clear();
                     s.cached_entries = entries;
                     s.selected_indices.clear();
+                    s.visible_count = REMOTE_PAGE_SIZE;
                     drop(s);
 
                     let _ = slint::invoke_from_event_loop(
@@ -172,13 +561,17 @@ fn bind_remote_connect(
                             if let Some(ui) = ui_h.upgrade() {
                                 ui.set_remote_connecting(false);
                                 ui.set_remote_connected(true);
+                                ui.set_remote_latency_ms(-1);
                                 ui.set_remote_path(
-                                    SharedString::from(&default_dir),
+                                    SharedString::from(&start_dir),
                                 );
                                 ui.set_remote_files(ModelRc::new(
                                     VecModel::from(ui_entries),
                                 ));
-                                ui.set_remote_status("".into());
+                                ui.set_remote_hidden_count(hidden_count);
+                                ui.set_remote_status(SharedString::from(&list_error));
+                                ui.set_remote_disk_usage(SharedString::from(&disk_usage));
+                                ui.set_remote_recent_dirs(super::recent_dirs_model(&recent_dirs));
                             }
                         },
                     );
@@ -209,16 +602,26 @@ fn bind_remote_delete_selected(
     let ui_handle = ui.as_weak();
     ui.on_remote_delete_selected(move || {
         if let Some(ui) = ui_handle.upgrade() {
-            let s = state.lock().unwrap();
-            let count = s.selected_indices.len();
+            // 远程没有回收站，确认框中列出全部待删除路径
+            let s = lock(&state);
+            let mut paths: Vec<SharedString> = s
+                .selected_indices
+                .iter()
+                .filter_map(|&i| s.cached_entries.get(i))
+                .map(|e| SharedString::from(remote_path::join(&s.current_path, &e.name)))
+                .collect();
             drop(s);
-            if count == 0 {
+            if paths.is_empty() {
                 return;
             }
+            paths.sort();
             ui.set_confirm_title(SharedString::from("确认删除"));
-            ui.set_confirm_message(SharedString::from(
-                format!("确定要删除远程的 {} 个项目吗？此操作不可撤销。", count),
-            ));
+            ui.set_confirm_message(SharedString::from(explorer_service::delete_confirm_message(
+                paths.len(),
+                true,
+                false,
+            )));
+            ui.set_confirm_details(ModelRc::new(VecModel::from(paths)));
             ui.set_confirm_action(SharedString::from("remote-delete"));
             ui.set_show_confirm(true);
         }
@@ -226,14 +629,35 @@ fn bind_remote_delete_selected(
 }
 fn bind_remote_disconnect(
     ui: &AppWindow,
+    config: Arc<Mutex<AppConfig>>,
+    repo: Arc<dyn ConfigRepository + Send + Sync>,
     state: Arc<Mutex<RemoteState>>,
+    pool: Arc<ConnectionPool>,
 ) {
     let ui_handle = ui.as_weak();
     ui.on_remote_disconnect(move || {
-        let mut s = state.lock().unwrap();
+        let mut s = lock(&state);
+        if let Some(uploader) = &s.uploader {
+            let server = uploader.config().clone();
+            pool.clear(&server);
+            let position = BrowsePosition {
+                path: s.current_path.clone(),
+                recent: s.recent_dirs.clone(),
+            };
+            if let Err(e) = explorer_service::remember_paths(
+                &config,
+                &repo,
+                None,
+                Some((&server, position)),
+            ) {
+                eprintln!("保存远程目录失败: {}", e);
+            }
+        }
         s.uploader = None;
         s.cached_entries.clear();
         s.selected_indices.clear();
+        s.recent_dirs.clear();
+        s.file_encodings.clear();
         s.current_path = "/".to_string();
         drop(s);
 
@@ -243,7 +667,11 @@ fn bind_remote_disconnect(
             ui.set_remote_files(ModelRc::new(VecModel::from(
                 Vec::<FileEntry>::new(),
             )));
+            ui.set_remote_hidden_count(0);
             ui.set_remote_status("".into());
+            ui.set_remote_disk_usage(SharedString::new());
+            ui.set_remote_latency_ms(-1);
+            ui.set_remote_recent_dirs(ModelRc::default());
         }
     });
 }
@@ -254,17 +682,73 @@ fn bind_remote_navigate(
 ) {
     let ui_handle = ui.as_weak();
     ui.on_remote_navigate(move |path_str| {
-        let mut s = state.lock().unwrap();
+        let mut s = lock(&state);
         s.selected_indices.clear();
         drop(s);
         refresh_remote_dir(
             &state,
             &ui_handle,
-            path_str.as_str(),
+            &remote_path::normalize(path_str.as_str()),
         );
     });
 }
 
+fn bind_remote_path_complete(ui: &AppWindow, state: Arc<Mutex<RemoteState>>) {
+    let ui_handle = ui.as_weak();
+    let debouncer = Debouncer::new();
+    ui.on_remote_path_complete(move |partial| {
+        let st = state.clone();
+        super::request_path_completions(
+            &debouncer,
+            &partial,
+            &['/'],
+            move |parent| {
+                let s = lock(&st);
+                let entries = match &s.uploader {
+                    Some(uploader) => remote_fs::list_dir_sftp(uploader, parent).unwrap_or_default(),
+                    None => Vec::new(),
+                };
+                entries.into_iter().filter(|e| e.is_dir).map(|e| e.name).collect()
+            },
+            &ui_handle,
+            |ui, completions| ui.set_remote_path_completions(completions),
+        );
+    });
+}
+
+/// 方向键在已显示的条目中移动焦点行并只选中该行，回车按双击打开
+fn bind_remote_key(ui: &AppWindow, state: Arc<Mutex<RemoteState>>) {
+    let ui_handle = ui.as_weak();
+    ui.on_remote_key(move |key| {
+        let ui = match ui_handle.upgrade() {
+            Some(ui) => ui,
+            None => return,
+        };
+        let mut s = lock(&state);
+        let current = super::focus_start(s.focused_index, &s.selected_indices);
+        if key == "enter" {
+            drop(s);
+            if let Some(index) = current {
+                ui.invoke_remote_file_double_clicked(index as i32);
+            }
+            return;
+        }
+        let shown = s.cached_entries.len().min(s.visible_count);
+        let next = match explorer_service::move_focus(current, shown, &key) {
+            Some(next) => next,
+            None => return,
+        };
+        s.focused_index = Some(next);
+        s.last_clicked_index = Some(next);
+        s.selected_indices = HashSet::from([next]);
+        drop(s);
+
+        super::select_only_row(&ui.get_remote_files(), next);
+        ui.set_remote_selected_count(1);
+        ui.set_remote_focused_row(next as i32);
+    });
+}
+
 fn bind_remote_go_up(
     ui: &AppWindow,
     state: Arc<Mutex<RemoteState>>,
@@ -272,23 +756,11 @@ fn bind_remote_go_up(
     let ui_handle = ui.as_weak();
     ui.on_remote_go_up(move || {
         let current = {
-            let s = state.lock().unwrap();
+            let s = lock(&state);
             s.current_path.clone()
         };
-        let parent = if current == "/" {
-            "/".to_string()
-        } else {
-            let p = std::path::Path::new(&current);
-            p.parent()
-                .map(|pp| pp.to_string_lossy().to_string())
-                .unwrap_or_else(|| "/".to_string())
-        };
-        let parent = if parent.is_empty() {
-            "/".to_string()
-        } else {
-            parent
-        };
-        let mut s = state.lock().unwrap();
+        let parent = remote_path::join(&current, "..");
+        let mut s = lock(&state);
         s.selected_indices.clear();
         drop(s);
         refresh_remote_dir(&state, &ui_handle, &parent);
@@ -304,7 +776,7 @@ fn bind_remote_file_clicked(
     let ui_handle = ui.as_weak();
     ui.on_remote_file_clicked(move |index| {
         if let Some(ui) = ui_handle.upgrade() {
-            let mut s = state.lock().unwrap();
+            let mut s = lock(&state);
             let idx = index as usize;
 
             let is_selected = if s.selected_indices.contains(&idx) {
@@ -312,20 +784,18 @@ fn bind_remote_file_clicked(
                 false
             } else {
                 s.selected_indices.insert(idx);
+                s.focused_index = Some(idx);
                 true
             };
 
-            if let Some(entry) = s.cached_entries.get(idx).cloned() {
+            if idx < s.cached_entries.len() {
                 let sel_count = s.selected_indices.len() as i32;
                 drop(s);
-                let file_entry = FileEntry {
-                    name: SharedString::from(&entry.name),
-                    is_dir: entry.is_dir,
-                    size: SharedString::from(format_size(entry.size, entry.is_dir)),
-                    modified: SharedString::from(&entry.modified),
-                    selected: is_selected,
-                };
-                ui.get_remote_files().set_row_data(idx, file_entry);
+                let model = ui.get_remote_files();
+                if let Some(mut row) = model.row_data(idx) {
+                    row.selected = is_selected;
+                    model.set_row_data(idx, row);
+                }
                 ui.set_remote_selected_count(sel_count);
             }
         }
@@ -339,7 +809,7 @@ fn bind_remote_double_click(
     let ui_handle = ui.as_weak();
     ui.on_remote_file_double_clicked(move |index| {
         let (is_dir, name, current) = {
-            let s = state.lock().unwrap();
+            let s = lock(&state);
             match s.cached_entries.get(index as usize) {
                 Some(e) => {
                     (e.is_dir, e.name.clone(), s.current_path.clone())
@@ -348,15 +818,13 @@ fn bind_remote_double_click(
             }
         };
         if is_dir {
-            let new_path = if current.ends_with('/') {
-                format!("{}{}", current, name)
-            } else {
-                format!("{}/{}", current, name)
-            };
-            let mut s = state.lock().unwrap();
+            let new_path = remote_path::join(&current, &name);
+            let mut s = lock(&state);
             s.selected_indices.clear();
             drop(s);
             refresh_remote_dir(&state, &ui_handle, &new_path);
+        } else if let Some(ui) = ui_handle.upgrade() {
+            ui.invoke_remote_edit_file(index);
         }
     });
 }
@@ -368,10 +836,11 @@ fn bind_remote_refresh(
     let ui_handle = ui.as_weak();
     ui.on_remote_refresh(move || {
         let current = {
-            let s = state.lock().unwrap();
+            let s = lock(&state);
             s.current_path.clone()
         };
         refresh_remote_dir(&state, &ui_handle, &current);
+        refresh_disk_usage(&state, &ui_handle);
     });
 }
 
@@ -382,7 +851,7 @@ fn bind_remote_select_all(
     let ui_handle = ui.as_weak();
     ui.on_remote_select_all(move || {
         if let Some(ui) = ui_handle.upgrade() {
-            let mut s = state.lock().unwrap();
+            let mut s = lock(&state);
             let total = s.cached_entries.len();
             if s.selected_indices.len() == total {
                 s.selected_indices.clear();
@@ -390,32 +859,54 @@ fn bind_remote_select_all(
                 s.selected_indices = (0..total).collect();
             }
             let selected = s.selected_indices.clone();
-            let entries = s.cached_entries.clone();
+            let ui_entries = remote_entries_to_ui(
+                &s.cached_entries,
+                &selected,
+                s.visible_count,
+                s.name_filter().as_ref(),
+            );
             drop(s);
 
-            let ui_entries = remote_entries_to_ui(&entries, &selected);
             ui.set_remote_files(ModelRc::new(VecModel::from(ui_entries)));
         }
     });
 }
 
+/// 超大目录再多渲染一页
+fn bind_remote_show_more(ui: &AppWindow, state: Arc<Mutex<RemoteState>>) {
+    let ui_handle = ui.as_weak();
+    ui.on_remote_show_more(move || {
+        if let Some(ui) = ui_handle.upgrade() {
+            let mut s = lock(&state);
+            s.visible_count = (s.visible_count + REMOTE_PAGE_SIZE).min(s.cached_entries.len());
+            let ui_entries = remote_entries_to_ui(
+                &s.cached_entries,
+                &s.selected_indices,
+                s.visible_count,
+                s.name_filter().as_ref(),
+            );
+            let hidden_count = (s.cached_entries.len() - ui_entries.len()) as i32;
+            drop(s);
+
+            ui.set_remote_files(ModelRc::new(VecModel::from(ui_entries)));
+            ui.set_remote_hidden_count(hidden_count);
+        }
+    });
+}
+
 fn bind_remote_mkdir(
     ui: &AppWindow,
     state: Arc<Mutex<RemoteState>>,
 ) {
     let ui_handle = ui.as_weak();
     ui.on_remote_mkdir(move |dir_name| {
-        let s = state.lock().unwrap();
+        let s = lock(&state);
         let uploader = match &s.uploader {
             Some(u) => u,
             None => return,
         };
         let current = s.current_path.clone();
-        let new_dir = if current.ends_with('/') {
-            format!("{}{}", current, dir_name)
-        } else {
-            format!("{}/{}", current, dir_name)
-        };
+        let new_dir = remote_path::join(&current, &dir_name);
         if let Err(e) = remote_fs::remote_mkdir(uploader, &new_dir) {
             eprintln!("创建目录失败: {}", e);
             return;
@@ -428,13 +919,47 @@ fn bind_remote_mkdir(
     });
 }
 
+/// 新建空文件，同名条目已存在时自动编号
+fn bind_remote_touch(
+    ui: &AppWindow,
+    state: Arc<Mutex<RemoteState>>,
+) {
+    let ui_handle = ui.as_weak();
+    ui.on_remote_touch(move |file_name| {
+        let s = lock(&state);
+        let uploader = match &s.uploader {
+            Some(u) => u,
+            None => return,
+        };
+        let current = s.current_path.clone();
+        // 列一次目录挑选空闲名称；列目录失败时直接报错，remote_touch 本身也不会覆盖已有文件
+        let result = remote_fs::list_dir_sftp(uploader, &current)
+            .map(|entries| {
+                let names: Vec<String> = entries.into_iter().map(|e| e.name).collect();
+                remote_path::join(&current, &path_utils::free_name(&file_name, &names))
+            })
+            .and_then(|path| remote_fs::remote_touch(uploader, &path));
+        drop(s);
+        if let Err(e) = result {
+            if let Some(ui) = ui_handle.upgrade() {
+                ui.set_global_error(SharedString::from(e.to_string()));
+            }
+            return;
+        }
+        refresh_remote_dir(&state, &ui_handle, &current);
+        let _ = state.lock().map(|mut s| {
+            s.selected_indices.clear();
+        });
+    });
+}
+
 fn bind_remote_rename(
     ui: &AppWindow,
     state: Arc<Mutex<RemoteState>>,
 ) {
     let ui_handle = ui.as_weak();
     ui.on_remote_rename(move |index, new_name| {
-        let s = state.lock().unwrap();
+        let s = lock(&state);
         let uploader = match &s.uploader {
             Some(u) => u,
             None => return,
@@ -444,25 +969,471 @@ fn bind_remote_rename(
             Some(e) => e,
             None => return,
         };
-        let old_path = if current.ends_with('/') {
-            format!("{}{}", current, entry.name)
-        } else {
-            format!("{}/{}", current, entry.name)
+        if new_name.is_empty() || entry.name == new_name.as_str() {
+            return;
+        }
+        let old_path = remote_path::join(&current, &entry.name);
+        let new_path = remote_path::join(&current, &new_name);
+        let exists = remote_fs::remote_exists(uploader, &new_path);
+        drop(s);
+
+        let ui = match ui_handle.upgrade() {
+            Some(ui) => ui,
+            None => return,
+        };
+        match exists {
+            Ok(true) => {
+                lock(&state).pending_rename = Some((old_path, new_path));
+                ui.set_confirm_title(SharedString::from("确认覆盖"));
+                ui.set_confirm_message(SharedString::from(format!(
+                    "「{}」已存在，重命名会用当前条目替换它 (非空目录无法替换)。确定继续吗？",
+                    new_name
+                )));
+                ui.set_confirm_action(SharedString::from("remote-rename-overwrite"));
+                ui.set_show_confirm(true);
+            }
+            Ok(false) => rename_remote(&ui, &state, &old_path, &new_path),
+            Err(e) => ui.set_global_error(format!("重命名失败: {}", e).into()),
+        }
+    });
+}
+
+/// 执行远程重命名并刷新当前目录
+pub(crate) fn rename_remote(
+    ui: &AppWindow,
+    state: &Arc<Mutex<RemoteState>>,
+    old_path: &str,
+    new_path: &str,
+) {
+    let s = lock(state);
+    let uploader = match &s.uploader {
+        Some(u) => u,
+        None => return,
+    };
+    let current = s.current_path.clone();
+    if let Err(e) = remote_fs::remote_rename(uploader, old_path, new_path) {
+        ui.set_global_error(format!("重命名失败: {}", e).into());
+        return;
+    }
+    drop(s);
+    refresh_remote_dir(state, &ui.as_weak(), &current);
+}
+
+/// 按模板批量重命名选中的远程条目，所有 mv 合并为一次远程命令，在后台线程执行
+fn bind_remote_bulk_rename(
+    ui: &AppWindow,
+    state: Arc<Mutex<RemoteState>>,
+) {
+    let ui_handle = ui.as_weak();
+    ui.on_remote_bulk_rename(move |template| {
+        let ui = match ui_handle.upgrade() {
+            Some(ui) => ui,
+            None => return,
         };
-        let new_path = if current.ends_with('/') {
-            format!("{}{}", current, new_name)
-        } else {
-            format!("{}/{}", current, new_name)
+        let (current, selected) = {
+            let s = lock(&state);
+            if s.uploader.is_none() {
+                return;
+            }
+            let mut indices: Vec<usize> = s.selected_indices.iter().copied().collect();
+            indices.sort_unstable();
+            let selected: Vec<String> = indices
+                .iter()
+                .filter_map(|&i| s.cached_entries.get(i))
+                .map(|e| e.name.clone())
+                .collect();
+            (s.current_path.clone(), selected)
         };
-        if let Err(e) = remote_fs::remote_rename(uploader, &old_path, &new_path) {
-            eprintln!("重命名失败: {}", e);
+        if selected.iter().any(|n| n.contains('/')) {
+            ui.set_global_error("子目录搜索结果不支持批量重命名".into());
             return;
         }
+        ui.set_remote_status("正在批量重命名...".into());
+
+        let st = state.clone();
+        let ui_h = ui_handle.clone();
+        let template = template.to_string();
+        thread::spawn(move || {
+            let result = {
+                let s = lock(&st);
+                match &s.uploader {
+                    // 用完整目录列表检查重名，避免过滤后漏掉未显示的条目
+                    Some(uploader) => remote_fs::list_dir_sftp(uploader, &current)
+                        .map(|entries| entries.into_iter().map(|e| e.name).collect::<Vec<_>>())
+                        .and_then(|existing| {
+                            explorer_service::plan_bulk_rename(&selected, &existing, &template)
+                        })
+                        .and_then(|plan| {
+                            let renames: Vec<(String, String)> = plan
+                                .iter()
+                                .map(|(old, new)| {
+                                    (remote_path::join(&current, old), remote_path::join(&current, new))
+                                })
+                                .collect();
+                            remote_fs::remote_rename_batch(uploader, &renames)
+                        }),
+                    None => return,
+                }
+            };
+
+            let _ = slint::invoke_from_event_loop(move || {
+                if let Some(ui) = ui_h.upgrade() {
+                    ui.set_remote_status("".into());
+                    if let Err(e) = result {
+                        ui.set_global_error(format!("批量重命名失败: {}", e).into());
+                    }
+                }
+                refresh_remote_dir(&st, &ui_h, &current);
+            });
+        });
+    });
+}
+
+/// 复制/剪切选中的远程条目，粘贴到当前远程目录
+fn bind_remote_clipboard(
+    ui: &AppWindow,
+    state: Arc<Mutex<RemoteState>>,
+) {
+    let ui_handle = ui.as_weak();
+    let st = state.clone();
+    ui.on_remote_copy_selected(move || {
+        set_clipboard(&st, &ui_handle, false);
+    });
+
+    let ui_handle = ui.as_weak();
+    let st = state.clone();
+    ui.on_remote_cut_selected(move || {
+        set_clipboard(&st, &ui_handle, true);
+    });
+
+    let ui_handle = ui.as_weak();
+    let st = state.clone();
+    ui.on_remote_paste(move || {
+        let paste = {
+            let s = lock(&st);
+            if s.uploader.is_none() || s.clipboard.is_empty() {
+                return;
+            }
+            PendingPaste {
+                dir: s.current_path.clone(),
+                items: s.clipboard.clone(),
+                cut: s.clipboard_cut,
+            }
+        };
+        paste_remote(&st, &ui_handle, paste, None);
+    });
+
+    let ui_handle = ui.as_weak();
+    let st = state.clone();
+    ui.on_remote_paste_resolved(move |policy| {
+        let paste = match lock(&st).pending_paste.take() {
+            Some(p) => p,
+            None => return,
+        };
+        if let Some(policy) = ConflictPolicy::parse(&policy) {
+            paste_remote(&st, &ui_handle, paste, Some(policy));
+        }
+    });
+
+    ui.on_remote_paste_cancelled(move || {
+        lock(&state).pending_paste = None;
+    });
+}
+
+/// 在后台线程粘贴：先按完整目录列表检查同名项，未指定策略且有冲突时弹出冲突对话框，
+/// 否则按策略复制或移动。剪切只有全部成功才清空剪贴板，失败的项可以再次粘贴
+fn paste_remote(
+    state: &Arc<Mutex<RemoteState>>,
+    ui_handle: &slint::Weak<AppWindow>,
+    paste: PendingPaste,
+    policy: Option<ConflictPolicy>,
+) {
+    if let Some(ui) = ui_handle.upgrade() {
+        ui.set_remote_status("正在粘贴...".into());
+    }
+    let st = state.clone();
+    let ui_h = ui_handle.clone();
+    thread::spawn(move || {
+        let s = lock(&st);
+        let uploader = match &s.uploader {
+            Some(u) => u,
+            None => return,
+        };
+        let existing: Vec<String> = match remote_fs::list_dir_sftp(uploader, &paste.dir) {
+            Ok(entries) => entries.into_iter().map(|e| e.name).collect(),
+            Err(e) => {
+                drop(s);
+                let _ = slint::invoke_from_event_loop(move || {
+                    if let Some(ui) = ui_h.upgrade() {
+                        ui.set_remote_status("".into());
+                        ui.set_global_error(format!("粘贴失败: {}", e).into());
+                    }
+                });
+                return;
+            }
+        };
+
+        let conflicts = explorer_service::paste_conflicts(&paste.items, &paste.dir, &existing);
+        let policy = match policy {
+            Some(p) => p,
+            None if conflicts.is_empty() => ConflictPolicy::Overwrite,
+            None => {
+                drop(s);
+                lock(&st).pending_paste = Some(paste);
+                let _ = slint::invoke_from_event_loop(move || {
+                    if let Some(ui) = ui_h.upgrade() {
+                        let shown: Vec<&str> = conflicts.iter().take(3).map(String::as_str).collect();
+                        let more = if conflicts.len() > shown.len() { " 等" } else { "" };
+                        ui.set_remote_status("".into());
+                        ui.set_conflict_message(SharedString::from(format!(
+                            "当前目录已存在 {} 个同名项: {}{}",
+                            conflicts.len(),
+                            shown.join(", "),
+                            more
+                        )));
+                        ui.set_conflict_action(SharedString::from("remote-paste"));
+                        ui.set_show_conflict(true);
+                    }
+                });
+                return;
+            }
+        };
+
+        let mut errors = Vec::new();
+        for step in explorer_service::plan_remote_paste(&paste.items, &paste.dir, &existing, policy) {
+            let result = if step.replace {
+                remote_fs::remote_remove(uploader, &step.dst, true)
+            } else {
+                Ok(())
+            };
+            let result = result.and_then(|_| {
+                if paste.cut {
+                    remote_fs::remote_rename(uploader, &step.src, &step.dst)
+                } else {
+                    remote_fs::remote_copy(uploader, &step.src, &step.dst, step.is_dir)
+                }
+            });
+            if let Err(e) = result {
+                let name = remote_path::file_name(&step.src).unwrap_or(step.src);
+                errors.push(format!("{}: {}", name, e));
+            }
+        }
         drop(s);
-        refresh_remote_dir(&state, &ui_handle, &current);
+
+        let mut s = lock(&st);
+        // 剪贴板期间被替换过则保留新内容
+        if paste.cut && errors.is_empty() && s.clipboard == paste.items {
+            s.clipboard.clear();
+            s.clipboard_cut = false;
+        }
+        s.selected_indices.clear();
+        let clipboard_count = s.clipboard.len() as i32;
+        let current = s.current_path.clone();
+        drop(s);
+
+        let _ = slint::invoke_from_event_loop(move || {
+            if let Some(ui) = ui_h.upgrade() {
+                ui.set_remote_status("".into());
+                ui.set_remote_clipboard_count(clipboard_count);
+                if !errors.is_empty() {
+                    ui.set_global_error(format!("粘贴失败: {}", errors.join("; ")).into());
+                }
+            }
+            refresh_remote_dir(&st, &ui_h, &current);
+        });
     });
 }
 
+/// 在后台线程计算选中远程目录的总大小，完成后只更新对应行
+fn bind_remote_calc_dir_size(
+    ui: &AppWindow,
+    state: Arc<Mutex<RemoteState>>,
+) {
+    let ui_handle = ui.as_weak();
+    ui.on_remote_calc_dir_size(move || {
+        let targets: Vec<String> = {
+            let s = lock(&state);
+            if s.uploader.is_none() {
+                return;
+            }
+            s.selected_indices
+                .iter()
+                .filter_map(|&i| s.cached_entries.get(i))
+                .filter(|e| e.is_dir)
+                .map(|e| remote_path::join(&s.current_path, &e.name))
+                .collect()
+        };
+        if targets.is_empty() {
+            return;
+        }
+
+        let state = state.clone();
+        let ui_handle = ui_handle.clone();
+        thread::spawn(move || {
+            for path in targets {
+                let result = {
+                    let s = lock(&state);
+                    match &s.uploader {
+                        Some(uploader) => remote_fs::remote_dir_size(uploader, &path),
+                        None => return,
+                    }
+                };
+                let total = match result {
+                    Ok(total) => total,
+                    Err(e) => {
+                        eprintln!("计算目录大小失败 {}: {}", path, e);
+                        continue;
+                    }
+                };
+                let state = state.clone();
+                let ui_handle = ui_handle.clone();
+                let _ = slint::invoke_from_event_loop(move || {
+                    let mut s = lock(&state);
+                    // 期间切换了目录或刷新了列表时按路径重新定位，找不到就放弃
+                    let idx = match s
+                        .cached_entries
+                        .iter()
+                        .position(|e| remote_path::join(&s.current_path, &e.name) == path)
+                    {
+                        Some(i) => i,
+                        None => return,
+                    };
+                    s.cached_entries[idx].dir_size = Some(total);
+                    let entry = s.cached_entries[idx].clone();
+                    let selected = s.selected_indices.contains(&idx);
+                    drop(s);
+
+                    if let Some(ui) = ui_handle.upgrade() {
+                        let model = ui.get_remote_files();
+                        if let Some(mut row) = model.row_data(idx) {
+                            row.size = SharedString::from(format_entry_size(
+                                entry.size,
+                                entry.is_dir,
+                                entry.dir_size,
+                            ));
+                            row.selected = selected;
+                            model.set_row_data(idx, row);
+                        }
+                    }
+                });
+            }
+        });
+    });
+}
+
+/// 右键菜单"属性"：读取条目的 stat 信息并打开属性对话框
+fn bind_remote_properties(ui: &AppWindow, state: Arc<Mutex<RemoteState>>) {
+    let ui_handle = ui.as_weak();
+    ui.on_remote_show_properties(move |index| {
+        let mut s = lock(&state);
+        let (uploader, entry) = match (&s.uploader, s.cached_entries.get(index as usize)) {
+            (Some(u), Some(e)) => (u, e),
+            _ => return,
+        };
+        let path = remote_path::join(&s.current_path, &entry.name);
+        let title = format!("{} 的属性", entry.name.rsplit('/').next().unwrap_or(&entry.name));
+        let result = remote_fs::stat(uploader, &path);
+        s.properties_path = Some(path);
+        drop(s);
+
+        let ui = match ui_handle.upgrade() {
+            Some(ui) => ui,
+            None => return,
+        };
+        match result {
+            Ok(stat) => {
+                ui.set_properties_title(title.into());
+                show_remote_stat(&ui, &stat);
+                // chmod 会作用到链接目标，符号链接只显示不修改
+                ui.set_properties_editable(stat.link_target.is_none());
+                ui.set_properties_error(SharedString::new());
+                ui.set_show_properties(true);
+            }
+            Err(e) => ui.set_global_error(format!("{:#}", e).into()),
+        }
+    });
+}
+
+/// 把 stat 信息填入属性对话框的行、权限和属主
+fn show_remote_stat(ui: &AppWindow, stat: &remote_fs::RemoteStat) {
+    let rows = explorer_service::remote_properties(stat);
+    ui.set_properties_rows(mapper::to_property_rows(&rows));
+    ui.set_properties_mode(stat.mode as i32);
+    ui.set_properties_owner(format!("{}:{}", stat.owner, stat.group).into());
+}
+
+/// 属性对话框中修改权限/属主，完成后重新读取属性；错误显示在对话框内
+fn bind_remote_change_attrs(ui: &AppWindow, state: Arc<Mutex<RemoteState>>) {
+    let ui_handle = ui.as_weak();
+    let st = state.clone();
+    ui.on_remote_chmod(move |mode| {
+        change_remote_attrs(&ui_handle, &st, |uploader, path| {
+            remote_fs::remote_chmod(uploader, path, mode as u32)
+        });
+    });
+
+    let ui_handle = ui.as_weak();
+    ui.on_remote_chown(move |owner| {
+        change_remote_attrs(&ui_handle, &state, |uploader, path| {
+            remote_fs::remote_chown(uploader, path, &owner)
+        });
+    });
+}
+
+/// 对属性对话框当前的路径执行修改，成功后刷新显示的属性
+fn change_remote_attrs(
+    ui_handle: &slint::Weak<AppWindow>,
+    state: &Arc<Mutex<RemoteState>>,
+    change: impl FnOnce(&SshUploader, &str) -> anyhow::Result<()>,
+) {
+    let s = lock(state);
+    let (uploader, path) = match (&s.uploader, &s.properties_path) {
+        (Some(u), Some(p)) => (u, p),
+        _ => return,
+    };
+    let result = change(uploader, path).and_then(|_| remote_fs::stat(uploader, path));
+    drop(s);
+
+    if let Some(ui) = ui_handle.upgrade() {
+        match result {
+            Ok(stat) => {
+                show_remote_stat(&ui, &stat);
+                ui.set_properties_error(SharedString::new());
+            }
+            Err(e) => ui.set_properties_error(format!("{:#}", e).into()),
+        }
+    }
+}
+
+fn set_clipboard(
+    state: &Arc<Mutex<RemoteState>>,
+    ui_handle: &slint::Weak<AppWindow>,
+    cut: bool,
+) {
+    let mut s = lock(state);
+    let current = s.current_path.clone();
+    let paths: Vec<(String, bool)> = s
+        .selected_indices
+        .iter()
+        .filter_map(|&i| s.cached_entries.get(i))
+        .map(|e| {
+            let path = remote_path::join(&current, &e.name);
+            (path, e.is_dir)
+        })
+        .collect();
+    if paths.is_empty() {
+        return;
+    }
+    s.clipboard = paths;
+    s.clipboard_cut = cut;
+    let count = s.clipboard.len() as i32;
+    drop(s);
+
+    if let Some(ui) = ui_handle.upgrade() {
+        ui.set_remote_clipboard_count(count);
+    }
+}
+
 fn bind_remote_sort_changed(
     ui: &AppWindow,
     state: Arc<Mutex<RemoteState>>,
@@ -470,7 +1441,7 @@ fn bind_remote_sort_changed(
     let ui_handle = ui.as_weak();
     ui.on_remote_sort_changed(move |field| {
         let current = {
-            let mut s = state.lock().unwrap();
+            let mut s = lock(&state);
             if s.sort_field == field.as_str() {
                 s.sort_ascending = !s.sort_ascending;
             } else {
@@ -490,7 +1461,7 @@ fn bind_remote_file_clicked_ex(
     let ui_handle = ui.as_weak();
     ui.on_remote_file_clicked_ex(move |index, ctrl, shift| {
         if let Some(ui) = ui_handle.upgrade() {
-            let mut s = state.lock().unwrap();
+            let mut s = lock(&state);
             let idx = index as usize;
             let total = s.cached_entries.len();
             if idx >= total {
@@ -528,11 +1499,15 @@ fn bind_remote_file_clicked_ex(
             }
 
             let selected = s.selected_indices.clone();
-            let entries = s.cached_entries.clone();
             let sel_count = selected.len() as i32;
+            let ui_entries = remote_entries_to_ui(
+                &s.cached_entries,
+                &selected,
+                s.visible_count,
+                s.name_filter().as_ref(),
+            );
             drop(s);
 
-            let ui_entries = remote_entries_to_ui(&entries, &selected);
             ui.set_remote_files(ModelRc::new(VecModel::from(ui_entries)));
             ui.set_remote_selected_count(sel_count);
         }
@@ -546,7 +1521,7 @@ fn bind_remote_filter_changed(
     let ui_handle = ui.as_weak();
     ui.on_remote_filter_changed(move |text| {
         let current = {
-            let mut s = state.lock().unwrap();
+            let mut s = lock(&state);
             s.filter_text = text.to_string();
             s.selected_indices.clear();
             s.current_path.clone()
@@ -554,3 +1529,372 @@ fn bind_remote_filter_changed(
         refresh_remote_dir(&state, &ui_handle, &current);
     });
 }
+
+fn bind_remote_filter_mode_changed(
+    ui: &AppWindow,
+    state: Arc<Mutex<RemoteState>>,
+) {
+    let ui_handle = ui.as_weak();
+    ui.on_remote_filter_mode_changed(move |mode| {
+        let current = {
+            let mut s = lock(&state);
+            s.filter_mode = FilterMode::from_key(mode.as_str());
+            s.selected_indices.clear();
+            s.current_path.clone()
+        };
+        refresh_remote_dir(&state, &ui_handle, &current);
+    });
+}
+
+fn bind_remote_quick_filter_changed(
+    ui: &AppWindow,
+    state: Arc<Mutex<RemoteState>>,
+) {
+    let ui_handle = ui.as_weak();
+    ui.on_remote_quick_filter_changed(move |key| {
+        let current = {
+            let mut s = lock(&state);
+            s.quick_filter = QuickFilter::from_key(key.as_str());
+            s.selected_indices.clear();
+            s.current_path.clone()
+        };
+        refresh_remote_dir(&state, &ui_handle, &current);
+    });
+}
+
+fn bind_remote_show_hidden_changed(
+    ui: &AppWindow,
+    state: Arc<Mutex<RemoteState>>,
+) {
+    let ui_handle = ui.as_weak();
+    ui.on_remote_show_hidden_changed(move |show| {
+        let current = {
+            let mut s = lock(&state);
+            s.show_hidden = show;
+            s.selected_indices.clear();
+            s.current_path.clone()
+        };
+        refresh_remote_dir(&state, &ui_handle, &current);
+    });
+}
+
+fn bind_remote_deep_search_changed(
+    ui: &AppWindow,
+    state: Arc<Mutex<RemoteState>>,
+) {
+    let ui_handle = ui.as_weak();
+    ui.on_remote_deep_search_changed(move |on| {
+        let current = {
+            let mut s = lock(&state);
+            s.deep_search = on;
+            s.selected_indices.clear();
+            s.current_path.clone()
+        };
+        refresh_remote_dir(&state, &ui_handle, &current);
+    });
+}
+
+/// 已读取的远程文本文件：完整路径和原始内容，切换编码时重新解码
+type LoadedText = Rc<RefCell<Option<(String, Vec<u8>)>>>;
+
+/// 同 `LoadedText`，供后台读取完成后跨线程写入
+type SharedText = Arc<Mutex<Option<(String, Vec<u8>)>>>;
+
+fn bind_remote_preview(
+    ui: &AppWindow,
+    config: Arc<Mutex<AppConfig>>,
+    state: Arc<Mutex<RemoteState>>,
+) {
+    let previewing: SharedText = Arc::new(Mutex::new(None));
+    // 每次预览递增，读取完成时已切换到其他文件则丢弃结果
+    let request = Arc::new(AtomicUsize::new(0));
+
+    let ui_handle = ui.as_weak();
+    let st = state.clone();
+    let previewing_clone = previewing.clone();
+    ui.on_preview_remote(move |index| {
+        let ui = match ui_handle.upgrade() {
+            Some(ui) => ui,
+            None => return,
+        };
+        let max_bytes = lock(&config).preview_max_bytes;
+        lock(&previewing_clone).take();
+        let id = request.fetch_add(1, Ordering::SeqCst) + 1;
+
+        let (entry, full_path, encoding) = {
+            let s = lock(&st);
+            if s.uploader.is_none() {
+                return;
+            }
+            let entry = match s.cached_entries.get(index as usize) {
+                Some(e) if !e.is_dir => e.clone(),
+                _ => return,
+            };
+            let full_path = remote_path::join(&s.current_path, &entry.name);
+            let encoding = s.file_encodings.get(&full_path).copied().unwrap_or_default();
+            (entry, full_path, encoding)
+        };
+
+        ui.set_preview_title(SharedString::from(&entry.name));
+        ui.set_preview_is_image(false);
+        ui.set_preview_image(Image::default());
+
+        if entry.size > max_bytes {
+            ui.set_preview_text(SharedString::from(format!(
+                "文件过大 ({})，超过预览上限 {}",
+                format_size(entry.size, false),
+                format_size(max_bytes, false),
+            )));
+            return;
+        }
+        ui.set_preview_text("正在读取...".into());
+
+        let st = st.clone();
+        let ui_h = ui_handle.clone();
+        let previewing = previewing_clone.clone();
+        let request = request.clone();
+        thread::spawn(move || {
+            let result = {
+                let s = lock(&st);
+                match &s.uploader {
+                    Some(uploader) => remote_fs::read_file_bytes(uploader, &full_path, max_bytes),
+                    None => return,
+                }
+            };
+
+            let _ = slint::invoke_from_event_loop(move || {
+                if request.load(Ordering::SeqCst) != id {
+                    return;
+                }
+                let ui = match ui_h.upgrade() {
+                    Some(ui) => ui,
+                    None => return,
+                };
+                let bytes = match result {
+                    Ok(b) => b,
+                    Err(e) => {
+                        ui.set_preview_text(SharedString::from(format!("预览失败: {}", e)));
+                        return;
+                    }
+                };
+
+                if explorer_service::is_previewable_image(&entry.name) {
+                    match load_preview_image(&entry.name, &bytes) {
+                        Some(img) => {
+                            ui.set_preview_image(img);
+                            ui.set_preview_is_image(true);
+                            ui.set_preview_text(SharedString::new());
+                        }
+                        None => ui.set_preview_text("图片解码失败".into()),
+                    }
+                    return;
+                }
+
+                ui.set_preview_encoding(SharedString::from(encoding.label()));
+                show_text_preview(&ui, &bytes, encoding);
+                *lock(&previewing) = Some((full_path, bytes));
+            });
+        });
+    });
+
+    let ui_handle = ui.as_weak();
+    ui.on_preview_encoding_changed(move |label| {
+        let ui = match ui_handle.upgrade() {
+            Some(ui) => ui,
+            None => return,
+        };
+        let encoding = TextEncoding::from_label(&label);
+        if let Some((path, bytes)) = lock(&previewing).as_ref() {
+            lock(&state).file_encodings.insert(path.clone(), encoding);
+            show_text_preview(&ui, bytes, encoding);
+        }
+    });
+}
+
+/// 图片写入唯一的临时文件后解码，读取完立即删除
+fn load_preview_image(name: &str, bytes: &[u8]) -> Option<Image> {
+    let ext = name.rsplit('.').next().unwrap_or("png");
+    let (path, mut file) = path_utils::create_unique_temp_file("flick_preview", ext).ok()?;
+    let written = std::io::Write::write_all(&mut file, bytes);
+    drop(file);
+    let loaded = written.ok().and_then(|_| Image::load_from_path(&path).ok());
+    let _ = std::fs::remove_file(&path);
+    loaded
+}
+
+fn show_text_preview(ui: &AppWindow, bytes: &[u8], encoding: TextEncoding) {
+    match explorer_service::decode_text_preview(bytes, encoding) {
+        Some(text) => ui.set_preview_text(SharedString::from(text)),
+        None => ui.set_preview_text(SharedString::from(format!(
+            "二进制文件 ({})，无法以文本预览",
+            format_size(bytes.len() as u64, false),
+        ))),
+    }
+}
+
+/// 双击远程文本文件打开编辑器，保存时原子写回
+fn bind_remote_editor(
+    ui: &AppWindow,
+    config: Arc<Mutex<AppConfig>>,
+    state: Arc<Mutex<RemoteState>>,
+) {
+    let editing: LoadedText = Rc::new(RefCell::new(None));
+
+    let ui_handle = ui.as_weak();
+    let st = state.clone();
+    let editing_clone = editing.clone();
+    ui.on_remote_edit_file(move |index| {
+        let ui = match ui_handle.upgrade() {
+            Some(ui) => ui,
+            None => return,
+        };
+        let max_bytes = lock(&config).preview_max_bytes;
+
+        let s = lock(&st);
+        let uploader = match &s.uploader {
+            Some(u) => u,
+            None => return,
+        };
+        let entry = match s.cached_entries.get(index as usize) {
+            Some(e) if !e.is_dir => e.clone(),
+            _ => return,
+        };
+        let full_path = remote_path::join(&s.current_path, &entry.name);
+
+        if entry.size > max_bytes {
+            ui.set_global_error(SharedString::from(format!(
+                "文件过大 ({})，超过编辑上限 {}",
+                format_size(entry.size, false),
+                format_size(max_bytes, false),
+            )));
+            return;
+        }
+
+        let bytes = match remote_fs::read_file_bytes(uploader, &full_path, max_bytes) {
+            Ok(b) => b,
+            Err(e) => {
+                ui.set_global_error(SharedString::from(format!("读取文件失败: {}", e)));
+                return;
+            }
+        };
+        let encoding = s.file_encodings.get(&full_path).copied().unwrap_or_default();
+        drop(s);
+
+        // 有解码错误时拒绝打开，可先在预览中换用其他编码
+        let text = match explorer_service::decode_text_for_edit(&bytes, encoding) {
+            Ok(t) => t,
+            Err(e) => {
+                ui.set_global_error(SharedString::from(format!("无法编辑 {}: {}", entry.name, e)));
+                return;
+            }
+        };
+
+        *editing_clone.borrow_mut() = Some((full_path, bytes));
+        ui.set_remote_editor_title(SharedString::from(&entry.name));
+        ui.set_remote_editor_encoding(SharedString::from(encoding.label()));
+        ui.set_remote_editor_text(SharedString::from(text));
+        ui.set_show_remote_editor(true);
+    });
+
+    // 切换编码会按新编码重新解码原始内容，未保存的修改随之丢弃
+    let ui_handle = ui.as_weak();
+    let st = state.clone();
+    let editing_clone = editing.clone();
+    ui.on_remote_editor_encoding_changed(move |label| {
+        let ui = match ui_handle.upgrade() {
+            Some(ui) => ui,
+            None => return,
+        };
+        let encoding = TextEncoding::from_label(&label);
+        if let Some((path, bytes)) = editing_clone.borrow().as_ref() {
+            lock(&st).file_encodings.insert(path.clone(), encoding);
+            // 解码失败时保留当前内容，保存会被拒绝，直到换回能正确解码的编码
+            match explorer_service::decode_text_for_edit(bytes, encoding) {
+                Ok(text) => ui.set_remote_editor_text(SharedString::from(text)),
+                Err(e) => ui.set_global_error(SharedString::from(format!("{}", e))),
+            }
+        }
+    });
+
+    let ui_handle = ui.as_weak();
+    ui.on_remote_editor_save(move |text| {
+        let ui = match ui_handle.upgrade() {
+            Some(ui) => ui,
+            None => return,
+        };
+        let (path, original) = match editing.borrow().as_ref() {
+            Some((p, bytes)) => (p.clone(), bytes.clone()),
+            None => return,
+        };
+
+        let (result, current) = {
+            let s = lock(&state);
+            let encoding = s.file_encodings.get(&path).copied().unwrap_or_default();
+            // 原内容按当前编码有解码错误时不能保存，否则会把未载入的字节改写掉
+            let result = explorer_service::decode_text_for_edit(&original, encoding)
+                .and_then(|_| explorer_service::encode_text(&text, encoding))
+                .and_then(|bytes| match &s.uploader {
+                    Some(u) => remote_fs::write_file_bytes(u, &path, &bytes),
+                    None => Err(anyhow::anyhow!("未连接")),
+                });
+            (result, s.current_path.clone())
+        };
+
+        match result {
+            Ok(()) => {
+                editing.borrow_mut().take();
+                ui.set_show_remote_editor(false);
+                refresh_remote_dir(&state, &ui_handle, &current);
+            }
+            Err(e) => ui.set_global_error(SharedString::from(format!("保存失败: {}", e))),
+        }
+    });
+}
+
+/// 命令面板单条命令的最长执行时间，超时即中止以释放连接
+const REMOTE_COMMAND_TIMEOUT: Duration = Duration::from_secs(120);
+
+/// 在当前远程目录下执行命令，输出显示在命令面板
+fn bind_remote_command(ui: &AppWindow, state: Arc<Mutex<RemoteState>>) {
+    let ui_handle = ui.as_weak();
+    ui.on_remote_run_command(move |command| {
+        let command = command.trim().to_string();
+        if command.is_empty() {
+            return;
+        }
+        if let Some(ui) = ui_handle.upgrade() {
+            ui.set_remote_command_running(true);
+            ui.set_remote_command_output(SharedString::from(format!("$ {}\n执行中...", command)));
+        }
+
+        let ui_h = ui_handle.clone();
+        let st = state.clone();
+        thread::spawn(move || {
+            let s = lock(&st);
+            let output = match &s.uploader {
+                Some(uploader) => {
+                    let full = format!(
+                        "cd {} && {}",
+                        remote_fs::escape_shell_arg(&s.current_path),
+                        command
+                    );
+                    match remote_fs::remote_exec_merged(uploader, &full, REMOTE_COMMAND_TIMEOUT) {
+                        Ok((output, code)) => explorer_service::format_command_output(
+                            &command, &output, "", code,
+                        ),
+                        Err(e) => format!("$ {}\n执行失败: {}", command, e),
+                    }
+                }
+                None => "未连接".to_string(),
+            };
+            drop(s);
+
+            let _ = slint::invoke_from_event_loop(move || {
+                if let Some(ui) = ui_h.upgrade() {
+                    ui.set_remote_command_running(false);
+                    ui.set_remote_command_output(SharedString::from(output));
+                }
+            });
+        });
+    });
+}
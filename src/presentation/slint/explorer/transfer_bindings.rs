@@ -1,12 +1,28 @@
-use slint::{ComponentHandle, ModelRc, SharedString, Timer, TimerMode, VecModel};
-use std::path::Path;
+use slint::winit_030::{winit, EventResult, WinitWindowAccessor};
+use slint::{ComponentHandle, Model, ModelRc, SharedString, Timer, TimerMode, VecModel};
+use std::cell::RefCell;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
 use std::sync::{Arc, Mutex};
 use std::thread;
+use std::time::Instant;
 
 use crate::app::services::transfer_service;
-use crate::infra::ssh::{FileTransfer, SshUploader};
-use crate::domain::transfer::{Direction, TransferQueue, TransferStatus};
+use crate::domain::config::{AppConfig, ServerConfig};
+use crate::domain::history::HistoryEntry;
+use crate::domain::ports::HistoryRepository;
+use crate::infra::ssh::{ConnectionPool, FileTransfer, PooledConnection, SshUploader};
+use crate::infra::{clipboard_image, local_fs, notifier, remote_fs};
+use crate::shared::format::{self, format_eta};
+use crate::shared::panic_guard::{catch_panic, lock};
+use crate::shared::path_utils;
+use crate::shared::remote_path;
+use crate::shared::throttle::Throttle;
+use crate::domain::transfer::{
+    CompressionStats, ConflictPolicy, DirArchive, Direction, StartDecision, TransferQueue, TransferStatus, TransferTask,
+};
 use crate::AppWindow;
+use crate::HistoryItem;
 use crate::TransferEntry;
 
 use super::local_bindings::{self, LocalState};
@@ -14,25 +30,89 @@ use super::remote_bindings::{self, RemoteState};
 
 pub(crate) fn bind(
     ui: &AppWindow,
+    config: Arc<Mutex<AppConfig>>,
     local_state: Arc<Mutex<LocalState>>,
     remote_state: Arc<Mutex<RemoteState>>,
     transfer_queue: Arc<Mutex<TransferQueue>>,
-) {
+    history: Arc<dyn HistoryRepository + Send + Sync>,
+    pool: Arc<ConnectionPool>,
+) -> Timer {
     bind_upload_selected(
         ui,
         local_state.clone(),
         remote_state.clone(),
         transfer_queue.clone(),
+        history.clone(),
+        pool.clone(),
     );
     bind_download_selected(
         ui,
+        config.clone(),
         local_state.clone(),
         remote_state.clone(),
         transfer_queue.clone(),
+        history.clone(),
+        pool.clone(),
+    );
+    bind_sync_selected(
+        ui,
+        local_state.clone(),
+        remote_state.clone(),
+        transfer_queue.clone(),
+        history.clone(),
+        pool.clone(),
+    );
+    bind_upload_clipboard(
+        ui,
+        remote_state.clone(),
+        transfer_queue.clone(),
+        history.clone(),
+        pool.clone(),
+    );
+    bind_files_dropped(
+        ui,
+        remote_state.clone(),
+        transfer_queue.clone(),
+        history.clone(),
+        pool.clone(),
     );
     bind_clear_completed_transfers(ui, transfer_queue.clone());
-    bind_retry_transfer(ui, local_state, remote_state, transfer_queue.clone());
-    start_transfer_queue_sync(ui, transfer_queue);
+    bind_pause_resume_queue(ui, transfer_queue.clone());
+    bind_retry_transfer(
+        ui,
+        local_state.clone(),
+        remote_state.clone(),
+        transfer_queue.clone(),
+        history.clone(),
+        pool.clone(),
+    );
+    bind_retry_all_failed(
+        ui,
+        config.clone(),
+        local_state.clone(),
+        remote_state.clone(),
+        transfer_queue.clone(),
+        history.clone(),
+        pool.clone(),
+    );
+    bind_resume_transfers(
+        ui,
+        config.clone(),
+        local_state.clone(),
+        remote_state.clone(),
+        transfer_queue.clone(),
+        history.clone(),
+        pool.clone(),
+    );
+    bind_transfer_history(
+        ui,
+        local_state,
+        remote_state,
+        transfer_queue.clone(),
+        history,
+        pool,
+    );
+    start_transfer_queue_sync(ui, config, transfer_queue)
 }
 
 fn bind_upload_selected(
@@ -40,137 +120,468 @@ fn bind_upload_selected(
     local_state: Arc<Mutex<LocalState>>,
     remote_state: Arc<Mutex<RemoteState>>,
     queue: Arc<Mutex<TransferQueue>>,
+    history: Arc<dyn HistoryRepository + Send + Sync>,
+    pool: Arc<ConnectionPool>,
 ) {
+    // 选中项包含目录时先预演，确认后再上传
     let ui_handle = ui.as_weak();
+    let ls_clone = local_state.clone();
+    let rs_clone = remote_state.clone();
     ui.on_upload_selected(move || {
-        let (local_files, remote_path, uploader_opt) = {
-            let ls = local_state.lock().unwrap();
-            let rs = remote_state.lock().unwrap();
-
-            let files: Vec<_> = ls.selected_indices
-                .iter()
-                .filter_map(|&i| ls.cached_entries.get(i))
-                .map(|e| (e.path.clone(), e.name.clone(), e.size, e.is_dir))
-                .collect();
+        let ui = match ui_handle.upgrade() {
+            Some(ui) => ui,
+            None => return,
+        };
+        let local_files = selected_local_files(&ls_clone);
+        if !local_files.iter().any(|(_, _, _, is_dir)| *is_dir) {
+            ui.invoke_upload_selected_confirmed();
+            return;
+        }
 
-            (files, rs.current_path.clone(), rs.uploader.as_ref().map(|u| u.config().clone()))
+        let (file_count, total_bytes) = {
+            let rs = lock(&rs_clone);
+            let uploader = match rs.uploader.as_ref() {
+                Some(u) => u,
+                None => return,
+            };
+            let mut count = 0usize;
+            let mut bytes = 0u64;
+            for (local_path, file_name, size, is_dir) in &local_files {
+                if !*is_dir {
+                    count += 1;
+                    bytes += size;
+                    continue;
+                }
+                let remote_dir = Path::new(&rs.current_path).join(file_name);
+                match uploader.plan_upload_dir(local_path, &remote_dir) {
+                    Ok(plan) => {
+                        count += plan.len();
+                        bytes += plan.iter().map(|p| p.size).sum::<u64>();
+                    }
+                    Err(e) => {
+                        ui.set_global_error(format!("预演上传失败: {}", e).into());
+                        return;
+                    }
+                }
+            }
+            (count, bytes)
         };
 
-        if local_files.is_empty() {
+        ui.set_confirm_title(SharedString::from("确认上传"));
+        ui.set_confirm_message(SharedString::from(format!(
+            "将上传 {} 个文件，共 {}，是否继续？",
+            file_count,
+            super::format_size(total_bytes, false)
+        )));
+        ui.set_confirm_action(SharedString::from("upload-selected"));
+        ui.set_show_confirm(true);
+    });
+
+    let ui_handle = ui.as_weak();
+    ui.on_upload_selected_confirmed(move || {
+        let local_files = selected_local_files(&local_state);
+        let delete_source = ui_handle.upgrade().is_some_and(|ui| ui.get_transfer_move());
+        enqueue_uploads(&ui_handle, &remote_state, &queue, &history, &pool, local_files, delete_source);
+    });
+}
+
+fn selected_local_files(local_state: &Arc<Mutex<LocalState>>) -> Vec<(PathBuf, String, u64, bool)> {
+    let ls = lock(local_state);
+    ls.selected_indices
+        .iter()
+        .filter_map(|&i| ls.cached_entries.get(i))
+        .map(|e| (e.path.clone(), e.name.clone(), e.size, e.is_dir))
+        .collect()
+}
+
+/// 从系统拖放的文件路径创建上传任务
+fn bind_files_dropped(
+    ui: &AppWindow,
+    remote_state: Arc<Mutex<RemoteState>>,
+    queue: Arc<Mutex<TransferQueue>>,
+    history: Arc<dyn HistoryRepository + Send + Sync>,
+    pool: Arc<ConnectionPool>,
+) {
+    let ui_handle = ui.as_weak();
+    ui.on_files_dropped(move |paths| {
+        let connected = lock(&remote_state).uploader.is_some();
+        if !connected {
+            if let Some(ui) = ui_handle.upgrade() {
+                ui.set_global_error("请先连接远程服务器，再拖放文件上传".into());
+            }
             return;
         }
 
-        let uploader_config = match uploader_opt {
-            Some(cfg) => cfg,
+        let local_files: Vec<_> = paths
+            .iter()
+            .filter_map(|p| {
+                let path = PathBuf::from(p.as_str());
+                let meta = std::fs::metadata(&path).ok()?;
+                let name = path.file_name()?.to_string_lossy().to_string();
+                let size = if meta.is_dir() { 0 } else { meta.len() };
+                Some((path, name, size, meta.is_dir()))
+            })
+            .collect();
+
+        enqueue_uploads(&ui_handle, &remote_state, &queue, &history, &pool, local_files, false);
+    });
+
+    // winit 每个文件单独发一次 DroppedFile 事件，稍作合并后一次性回调
+    let pending: Rc<RefCell<Vec<SharedString>>> = Rc::new(RefCell::new(Vec::new()));
+    let ui_handle = ui.as_weak();
+    ui.window().on_winit_window_event(move |_, event| {
+        if let winit::event::WindowEvent::DroppedFile(path) = event {
+            let first = pending.borrow().is_empty();
+            pending
+                .borrow_mut()
+                .push(SharedString::from(path.to_string_lossy().as_ref()));
+            if first {
+                let pending = pending.clone();
+                let ui_handle = ui_handle.clone();
+                Timer::single_shot(std::time::Duration::from_millis(100), move || {
+                    let paths: Vec<SharedString> = pending.borrow_mut().drain(..).collect();
+                    if let Some(ui) = ui_handle.upgrade() {
+                        ui.invoke_files_dropped(ModelRc::new(VecModel::from(paths)));
+                    }
+                });
+            }
+        }
+        EventResult::Propagate
+    });
+}
+
+/// 把剪贴板中的图片存为带时间戳的临时 PNG 并上传到当前远程目录，成功后删除临时文件
+fn bind_upload_clipboard(
+    ui: &AppWindow,
+    remote_state: Arc<Mutex<RemoteState>>,
+    queue: Arc<Mutex<TransferQueue>>,
+    history: Arc<dyn HistoryRepository + Send + Sync>,
+    pool: Arc<ConnectionPool>,
+) {
+    let ui_handle = ui.as_weak();
+    ui.on_upload_clipboard(move || {
+        let ui = match ui_handle.upgrade() {
+            Some(ui) => ui,
             None => return,
         };
+        if lock(&remote_state).uploader.is_none() {
+            return;
+        }
+        let name = transfer_service::clipboard_image_name(&chrono::Local::now());
+        let dir = std::env::temp_dir().join("flick_clipboard");
+        match clipboard_image::save_png(&dir, &name) {
+            Ok(Some(path)) => {
+                let size = std::fs::metadata(&path).map(|m| m.len()).unwrap_or(0);
+                let files = vec![(path, name, size, false)];
+                enqueue_uploads(&ui_handle, &remote_state, &queue, &history, &pool, files, true);
+            }
+            Ok(None) => ui.set_remote_status("剪贴板中没有图片".into()),
+            Err(e) => ui.set_global_error(format!("{:#}", e).into()),
+        }
+    });
+}
 
-        for (local_path, file_name, size, is_dir) in local_files {
-            let remote_file_path = if remote_path.ends_with('/') {
-                format!("{}{}", remote_path, file_name)
-            } else {
-                format!("{}/{}", remote_path, file_name)
-            };
+/// 为一组本地文件/目录创建上传任务，目标为当前远程目录；`delete_source` 时上传成功后删除本地源
+fn enqueue_uploads(
+    ui_handle: &slint::Weak<AppWindow>,
+    remote_state: &Arc<Mutex<RemoteState>>,
+    queue: &Arc<Mutex<TransferQueue>>,
+    history: &Arc<dyn HistoryRepository + Send + Sync>,
+    pool: &Arc<ConnectionPool>,
+    local_files: Vec<(PathBuf, String, u64, bool)>,
+    delete_source: bool,
+) {
+    if local_files.is_empty() {
+        return;
+    }
+    let (remote_path, uploader_config) = {
+        let rs = lock(remote_state);
+        match rs.uploader.as_ref() {
+            Some(u) => (rs.current_path.clone(), u.config().clone()),
+            None => return,
+        }
+    };
 
-            let task_id = {
-                let mut q = queue.lock().unwrap();
-                q.enqueue(
-                    Direction::Upload,
-                    local_path.clone(),
-                    remote_file_path.clone(),
-                    file_name.clone(),
-                    size,
-                )
-            };
+    let batch_id = lock(queue).new_batch();
+    let mut jobs = Vec::new();
+    for (local_path, file_name, size, is_dir) in local_files.iter().cloned() {
+        let remote_file_path = remote_path::join(&remote_path, &file_name);
+
+        let task_id = {
+            let mut q = lock(queue);
+            let id = q.enqueue(
+                Direction::Upload,
+                local_path.clone(),
+                remote_file_path.clone(),
+                file_name.clone(),
+                size,
+                uploader_config.name.clone(),
+            );
+            if is_dir {
+                q.mark_dir(id);
+            }
+            if delete_source {
+                q.mark_move(id);
+            }
+            q.mark_batch(id, batch_id);
+            id
+        };
+        jobs.push((task_id, local_path, remote_file_path, is_dir));
+    }
 
+    let queue = queue.clone();
+    let history = history.clone();
+    let pool = pool.clone();
+    let remote_state = remote_state.clone();
+    let ui_handle = ui_handle.clone();
+    thread::spawn(move || {
+        // 统计目录大小、查询远程剩余空间可能较慢，放在后台线程且不占用浏览连接
+        let ids: Vec<usize> = jobs.iter().map(|job| job.0).collect();
+        let space_check = pool
+            .acquire(&uploader_config)
+            .map_or(Ok(()), |u| check_remote_space(&u, &remote_path, &local_files));
+        if !space_check_passed(&queue, &ui_handle, &ids, space_check) {
+            return;
+        }
+
+        for (task_id, local_path, remote_file_path, is_dir) in jobs {
             let queue_clone = queue.clone();
+            let history = history.clone();
+            let pool = pool.clone();
             let cfg = uploader_config.clone();
             let rs_clone = remote_state.clone();
             let ui_h = ui_handle.clone();
             let rp = remote_path.clone();
             thread::spawn(move || {
-                let mut uploader = match SshUploader::connect(&cfg) {
-                    Ok(u) => u,
-                    Err(e) => {
-                        let mut q = queue_clone.lock().unwrap();
-                        q.mark_failed(task_id, format!("连接失败: {}", e));
-                        return;
+                if !wait_until_resumed(&queue_clone, task_id) {
+                    return;
+                }
+                let result = transfer_with_retries(&queue_clone, &pool, &cfg, task_id, |uploader, progress_cb| {
+                    if is_dir {
+                        uploader.upload_dir(&local_path, Path::new(&remote_file_path), progress_cb)
+                    } else {
+                        uploader.upload(&local_path, Path::new(&remote_file_path), progress_cb)
                     }
-                };
+                });
 
-                let progress_cb = |progress: f32| {
-                    let q_clone = queue_clone.clone();
+                let ok = result.is_ok();
+                finish_task(&queue_clone, &history, task_id, &cfg, is_dir, result);
+                if ok {
+                    let moved = delete_moved_source(&queue_clone, &pool, &cfg, task_id);
                     let _ = slint::invoke_from_event_loop(move || {
-                        let mut q = q_clone.lock().unwrap();
-                        q.update_progress(task_id, progress);
+                        remote_bindings::refresh_remote_dir(&rs_clone, &ui_h, &rp);
+                        remote_bindings::refresh_disk_usage(&rs_clone, &ui_h);
+                        if let Some(ui) = ui_h.upgrade().filter(|_| moved) {
+                            ui.invoke_local_refresh();
+                        }
                     });
-                };
-                let result = if is_dir {
-                    uploader.upload_dir(
-                        &local_path,
-                        Path::new(&remote_file_path),
-                        progress_cb,
-                    )
-                } else {
-                    uploader.upload(
-                        &local_path,
-                        Path::new(&remote_file_path),
+                }
+            });
+        }
+    });
+}
+
+/// 剩余空间检查未通过时把整批任务标记为失败并提示，返回是否可以开始传输
+fn space_check_passed(
+    queue: &Arc<Mutex<TransferQueue>>,
+    ui_handle: &slint::Weak<AppWindow>,
+    task_ids: &[usize],
+    check: anyhow::Result<()>,
+) -> bool {
+    let error = match check {
+        Ok(()) => return true,
+        Err(e) => e.to_string(),
+    };
+    {
+        let mut q = lock(queue);
+        for &id in task_ids {
+            q.mark_failed(id, error.clone());
+        }
+    }
+    let ui_h = ui_handle.clone();
+    let _ = slint::invoke_from_event_loop(move || {
+        if let Some(ui) = ui_h.upgrade() {
+            ui.set_global_error(error.into());
+        }
+    });
+    false
+}
+
+/// 上传前确认远程目录剩余空间足够，df 不可用时不阻止上传
+fn check_remote_space(
+    uploader: &SshUploader,
+    remote_dir: &str,
+    local_files: &[(PathBuf, String, u64, bool)],
+) -> anyhow::Result<()> {
+    let available = match remote_fs::remote_free_space(uploader, remote_dir) {
+        Ok(n) => n,
+        Err(_) => return Ok(()),
+    };
+    let required = local_files
+        .iter()
+        .map(|(path, _, size, is_dir)| if *is_dir { local_fs::dir_size(path) } else { *size })
+        .sum();
+    transfer_service::ensure_free_space("远程", required, available)
+}
+
+/// 把选中的本地目录增量同步到当前远程目录下的同名目录，完成后在队列中显示传输/跳过数量
+fn bind_sync_selected(
+    ui: &AppWindow,
+    local_state: Arc<Mutex<LocalState>>,
+    remote_state: Arc<Mutex<RemoteState>>,
+    queue: Arc<Mutex<TransferQueue>>,
+    history: Arc<dyn HistoryRepository + Send + Sync>,
+    pool: Arc<ConnectionPool>,
+) {
+    let ui_handle = ui.as_weak();
+    ui.on_sync_selected(move || {
+        let ui = match ui_handle.upgrade() {
+            Some(ui) => ui,
+            None => return,
+        };
+        let mirror_delete = ui.get_sync_mirror_delete();
+        let dirs: Vec<_> = selected_local_files(&local_state)
+            .into_iter()
+            .filter(|(_, _, _, is_dir)| *is_dir)
+            .collect();
+        if dirs.is_empty() {
+            ui.set_global_error("请先在本地面板选择要同步的目录".into());
+            return;
+        }
+        let (current, uploader_config) = {
+            let rs = lock(&remote_state);
+            match rs.uploader.as_ref() {
+                Some(u) => (rs.current_path.clone(), u.config().clone()),
+                None => return,
+            }
+        };
+
+        for (local_dir, dir_name, size, _) in dirs {
+            let remote_dir = remote_path::join(&current, &dir_name);
+            let task_id = {
+                let mut q = lock(&queue);
+                let id = q.enqueue(
+                    Direction::Upload,
+                    local_dir.clone(),
+                    remote_dir.clone(),
+                    dir_name,
+                    size,
+                    uploader_config.name.clone(),
+                );
+                q.mark_dir(id);
+                id
+            };
+
+            let queue_clone = queue.clone();
+            let history = history.clone();
+            let pool = pool.clone();
+            let cfg = uploader_config.clone();
+            let rs_clone = remote_state.clone();
+            let ui_h = ui_handle.clone();
+            let rp = current.clone();
+            thread::spawn(move || {
+                if !wait_until_resumed(&queue_clone, task_id) {
+                    return;
+                }
+                // 与其他传输一样按退避间隔重试；已同步的文件会在重试时被跳过
+                let sync = |uploader: &mut SshUploader, progress_cb: &dyn Fn(f32)| {
+                    let report = uploader.sync_dir(
+                        &local_dir,
+                        Path::new(&remote_dir),
+                        mirror_delete,
                         progress_cb,
-                    )
+                    )?;
+                    // 先取走传输方式和压缩统计，和同步结果一起写入说明
+                    let note: Vec<String> = std::iter::once(report.summary())
+                        .chain(uploader.take_method_note())
+                        .chain(uploader.take_compression_stats().map(compression_note))
+                        .collect();
+                    lock(&queue_clone).set_note(task_id, note.join("，"));
+                    Ok(())
                 };
+                let result = transfer_with_retries(&queue_clone, &pool, &cfg, task_id, sync);
 
-                match result {
-                    Ok(_) => {
-                        let mut q = queue_clone.lock().unwrap();
-                        q.mark_completed(task_id);
-                        let rs = rs_clone.clone();
-                        let uh = ui_h.clone();
-                        let path = rp.clone();
-                        let _ = slint::invoke_from_event_loop(move || {
-                            remote_bindings::refresh_remote_dir(&rs, &uh, &path);
-                        });
-                    }
-                    Err(e) => {
-                        let mut q = queue_clone.lock().unwrap();
-                        q.mark_failed(task_id, format!("{}", e));
-                    }
+                let ok = result.is_ok();
+                finish_task(&queue_clone, &history, task_id, &cfg, true, result);
+                if ok {
+                    let _ = slint::invoke_from_event_loop(move || {
+                        remote_bindings::refresh_remote_dir(&rs_clone, &ui_h, &rp);
+                        remote_bindings::refresh_disk_usage(&rs_clone, &ui_h);
+                    });
                 }
             });
         }
     });
 }
 
+/// 待下载的单个条目
+struct DownloadItem {
+    remote_path: String,
+    local_path: PathBuf,
+    file_name: String,
+    size: u64,
+    is_dir: bool,
+}
+
+/// 等待冲突对话框决定的下载批次
+struct PendingDownloads {
+    config: ServerConfig,
+    local_dir: PathBuf,
+    items: Vec<DownloadItem>,
+    delete_source: bool,
+    archive: DirArchive,
+}
+
+/// 下载条目最终写入的本地路径，按它检查冲突 (保留压缩包时目录下载写入 `.tar.gz`)
+fn download_target(item: &DownloadItem, archive: DirArchive) -> PathBuf {
+    if item.is_dir {
+        archive.output_path(&item.local_path)
+    } else {
+        item.local_path.clone()
+    }
+}
+
 fn bind_download_selected(
     ui: &AppWindow,
+    config: Arc<Mutex<AppConfig>>,
     local_state: Arc<Mutex<LocalState>>,
     remote_state: Arc<Mutex<RemoteState>>,
     queue: Arc<Mutex<TransferQueue>>,
+    history: Arc<dyn HistoryRepository + Send + Sync>,
+    pool: Arc<ConnectionPool>,
 ) {
+    // 本地已有同名文件时先询问覆盖 / 跳过 / 保留两者
+    let pending: Rc<RefCell<Option<PendingDownloads>>> = Rc::new(RefCell::new(None));
+
     let ui_handle = ui.as_weak();
+    let pending_clone = pending.clone();
+    let ls_clone = local_state.clone();
+    let queue_clone = queue.clone();
+    let history_clone = history.clone();
+    let pool_clone = pool.clone();
+    let config_clone = config.clone();
     ui.on_download_selected(move || {
-        let (remote_files, local_path, uploader_opt) = {
-            let rs = remote_state.lock().unwrap();
-            let ls = local_state.lock().unwrap();
+        let delete_source = ui_handle.upgrade().is_some_and(|ui| ui.get_transfer_move());
+        let (items, uploader_opt, local_dir) = {
+            let rs = lock(&remote_state);
+            let ls = lock(&ls_clone);
 
-            let files: Vec<_> = rs.selected_indices
+            let items: Vec<_> = rs.selected_indices
                 .iter()
                 .filter_map(|&i| rs.cached_entries.get(i))
-                .map(|e| {
-                    let remote_full_path = if rs.current_path.ends_with('/') {
-                        format!("{}{}", rs.current_path, e.name)
-                    } else {
-                        format!("{}/{}", rs.current_path, e.name)
-                    };
-                    (remote_full_path, e.name.clone(), e.size, e.is_dir)
+                .map(|e| DownloadItem {
+                    remote_path: remote_path::join(&rs.current_path, &e.name),
+                    local_path: ls.current_path.join(&e.name),
+                    file_name: e.name.clone(),
+                    size: e.size,
+                    is_dir: e.is_dir,
                 })
                 .collect();
 
-            let local_dir = ls.current_path.clone();
-
-            (files, local_dir, rs.uploader.as_ref().map(|u| u.config().clone()))
+            (items, rs.uploader.as_ref().map(|u| u.config().clone()), ls.current_path.clone())
         };
 
-        if remote_files.is_empty() {
+        if items.is_empty() {
             return;
         }
 
@@ -179,71 +590,222 @@ fn bind_download_selected(
             None => return,
         };
 
-        for (remote_file_path, file_name, size, is_dir) in remote_files {
-            let local_file_path = local_path.join(&file_name);
+        let archive = lock(&config_clone).dir_archive();
+        let conflicts: Vec<&str> = items
+            .iter()
+            .filter(|item| download_target(item, archive).exists())
+            .map(|item| item.file_name.as_str())
+            .collect();
+        if conflicts.is_empty() {
+            enqueue_downloads(
+                &ui_handle,
+                &ls_clone,
+                &queue_clone,
+                &history_clone,
+                &pool_clone,
+                &uploader_config,
+                &local_dir,
+                items,
+                delete_source,
+                archive,
+            );
+            return;
+        }
 
-            let task_id = {
-                let mut q = queue.lock().unwrap();
-                q.enqueue(
-                    Direction::Download,
-                    local_file_path.clone(),
-                    remote_file_path.clone(),
-                    file_name.clone(),
-                    size,
-                )
-            };
+        if let Some(ui) = ui_handle.upgrade() {
+            let shown: Vec<&str> = conflicts.iter().take(3).copied().collect();
+            let more = if conflicts.len() > shown.len() { " 等" } else { "" };
+            ui.set_conflict_message(SharedString::from(format!(
+                "本地已存在 {} 个同名项: {}{}",
+                conflicts.len(),
+                shown.join(", "),
+                more
+            )));
+            ui.set_conflict_action(SharedString::from("download"));
+            ui.set_show_conflict(true);
+        }
+        *pending_clone.borrow_mut() = Some(PendingDownloads {
+            config: uploader_config,
+            local_dir,
+            items,
+            delete_source,
+            archive,
+        });
+    });
 
+    let ui_handle = ui.as_weak();
+    let pending_clone = pending.clone();
+    ui.on_conflict_resolved(move |policy| {
+        let batch = match pending_clone.borrow_mut().take() {
+            Some(b) => b,
+            None => return,
+        };
+        let policy = match ConflictPolicy::parse(&policy) {
+            Some(p) => p,
+            None => return,
+        };
+        let items: Vec<DownloadItem> = batch
+            .items
+            .into_iter()
+            .filter_map(|mut item| {
+                if !download_target(&item, batch.archive).exists() {
+                    return Some(item);
+                }
+                match policy {
+                    ConflictPolicy::Overwrite => Some(item),
+                    ConflictPolicy::Skip => None,
+                    ConflictPolicy::KeepBoth => {
+                        item.local_path = if item.is_dir {
+                            // 目录与对应的压缩包都不能占用
+                            path_utils::keep_both_path_with(&item.local_path, true, |p| {
+                                p.exists() || batch.archive.output_path(p).exists()
+                            })
+                        } else {
+                            path_utils::keep_both_path(&item.local_path)
+                        };
+                        Some(item)
+                    }
+                }
+            })
+            .collect();
+        enqueue_downloads(
+            &ui_handle,
+            &local_state,
+            &queue,
+            &history,
+            &pool,
+            &batch.config,
+            &batch.local_dir,
+            items,
+            batch.delete_source,
+            batch.archive,
+        );
+    });
+
+    ui.on_conflict_cancelled(move || {
+        pending.borrow_mut().take();
+    });
+}
+
+/// 下载前确认本地磁盘剩余空间足够，远程目录大小用 du 统计
+fn check_local_space(
+    uploader: &SshUploader,
+    local_dir: &Path,
+    items: &[DownloadItem],
+) -> anyhow::Result<()> {
+    let available = match local_fs::available_space(local_dir) {
+        Some(n) => n,
+        None => return Ok(()),
+    };
+    let required = items
+        .iter()
+        .map(|item| {
+            if item.is_dir {
+                remote_fs::remote_dir_size(uploader, &item.remote_path).unwrap_or(0)
+            } else {
+                item.size
+            }
+        })
+        .sum();
+    transfer_service::ensure_free_space("本地", required, available)
+}
+
+/// 为一组远程文件/目录创建下载任务；`delete_source` 时下载成功后删除远程源
+#[allow(clippy::too_many_arguments)]
+fn enqueue_downloads(
+    ui_handle: &slint::Weak<AppWindow>,
+    local_state: &Arc<Mutex<LocalState>>,
+    queue: &Arc<Mutex<TransferQueue>>,
+    history: &Arc<dyn HistoryRepository + Send + Sync>,
+    pool: &Arc<ConnectionPool>,
+    uploader_config: &ServerConfig,
+    local_dir: &Path,
+    items: Vec<DownloadItem>,
+    delete_source: bool,
+    archive: DirArchive,
+) {
+    if items.is_empty() {
+        return;
+    }
+    let batch_id = lock(queue).new_batch();
+    let mut ids = Vec::new();
+    for item in &items {
+        let id = {
+            let mut q = lock(queue);
+            let id = q.enqueue(
+                Direction::Download,
+                item.local_path.clone(),
+                item.remote_path.clone(),
+                item.file_name.clone(),
+                item.size,
+                uploader_config.name.clone(),
+            );
+            if item.is_dir {
+                q.mark_dir(id);
+                q.mark_archive(id, archive);
+            }
+            if delete_source {
+                q.mark_move(id);
+            }
+            q.mark_batch(id, batch_id);
+            id
+        };
+        ids.push(id);
+    }
+
+    let queue = queue.clone();
+    let history = history.clone();
+    let pool = pool.clone();
+    let cfg = uploader_config.clone();
+    let local_state = local_state.clone();
+    let ui_handle = ui_handle.clone();
+    let local_dir = local_dir.to_path_buf();
+    thread::spawn(move || {
+        // 远程目录用 du 统计大小可能较慢，放在后台线程且不占用浏览连接
+        let space_check = pool
+            .acquire(&cfg)
+            .map_or(Ok(()), |u| check_local_space(&u, &local_dir, &items));
+        if !space_check_passed(&queue, &ui_handle, &ids, space_check) {
+            return;
+        }
+
+        for (item, task_id) in items.into_iter().zip(ids) {
+            let DownloadItem {
+                remote_path: remote_file_path,
+                local_path: local_file_path,
+                is_dir,
+                ..
+            } = item;
             let queue_clone = queue.clone();
-            let cfg = uploader_config.clone();
+            let history = history.clone();
+            let pool = pool.clone();
+            let cfg = cfg.clone();
             let ls_clone = local_state.clone();
             let ui_h = ui_handle.clone();
             thread::spawn(move || {
-                let mut uploader = match SshUploader::connect(&cfg) {
-                    Ok(u) => u,
-                    Err(e) => {
-                        let mut q = queue_clone.lock().unwrap();
-                        q.mark_failed(task_id, format!("连接失败: {}", e));
-                        return;
+                if !wait_until_resumed(&queue_clone, task_id) {
+                    return;
+                }
+                let result = transfer_with_retries(&queue_clone, &pool, &cfg, task_id, |uploader, progress_cb| {
+                    if is_dir {
+                        download_dir(uploader, Path::new(&remote_file_path), &local_file_path, archive, progress_cb)
+                    } else {
+                        uploader.download(Path::new(&remote_file_path), &local_file_path, progress_cb)
                     }
-                };
+                });
 
-                let progress_cb = |progress: f32| {
-                    let q_clone = queue_clone.clone();
+                let ok = result.is_ok();
+                finish_task(&queue_clone, &history, task_id, &cfg, is_dir, result);
+                if ok {
+                    let moved = delete_moved_source(&queue_clone, &pool, &cfg, task_id);
                     let _ = slint::invoke_from_event_loop(move || {
-                        let mut q = q_clone.lock().unwrap();
-                        q.update_progress(task_id, progress);
-                    });
-                };
-                let result = if is_dir {
-                    uploader.download_dir(
-                        Path::new(&remote_file_path),
-                        &local_file_path,
-                        progress_cb,
-                    )
-                } else {
-                    uploader.download(
-                        Path::new(&remote_file_path),
-                        &local_file_path,
-                        progress_cb,
-                    )
-                };
-
-                match result {
-                    Ok(_) => {
-                        let mut q = queue_clone.lock().unwrap();
-                        q.mark_completed(task_id);
-                        let ls = ls_clone.clone();
-                        let uh = ui_h.clone();
-                        let _ = slint::invoke_from_event_loop(move || {
-                            if let Some(ui) = uh.upgrade() {
-                                local_bindings::refresh_local(&ui, &ls);
+                        if let Some(ui) = ui_h.upgrade() {
+                            local_bindings::refresh_local(&ui, &ls_clone);
+                            if moved {
+                                ui.invoke_remote_refresh();
                             }
-                        });
-                    }
-                    Err(e) => {
-                        let mut q = queue_clone.lock().unwrap();
-                        q.mark_failed(task_id, format!("{}", e));
-                    }
+                        }
+                    });
                 }
             });
         }
@@ -255,21 +817,80 @@ fn bind_clear_completed_transfers(
     queue: Arc<Mutex<TransferQueue>>,
 ) {
     ui.on_clear_completed_transfers(move || {
-        let mut q = queue.lock().unwrap();
+        let mut q = lock(&queue);
         q.clear_completed();
     });
 }
 
+fn bind_pause_resume_queue(ui: &AppWindow, queue: Arc<Mutex<TransferQueue>>) {
+    let ui_handle = ui.as_weak();
+    let q = queue.clone();
+    ui.on_pause_queue(move || {
+        lock(&q).pause();
+        if let Some(ui) = ui_handle.upgrade() {
+            ui.set_transfer_paused(true);
+        }
+    });
+
+    let ui_handle = ui.as_weak();
+    ui.on_resume_queue(move || {
+        lock(&queue).resume();
+        if let Some(ui) = ui_handle.upgrade() {
+            ui.set_transfer_paused(false);
+        }
+    });
+}
+
+/// 队列暂停时挂起尚未开始的任务：等到任务可以开始 (队列未暂停且轮到该任务)，
+/// 只检查不标记，任务已被取消时返回 false
+fn wait_until_resumed(queue: &Arc<Mutex<TransferQueue>>, task_id: usize) -> bool {
+    loop {
+        match lock(queue).start_decision(task_id) {
+            StartDecision::Start => return true,
+            StartDecision::Cancelled => return false,
+            StartDecision::Wait => {}
+        }
+        thread::sleep(std::time::Duration::from_millis(200));
+    }
+}
+
+/// 取到连接后才把任务标记为开始：同一服务器的连接已达上限时要等其他任务归还，
+/// 期间队列被暂停 (或轮不到该任务) 就归还连接重新等待，保证暂停后不再有任务开始传输。
+/// 任务已被取消时返回 None
+fn acquire_for_task<'a>(
+    queue: &Arc<Mutex<TransferQueue>>,
+    pool: &'a ConnectionPool,
+    server: &ServerConfig,
+    task_id: usize,
+) -> Option<anyhow::Result<PooledConnection<'a>>> {
+    loop {
+        if !wait_until_resumed(queue, task_id) {
+            return None;
+        }
+        let uploader = match pool.acquire(server) {
+            Ok(u) => u,
+            Err(e) => return Some(Err(e)),
+        };
+        match lock(queue).try_start(task_id) {
+            StartDecision::Start => return Some(Ok(uploader)),
+            StartDecision::Cancelled => return None,
+            StartDecision::Wait => {}
+        }
+    }
+}
+
 fn bind_retry_transfer(
     ui: &AppWindow,
     local_state: Arc<Mutex<LocalState>>,
     remote_state: Arc<Mutex<RemoteState>>,
     queue: Arc<Mutex<TransferQueue>>,
+    history: Arc<dyn HistoryRepository + Send + Sync>,
+    pool: Arc<ConnectionPool>,
 ) {
     let ui_handle = ui.as_weak();
     ui.on_retry_transfer(move |task_id| {
         let task_id = task_id as usize;
-        let mut q = queue.lock().unwrap();
+        let mut q = lock(&queue);
         if !q.retry(task_id) {
             return;
         }
@@ -280,175 +901,572 @@ fn bind_retry_transfer(
         drop(q);
 
         let uploader_config = {
-            let rs = remote_state.lock().unwrap();
+            let rs = lock(&remote_state);
             match &rs.uploader {
                 Some(u) => u.config().clone(),
                 None => return,
             }
         };
 
-        let queue_clone = queue.clone();
-        let rs_clone = remote_state.clone();
-        let ls_clone = local_state.clone();
-        let ui_h = ui_handle.clone();
-        let local_path = task.local_path.clone();
-        let remote_path = task.remote_path.clone();
-        let is_dir = local_path.is_dir();
-        let direction = task.direction.clone();
-
-        thread::spawn(move || {
-            let mut uploader = match SshUploader::connect(&uploader_config) {
-                Ok(u) => u,
-                Err(e) => {
-                    let mut q = queue_clone.lock().unwrap();
-                    q.mark_failed(task_id, format!("连接失败: {}", e));
-                    return;
-                }
-            };
+        let is_dir = task.is_dir;
+        run_task(
+            task,
+            is_dir,
+            uploader_config,
+            &queue,
+            &history,
+            &pool,
+            &local_state,
+            &remote_state,
+            &ui_handle,
+        );
+    });
+}
 
-            let progress_cb = |progress: f32| {
-                let q_clone = queue_clone.clone();
-                let _ = slint::invoke_from_event_loop(move || {
-                    let mut q = q_clone.lock().unwrap();
-                    q.update_progress(task_id, progress);
-                });
-            };
+/// 启动时恢复的未完成任务：确认后按任务记录的服务器继续传输，取消则从队列移除
+fn bind_resume_transfers(
+    ui: &AppWindow,
+    config: Arc<Mutex<AppConfig>>,
+    local_state: Arc<Mutex<LocalState>>,
+    remote_state: Arc<Mutex<RemoteState>>,
+    queue: Arc<Mutex<TransferQueue>>,
+    history: Arc<dyn HistoryRepository + Send + Sync>,
+    pool: Arc<ConnectionPool>,
+) {
+    let ui_handle = ui.as_weak();
+    ui.on_resume_transfers(move |resume| {
+        let ids = lock(&queue).interrupted_ids();
+        if !resume {
+            lock(&queue).remove(&ids);
+            return;
+        }
+        let tasks: Vec<TransferTask> = {
+            let mut q = lock(&queue);
+            let ids: Vec<usize> = ids.into_iter().filter(|&id| q.retry(id)).collect();
+            ids.into_iter().filter_map(|id| q.get_task(id)).collect()
+        };
+        relaunch_tasks(
+            tasks,
+            &config,
+            &queue,
+            &history,
+            &pool,
+            &local_state,
+            &remote_state,
+            &ui_handle,
+        );
+    });
+}
 
-            let result = match direction {
-                Direction::Upload => {
-                    if is_dir {
-                        uploader.upload_dir(&local_path, Path::new(&remote_path), progress_cb)
-                    } else {
-                        uploader.upload(&local_path, Path::new(&remote_path), progress_cb)
-                    }
+/// 一次性重试所有失败的任务，各任务按记录的服务器重新传输
+fn bind_retry_all_failed(
+    ui: &AppWindow,
+    config: Arc<Mutex<AppConfig>>,
+    local_state: Arc<Mutex<LocalState>>,
+    remote_state: Arc<Mutex<RemoteState>>,
+    queue: Arc<Mutex<TransferQueue>>,
+    history: Arc<dyn HistoryRepository + Send + Sync>,
+    pool: Arc<ConnectionPool>,
+) {
+    let ui_handle = ui.as_weak();
+    ui.on_retry_all_failed(move || {
+        let tasks: Vec<TransferTask> = {
+            let mut q = lock(&queue);
+            let ids = q.retry_all_failed();
+            ids.into_iter().filter_map(|id| q.get_task(id)).collect()
+        };
+        relaunch_tasks(
+            tasks,
+            &config,
+            &queue,
+            &history,
+            &pool,
+            &local_state,
+            &remote_state,
+            &ui_handle,
+        );
+    });
+}
+
+/// 重新启动已重置为 Pending 的任务，找不到对应服务器的任务直接标记失败
+#[allow(clippy::too_many_arguments)]
+fn relaunch_tasks(
+    tasks: Vec<TransferTask>,
+    config: &Arc<Mutex<AppConfig>>,
+    queue: &Arc<Mutex<TransferQueue>>,
+    history: &Arc<dyn HistoryRepository + Send + Sync>,
+    pool: &Arc<ConnectionPool>,
+    local_state: &Arc<Mutex<LocalState>>,
+    remote_state: &Arc<Mutex<RemoteState>>,
+    ui_handle: &slint::Weak<AppWindow>,
+) {
+    for task in tasks {
+        let server = config
+            .lock()
+            .unwrap()
+            .servers
+            .iter()
+            .find(|s| s.name == task.server)
+            .cloned();
+        let server = match server {
+            Some(s) => s,
+            None => {
+                let error = format!("找不到服务器: {}", task.server);
+                lock(queue).mark_failed(task.id, error);
+                continue;
+            }
+        };
+        let is_dir = task.is_dir;
+        run_task(
+            task,
+            is_dir,
+            server,
+            queue,
+            history,
+            pool,
+            local_state,
+            remote_state,
+            ui_handle,
+        );
+    }
+}
+
+/// 传输历史面板：打开时加载记录，重新执行复用重试流程。
+/// 重新执行按面板打开时的记录快照定位，期间追加的新记录不会让行号错位
+fn bind_transfer_history(
+    ui: &AppWindow,
+    local_state: Arc<Mutex<LocalState>>,
+    remote_state: Arc<Mutex<RemoteState>>,
+    queue: Arc<Mutex<TransferQueue>>,
+    history: Arc<dyn HistoryRepository + Send + Sync>,
+    pool: Arc<ConnectionPool>,
+) {
+    let shown: Rc<RefCell<Vec<HistoryEntry>>> = Rc::new(RefCell::new(Vec::new()));
+
+    let ui_handle = ui.as_weak();
+    let repo = history.clone();
+    let shown_clone = shown.clone();
+    ui.on_show_transfer_history(move || {
+        let ui = match ui_handle.upgrade() {
+            Some(ui) => ui,
+            None => return,
+        };
+        let entries = match repo.load() {
+            Ok(h) => h,
+            Err(e) => {
+                ui.set_global_error(format!("读取传输历史失败: {}", e).into());
+                return;
+            }
+        };
+        let recent: Vec<HistoryEntry> = entries.recent().cloned().collect();
+        let items: Vec<HistoryItem> = recent
+            .iter()
+            .map(|e| HistoryItem {
+                timestamp: SharedString::from(&e.timestamp),
+                direction: SharedString::from(if e.direction == "download" {
+                    "下载"
+                } else {
+                    "上传"
+                }),
+                file_name: SharedString::from(&e.file_name),
+                server: SharedString::from(&e.server),
+                size: SharedString::from(super::format_size(e.size, false)),
+                duration: SharedString::from(format_eta(e.duration_secs as u64)),
+                success: e.success,
+                error_msg: SharedString::from(e.error.clone().unwrap_or_default()),
+            })
+            .collect();
+        *shown_clone.borrow_mut() = recent;
+        ui.set_transfer_history(ModelRc::new(VecModel::from(items)));
+        ui.set_show_history(true);
+    });
+
+    let ui_handle = ui.as_weak();
+    ui.on_rerun_history(move |index| {
+        let ui = match ui_handle.upgrade() {
+            Some(ui) => ui,
+            None => return,
+        };
+        let entry = match shown.borrow().get(index as usize).cloned() {
+            Some(e) => e,
+            None => return,
+        };
+
+        let uploader_config = {
+            let rs = lock(&remote_state);
+            rs.uploader.as_ref().map(|u| u.config().clone())
+        };
+        let uploader_config = match uploader_config {
+            Some(cfg) if cfg.name == entry.server => cfg,
+            _ => {
+                ui.set_global_error(
+                    format!("请先连接服务器「{}」再重新执行", entry.server).into(),
+                );
+                return;
+            }
+        };
+
+        let direction = if entry.direction == "download" {
+            Direction::Download
+        } else {
+            Direction::Upload
+        };
+        let task = {
+            let mut q = lock(&queue);
+            let id = q.enqueue(
+                direction,
+                PathBuf::from(&entry.local_path),
+                entry.remote_path.clone(),
+                entry.file_name.clone(),
+                entry.size,
+                entry.server.clone(),
+            );
+            if entry.is_dir {
+                q.mark_dir(id);
+            }
+            q.get_task(id)
+        };
+        let task = match task {
+            Some(t) => t,
+            None => return,
+        };
+
+        run_task(
+            task,
+            entry.is_dir,
+            uploader_config,
+            &queue,
+            &history,
+            &pool,
+            &local_state,
+            &remote_state,
+            &ui_handle,
+        );
+    });
+}
+
+/// 在后台线程执行一个已入队的任务，结束后刷新对应面板
+#[allow(clippy::too_many_arguments)]
+fn run_task(
+    task: TransferTask,
+    is_dir: bool,
+    uploader_config: ServerConfig,
+    queue: &Arc<Mutex<TransferQueue>>,
+    history: &Arc<dyn HistoryRepository + Send + Sync>,
+    pool: &Arc<ConnectionPool>,
+    local_state: &Arc<Mutex<LocalState>>,
+    remote_state: &Arc<Mutex<RemoteState>>,
+    ui_handle: &slint::Weak<AppWindow>,
+) {
+    let queue_clone = queue.clone();
+    let history = history.clone();
+    let pool = pool.clone();
+    let rs_clone = remote_state.clone();
+    let ls_clone = local_state.clone();
+    let ui_h = ui_handle.clone();
+    let task_id = task.id;
+    let local_path = task.local_path.clone();
+    let remote_path = task.remote_path.clone();
+    let direction = task.direction.clone();
+    let archive = task.archive;
+
+    thread::spawn(move || {
+        if !wait_until_resumed(&queue_clone, task_id) {
+            return;
+        }
+        let result = transfer_with_retries(
+            &queue_clone,
+            &pool,
+            &uploader_config,
+            task_id,
+            |uploader, progress_cb| match direction {
+                Direction::Upload if is_dir => {
+                    uploader.upload_dir(&local_path, Path::new(&remote_path), progress_cb)
+                }
+                Direction::Upload => uploader.upload(&local_path, Path::new(&remote_path), progress_cb),
+                Direction::Download if is_dir => {
+                    download_dir(uploader, Path::new(&remote_path), &local_path, archive, progress_cb)
                 }
                 Direction::Download => {
-                    if is_dir {
-                        uploader.download_dir(Path::new(&remote_path), &local_path, progress_cb)
-                    } else {
-                        uploader.download(Path::new(&remote_path), &local_path, progress_cb)
-                    }
+                    uploader.download(Path::new(&remote_path), &local_path, progress_cb)
                 }
-            };
+            },
+        );
 
-            match result {
-                Ok(_) => {
-                    let mut q = queue_clone.lock().unwrap();
-                    q.mark_completed(task_id);
-                    match direction {
-                        Direction::Upload => {
-                            let rs = rs_clone.clone();
-                            let uh = ui_h.clone();
-                            let rp = rs.lock().unwrap().current_path.clone();
-                            let _ = slint::invoke_from_event_loop(move || {
-                                remote_bindings::refresh_remote_dir(&rs, &uh, &rp);
-                            });
-                        }
-                        Direction::Download => {
-                            let ls = ls_clone.clone();
-                            let uh = ui_h.clone();
-                            let _ = slint::invoke_from_event_loop(move || {
-                                if let Some(ui) = uh.upgrade() {
-                                    local_bindings::refresh_local(&ui, &ls);
-                                }
-                            });
-                        }
-                    }
-                }
-                Err(e) => {
-                    let mut q = queue_clone.lock().unwrap();
-                    q.mark_failed(task_id, format!("{}", e));
+        let ok = result.is_ok();
+        finish_task(&queue_clone, &history, task_id, &uploader_config, is_dir, result);
+        if !ok {
+            return;
+        }
+        let moved = delete_moved_source(&queue_clone, &pool, &uploader_config, task_id);
+        let rp = lock(&rs_clone).current_path.clone();
+        let _ = slint::invoke_from_event_loop(move || {
+            if direction == Direction::Upload || moved {
+                remote_bindings::refresh_remote_dir(&rs_clone, &ui_h, &rp);
+                remote_bindings::refresh_disk_usage(&rs_clone, &ui_h);
+            }
+            if direction == Direction::Download || moved {
+                if let Some(ui) = ui_h.upgrade() {
+                    local_bindings::refresh_local(&ui, &ls_clone);
                 }
             }
         });
     });
 }
 
+/// 按任务的打包方式下载目录
+fn download_dir(
+    uploader: &mut SshUploader,
+    remote_dir: &Path,
+    local_dir: &Path,
+    archive: DirArchive,
+    progress_cb: &dyn Fn(f32),
+) -> anyhow::Result<()> {
+    match archive {
+        DirArchive::None => uploader.download_dir(remote_dir, local_dir, progress_cb),
+        DirArchive::Extract => uploader.download_dir_archive(remote_dir, local_dir, true, progress_cb),
+        DirArchive::Keep => uploader.download_dir_archive(remote_dir, local_dir, false, progress_cb),
+    }
+}
+
+/// 从连接池取连接执行一次传输；连接中断、超时等错误按退避间隔自动重试，
+/// 每次重试都重新取连接，失败的连接直接丢弃
+fn transfer_with_retries(
+    queue: &Arc<Mutex<TransferQueue>>,
+    pool: &Arc<ConnectionPool>,
+    server: &ServerConfig,
+    task_id: usize,
+    transfer: impl Fn(&mut SshUploader, &dyn Fn(f32)) -> anyhow::Result<()>,
+) -> Result<(), String> {
+    loop {
+        let result = match acquire_for_task(queue, pool, server, task_id) {
+            Some(Ok(mut uploader)) => {
+                let throttle = Throttle::for_progress();
+                let progress_cb = |progress: f32| {
+                    if !throttle.ready(progress) {
+                        return;
+                    }
+                    let q_clone = queue.clone();
+                    let _ = slint::invoke_from_event_loop(move || {
+                        let mut q = lock(&q_clone);
+                        q.update_progress(task_id, progress);
+                    });
+                };
+                // 清掉池中连接上次操作留下的说明
+                uploader.take_method_note();
+                // 传输中的 panic 转为本任务的失败，线程继续完成收尾
+                let result = catch_panic(|| transfer(&mut uploader, &progress_cb))
+                    .and_then(|r| r.map_err(|e| format!("{}", e)));
+                match result {
+                    Ok(()) => note_transfer(queue, task_id, &uploader),
+                    Err(_) => uploader.discard(),
+                }
+                result
+            }
+            Some(Err(e)) => Err(format!("连接失败: {}", e)),
+            // 等待期间被取消：保留取消时记录的原因
+            None => {
+                return Err(match lock(queue).get_task(task_id).map(|t| t.status) {
+                    Some(TransferStatus::Failed(e)) => e,
+                    _ => "已取消".to_string(),
+                })
+            }
+        };
+        let err = match result {
+            Ok(()) => return Ok(()),
+            Err(e) => e,
+        };
+        let delay = lock(queue).record_retry(task_id, &err);
+        match delay {
+            Some(delay) => thread::sleep(delay),
+            None => return Err(err),
+        }
+    }
+}
+
+/// 压缩上传的统计说明，显示实际传输量与原始大小
+fn compression_note(stats: CompressionStats) -> String {
+    format!(
+        "压缩传输 {} / 原始 {}",
+        super::format_size(stats.on_wire, false),
+        super::format_size(stats.original, false)
+    )
+}
+
+/// 把实际使用的传输方式和压缩统计写入任务说明
+fn note_transfer(queue: &Arc<Mutex<TransferQueue>>, task_id: usize, uploader: &SshUploader) {
+    let notes: Vec<String> = uploader
+        .take_method_note()
+        .into_iter()
+        .chain(uploader.take_compression_stats().map(compression_note))
+        .collect();
+    if !notes.is_empty() {
+        lock(queue).set_note(task_id, notes.join("，"));
+    }
+}
+
+/// 标记任务结束并追加到传输历史
+fn finish_task(
+    queue: &Arc<Mutex<TransferQueue>>,
+    history: &Arc<dyn HistoryRepository + Send + Sync>,
+    task_id: usize,
+    server: &ServerConfig,
+    is_dir: bool,
+    result: Result<(), String>,
+) {
+    let task = {
+        let mut q = lock(queue);
+        match result {
+            Ok(()) => q.mark_completed(task_id),
+            Err(e) => q.mark_failed(task_id, e),
+        }
+        q.get_task(task_id)
+    };
+    if let Some(task) = task {
+        let entry = transfer_service::history_entry(&task, &server.name, is_dir);
+        if let Err(e) = history.append(entry) {
+            eprintln!("写入传输历史失败: {}", e);
+        }
+    }
+}
+
+/// 移动任务成功后删除源：先核对源与目标的字节数，校验或删除失败时保留源并写入任务说明。
+/// 返回源是否已删除
+fn delete_moved_source(
+    queue: &Arc<Mutex<TransferQueue>>,
+    pool: &Arc<ConnectionPool>,
+    server: &ServerConfig,
+    task_id: usize,
+) -> bool {
+    let task = match lock(queue).get_task(task_id) {
+        Some(t) if t.delete_source_on_success && t.status == TransferStatus::Completed => t,
+        _ => return false,
+    };
+    let result = pool
+        .acquire(server)
+        .and_then(|uploader| remove_verified_source(&uploader, &task));
+    let outcome = match &result {
+        Ok(()) => "已删除源文件".to_string(),
+        Err(e) => format!("未删除源文件: {}", e),
+    };
+    let note = if task.note.is_empty() {
+        outcome
+    } else {
+        format!("{}；{}", task.note, outcome)
+    };
+    lock(queue).set_note(task_id, note);
+    result.is_ok()
+}
+
+fn remove_verified_source(uploader: &SshUploader, task: &TransferTask) -> anyhow::Result<()> {
+    let local_size = if task.is_dir {
+        local_fs::dir_size(&task.local_path)
+    } else {
+        std::fs::metadata(&task.local_path)?.len()
+    };
+    let remote_size = remote_fs::remote_file_bytes(uploader, &task.remote_path)?;
+    transfer_service::ensure_same_size(local_size, remote_size)?;
+    match task.direction {
+        Direction::Upload => local_fs::remove_path(&task.local_path),
+        Direction::Download => remote_fs::remote_remove(uploader, &task.remote_path, task.is_dir),
+    }
+}
+
+/// 未完成任务写入存储的最短间隔
+const QUEUE_SAVE_INTERVAL: std::time::Duration = std::time::Duration::from_secs(1);
+
 fn start_transfer_queue_sync(
     ui: &AppWindow,
+    config: Arc<Mutex<AppConfig>>,
     queue: Arc<Mutex<TransferQueue>>,
-) {
+) -> Timer {
     let ui_handle = ui.as_weak();
     let timer = Timer::default();
+    let mut drain = transfer_service::DrainTracker::new(&lock(&queue).snapshot());
+    let mut last_save = Instant::now();
 
     timer.start(TimerMode::Repeated, std::time::Duration::from_millis(200), move || {
         if let Some(ui) = ui_handle.upgrade() {
-            let q = queue.lock().unwrap();
+            let mut q = lock(&queue);
             let tasks = q.snapshot();
+            let paused = q.is_paused();
+            let has_failed = q.has_failed();
+            // 队列变化合并后定期保存，在锁外的后台线程写入
+            let save = if last_save.elapsed() >= QUEUE_SAVE_INTERVAL { q.take_unsaved() } else { None };
             drop(q);
+            if let Some(save) = save {
+                last_save = Instant::now();
+                thread::spawn(move || save.write());
+            }
+
+            if let Some((done, failed)) = drain.observe(&tasks) {
+                notify_queue_drained(&config, done, failed);
+            }
 
             let transfer_entries: Vec<TransferEntry> = tasks
                 .iter()
                 .map(|t| {
                     let (status_text, error_msg) =
-                        transfer_service::status_to_text(&t.status);
+                        transfer_service::status_to_text(&t.status, paused);
+                    let status_text = if transfer_service::is_scanning(t) {
+                        "scanning"
+                    } else {
+                        status_text
+                    };
 
                     let direction = match t.direction {
                         Direction::Upload => "上传",
                         Direction::Download => "下载",
                     };
 
-                    let (speed, eta) = if t.status == TransferStatus::InProgress {
-                        if let Some(started) = t.started_at {
-                            let elapsed = started.elapsed().as_secs_f64();
-                            if elapsed > 0.5 && t.progress > 0.0 {
-                                let bytes_done = (t.size as f64) * (t.progress as f64);
-                                let bps = bytes_done / elapsed;
-                                let speed_str = format_speed(bps);
-                                let remaining = if t.progress < 1.0 {
-                                    let remaining_bytes = (t.size as f64) * (1.0 - t.progress as f64);
-                                    let secs = (remaining_bytes / bps) as u64;
-                                    format_eta(secs)
-                                } else {
-                                    String::new()
-                                };
-                                (speed_str, remaining)
-                            } else {
-                                (String::new(), String::new())
-                            }
-                        } else {
-                            (String::new(), String::new())
+                    let (speed, eta) = match t.started_at {
+                        Some(started) if t.status == TransferStatus::InProgress => {
+                            format::speed_and_eta(t.size, t.progress, started.elapsed())
                         }
-                    } else {
-                        (String::new(), String::new())
+                        _ => (String::new(), String::new()),
                     };
 
                     TransferEntry {
                         task_id: t.id as i32,
                         file_name: SharedString::from(&t.file_name),
+                        server: SharedString::from(&t.server),
                         direction: SharedString::from(direction),
                         progress: t.progress,
                         status: SharedString::from(status_text),
                         error_msg: SharedString::from(&error_msg),
                         speed: SharedString::from(&speed),
                         eta: SharedString::from(&eta),
+                        note: SharedString::from(&t.note),
+                        throughput: ModelRc::new(VecModel::from(t.throughput.sparkline())),
                     }
                 })
                 .collect();
 
             ui.set_transfer_tasks(ModelRc::new(VecModel::from(transfer_entries)));
             ui.set_has_transfer_tasks(!tasks.is_empty());
+            ui.set_transfer_paused(paused);
+            ui.set_has_failed_transfers(has_failed);
         }
     });
+    // Timer 在 drop 时会停止，由调用方持有到窗口关闭
+    timer
 }
 
-fn format_speed(bytes_per_sec: f64) -> String {
-    if bytes_per_sec < 1024.0 {
-        format!("{:.0} B/s", bytes_per_sec)
-    } else if bytes_per_sec < 1024.0 * 1024.0 {
-        format!("{:.1} KB/s", bytes_per_sec / 1024.0)
-    } else {
-        format!("{:.1} MB/s", bytes_per_sec / (1024.0 * 1024.0))
+/// 队列全部结束时按配置弹出桌面通知，在后台线程发送以免阻塞界面
+fn notify_queue_drained(config: &Arc<Mutex<AppConfig>>, done: usize, failed: usize) {
+    let (enabled, sound) = {
+        let c = lock(config);
+        (c.notify_on_complete, c.notify_sound)
+    };
+    if !enabled {
+        return;
     }
+    thread::spawn(move || {
+        let body = transfer_service::drain_message(done, failed);
+        if let Err(e) = notifier::show("Flick", &body, sound) {
+            eprintln!("{}", e);
+        }
+    });
 }
 
-fn format_eta(secs: u64) -> String {
-    if secs < 60 {
-        format!("{}s", secs)
-    } else if secs < 3600 {
-        format!("{}:{:02}", secs / 60, secs % 60)
-    } else {
-        format!("{}:{:02}:{:02}", secs / 3600, (secs % 3600) / 60, secs % 60)
-    }
-}
 
@@ -1,7 +1,10 @@
-use slint::SharedString;
+use slint::{Color, ModelRc, SharedString, VecModel};
 
-use crate::domain::config::ServerConfig;
-use crate::ServerConfigUI;
+use crate::domain::config::{
+    default_connect_timeout_secs, default_io_timeout_secs, default_upload_ignore, AppConfig, ColumnPrefs,
+    PaneSort, ServerConfig, TransferMethod,
+};
+use crate::{AppSettingsUI, FileColumns, PropertyRow, ServerConfigUI, ServerItem};
 
 /// ServerConfig -> ServerConfigUI
 pub fn to_ui(server: &ServerConfig) -> ServerConfigUI {
@@ -15,6 +18,20 @@ pub fn to_ui(server: &ServerConfig) -> ServerConfigUI {
         key_path: SharedString::from(server.key_path.as_deref().unwrap_or("")),
         default_target_dir: SharedString::from(&server.default_target_dir),
         is_default: server.is_default,
+        proxy_jump: SharedString::from(server.proxy_jump.as_deref().unwrap_or("")),
+        atomic_upload: server.atomic_upload,
+        preserve_mtime: server.preserve_mtime,
+        compress_on_upload: server.compress_on_upload,
+        connect_timeout: SharedString::from(server.connect_timeout_secs.to_string()),
+        io_timeout: SharedString::from(server.io_timeout_secs.to_string()),
+        tag: SharedString::from(server.tag.as_deref().unwrap_or("")),
+        color: SharedString::from(server.color.as_deref().unwrap_or("")),
+        transfer_method: SharedString::from(server.transfer_method().as_str()),
+        agent_socket: SharedString::from(server.agent_socket.as_deref().unwrap_or("")),
+        force_native: server.force_native,
+        allow_native_fallback: server.allow_native_fallback,
+        upload_ignore: SharedString::from(server.upload_ignore.join(", ")),
+        skip_unchanged: server.skip_unchanged,
     }
 }
 
@@ -41,7 +58,138 @@ pub fn from_ui(ui_config: &ServerConfigUI) -> ServerConfig {
         },
         default_target_dir: ui_config.default_target_dir.to_string(),
         is_default: ui_config.is_default,
+        proxy_jump: if ui_config.proxy_jump.trim().is_empty() {
+            None
+        } else {
+            Some(ui_config.proxy_jump.trim().to_string())
+        },
+        last_remote_path: None,
+        recent_dirs: Vec::new(),
+        atomic_upload: ui_config.atomic_upload,
+        preserve_mtime: ui_config.preserve_mtime,
+        compress_on_upload: ui_config.compress_on_upload,
+        connect_timeout_secs: parse_timeout(&ui_config.connect_timeout, default_connect_timeout_secs()),
+        io_timeout_secs: parse_timeout(&ui_config.io_timeout, default_io_timeout_secs()),
+        tag: non_empty(&ui_config.tag),
+        color: non_empty(&ui_config.color),
+        transfer_method: TransferMethod::parse(&ui_config.transfer_method).as_str().to_string(),
+        agent_socket: non_empty(&ui_config.agent_socket),
+        force_native: ui_config.force_native,
+        allow_native_fallback: ui_config.allow_native_fallback,
+        upload_ignore: ui_config
+            .upload_ignore
+            .split([',', '\n'])
+            .map(str::trim)
+            .filter(|p| !p.is_empty())
+            .map(str::to_string)
+            .collect(),
+        skip_unchanged: ui_config.skip_unchanged,
+    }
+}
+
+/// 去掉首尾空白，空串视为未设置
+fn non_empty(text: &str) -> Option<String> {
+    let text = text.trim();
+    (!text.is_empty()).then(|| text.to_string())
+}
+
+/// ServerConfig -> 服务器列表项，颜色无效时为透明；`reachable` 为最近一次探测结果
+pub fn to_server_item(server: &ServerConfig, reachable: Option<bool>) -> ServerItem {
+    ServerItem {
+        name: SharedString::from(&server.name),
+        tag: SharedString::from(server.tag.as_deref().unwrap_or("")),
+        color: server
+            .color_rgb()
+            .map(|(r, g, b)| Color::from_rgb_u8(r, g, b))
+            .unwrap_or_default(),
+        status: SharedString::from(match reachable {
+            Some(true) => "up",
+            Some(false) => "down",
+            None => "",
+        }),
+    }
+}
+
+/// ColumnPrefs -> FileColumns
+pub fn to_file_columns(prefs: &ColumnPrefs) -> FileColumns {
+    let prefs = prefs.clamped();
+    FileColumns {
+        show_size: prefs.show_size,
+        show_modified: prefs.show_modified,
+        size_width: prefs.size_width as f32,
+        modified_width: prefs.modified_width as f32,
+    }
+}
+
+/// FileColumns -> ColumnPrefs
+pub fn from_file_columns(columns: &FileColumns) -> ColumnPrefs {
+    ColumnPrefs {
+        show_size: columns.show_size,
+        show_modified: columns.show_modified,
+        size_width: columns.size_width.round() as u32,
+        modified_width: columns.modified_width.round() as u32,
     }
+    .clamped()
+}
+
+/// AppConfig -> AppSettingsUI
+pub fn to_app_settings(config: &AppConfig) -> AppSettingsUI {
+    AppSettingsUI {
+        delete_to_trash: config.delete_to_trash,
+        notify_on_complete: config.notify_on_complete,
+        notify_sound: config.notify_sound,
+        archive_dir_download: config.archive_dir_download,
+        extract_archive: config.extract_archive,
+        local_sort_index: sort_to_index(config.default_local_sort.as_ref()),
+        remote_sort_index: sort_to_index(config.default_remote_sort.as_ref()),
+    }
+}
+
+/// 把设置页"通用"部分的修改写回 AppConfig
+pub fn apply_app_settings(config: &mut AppConfig, settings: &AppSettingsUI) {
+    config.delete_to_trash = settings.delete_to_trash;
+    config.notify_on_complete = settings.notify_on_complete;
+    config.notify_sound = settings.notify_sound;
+    config.archive_dir_download = settings.archive_dir_download;
+    config.extract_archive = settings.extract_archive;
+    config.default_local_sort = index_to_sort(settings.local_sort_index);
+    config.default_remote_sort = index_to_sort(settings.remote_sort_index);
+}
+
+/// 设置页启动排序下拉框的选项 (字段, 升序)，下拉框中的序号从 1 开始，0 表示沿用上次退出时的排序
+const SORT_OPTIONS: [(&str, bool); 6] = [
+    ("name", true),
+    ("name", false),
+    ("size", true),
+    ("size", false),
+    ("modified", true),
+    ("modified", false),
+];
+
+fn sort_to_index(sort: Option<&PaneSort>) -> i32 {
+    sort.and_then(|s| {
+        SORT_OPTIONS
+            .iter()
+            .position(|&(field, ascending)| field == s.field() && ascending == s.ascending)
+    })
+    .map_or(0, |i| i as i32 + 1)
+}
+
+fn index_to_sort(index: i32) -> Option<PaneSort> {
+    let (field, ascending) = *SORT_OPTIONS.get(usize::try_from(index).ok()?.checked_sub(1)?)?;
+    Some(PaneSort {
+        field: field.to_string(),
+        ascending,
+    })
+}
+
+/// 解析超时秒数，非法或为 0 时使用默认值
+fn parse_timeout(text: &str, default: u64) -> u64 {
+    text.trim()
+        .parse::<u64>()
+        .ok()
+        .filter(|&v| v >= 1)
+        .unwrap_or(default)
 }
 
 /// 默认的 UI 配置（新建服务器时使用）
@@ -56,6 +204,32 @@ pub fn default_ui_config() -> ServerConfigUI {
         key_path: "".into(),
         default_target_dir: "/tmp".into(),
         is_default: false,
+        proxy_jump: "".into(),
+        atomic_upload: true,
+        preserve_mtime: true,
+        compress_on_upload: false,
+        connect_timeout: default_connect_timeout_secs().to_string().into(),
+        io_timeout: default_io_timeout_secs().to_string().into(),
+        tag: "".into(),
+        color: "".into(),
+        transfer_method: TransferMethod::Auto.as_str().into(),
+        agent_socket: "".into(),
+        force_native: false,
+        allow_native_fallback: true,
+        upload_ignore: SharedString::from(default_upload_ignore().join(", ")),
+        skip_unchanged: false,
     }
 }
 
+/// (名称, 值) 列表 -> 属性对话框的行
+pub fn to_property_rows(rows: &[(String, String)]) -> ModelRc<PropertyRow> {
+    let rows: Vec<PropertyRow> = rows
+        .iter()
+        .map(|(label, value)| PropertyRow {
+            label: SharedString::from(label),
+            value: SharedString::from(value),
+        })
+        .collect();
+    ModelRc::new(VecModel::from(rows))
+}
+
@@ -1,3 +1,4 @@
+pub mod connection_log_bindings;
 pub mod explorer;
 pub mod mapper;
 pub mod quick_upload_bindings;
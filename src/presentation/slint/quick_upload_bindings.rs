@@ -1,31 +1,80 @@
-use slint::{ComponentHandle, SharedString, Weak};
+use slint::{ComponentHandle, Model, ModelRc, SharedString, VecModel, Weak};
 use std::path::{Path, PathBuf};
 use std::sync::{Arc, Mutex};
 use std::thread;
+use std::time::Instant;
 
 use crate::app::services::quick_upload_service;
 use crate::domain::config::{AppConfig, ServerConfig};
+use crate::domain::transfer::{Direction, TransferQueue, PROGRESS_INDETERMINATE};
+use crate::shared::format;
+use crate::shared::target_template::{self, TemplateContext};
+use crate::shared::throttle::Throttle;
+use crate::shared::panic_guard::lock;
 use crate::AppWindow;
 
-pub fn bind(ui: &AppWindow, config: Arc<Mutex<AppConfig>>) {
+/// 上次失败的快速上传：服务器配置、目录模板上下文和尚未成功的文件，供重试使用
+type FailedUpload = Arc<Mutex<Option<(ServerConfig, TemplateContext, Vec<PathBuf>)>>>;
+
+pub fn bind(
+    ui: &AppWindow,
+    config: Arc<Mutex<AppConfig>>,
+    queue: Arc<Mutex<TransferQueue>>,
+) {
+    let failed: FailedUpload = Arc::new(Mutex::new(None));
     bind_pick_file(ui);
     bind_server_selected(ui, config.clone());
-    bind_start_upload(ui, config);
+    bind_start_upload(ui, config.clone(), failed.clone());
+    bind_retry_upload(ui, failed);
+    bind_broadcast_upload(ui, config, queue);
 }
 
 fn bind_pick_file(ui: &AppWindow) {
     let ui_handle = ui.as_weak();
     ui.on_pick_file(move || {
         if let Some(ui) = ui_handle.upgrade() {
-            if let Some(path) = rfd::FileDialog::new().pick_file() {
-                ui.set_file_path(SharedString::from(
-                    path.to_string_lossy().as_ref(),
-                ));
+            if let Some(paths) = rfd::FileDialog::new().pick_files() {
+                stage_files(&ui, &paths);
             }
         }
     });
 }
 
+/// 设置待上传的文件；多个文件时只显示文件名
+pub(crate) fn stage_files(ui: &AppWindow, files: &[PathBuf]) {
+    let display = match files {
+        [] => "未选择文件".to_string(),
+        [single] => single.to_string_lossy().to_string(),
+        _ => files
+            .iter()
+            .map(|p| p.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default())
+            .collect::<Vec<_>>()
+            .join(", "),
+    };
+    let staged: Vec<SharedString> = files
+        .iter()
+        .map(|p| SharedString::from(p.to_string_lossy().as_ref()))
+        .collect();
+    ui.set_staged_files(ModelRc::new(VecModel::from(staged)));
+    ui.set_file_path(SharedString::from(display));
+}
+
+/// 读取并校验待上传的文件
+fn staged_files(ui: &AppWindow) -> Result<Vec<PathBuf>, String> {
+    let files: Vec<PathBuf> = ui
+        .get_staged_files()
+        .iter()
+        .map(|p| PathBuf::from(p.as_str()))
+        .collect();
+    if files.is_empty() {
+        return Err("请先选择文件".to_string());
+    }
+    for file in &files {
+        quick_upload_service::validate_upload_path(file).map_err(|e| format!("错误: {}", e))?;
+    }
+    Ok(files)
+}
+
 fn bind_server_selected(
     ui: &AppWindow,
     config: Arc<Mutex<AppConfig>>,
@@ -46,7 +95,7 @@ fn bind_server_selected(
     });
 }
 
-fn bind_start_upload(ui: &AppWindow, config: Arc<Mutex<AppConfig>>) {
+fn bind_start_upload(ui: &AppWindow, config: Arc<Mutex<AppConfig>>, failed: FailedUpload) {
     let ui_handle = ui.as_weak();
     ui.on_start_upload(move |server_index| {
         let ui = match ui_handle.upgrade() {
@@ -54,19 +103,13 @@ fn bind_start_upload(ui: &AppWindow, config: Arc<Mutex<AppConfig>>) {
             None => return,
         };
 
-        let file_path_str = ui.get_file_path();
-        if file_path_str == "未选择文件" {
-            ui.set_status_log("请先选择文件".into());
-            return;
-        }
-
-        let local_path = PathBuf::from(file_path_str.as_str());
-        if let Err(e) =
-            quick_upload_service::validate_upload_path(&local_path)
-        {
-            ui.set_status_log(format!("错误: {}", e).into());
-            return;
-        }
+        let files = match staged_files(&ui) {
+            Ok(files) => files,
+            Err(e) => {
+                ui.set_status_log(e.into());
+                return;
+            }
+        };
 
         let config_guard = match config.lock() {
             Ok(g) => g,
@@ -88,57 +131,280 @@ fn bind_start_upload(ui: &AppWindow, config: Arc<Mutex<AppConfig>>) {
         server_config.default_target_dir =
             ui.get_target_dir().to_string();
 
+        let ctx = TemplateContext::now(&server_config.name);
+        run_upload(&ui, server_config, ctx, files, failed.clone());
+    });
+}
+
+/// 以上次失败时的服务器配置和目标目录，重新上传失败的文件及其后尚未上传的文件
+fn bind_retry_upload(ui: &AppWindow, failed: FailedUpload) {
+    let ui_handle = ui.as_weak();
+    ui.on_retry_upload(move || {
+        let ui = match ui_handle.upgrade() {
+            Some(ui) => ui,
+            None => return,
+        };
+        let pending = lock(&failed).take();
+        if let Some((server_config, ctx, files)) = pending {
+            run_upload(&ui, server_config, ctx, files, failed.clone());
+        }
+    });
+}
+
+/// 在后台线程依次上传文件，整批共用 `ctx` 展开目录模板；失败时记下剩余文件以便重试
+fn run_upload(
+    ui: &AppWindow,
+    server_config: ServerConfig,
+    ctx: TemplateContext,
+    files: Vec<PathBuf>,
+    failed: FailedUpload,
+) {
+    *lock(&failed) = None;
+    ui.set_is_uploading(true);
+    ui.set_upload_failed(false);
+    ui.set_progress(0.0);
+    ui.set_progress_indeterminate(false);
+    clear_speed(ui);
+    ui.set_status_log(
+        format!(
+            "正在连接到 {} ({}:{})...",
+            server_config.name,
+            server_config.host,
+            server_config.port
+        )
+        .into(),
+    );
+
+    let ui_handle_thread = ui.as_weak();
+    thread::spawn(move || {
+        let count = files.len();
+        let mut result = Ok(());
+        for (index, local_path) in files.iter().enumerate() {
+            result = execute_upload(
+                server_config.clone(),
+                local_path.clone(),
+                &ctx,
+                (index, count),
+                ui_handle_thread.clone(),
+            );
+            if result.is_err() {
+                *lock(&failed) = Some((server_config, ctx, files[index..].to_vec()));
+                break;
+            }
+        }
+        finish_upload(ui_handle_thread, result, count);
+    });
+}
+
+/// 多服务器同时上传：勾选的服务器各开一个线程，结果写入传输队列并汇总到状态栏
+fn bind_broadcast_upload(
+    ui: &AppWindow,
+    config: Arc<Mutex<AppConfig>>,
+    queue: Arc<Mutex<TransferQueue>>,
+) {
+    let server_count = config.lock().map(|g| g.servers.len()).unwrap_or(0);
+    ui.set_broadcast_selected(ModelRc::new(VecModel::from(vec![false; server_count])));
+
+    let ui_handle = ui.as_weak();
+    ui.on_broadcast_toggled(move |index, checked| {
+        if let Some(ui) = ui_handle.upgrade() {
+            let model = ui.get_broadcast_selected();
+            if index >= 0 && (index as usize) < model.row_count() {
+                model.set_row_data(index as usize, checked);
+            }
+        }
+    });
+
+    let ui_handle = ui.as_weak();
+    ui.on_start_broadcast_upload(move || {
+        let ui = match ui_handle.upgrade() {
+            Some(ui) => ui,
+            None => return,
+        };
+
+        let files = match staged_files(&ui) {
+            Ok(files) => files,
+            Err(e) => {
+                ui.set_status_log(e.into());
+                return;
+            }
+        };
+
+        let target_dir = ui.get_target_dir().to_string();
+        let selected: Vec<bool> = ui.get_broadcast_selected().iter().collect();
+        let servers: Vec<ServerConfig> = match config.lock() {
+            Ok(guard) => guard
+                .servers
+                .iter()
+                .zip(selected.iter())
+                .filter(|(_, &checked)| checked)
+                .map(|(s, _)| {
+                    let mut s = s.clone();
+                    s.default_target_dir = target_dir.clone();
+                    s
+                })
+                .collect(),
+            Err(_) => {
+                ui.set_status_log("内部错误: 配置锁定失败".into());
+                return;
+            }
+        };
+        if servers.is_empty() {
+            ui.set_status_log("请至少勾选一台服务器".into());
+            return;
+        }
+
         ui.set_is_uploading(true);
+        ui.set_upload_failed(false);
         ui.set_progress(0.0);
-        ui.set_status_log(
-            format!(
-                "正在连接到 {} ({}:{})...",
-                server_config.name,
-                server_config.host,
-                server_config.port
-            )
-            .into(),
-        );
+        ui.set_progress_indeterminate(false);
+        ui.set_status_log(format!("正在上传到 {} 台服务器...", servers.len()).into());
 
         let ui_handle_thread = ui_handle.clone();
+        let queue = queue.clone();
         thread::spawn(move || {
-            let result = execute_upload(
-                server_config,
-                local_path,
-                ui_handle_thread.clone(),
-            );
-            finish_upload(ui_handle_thread, result);
+            let count = files.len();
+            let ctx = TemplateContext::now("");
+            let mut summaries = Vec::new();
+            for (file_index, local_path) in files.into_iter().enumerate() {
+                let file_name = local_path
+                    .file_name()
+                    .map(|n| n.to_string_lossy().to_string())
+                    .unwrap_or_default();
+                let size = std::fs::metadata(&local_path).map(|m| m.len()).unwrap_or(0);
+                let remote_path = Path::new(&target_dir)
+                    .join(&file_name)
+                    .to_string_lossy()
+                    .replace('\\', "/");
+                let task_ids: Vec<usize> = {
+                    let mut q = lock(&queue);
+                    servers
+                        .iter()
+                        .map(|s| {
+                            q.enqueue(
+                                Direction::Upload,
+                                local_path.clone(),
+                                remote_path.clone(),
+                                file_name.clone(),
+                                size,
+                                s.name.clone(),
+                            )
+                        })
+                        .collect()
+                };
+
+                let progresses = Mutex::new(vec![0.0f32; servers.len()]);
+                let throttle = Mutex::new(Throttle::for_progress());
+                let results = quick_upload_service::execute_broadcast_upload(
+                    servers.clone(),
+                    local_path,
+                    &ctx,
+                    |index, progress| {
+                        lock(&queue).update_progress(task_ids[index], progress);
+                        if progress == PROGRESS_INDETERMINATE {
+                            show_indeterminate(&ui_handle_thread);
+                            return;
+                        }
+                        let file_progress = {
+                            let mut p = lock(&progresses);
+                            p[index] = progress;
+                            p.iter().sum::<f32>() / p.len() as f32
+                        };
+                        let overall = (file_index as f32 + file_progress) / count as f32;
+                        if !lock(&throttle).ready(overall) {
+                            return;
+                        }
+                        let ui_copy = ui_handle_thread.clone();
+                        let _ = slint::invoke_from_event_loop(move || {
+                            if let Some(ui) = ui_copy.upgrade() {
+                                ui.set_progress(overall);
+                            }
+                        });
+                    },
+                );
+
+                {
+                    let mut q = lock(&queue);
+                    for (task_id, (_, result)) in task_ids.iter().zip(results.iter()) {
+                        match result {
+                            Ok(_) => q.mark_completed(*task_id),
+                            Err(e) => q.mark_failed(*task_id, e.to_string()),
+                        }
+                    }
+                }
+
+                let summary = quick_upload_service::summarize_broadcast(&results);
+                if count > 1 {
+                    summaries.push(format!("{}\n{}", file_name, summary));
+                } else {
+                    summaries.push(summary);
+                }
+            }
+
+            let summary = summaries.join("\n\n");
+            let _ = slint::invoke_from_event_loop(move || {
+                if let Some(ui) = ui_handle_thread.upgrade() {
+                    ui.set_is_uploading(false);
+                    ui.set_progress_indeterminate(false);
+                    ui.set_progress(1.0);
+                    ui.set_status_log(summary.into());
+                }
+            });
         });
     });
 }
 
+/// 上传单个文件；`(index, count)` 为该文件在本批中的位置，用于换算总进度
 fn execute_upload(
     config: ServerConfig,
     local_path: PathBuf,
+    ctx: &TemplateContext,
+    (index, count): (usize, usize),
     ui_handle: Weak<AppWindow>,
 ) -> anyhow::Result<()> {
     let file_name = local_path
         .file_name()
         .ok_or_else(|| anyhow::anyhow!("无效的文件名"))?;
-    let remote_path =
-        Path::new(&config.default_target_dir).join(file_name);
+    // 仅用于状态显示，实际目录在连接后由服务层展开
+    let target_dir = target_template::expand_target_template(&config.default_target_dir, ctx);
+    let remote_path = Path::new(&target_dir).join(file_name);
 
     let ui_copy = ui_handle.clone();
     let rp = remote_path.clone();
     slint::invoke_from_event_loop(move || {
         if let Some(ui) = ui_copy.upgrade() {
-            ui.set_status_log(
-                format!("正在上传至 {:?}...", rp).into(),
-            );
+            let text = if count > 1 {
+                format!("({}/{}) 正在上传至 {:?}...", index + 1, count, rp)
+            } else {
+                format!("正在上传至 {:?}...", rp)
+            };
+            ui.set_status_log(text.into());
         }
     })
     .ok();
 
-    quick_upload_service::execute_upload(config, local_path, |progress| {
+    let size = std::fs::metadata(&local_path).map(|m| m.len()).unwrap_or(0);
+    let started = Instant::now();
+    let throttle = Throttle::for_progress();
+    quick_upload_service::execute_upload(config, local_path, ctx, |progress| {
+        // 不确定进度不经过节流，否则紧跟 0.0 之后的信号会被丢掉
+        if progress == PROGRESS_INDETERMINATE {
+            show_indeterminate(&ui_handle);
+            return;
+        }
+        let overall = (index as f32 + progress) / count as f32;
+        if !throttle.ready(overall) {
+            return;
+        }
+        // 速度和剩余时间按当前文件计算
+        let (speed, eta) = format::speed_and_eta(size, progress, started.elapsed());
         let ui_copy = ui_handle.clone();
         let _ = slint::invoke_from_event_loop(move || {
             if let Some(ui) = ui_copy.upgrade() {
-                ui.set_progress(progress);
+                ui.set_progress_indeterminate(false);
+                ui.set_progress(overall);
+                ui.set_upload_speed(speed.into());
+                ui.set_upload_eta(eta.into());
             }
         });
     })?;
@@ -146,19 +412,43 @@ fn execute_upload(
     Ok(())
 }
 
+/// 进度条切换为不确定状态，直到收到真实进度或上传结束；此时无法估算速度
+fn show_indeterminate(ui_handle: &Weak<AppWindow>) {
+    let ui_copy = ui_handle.clone();
+    let _ = slint::invoke_from_event_loop(move || {
+        if let Some(ui) = ui_copy.upgrade() {
+            ui.set_progress_indeterminate(true);
+            clear_speed(&ui);
+        }
+    });
+}
+
+fn clear_speed(ui: &AppWindow) {
+    ui.set_upload_speed(SharedString::new());
+    ui.set_upload_eta(SharedString::new());
+}
+
 fn finish_upload(
     ui_handle: Weak<AppWindow>,
     result: anyhow::Result<()>,
+    count: usize,
 ) {
     let _ = slint::invoke_from_event_loop(move || {
         if let Some(ui) = ui_handle.upgrade() {
             ui.set_is_uploading(false);
+            ui.set_progress_indeterminate(false);
+            clear_speed(&ui);
             match result {
+                Ok(_) if count > 1 => {
+                    ui.set_status_log(format!("{} 个文件上传成功! 🎉", count).into());
+                    ui.set_progress(1.0);
+                }
                 Ok(_) => {
                     ui.set_status_log("上传成功! 🎉".into());
                     ui.set_progress(1.0);
                 }
                 Err(e) => {
+                    ui.set_upload_failed(true);
                     ui.set_status_log(
                         format!("上传失败: {}", e).into(),
                     );
@@ -1,38 +1,109 @@
-use slint::{ComponentHandle, ModelRc, SharedString, VecModel};
+use slint::{ComponentHandle, Model, ModelRc, SharedString, VecModel};
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::rc::Rc;
 use std::sync::{Arc, Mutex};
 use std::thread;
 
-use crate::app::services::settings_service;
-use crate::domain::config::AppConfig;
+use crate::app::services::{explorer_service, settings_service};
+use crate::domain::config::{AppConfig, ServerConfig};
 use crate::domain::ports::ConfigRepository;
-use crate::presentation::slint::mapper;
+use crate::infra::ssh_config::{self, SshHostEntry};
+use crate::presentation::slint::{explorer, mapper};
+use crate::shared::panic_guard::lock;
 use crate::AppWindow;
+use crate::{ServerItem, SshHostUI};
 
 pub fn bind(
     ui: &AppWindow,
     config: Arc<Mutex<AppConfig>>,
     repo: Arc<dyn ConfigRepository + Send + Sync>,
 ) {
-    bind_save(ui, config.clone(), repo.clone());
-    bind_delete(ui, config.clone(), repo);
-    bind_load(ui, config);
+    let reachability = Reachability::default();
+    bind_save(ui, config.clone(), repo.clone(), reachability.clone());
+    bind_delete(ui, config.clone(), repo.clone(), reachability.clone());
+    bind_duplicate(ui, config.clone(), repo.clone(), reachability.clone());
+    bind_ssh_import(ui, config.clone(), repo.clone(), reachability.clone());
+    bind_export_import(ui, config.clone(), repo.clone(), reachability.clone());
+    bind_app_settings(ui, config.clone(), repo);
+    bind_load(ui, config.clone());
     bind_pick_key(ui);
     bind_test(ui);
+    bind_reachability(ui, config, reachability);
 }
 
-fn refresh_server_list(ui: &AppWindow, config: &AppConfig) {
+/// 最近一次可达性探测的结果，按 `host:port` 记录，服务器改名或重排后仍然有效
+type Reachability = Arc<Mutex<BTreeMap<String, bool>>>;
+
+fn reachability_key(server: &ServerConfig) -> String {
+    format!("{}:{}", server.host, server.port)
+}
+
+/// 启动时和手动刷新时在后台探测全部服务器，完成后刷新列表中的状态点
+fn bind_reachability(ui: &AppWindow, config: Arc<Mutex<AppConfig>>, reachability: Reachability) {
+    let ui_handle = ui.as_weak();
+    ui.on_refresh_reachability(move || {
+        let servers = lock(&config).servers.clone();
+        let ui_handle = ui_handle.clone();
+        let config = config.clone();
+        let reachability = reachability.clone();
+        thread::spawn(move || {
+            let results = settings_service::probe_reachability(&servers);
+            {
+                let mut map = lock(&reachability);
+                for (server, result) in servers.iter().zip(results) {
+                    match result {
+                        Some(up) => map.insert(reachability_key(server), up),
+                        None => map.remove(&reachability_key(server)),
+                    };
+                }
+            }
+            let _ = slint::invoke_from_event_loop(move || {
+                if let Some(ui) = ui_handle.upgrade() {
+                    let cfg = lock(&config).clone();
+                    refresh_server_list(&ui, &cfg, &lock(&reachability));
+                }
+            });
+        });
+    });
+    ui.invoke_refresh_reachability();
+}
+
+/// 刷新各处的服务器列表：名称、带标签和颜色的列表项、标签筛选选项及远程下拉框
+///
+/// `reachability` 为可达性探测结果，没有记录的服务器不显示状态点
+pub(crate) fn refresh_server_list(ui: &AppWindow, config: &AppConfig, reachability: &BTreeMap<String, bool>) {
     let servers: Vec<SharedString> = config
         .servers
         .iter()
         .map(|s| SharedString::from(&s.name))
         .collect();
     ui.set_servers(ModelRc::new(VecModel::from(servers)));
+
+    let items: Vec<ServerItem> = config
+        .servers
+        .iter()
+        .map(|s| mapper::to_server_item(s, reachability.get(&reachability_key(s)).copied()))
+        .collect();
+    ui.set_server_items(ModelRc::new(VecModel::from(items)));
+
+    let tags = explorer_service::server_tags(&config.servers);
+    let options: Vec<SharedString> = if tags.is_empty() {
+        Vec::new()
+    } else {
+        std::iter::once(SharedString::from("全部标签"))
+            .chain(tags.iter().map(SharedString::from))
+            .collect()
+    };
+    ui.set_server_tag_options(ModelRc::new(VecModel::from(options)));
+    explorer::remote_bindings::apply_server_filter(ui, &config.servers);
 }
 
 fn bind_save(
     ui: &AppWindow,
     config: Arc<Mutex<AppConfig>>,
     repo: Arc<dyn ConfigRepository + Send + Sync>,
+    reachability: Reachability,
 ) {
     let ui_handle = ui.as_weak();
     ui.on_save_config(move |index, ui_config| {
@@ -51,16 +122,35 @@ fn bind_save(
         };
 
         if let Some(ui) = ui_handle.upgrade() {
-            refresh_server_list(&ui, &updated);
+            refresh_server_list(&ui, &updated, &lock(&reachability));
             ui.set_show_settings(false);
         }
     });
 }
 
+/// 设置页"通用"部分：启动时载入，勾选变化后立即保存
+fn bind_app_settings(
+    ui: &AppWindow,
+    config: Arc<Mutex<AppConfig>>,
+    repo: Arc<dyn ConfigRepository + Send + Sync>,
+) {
+    ui.set_app_settings(mapper::to_app_settings(&lock(&config)));
+    let ui_handle = ui.as_weak();
+    ui.on_app_settings_changed(move |settings| {
+        let result = settings_service::update_app_settings(&config, &repo, |cfg| {
+            mapper::apply_app_settings(cfg, &settings)
+        });
+        if let (Err(e), Some(ui)) = (result, ui_handle.upgrade()) {
+            ui.set_global_error(format!("保存设置失败: {}", e).into());
+        }
+    });
+}
+
 fn bind_delete(
     ui: &AppWindow,
     config: Arc<Mutex<AppConfig>>,
     repo: Arc<dyn ConfigRepository + Send + Sync>,
+    reachability: Reachability,
 ) {
     let ui_handle = ui.as_weak();
     ui.on_delete_config(move |index| {
@@ -76,13 +166,176 @@ fn bind_delete(
             }
         };
         if let Some(ui) = ui_handle.upgrade() {
-            refresh_server_list(&ui, &updated);
+            refresh_server_list(&ui, &updated, &lock(&reachability));
             ui.set_current_settings_index(-1);
             ui.set_current_config(mapper::default_ui_config());
         }
     });
 }
 
+/// 复制当前服务器并切换到副本，方便在其基础上修改
+fn bind_duplicate(
+    ui: &AppWindow,
+    config: Arc<Mutex<AppConfig>>,
+    repo: Arc<dyn ConfigRepository + Send + Sync>,
+    reachability: Reachability,
+) {
+    let ui_handle = ui.as_weak();
+    ui.on_duplicate_config(move |index| {
+        let ui = match ui_handle.upgrade() {
+            Some(ui) => ui,
+            None => return,
+        };
+        match settings_service::duplicate_server(&config, &repo, index) {
+            Ok((updated, new_index)) => {
+                refresh_server_list(&ui, &updated, &lock(&reachability));
+                ui.set_current_settings_index(new_index as i32);
+                ui.set_current_config(mapper::to_ui(&updated.servers[new_index]));
+            }
+            Err(e) => ui.set_global_error(format!("复制服务器失败: {}", e).into()),
+        }
+    });
+}
+
+/// 扫描 ~/.ssh/config 列出主机，勾选的主机追加为服务器 (已存在的同名服务器默认不勾选)
+fn bind_ssh_import(
+    ui: &AppWindow,
+    config: Arc<Mutex<AppConfig>>,
+    repo: Arc<dyn ConfigRepository + Send + Sync>,
+    reachability: Reachability,
+) {
+    let scanned: Rc<RefCell<Vec<SshHostEntry>>> = Rc::new(RefCell::new(Vec::new()));
+
+    let ui_handle = ui.as_weak();
+    let scanned_clone = scanned.clone();
+    let config_clone = config.clone();
+    ui.on_scan_ssh_config(move || {
+        let ui = match ui_handle.upgrade() {
+            Some(ui) => ui,
+            None => return,
+        };
+        let path = match ssh_config::default_path() {
+            Some(p) => p,
+            None => {
+                ui.set_global_error("无法定位用户主目录".into());
+                return;
+            }
+        };
+        let hosts = match ssh_config::load(&path) {
+            Ok(h) => h,
+            Err(e) => {
+                ui.set_global_error(format!("{}", e).into());
+                return;
+            }
+        };
+        if hosts.is_empty() {
+            ui.set_global_error("SSH config 中没有可导入的主机".into());
+            return;
+        }
+
+        let existing: Vec<String> = config_clone
+            .lock()
+            .unwrap()
+            .servers
+            .iter()
+            .map(|s| s.name.clone())
+            .collect();
+        let rows: Vec<SshHostUI> = hosts
+            .iter()
+            .map(|h| {
+                let exists = existing.contains(&h.alias);
+                let user = h.user.as_deref().unwrap_or("root");
+                let mut summary = format!("{}@{}:{}", user, h.host(), h.port());
+                if exists {
+                    summary.push_str(" (已存在)");
+                }
+                SshHostUI {
+                    alias: SharedString::from(&h.alias),
+                    summary: SharedString::from(summary),
+                    checked: !exists,
+                }
+            })
+            .collect();
+        *scanned_clone.borrow_mut() = hosts;
+        ui.set_ssh_import_hosts(ModelRc::new(VecModel::from(rows)));
+        ui.set_show_ssh_import(true);
+    });
+
+    let ui_handle = ui.as_weak();
+    ui.on_import_ssh_hosts(move || {
+        let ui = match ui_handle.upgrade() {
+            Some(ui) => ui,
+            None => return,
+        };
+        let hosts = std::mem::take(&mut *scanned.borrow_mut());
+        let servers = ui
+            .get_ssh_import_hosts()
+            .iter()
+            .zip(hosts.iter())
+            .filter(|(row, _)| row.checked)
+            .map(|(_, h)| h.to_server_config())
+            .collect();
+        match settings_service::import_servers(&config, &repo, servers) {
+            Ok(updated) => refresh_server_list(&ui, &updated, &lock(&reachability)),
+            Err(e) => ui.set_global_error(format!("导入失败: {}", e).into()),
+        }
+    });
+}
+
+fn bind_export_import(
+    ui: &AppWindow,
+    config: Arc<Mutex<AppConfig>>,
+    repo: Arc<dyn ConfigRepository + Send + Sync>,
+    reachability: Reachability,
+) {
+    let ui_handle = ui.as_weak();
+    let config_clone = config.clone();
+    let repo_clone = repo.clone();
+    ui.on_export_config(move |strip_passwords| {
+        let path = match rfd::FileDialog::new()
+            .add_filter("TOML", &["toml"])
+            .set_file_name("flick-config.toml")
+            .save_file()
+        {
+            Some(p) => p,
+            None => return,
+        };
+        let result = settings_service::export_config(&config_clone, &repo_clone, &path, strip_passwords);
+        if let Some(ui) = ui_handle.upgrade() {
+            ui.set_test_success(result.is_ok());
+            ui.set_test_result(match result {
+                Ok(()) => format!("已导出到 {}", path.display()).into(),
+                Err(e) => format!("导出失败: {}", e).into(),
+            });
+        }
+    });
+
+    // 导入时与现有配置合并，不覆盖
+    let ui_handle = ui.as_weak();
+    ui.on_import_config(move || {
+        let path = match rfd::FileDialog::new().add_filter("TOML", &["toml"]).pick_file() {
+            Some(p) => p,
+            None => return,
+        };
+        let ui = match ui_handle.upgrade() {
+            Some(ui) => ui,
+            None => return,
+        };
+        match settings_service::import_config(&config, &repo, &path) {
+            Ok(updated) => {
+                refresh_server_list(&ui, &updated, &lock(&reachability));
+                explorer::refresh_bookmarks(&ui, &config);
+                ui.set_test_success(true);
+                ui.set_test_result("已导入并合并配置".into());
+            }
+            Err(e) => {
+                ui.set_test_success(false);
+                ui.set_test_result(format!("导入失败: {:#}", e).into());
+            }
+        }
+    });
+}
+
 fn bind_load(ui: &AppWindow, config: Arc<Mutex<AppConfig>>) {
     let ui_handle = ui.as_weak();
     ui.on_load_config(move |index| {
@@ -113,13 +366,14 @@ fn bind_pick_key(ui: &AppWindow) {
 
 fn bind_test(ui: &AppWindow) {
     let ui_handle = ui.as_weak();
-    ui.on_test_connection(move |ui_config| {
+    ui.on_test_connection(move |ui_config, timeout_secs| {
         let server_config = mapper::from_ui(&ui_config);
+        let timeout = u64::try_from(timeout_secs).ok();
         let ui_handle_thread = ui_handle.clone();
 
         thread::spawn(move || {
             let (result, logs) =
-                settings_service::test_connection(&server_config);
+                settings_service::test_connection(&server_config, timeout);
 
             let _ = slint::invoke_from_event_loop(move || {
                 if let Some(ui) = ui_handle_thread.upgrade() {
@@ -0,0 +1,74 @@
+//! 传输速度与剩余时间的显示格式，传输队列与快速上传共用
+
+use std::time::Duration;
+
+pub fn format_speed(bytes_per_sec: f64) -> String {
+    if bytes_per_sec < 1024.0 {
+        format!("{:.0} B/s", bytes_per_sec)
+    } else if bytes_per_sec < 1024.0 * 1024.0 {
+        format!("{:.1} KB/s", bytes_per_sec / 1024.0)
+    } else {
+        format!("{:.1} MB/s", bytes_per_sec / (1024.0 * 1024.0))
+    }
+}
+
+pub fn format_eta(secs: u64) -> String {
+    if secs < 60 {
+        format!("{}s", secs)
+    } else if secs < 3600 {
+        format!("{}:{:02}", secs / 60, secs % 60)
+    } else {
+        format!("{}:{:02}:{:02}", secs / 3600, (secs % 3600) / 60, secs % 60)
+    }
+}
+
+/// 按已用时间和进度估算 (速度, 剩余时间)；刚开始 (不足 0.5 秒) 或尚无进度时为空，完成后剩余时间为空
+pub fn speed_and_eta(total_bytes: u64, progress: f32, elapsed: Duration) -> (String, String) {
+    let elapsed = elapsed.as_secs_f64();
+    if elapsed <= 0.5 || progress <= 0.0 {
+        return (String::new(), String::new());
+    }
+    let bytes_done = (total_bytes as f64) * (progress as f64);
+    let bps = bytes_done / elapsed;
+    let eta = if progress < 1.0 && bps > 0.0 {
+        let remaining_bytes = (total_bytes as f64) * (1.0 - progress as f64);
+        format_eta((remaining_bytes / bps) as u64)
+    } else {
+        String::new()
+    };
+    (format_speed(bps), eta)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_speed() {
+        assert_eq!(format_speed(512.0), "512 B/s");
+        assert_eq!(format_speed(2048.0), "2.0 KB/s");
+        assert_eq!(format_speed(3.5 * 1024.0 * 1024.0), "3.5 MB/s");
+    }
+
+    #[test]
+    fn test_format_eta() {
+        assert_eq!(format_eta(42), "42s");
+        assert_eq!(format_eta(125), "2:05");
+        assert_eq!(format_eta(3723), "1:02:03");
+    }
+
+    #[test]
+    fn test_speed_and_eta() {
+        let empty = (String::new(), String::new());
+        assert_eq!(speed_and_eta(1024, 0.5, Duration::from_millis(100)), empty);
+        assert_eq!(speed_and_eta(1024, 0.0, Duration::from_secs(5)), empty);
+
+        // 10 秒传了 1/4 的 40 KB → 1 KB/s，剩余 30 KB 需 30 秒
+        let (speed, eta) = speed_and_eta(40 * 1024, 0.25, Duration::from_secs(10));
+        assert_eq!(speed, "1.0 KB/s");
+        assert_eq!(eta, "30s");
+
+        let (_, eta) = speed_and_eta(1024, 1.0, Duration::from_secs(2));
+        assert!(eta.is_empty());
+    }
+}
@@ -0,0 +1,161 @@
+//! 目录上传时跳过的条目：类似 .gitignore 的忽略规则
+
+use super::name_filter::glob_match;
+
+/// 单条规则，路径按 `/` 拆成段逐段匹配
+#[derive(Debug, Clone)]
+struct Rule {
+    segments: Vec<Vec<char>>,
+    /// 含 `/` 的规则相对上传根目录匹配完整路径，否则匹配任意层级的名称
+    anchored: bool,
+    /// 以 `/` 结尾，只匹配目录
+    dir_only: bool,
+    /// 以 `!` 开头，重新包含之前被忽略的条目
+    negate: bool,
+}
+
+impl Rule {
+    /// 解析一行规则，空行和 `#` 开头的注释返回 None
+    fn parse(line: &str) -> Option<Rule> {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            return None;
+        }
+        let (negate, line) = match line.strip_prefix('!') {
+            Some(rest) => (true, rest),
+            None => (false, line),
+        };
+        let (dir_only, line) = match line.strip_suffix('/') {
+            Some(rest) => (true, rest),
+            None => (false, line),
+        };
+        let segments: Vec<Vec<char>> = line
+            .split('/')
+            .filter(|s| !s.is_empty())
+            .map(|s| s.chars().collect())
+            .collect();
+        if segments.is_empty() {
+            return None;
+        }
+        Some(Rule {
+            segments,
+            anchored: line.contains('/'),
+            dir_only,
+            negate,
+        })
+    }
+
+    fn matches(&self, parts: &[Vec<char>], is_dir: bool) -> bool {
+        if self.dir_only && !is_dir {
+            return false;
+        }
+        if self.anchored {
+            match_segments(&self.segments, parts)
+        } else {
+            parts.last().is_some_and(|name| glob_match(&self.segments[0], name))
+        }
+    }
+}
+
+/// 逐段匹配，`**` 段匹配零个或多个目录
+fn match_segments(pattern: &[Vec<char>], parts: &[Vec<char>]) -> bool {
+    match pattern.split_first() {
+        None => parts.is_empty(),
+        Some((first, rest)) if first.as_slice() == ['*', '*'] => {
+            (0..=parts.len()).any(|skip| match_segments(rest, &parts[skip..]))
+        }
+        Some((first, rest)) => parts
+            .split_first()
+            .is_some_and(|(name, remaining)| glob_match(first, name) && match_segments(rest, remaining)),
+    }
+}
+
+/// 编译后的忽略规则，按顺序匹配，后面的规则覆盖前面的。
+/// `*`、`?` 不跨越 `/`，被忽略的目录整体跳过，其中的条目无法再用 `!` 重新包含
+#[derive(Debug, Clone, Default)]
+pub struct IgnoreRules {
+    rules: Vec<Rule>,
+}
+
+impl IgnoreRules {
+    pub fn new<S: AsRef<str>>(patterns: &[S]) -> Self {
+        IgnoreRules {
+            rules: patterns.iter().filter_map(|p| Rule::parse(p.as_ref())).collect(),
+        }
+    }
+
+    /// `relative` 为相对上传根目录、`/` 分隔的路径
+    pub fn is_ignored(&self, relative: &str, is_dir: bool) -> bool {
+        let parts: Vec<Vec<char>> = relative
+            .split('/')
+            .filter(|s| !s.is_empty())
+            .map(|s| s.chars().collect())
+            .collect();
+        let mut ignored = false;
+        for rule in &self.rules {
+            if rule.negate == ignored && rule.matches(&parts, is_dir) {
+                ignored = !rule.negate;
+            }
+        }
+        ignored
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_name_rules_match_at_any_depth() {
+        let rules = IgnoreRules::new(&[".git/", "*.tmp", "Thumbs.db"]);
+        assert!(rules.is_ignored(".git", true));
+        assert!(rules.is_ignored("web/.git", true));
+        assert!(rules.is_ignored("a.tmp", false));
+        assert!(rules.is_ignored("cache/b.tmp", false));
+        assert!(rules.is_ignored("img/Thumbs.db", false));
+        assert!(!rules.is_ignored("src/main.rs", false));
+        assert!(!rules.is_ignored("a.tmp.bak", false));
+    }
+
+    #[test]
+    fn test_dir_only_rules() {
+        let rules = IgnoreRules::new(&["build/"]);
+        assert!(rules.is_ignored("build", true));
+        assert!(!rules.is_ignored("build", false));
+    }
+
+    #[test]
+    fn test_anchored_rules() {
+        let rules = IgnoreRules::new(&["/dist", "docs/*.md"]);
+        assert!(rules.is_ignored("dist", true));
+        assert!(!rules.is_ignored("web/dist", true));
+        assert!(rules.is_ignored("docs/readme.md", false));
+        assert!(!rules.is_ignored("docs/api/readme.md", false));
+        assert!(!rules.is_ignored("readme.md", false));
+    }
+
+    #[test]
+    fn test_double_star() {
+        let rules = IgnoreRules::new(&["logs/**/*.log"]);
+        assert!(rules.is_ignored("logs/a.log", false));
+        assert!(rules.is_ignored("logs/2024/01/a.log", false));
+        assert!(!rules.is_ignored("other/a.log", false));
+    }
+
+    #[test]
+    fn test_negation_and_order() {
+        let rules = IgnoreRules::new(&["*.log", "!keep.log"]);
+        assert!(rules.is_ignored("debug.log", false));
+        assert!(!rules.is_ignored("keep.log", false));
+
+        let rules = IgnoreRules::new(&["!keep.log", "*.log"]);
+        assert!(rules.is_ignored("keep.log", false));
+    }
+
+    #[test]
+    fn test_comments_and_blank_lines() {
+        let rules = IgnoreRules::new(&["# 注释", "  ", "/", "!"]);
+        assert!(rules.rules.is_empty());
+        assert!(!rules.is_ignored("anything", false));
+    }
+}
@@ -1 +1,9 @@
+pub mod format;
+pub mod ignore;
+pub mod name_filter;
+pub mod panic_guard;
 pub mod path_utils;
+pub mod quick_filter;
+pub mod remote_path;
+pub mod target_template;
+pub mod throttle;
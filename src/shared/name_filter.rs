@@ -0,0 +1,186 @@
+use regex::{Regex, RegexBuilder};
+use std::ops::Range;
+
+/// 文件名过滤模式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum FilterMode {
+    /// 包含子串 (不区分大小写)
+    #[default]
+    Substring,
+    /// 通配符: `*` 任意多个字符，`?` 单个字符
+    Glob,
+    /// 正则表达式
+    Regex,
+}
+
+impl FilterMode {
+    /// 从 UI 传入的模式名解析，未知值按子串处理
+    pub fn from_key(key: &str) -> Self {
+        match key {
+            "glob" => FilterMode::Glob,
+            "regex" => FilterMode::Regex,
+            _ => FilterMode::Substring,
+        }
+    }
+}
+
+/// 编译后的文件名过滤器，无效的正则回退为子串匹配
+pub enum NameFilter {
+    Substring(String),
+    Glob(Vec<char>),
+    Regex(Regex),
+}
+
+impl NameFilter {
+    pub fn new(mode: FilterMode, pattern: &str) -> Self {
+        let lower = pattern.to_lowercase();
+        match mode {
+            FilterMode::Substring => NameFilter::Substring(lower),
+            FilterMode::Glob => NameFilter::Glob(lower.chars().collect()),
+            FilterMode::Regex => match RegexBuilder::new(pattern).case_insensitive(true).build() {
+                Ok(re) => NameFilter::Regex(re),
+                Err(_) => NameFilter::Substring(lower),
+            },
+        }
+    }
+
+    pub fn matches(&self, name: &str) -> bool {
+        match self {
+            NameFilter::Substring(p) => name.to_lowercase().contains(p.as_str()),
+            NameFilter::Glob(p) => {
+                let name: Vec<char> = name.to_lowercase().chars().collect();
+                glob_match(p, &name)
+            }
+            NameFilter::Regex(re) => re.is_match(name),
+        }
+    }
+
+    /// 名称中用于高亮的匹配部分 (字节范围)；通配符取最长的一段字面量，空匹配返回 None
+    pub fn find(&self, name: &str) -> Option<Range<usize>> {
+        match self {
+            NameFilter::Substring(p) => find_ignore_case(name, p),
+            NameFilter::Glob(p) => {
+                let literal = p
+                    .split(|&c| c == '*' || c == '?')
+                    .max_by_key(|run| run.len())
+                    .map(|run| run.iter().collect::<String>())?;
+                find_ignore_case(name, &literal)
+            }
+            NameFilter::Regex(re) => re.find(name).map(|m| m.range()).filter(|r| !r.is_empty()),
+        }
+    }
+}
+
+/// 不区分大小写查找 `lower_pattern` (已转小写)，返回在原名称中的字节范围
+fn find_ignore_case(name: &str, lower_pattern: &str) -> Option<Range<usize>> {
+    if lower_pattern.is_empty() {
+        return None;
+    }
+    for (start, _) in name.char_indices() {
+        let mut lower = String::new();
+        for (offset, c) in name[start..].char_indices() {
+            lower.extend(c.to_lowercase());
+            if !lower_pattern.starts_with(lower.as_str()) {
+                break;
+            }
+            if lower.len() == lower_pattern.len() {
+                return Some(start..start + offset + c.len_utf8());
+            }
+        }
+    }
+    None
+}
+
+/// 通配符匹配，`*` 失配时回溯到上一个星号
+pub(crate) fn glob_match(pattern: &[char], name: &[char]) -> bool {
+    let (mut p, mut n) = (0, 0);
+    let mut star: Option<(usize, usize)> = None;
+
+    while n < name.len() {
+        if p < pattern.len() && (pattern[p] == '?' || pattern[p] == name[n]) {
+            p += 1;
+            n += 1;
+        } else if p < pattern.len() && pattern[p] == '*' {
+            star = Some((p, n));
+            p += 1;
+        } else if let Some((sp, sn)) = star {
+            p = sp + 1;
+            n = sn + 1;
+            star = Some((sp, sn + 1));
+        } else {
+            return false;
+        }
+    }
+
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_substring_case_insensitive() {
+        let f = NameFilter::new(FilterMode::Substring, "Log");
+        assert!(f.matches("app.log"));
+        assert!(!f.matches("data.csv"));
+    }
+
+    #[test]
+    fn test_glob() {
+        let f = NameFilter::new(FilterMode::Glob, "*.log");
+        assert!(f.matches("app.log"));
+        assert!(f.matches("APP.LOG"));
+        assert!(!f.matches("app.log.gz"));
+
+        let f = NameFilter::new(FilterMode::Glob, "data??.csv");
+        assert!(f.matches("data01.csv"));
+        assert!(!f.matches("data1.csv"));
+
+        let f = NameFilter::new(FilterMode::Glob, "a*b*c");
+        assert!(f.matches("aXXbYYbc"));
+        assert!(!f.matches("aXXbYY"));
+    }
+
+    #[test]
+    fn test_regex_and_invalid_fallback() {
+        let f = NameFilter::new(FilterMode::Regex, r"^data\d+\.csv$");
+        assert!(f.matches("data42.csv"));
+        assert!(!f.matches("data.csv"));
+
+        // 无效正则回退为子串
+        let f = NameFilter::new(FilterMode::Regex, "(abc");
+        assert!(f.matches("x(abc).txt"));
+        assert!(!f.matches("abc.txt"));
+    }
+
+    #[test]
+    fn test_find_match_range() {
+        let f = NameFilter::new(FilterMode::Substring, "LOG");
+        assert_eq!(f.find("app.Log.gz"), Some(4..7));
+        assert_eq!(f.find("data.csv"), None);
+
+        // 多字节字符返回原名称中的字节范围
+        let f = NameFilter::new(FilterMode::Substring, "报告");
+        let name = "月度报告.pdf";
+        let range = f.find(name).unwrap();
+        assert_eq!(&name[range], "报告");
+
+        let f = NameFilter::new(FilterMode::Glob, "*.log");
+        assert_eq!(f.find("app.log"), Some(3..7));
+        let f = NameFilter::new(FilterMode::Glob, "*");
+        assert_eq!(f.find("app.log"), None);
+
+        let f = NameFilter::new(FilterMode::Regex, r"\d+");
+        assert_eq!(f.find("data42.csv"), Some(4..6));
+        let f = NameFilter::new(FilterMode::Regex, "x*");
+        assert_eq!(f.find("abc"), None);
+    }
+
+    #[test]
+    fn test_from_key() {
+        assert_eq!(FilterMode::from_key("glob"), FilterMode::Glob);
+        assert_eq!(FilterMode::from_key("regex"), FilterMode::Regex);
+        assert_eq!(FilterMode::from_key("other"), FilterMode::Substring);
+    }
+}
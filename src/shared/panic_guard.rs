@@ -0,0 +1,56 @@
+//! 后台线程 panic 的隔离：锁中毒后继续使用数据，传输闭包中的 panic 转为错误
+
+use std::any::Any;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::{Mutex, MutexGuard};
+
+/// 加锁；持锁线程 panic 导致锁中毒时仍取回数据，避免连带界面线程 panic
+pub fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// 执行闭包并捕获其中的 panic，转为带 panic 信息的错误文本
+pub fn catch_panic<T>(f: impl FnOnce() -> T) -> Result<T, String> {
+    panic::catch_unwind(AssertUnwindSafe(f)).map_err(|payload| panic_message(payload.as_ref()))
+}
+
+fn panic_message(payload: &(dyn Any + Send)) -> String {
+    let detail = payload
+        .downcast_ref::<&str>()
+        .map(|s| s.to_string())
+        .or_else(|| payload.downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| "未知错误".to_string());
+    format!("内部错误: {}", detail)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+    use std::thread;
+
+    #[test]
+    fn test_lock_recovers_poisoned_mutex() {
+        let data = Arc::new(Mutex::new(vec![1]));
+        let cloned = data.clone();
+        let _ = thread::spawn(move || {
+            let mut guard = cloned.lock().unwrap();
+            guard.push(2);
+            panic!("持锁时 panic");
+        })
+        .join();
+        assert!(data.is_poisoned());
+
+        lock(&data).push(3);
+        assert_eq!(*lock(&data), vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn test_catch_panic() {
+        assert_eq!(catch_panic(|| 7), Ok(7));
+        let err = catch_panic(|| -> u8 { panic!("boom {}", 1) }).unwrap_err();
+        assert_eq!(err, "内部错误: boom 1");
+        let err = catch_panic(|| -> u8 { panic!("static") }).unwrap_err();
+        assert_eq!(err, "内部错误: static");
+    }
+}
@@ -1,5 +1,8 @@
 use anyhow::{Context, Result};
 use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use crate::shared::name_filter::{FilterMode, NameFilter};
 
 /// 标准化路径，处理相对路径和波浪号 (~) 等
 /// 目前简单实现，直接返回绝对路径
@@ -33,6 +36,100 @@ pub fn ensure_file_exists(path: &Path) -> Result<()> {
     Ok(())
 }
 
+/// 展开命令行传入的文件参数：相对路径基于当前目录，文件名部分支持 `*` / `?` 通配。
+/// 通配结果按名称排序且只保留文件；每个结果都必须是已存在的文件
+pub fn expand_file_args(args: &[String]) -> Result<Vec<PathBuf>> {
+    let mut files: Vec<PathBuf> = Vec::new();
+    for arg in args {
+        let path = normalize_path(arg)?;
+        let name = path
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_default();
+        if !name.contains(['*', '?']) {
+            ensure_file_exists(&path)?;
+            if !files.contains(&path) {
+                files.push(path);
+            }
+            continue;
+        }
+        let dir = path.parent().unwrap_or(Path::new("."));
+        let filter = NameFilter::new(FilterMode::Glob, &name);
+        let mut matched: Vec<PathBuf> = std::fs::read_dir(dir)
+            .with_context(|| format!("无法读取目录: {:?}", dir))?
+            .flatten()
+            .map(|e| e.path())
+            .filter(|p| p.is_file())
+            .filter(|p| p.file_name().is_some_and(|n| filter.matches(&n.to_string_lossy())))
+            .collect();
+        if matched.is_empty() {
+            anyhow::bail!("没有匹配的文件: {}", arg);
+        }
+        matched.sort();
+        for file in matched {
+            if !files.contains(&file) {
+                files.push(file);
+            }
+        }
+    }
+    Ok(files)
+}
+
+/// 生成 `name (n).ext` 形式的名称；目录和无扩展名 (含 `.bashrc` 这类隐藏文件) 直接追加
+pub fn numbered_name(file_name: &str, n: usize, is_dir: bool) -> String {
+    match file_name.rfind('.') {
+        Some(dot) if dot > 0 && !is_dir => {
+            format!("{} ({}){}", &file_name[..dot], n, &file_name[dot..])
+        }
+        _ => format!("{} ({})", file_name, n),
+    }
+}
+
+/// 在目录现有名称中为新文件挑一个未被占用的名称：原名可用时直接使用，否则依次尝试 `name (n).ext`
+pub fn free_name(file_name: &str, existing: &[String]) -> String {
+    let taken = |name: &str| existing.iter().any(|e| e == name);
+    if !taken(file_name) {
+        return file_name.to_string();
+    }
+    (1..)
+        .map(|n| numbered_name(file_name, n, false))
+        .find(|name| !taken(name))
+        .unwrap_or_else(|| file_name.to_string())
+}
+
+/// 在同一目录下找一个尚不存在的 `name (n).ext` 路径，用于"保留两者"
+pub fn keep_both_path(path: &Path) -> PathBuf {
+    keep_both_path_with(path, path.is_dir(), |candidate| candidate.exists())
+}
+
+/// 与 `keep_both_path` 相同的编号规则，由 `taken` 判断候选路径是否已被占用
+pub fn keep_both_path_with(path: &Path, is_dir: bool, taken: impl Fn(&Path) -> bool) -> PathBuf {
+    let name = match path.file_name() {
+        Some(n) => n.to_string_lossy().to_string(),
+        None => return path.to_path_buf(),
+    };
+    (1..)
+        .map(|n| path.with_file_name(numbered_name(&name, n, is_dir)))
+        .find(|candidate| !taken(candidate))
+        .unwrap_or_else(|| path.to_path_buf())
+}
+
+/// 在系统临时目录新建文件，文件名含进程号和序号；以 create_new 打开，
+/// 不会复用其他实例或预先放置的同名文件
+pub fn create_unique_temp_file(prefix: &str, ext: &str) -> std::io::Result<(PathBuf, std::fs::File)> {
+    static COUNTER: AtomicUsize = AtomicUsize::new(0);
+    let dir = std::env::temp_dir();
+    loop {
+        let n = COUNTER.fetch_add(1, Ordering::Relaxed);
+        let path = dir.join(format!("{}_{}_{}.{}", prefix, std::process::id(), n, ext));
+        match std::fs::OpenOptions::new().write(true).create_new(true).open(&path) {
+            Ok(file) => return Ok((path, file)),
+            Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => continue,
+            Err(e) => return Err(e),
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -63,6 +160,75 @@ mod tests {
         assert!(result.is_err());
     }
 
+    #[test]
+    fn test_numbered_name() {
+        assert_eq!(numbered_name("report.pdf", 1, false), "report (1).pdf");
+        assert_eq!(numbered_name("README", 2, false), "README (2)");
+        assert_eq!(numbered_name(".bashrc", 1, false), ".bashrc (1)");
+        assert_eq!(numbered_name("v1.2", 1, true), "v1.2 (1)");
+    }
+
+    #[test]
+    fn test_free_name() {
+        let existing: Vec<String> = ["a.txt", "a (1).txt", "b"].iter().map(|s| s.to_string()).collect();
+        assert_eq!(free_name("new.txt", &existing), "new.txt");
+        assert_eq!(free_name("a.txt", &existing), "a (2).txt");
+        assert_eq!(free_name("b", &existing), "b (1)");
+    }
+
+    #[test]
+    fn test_keep_both_path_skips_existing() {
+        let dir = std::env::temp_dir().join("flick_test_keep_both");
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("a.txt"), "x").unwrap();
+        fs::write(dir.join("a (1).txt"), "x").unwrap();
+        assert_eq!(keep_both_path(&dir.join("a.txt")), dir.join("a (2).txt"));
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_create_unique_temp_file() {
+        let (a, _) = create_unique_temp_file("flick_test_unique", "png").unwrap();
+        let (b, _) = create_unique_temp_file("flick_test_unique", "png").unwrap();
+        assert_ne!(a, b);
+        assert_eq!(a.extension().unwrap(), "png");
+        fs::remove_file(&a).unwrap();
+        fs::remove_file(&b).unwrap();
+    }
+
+    #[test]
+    fn test_keep_both_path_with() {
+        let taken = |p: &Path| p == Path::new("/tmp/logs (1)");
+        assert_eq!(
+            keep_both_path_with(Path::new("/tmp/logs"), true, taken),
+            Path::new("/tmp/logs (2)")
+        );
+    }
+
+    #[test]
+    fn test_expand_file_args() {
+        let dir = std::env::temp_dir().join("flick_test_expand_args");
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(dir.join("sub.log")).unwrap();
+        fs::write(dir.join("b.log"), "x").unwrap();
+        fs::write(dir.join("a.log"), "x").unwrap();
+        fs::write(dir.join("c.txt"), "x").unwrap();
+        let arg = |name: &str| dir.join(name).to_string_lossy().to_string();
+
+        let files = expand_file_args(&[arg("*.log"), arg("c.txt"), arg("a.log")]).unwrap();
+        let names: Vec<String> = files
+            .iter()
+            .map(|p| p.file_name().unwrap().to_string_lossy().to_string())
+            .collect();
+        assert_eq!(names, vec!["a.log", "b.log", "c.txt"]);
+
+        assert!(expand_file_args(&[arg("*.zip")]).is_err());
+        assert!(expand_file_args(&[arg("missing.txt")]).is_err());
+        assert!(expand_file_args(&[arg("sub.log")]).is_err());
+        let _ = fs::remove_dir_all(&dir);
+    }
+
     #[test]
     fn test_ensure_file_exists_ok() {
         let tmp = std::env::temp_dir().join("flick_test_utils.tmp");
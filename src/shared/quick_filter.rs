@@ -0,0 +1,82 @@
+//! 按大小和修改时间筛选列表的快捷过滤，在文件名过滤之后应用
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// "大文件" 的大小下限
+pub const LARGE_FILE_BYTES: u64 = 100 * 1024 * 1024;
+/// "最近修改" 的时间范围 (秒)
+pub const RECENT_SECS: u64 = 7 * 24 * 3600;
+
+/// 快捷过滤条件
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum QuickFilter {
+    #[default]
+    None,
+    /// 大于 100MB 的文件 (不含目录)
+    LargeFiles,
+    /// 最近 7 天内修改过的条目
+    RecentlyModified,
+}
+
+impl QuickFilter {
+    /// 从 UI 传入的键解析，未知值视为不过滤
+    pub fn from_key(key: &str) -> Self {
+        match key {
+            "large" => QuickFilter::LargeFiles,
+            "recent" => QuickFilter::RecentlyModified,
+            _ => QuickFilter::None,
+        }
+    }
+
+    /// 条目是否保留，`mtime` 与 `now` 为 Unix 秒，mtime 为 0 表示未知
+    pub fn matches(self, is_dir: bool, size: u64, mtime: u64, now: u64) -> bool {
+        match self {
+            QuickFilter::None => true,
+            QuickFilter::LargeFiles => !is_dir && size > LARGE_FILE_BYTES,
+            QuickFilter::RecentlyModified => mtime > 0 && now.saturating_sub(mtime) <= RECENT_SECS,
+        }
+    }
+}
+
+/// 当前 Unix 时间 (秒)
+pub fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_key() {
+        assert_eq!(QuickFilter::from_key("large"), QuickFilter::LargeFiles);
+        assert_eq!(QuickFilter::from_key("recent"), QuickFilter::RecentlyModified);
+        assert_eq!(QuickFilter::from_key("none"), QuickFilter::None);
+        assert_eq!(QuickFilter::from_key("other"), QuickFilter::None);
+    }
+
+    #[test]
+    fn test_large_files() {
+        let f = QuickFilter::LargeFiles;
+        assert!(f.matches(false, LARGE_FILE_BYTES + 1, 0, 0));
+        assert!(!f.matches(false, LARGE_FILE_BYTES, 0, 0));
+        assert!(!f.matches(true, LARGE_FILE_BYTES * 2, 0, 0));
+    }
+
+    #[test]
+    fn test_recently_modified() {
+        let f = QuickFilter::RecentlyModified;
+        let now = 1_700_000_000;
+        assert!(f.matches(false, 0, now - 3600, now));
+        assert!(f.matches(true, 0, now - RECENT_SECS, now));
+        assert!(!f.matches(false, 0, now - RECENT_SECS - 1, now));
+        // 未知修改时间不算最近
+        assert!(!f.matches(false, 0, 0, now));
+        // 时钟偏差导致的未来时间视为最近
+        assert!(f.matches(false, 0, now + 60, now));
+        assert!(QuickFilter::None.matches(false, 0, 0, now));
+    }
+}
@@ -0,0 +1,127 @@
+//! 远程 (Unix 风格) 路径拼接与规范化
+
+/// 规范化远程路径：合并重复的 `/`，去掉 `.`，折叠 `..` (不会越过根目录)
+pub fn normalize(path: &str) -> String {
+    let absolute = path.starts_with('/');
+    let mut parts: Vec<&str> = Vec::new();
+    for seg in path.split('/') {
+        match seg {
+            "" | "." => {}
+            ".." => {
+                if matches!(parts.last(), Some(&last) if last != "..") {
+                    parts.pop();
+                } else if !absolute {
+                    parts.push("..");
+                }
+            }
+            _ => parts.push(seg),
+        }
+    }
+    let joined = parts.join("/");
+    if absolute {
+        format!("/{}", joined)
+    } else if joined.is_empty() {
+        ".".to_string()
+    } else {
+        joined
+    }
+}
+
+/// 把开头的 `~` / `~/` 替换为 home，其余路径 (包括 `~user`) 原样返回
+pub fn expand_tilde(path: &str, home: &str) -> String {
+    if path == "~" {
+        home.to_string()
+    } else if let Some(rest) = path.strip_prefix("~/") {
+        join(home, rest)
+    } else {
+        path.to_string()
+    }
+}
+
+/// 拼接目录与名称，name 为绝对路径时直接使用 name
+pub fn join(base: &str, name: &str) -> String {
+    if name.starts_with('/') {
+        normalize(name)
+    } else {
+        normalize(&format!("{}/{}", base, name))
+    }
+}
+
+/// 路径的最后一段名称，根目录、`~`、`.` 等无法作为文件名时返回 None
+pub fn file_name(path: &str) -> Option<String> {
+    let normalized = normalize(path);
+    match normalized.rsplit('/').next() {
+        Some(name) if !matches!(name, "" | "." | ".." | "~") => Some(name.to_string()),
+        _ => None,
+    }
+}
+
+/// 拆分为所在目录和最后一段名称，无法作为文件名的路径 (根目录等) 返回 None
+pub fn split_parent(path: &str) -> Option<(String, String)> {
+    let name = file_name(path)?;
+    Some((join(path, ".."), name))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_parent() {
+        assert_eq!(
+            split_parent("/var/log/syslog"),
+            Some(("/var/log".to_string(), "syslog".to_string()))
+        );
+        assert_eq!(split_parent("/etc"), Some(("/".to_string(), "etc".to_string())));
+        assert_eq!(split_parent("/srv/app/"), Some(("/srv".to_string(), "app".to_string())));
+        assert_eq!(split_parent("/"), None);
+        assert_eq!(split_parent("~"), None);
+    }
+
+    #[test]
+    fn test_file_name() {
+        assert_eq!(file_name("/var/log/syslog").as_deref(), Some("syslog"));
+        assert_eq!(file_name("/var/log/").as_deref(), Some("log"));
+        assert_eq!(file_name("data.zip").as_deref(), Some("data.zip"));
+        assert_eq!(file_name("/"), None);
+        assert_eq!(file_name("~"), None);
+        assert_eq!(file_name(""), None);
+    }
+
+    #[test]
+    fn test_join_onto_root() {
+        assert_eq!(join("/", "etc"), "/etc");
+        assert_eq!(join("/", ""), "/");
+    }
+
+    #[test]
+    fn test_join_with_trailing_slash() {
+        assert_eq!(join("/var/log/", "app.log"), "/var/log/app.log");
+        assert_eq!(join("/var/log", "app.log"), "/var/log/app.log");
+        assert_eq!(join("/var/log//", "sub/"), "/var/log/sub");
+    }
+
+    #[test]
+    fn test_join_name_with_slashes() {
+        assert_eq!(join("/srv", "app/logs/a.txt"), "/srv/app/logs/a.txt");
+        assert_eq!(join("/srv", "/etc/hosts"), "/etc/hosts");
+        assert_eq!(join("/srv/app", "../data"), "/srv/data");
+    }
+
+    #[test]
+    fn test_expand_tilde() {
+        assert_eq!(expand_tilde("~", "/home/me"), "/home/me");
+        assert_eq!(expand_tilde("~/uploads/x", "/home/me"), "/home/me/uploads/x");
+        assert_eq!(expand_tilde("/tmp/~x", "/home/me"), "/tmp/~x");
+        assert_eq!(expand_tilde("~other/x", "/home/me"), "~other/x");
+    }
+
+    #[test]
+    fn test_normalize() {
+        assert_eq!(normalize("//a/./b/../c/"), "/a/c");
+        assert_eq!(normalize("/.."), "/");
+        assert_eq!(normalize("/a/b/../../.."), "/");
+        assert_eq!(normalize("a/../../b"), "../b");
+        assert_eq!(normalize(""), ".");
+    }
+}
@@ -0,0 +1,114 @@
+//! 上传目标目录模板
+//!
+//! 可用占位符：
+//! - `{date}`：当天日期，如 `2024-05-01`
+//! - `{time}`：当前时间，如 `153000`
+//! - `{hostname}`：本机名称
+//! - `{server}`：目标服务器别名
+//!
+//! 未知的占位符原样保留。相对路径和 `~` 开头的路径相对于远程用户主目录。
+
+/// 模板展开所需的上下文
+#[derive(Debug, Clone)]
+pub struct TemplateContext {
+    pub date: String,
+    pub time: String,
+    pub hostname: String,
+    pub server: String,
+}
+
+impl TemplateContext {
+    /// 以当前时间和本机名称构造上下文
+    pub fn now(server: &str) -> Self {
+        let now = chrono::Local::now();
+        Self {
+            date: now.format("%Y-%m-%d").to_string(),
+            time: now.format("%H%M%S").to_string(),
+            hostname: local_hostname(),
+            server: server.to_string(),
+        }
+    }
+
+    /// 同一批上传换一台服务器，时间保持不变
+    pub fn for_server(&self, server: &str) -> Self {
+        Self {
+            server: server.to_string(),
+            ..self.clone()
+        }
+    }
+}
+
+/// 模板中是否含有可展开的占位符
+pub fn has_placeholders(template: &str) -> bool {
+    ["{date}", "{time}", "{hostname}", "{server}"]
+        .iter()
+        .any(|p| template.contains(p))
+}
+
+/// 展开目标目录模板，替换值中的 `/` 和 `\` 换成 `_`，避免意外产生多级目录
+pub fn expand_target_template(template: &str, ctx: &TemplateContext) -> String {
+    let safe = |v: &str| v.replace(['/', '\\'], "_");
+    template
+        .replace("{date}", &safe(&ctx.date))
+        .replace("{time}", &safe(&ctx.time))
+        .replace("{hostname}", &safe(&ctx.hostname))
+        .replace("{server}", &safe(&ctx.server))
+}
+
+/// 本机名称：依次读取 COMPUTERNAME、HOSTNAME 环境变量和 /etc/hostname
+fn local_hostname() -> String {
+    ["COMPUTERNAME", "HOSTNAME"]
+        .iter()
+        .filter_map(|key| std::env::var(key).ok())
+        .chain(std::fs::read_to_string("/etc/hostname").ok())
+        .map(|name| name.trim().to_string())
+        .find(|name| !name.is_empty())
+        .unwrap_or_else(|| "localhost".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ctx() -> TemplateContext {
+        TemplateContext {
+            date: "2024-05-01".into(),
+            time: "153000".into(),
+            hostname: "dev-pc".into(),
+            server: "prod/web".into(),
+        }
+    }
+
+    #[test]
+    fn test_expand_target_template() {
+        assert_eq!(
+            expand_target_template("/deploy/{date}/{hostname}", &ctx()),
+            "/deploy/2024-05-01/dev-pc"
+        );
+        assert_eq!(
+            expand_target_template("backup/{server}_{time}", &ctx()),
+            "backup/prod_web_153000"
+        );
+        // 未知占位符和普通路径原样保留
+        assert_eq!(expand_target_template("/tmp/{other}", &ctx()), "/tmp/{other}");
+        assert_eq!(expand_target_template("/tmp", &ctx()), "/tmp");
+    }
+
+    #[test]
+    fn test_has_placeholders() {
+        assert!(has_placeholders("/deploy/{date}"));
+        assert!(!has_placeholders("/deploy/{other}"));
+        assert!(!has_placeholders("/tmp"));
+    }
+
+    #[test]
+    fn test_context_now() {
+        let ctx = TemplateContext::now("web");
+        assert_eq!(ctx.date.len(), 10);
+        assert!(!ctx.hostname.is_empty());
+        assert_eq!(ctx.server, "web");
+        let other = ctx.for_server("db");
+        assert_eq!(other.server, "db");
+        assert_eq!(other.time, ctx.time);
+    }
+}
@@ -0,0 +1,108 @@
+use std::cell::Cell;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// 进度回调节流：进度前进足够多或距上次转发足够久才放行，终点 (>= 1.0) 总是放行
+pub struct Throttle {
+    min_delta: f32,
+    min_interval: Duration,
+    last_sent: Cell<Option<Instant>>,
+    last_value: Cell<f32>,
+}
+
+impl Throttle {
+    pub fn new(min_delta: f32, min_interval: Duration) -> Self {
+        Self {
+            min_delta,
+            min_interval,
+            last_sent: Cell::new(None),
+            last_value: Cell::new(0.0),
+        }
+    }
+
+    /// 进度传输默认: 前进 0.5% 或间隔 100ms
+    pub fn for_progress() -> Self {
+        Self::new(0.005, Duration::from_millis(100))
+    }
+
+    /// 判断本次值是否应该转发，放行时记录下来
+    pub fn ready(&self, value: f32) -> bool {
+        let now = Instant::now();
+        let pass = value >= 1.0
+            || match self.last_sent.get() {
+                None => true,
+                Some(last) => {
+                    value - self.last_value.get() >= self.min_delta
+                        || now.duration_since(last) >= self.min_interval
+                }
+            };
+        if pass {
+            self.last_sent.set(Some(now));
+            self.last_value.set(value);
+        }
+        pass
+    }
+}
+
+/// 防抖：每次请求领取一个序号，只有仍是最新序号的请求才应继续执行
+#[derive(Clone, Default)]
+pub struct Debouncer {
+    latest: Arc<AtomicUsize>,
+}
+
+impl Debouncer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 登记一次新请求，之前的请求随之过期
+    pub fn next_ticket(&self) -> usize {
+        self.latest.fetch_add(1, Ordering::SeqCst) + 1
+    }
+
+    pub fn is_latest(&self, ticket: usize) -> bool {
+        self.latest.load(Ordering::SeqCst) == ticket
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_first_and_final_always_pass() {
+        let t = Throttle::new(0.5, Duration::from_secs(60));
+        assert!(t.ready(0.0));
+        assert!(!t.ready(0.1));
+        assert!(t.ready(1.0));
+    }
+
+    #[test]
+    fn test_delta_threshold() {
+        let t = Throttle::new(0.1, Duration::from_secs(60));
+        assert!(t.ready(0.0));
+        assert!(!t.ready(0.05));
+        assert!(t.ready(0.1));
+        assert!(!t.ready(0.15));
+    }
+
+    #[test]
+    fn test_interval_threshold() {
+        let t = Throttle::new(1.0, Duration::from_millis(10));
+        assert!(t.ready(0.0));
+        assert!(!t.ready(0.001));
+        std::thread::sleep(Duration::from_millis(15));
+        assert!(t.ready(0.002));
+    }
+
+    #[test]
+    fn test_debouncer_keeps_only_latest() {
+        let d = Debouncer::new();
+        let first = d.next_ticket();
+        assert!(d.is_latest(first));
+        let second = d.clone().next_ticket();
+        assert!(!d.is_latest(first));
+        assert!(d.is_latest(second));
+    }
+}